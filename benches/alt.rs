@@ -0,0 +1,95 @@
+//! Compares the error types on an alt heavy grammar. Most alternatives fail on each token,
+//! so the cost of constructing errors dominates.
+//! Run with `cargo bench --bench alt`.
+use std::{hint::black_box, time::Instant};
+
+use fabparse::{alt, FabError, NoContextFabError, OffsetFabError, Parser, ParserError};
+
+const ITERATIONS: u32 = 200;
+
+fn keyword<E: ParserError>(input: &mut &str) -> Result<&'static str, E> {
+    alt((
+        "let".fab_value("let"),
+        "fn".fab_value("fn"),
+        "struct".fab_value("struct"),
+        "enum".fab_value("enum"),
+        "impl".fab_value("impl"),
+        "trait".fab_value("trait"),
+        "match".fab_value("match"),
+        "return".fab_value("return"),
+    ))
+    .fab(input)
+}
+
+fn operator<E: ParserError>(input: &mut &str) -> Result<&'static str, E> {
+    alt((
+        "+=".fab_value("+="),
+        "-=".fab_value("-="),
+        "*=".fab_value("*="),
+        "/=".fab_value("/="),
+        "==".fab_value("=="),
+        "!=".fab_value("!="),
+        "<=".fab_value("<="),
+        ">=".fab_value(">="),
+        "&&".fab_value("&&"),
+        "||".fab_value("||"),
+    ))
+    .fab(input)
+}
+
+//Every token is an identifier, so each call to operator fails in all ten branches.
+fn operators_or_words<E: ParserError>(input: &mut &str) -> Result<usize, E> {
+    (
+        alt((operator::<E>.fab_value(()), ('a'..='z').fab_value(()))),
+        opt_space::<E>,
+    )
+        .fab_repeat()
+        .count()
+        .fab(input)
+}
+
+fn keywords<E: ParserError>(input: &mut &str) -> Result<usize, E> {
+    (keyword::<E>, opt_space::<E>)
+        .fab_repeat()
+        .count()
+        .fab(input)
+}
+
+fn opt_space<E: ParserError>(input: &mut &str) -> Result<(), E> {
+    ' '.fab_repeat().fab_value(()).fab(input)
+}
+
+fn bench<E: ParserError>(name: &str, source: &str, parser: impl Fn(&mut &str) -> Result<usize, E>) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let mut input = black_box(source);
+        let count = parser(&mut input).ok();
+        black_box(count);
+    }
+    println!(
+        "{:<20} {:?} per iteration",
+        name,
+        start.elapsed() / ITERATIONS
+    );
+}
+
+fn main() {
+    let source = "return match trait impl enum struct fn let ".repeat(1000);
+    println!("8 keyword alt");
+    bench("FabError", &source, keywords::<FabError>);
+    bench("OffsetFabError", &source, keywords::<OffsetFabError>);
+    bench("NoContextFabError", &source, keywords::<NoContextFabError>);
+    let source = "a b c d e f g h i j ".repeat(1000);
+    println!("10 branch alt, no branch matches");
+    bench("FabError", &source, operators_or_words::<FabError>);
+    bench(
+        "OffsetFabError",
+        &source,
+        operators_or_words::<OffsetFabError>,
+    );
+    bench(
+        "NoContextFabError",
+        &source,
+        operators_or_words::<NoContextFabError>,
+    );
+}
@@ -0,0 +1,43 @@
+//! Compares parsing integers directly from bytes with taking the digits, converting them
+//! to a str and parsing that. Run with `cargo bench --bench int`.
+use std::{hint::black_box, str::from_utf8, time::Instant};
+
+use fabparse::{util::uint, FabError, Parser};
+
+const ITERATIONS: u32 = 20;
+
+fn bench(name: &str, source: &[u8], parser: impl for<'a> Fn(&mut &'a [u8]) -> Option<u64>) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let mut input = black_box(source);
+        let mut sum = 0u64;
+        while let Some(num) = parser(&mut input) {
+            sum = sum.wrapping_add(num);
+            input = &input[1..];
+        }
+        black_box(sum);
+    }
+    println!(
+        "{:<20} {:?} per iteration",
+        name,
+        start.elapsed() / ITERATIONS
+    );
+}
+
+fn main() {
+    let source: Vec<u8> = (0..200_000u64)
+        .flat_map(|num| format!("{} ", num * 7919).into_bytes())
+        .collect();
+    bench("uint", &source, |input| {
+        let res: Result<u64, FabError> = uint().fab(input);
+        res.ok()
+    });
+    bench("from_utf8 + parse", &source, |input| {
+        let res: Result<&[u8], FabError> = u8::is_ascii_digit
+            .fab_repeat()
+            .min(1)
+            .as_input_slice()
+            .fab(input);
+        from_utf8(res.ok()?).ok()?.parse().ok()
+    });
+}
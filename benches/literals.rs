@@ -0,0 +1,49 @@
+//! Compares matching one of 40 keywords with `literals` against an alt of tags, which
+//! scans the start of the input again for each branch. Run with `cargo bench --bench literals`.
+use std::{cmp::Reverse, hint::black_box, time::Instant};
+
+use fabparse::{alt, literals, tag::Literals, FabError, Parser};
+
+const ITERATIONS: u32 = 50;
+
+const KEYWORDS: [&str; 40] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "union",
+    "unsafe", "use", "where", "while", "yield",
+];
+
+fn bench(name: &str, source: &str, parser: impl Fn(&mut &str) -> Option<&'static str>) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let mut input = black_box(source);
+        let mut count = 0;
+        while let Some(keyword) = parser(&mut input) {
+            count += keyword.len();
+            input = &input[1..];
+        }
+        black_box(count);
+    }
+    println!(
+        "{:<20} {:?} per iteration",
+        name,
+        start.elapsed() / ITERATIONS
+    );
+}
+
+fn main() {
+    let source = format!("{} ", KEYWORDS.join(" ")).repeat(500);
+    //An alt tries the tags in order, so longer keywords must come before their prefixes.
+    let mut by_length = KEYWORDS;
+    by_length.sort_by_key(|keyword| Reverse(keyword.len()));
+    let tags = alt(by_length.map(|keyword| keyword.fab_value(keyword)).to_vec());
+    let table: Literals<&'static str> = literals(KEYWORDS.map(|keyword| (keyword, keyword)));
+    bench("alt", &source, |input| {
+        let res: Result<_, FabError> = tags.fab(input);
+        res.ok()
+    });
+    bench("literals", &source, |input| {
+        let res: Result<_, FabError> = table.fab(input);
+        res.ok()
+    });
+}
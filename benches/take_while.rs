@@ -0,0 +1,38 @@
+//! Compares take_while with the generic repeat path on long runs of whitespace.
+//! Run with `cargo bench --bench take_while`.
+use std::{hint::black_box, time::Instant};
+
+use fabparse::{take_while, FabError, Parser};
+
+const ITERATIONS: u32 = 200;
+
+fn bench(
+    name: &str,
+    source: &str,
+    parser: impl for<'a> Fn(&mut &'a str) -> Result<&'a str, FabError>,
+) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let mut input = black_box(source);
+        let matched = parser(&mut input).ok();
+        black_box(matched);
+    }
+    println!(
+        "{:<20} {:?} per iteration",
+        name,
+        start.elapsed() / ITERATIONS
+    );
+}
+
+fn main() {
+    let ascii = " \t\n".repeat(100_000) + "x";
+    let unicode = " \u{3000}".repeat(100_000) + "x";
+    for (label, source) in [("ascii", &ascii), ("unicode", &unicode)] {
+        bench(&format!("repeat {}", label), source, |input| {
+            char::is_whitespace.fab_repeat().as_input_slice().fab(input)
+        });
+        bench(&format!("take_while {}", label), source, |input| {
+            take_while(char::is_whitespace).fab(input)
+        });
+    }
+}
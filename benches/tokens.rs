@@ -0,0 +1,64 @@
+//! Parses a stream of String tokens with item tags and predicates, which return clones of
+//! the matched tokens, against `item_ref`, which returns references into the stream.
+//! Run with `cargo bench --bench tokens`.
+use std::{hint::black_box, time::Instant};
+
+use fabparse::{alt, item_ref, FabError, Parser};
+
+const ITERATIONS: u32 = 50;
+
+const TOKENS: [&str; 8] = ["let", "value", "=", "compute", "(", "argument", ")", ";"];
+
+fn bench(name: &str, source: &[String], parser: impl Fn(&mut &[String]) -> Option<usize>) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let mut input = black_box(source);
+        let mut count = 0;
+        while !input.is_empty() {
+            if let Some(len) = parser(&mut input) {
+                count += len;
+            } else {
+                input = &input[1..];
+            }
+        }
+        black_box(count);
+    }
+    println!(
+        "{:<20} {:?} per iteration",
+        name,
+        start.elapsed() / ITERATIONS
+    );
+}
+
+fn main() {
+    let source: Vec<String> = TOKENS
+        .repeat(20_000)
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let [eq, open, close, semi] = ["=", "(", ")", ";"].map(String::from);
+    let tags = alt((eq.clone(), open.clone(), close.clone(), semi.clone()));
+    let tag_refs = alt((
+        item_ref(eq),
+        item_ref(open),
+        item_ref(close),
+        item_ref(semi),
+    ));
+    bench("alt of item tags", &source, |input| {
+        let res: Result<_, FabError> = tags.fab(input);
+        res.ok().map(|token| token.len())
+    });
+    bench("alt of item_ref", &source, |input| {
+        let res: Result<_, FabError> = tag_refs.fab(input);
+        res.ok().map(|token| token.len())
+    });
+    let is_word = |token: &String| token.chars().all(char::is_alphabetic);
+    bench("predicate", &source, |input| {
+        let res: Result<String, FabError> = is_word.fab(input);
+        res.ok().map(|token| token.len())
+    });
+    bench("predicate item_ref", &source, |input| {
+        let res: Result<&String, FabError> = item_ref(is_word).fab(input);
+        res.ok().map(|token| token.len())
+    });
+}
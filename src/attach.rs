@@ -0,0 +1,82 @@
+use std::{any::TypeId, cell::RefCell};
+
+/**
+ * Data attached to the input of a running parser. Input wrappers such as `Located` are
+ * references into the original input, which leaves no room for the data they carry.
+ * Instead it is attached to the address range of the original input while the parser
+ * runs, and any part of that input can look it up.
+ */
+struct Attached {
+    key: TypeId,
+    start: usize,
+    end: usize,
+    data: *const (),
+}
+
+thread_local! {
+    static ATTACHED: RefCell<Vec<Attached>> = const { RefCell::new(Vec::new()) };
+}
+
+/**
+ * Removes the data attached by `attach` when dropped, so it is removed even if a
+ * parser panics.
+ */
+struct AttachGuard;
+
+impl Drop for AttachGuard {
+    fn drop(&mut self) {
+        ATTACHED.with_borrow_mut(|attached| attached.pop());
+    }
+}
+
+fn bounds<I: ?Sized>(input: &I) -> (usize, usize) {
+    let start = input as *const I as *const u8 as usize;
+    (start, start + std::mem::size_of_val(input))
+}
+
+/**
+ * Attaches `data` under `K` to the input while `run` runs.
+ */
+pub(crate) fn attach<K: 'static, I: ?Sized, D, R>(
+    input: &I,
+    data: &D,
+    run: impl FnOnce() -> R,
+) -> R {
+    let (start, end) = bounds(input);
+    ATTACHED.with_borrow_mut(|attached| {
+        attached.push(Attached {
+            key: TypeId::of::<K>(),
+            start,
+            end,
+            data: data as *const D as *const (),
+        })
+    });
+    let _guard = AttachGuard;
+    run()
+}
+
+/**
+ * Calls `func` with the start address of the input that the innermost data attached
+ * under `K` covers `input`, along with the data. Returns None if there is none.
+ *
+ * The caller must pass the same `D` as the `attach` for `K`.
+ */
+pub(crate) fn with_attached<K: 'static, I: ?Sized, D, R>(
+    input: &I,
+    func: impl FnOnce(usize, &D) -> R,
+) -> Option<R> {
+    let (start, end) = bounds(input);
+    let found = ATTACHED.with_borrow(|attached| {
+        attached
+            .iter()
+            .rev()
+            .find(|item| item.key == TypeId::of::<K>() && item.start <= start && end <= item.end)
+            .map(|item| (item.start, item.data))
+    });
+    let (original, data) = found?;
+    //SAFETY: The data is only attached while `attach` runs, which borrows it for that
+    //whole time. Since it is still attached, this call is inside that `attach`, so the
+    //data outlives `func`. `K` identifies the type it was attached with.
+    let data = unsafe { &*(data as *const D) };
+    Some(func(original, data))
+}
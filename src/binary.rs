@@ -0,0 +1,106 @@
+use crate::{Parser, ParserError, ParserType};
+
+/**
+ * Takes exactly N bytes from the front of the input. Fails without consuming
+ * input if there are fewer than N bytes.
+ */
+fn take_array<E: ParserError, const N: usize>(input: &mut &[u8]) -> Result<[u8; N], E> {
+    match input.split_first_chunk::<N>() {
+        Some((bytes, rest)) => {
+            *input = rest;
+            Ok(*bytes)
+        }
+        None => Err(E::from_incomplete(*input, ParserType::Take)),
+    }
+}
+
+macro_rules! binary_impl {
+    ( $le:ident $be:ident $num:ident ) => {
+        #[doc = concat!(" Parses a little endian ", stringify!($num), " from the input.")]
+        pub fn $le<E: ParserError>(input: &mut &[u8]) -> Result<$num, E> {
+            take_array(input).map($num::from_le_bytes)
+        }
+        #[doc = concat!(" Parses a big endian ", stringify!($num), " from the input.")]
+        pub fn $be<E: ParserError>(input: &mut &[u8]) -> Result<$num, E> {
+            take_array(input).map($num::from_be_bytes)
+        }
+    };
+}
+
+binary_impl!(le_u16 be_u16 u16);
+binary_impl!(le_u32 be_u32 u32);
+binary_impl!(le_u64 be_u64 u64);
+binary_impl!(le_u128 be_u128 u128);
+binary_impl!(le_i16 be_i16 i16);
+binary_impl!(le_i32 be_i32 i32);
+binary_impl!(le_i64 be_i64 i64);
+binary_impl!(le_i128 be_i128 i128);
+binary_impl!(le_f32 be_f32 f32);
+binary_impl!(le_f64 be_f64 f64);
+
+/**
+ * Parses a u8 from the input.
+ */
+pub fn u8<E: ParserError>(input: &mut &[u8]) -> Result<u8, E> {
+    take_array(input).map(u8::from_ne_bytes)
+}
+
+/**
+ * Parses an i8 from the input.
+ */
+pub fn i8<E: ParserError>(input: &mut &[u8]) -> Result<i8, E> {
+    take_array(input).map(i8::from_ne_bytes)
+}
+
+/**
+ * This struct can be constructed through the function `masked_tag`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct MaskedTag<'k> {
+    pattern: &'k [u8],
+    mask: &'k [u8],
+}
+
+/**
+ * Matches bytes against a pattern where only the bits set in the mask are compared, so
+ * byte i matches when `input[i] & mask[i] == pattern[i] & mask[i]`. A mask byte of 0x00
+ * accepts any byte, and 0xF0 checks only the high nibble. It returns the matched bytes,
+ * so the fields that were masked out can be read from them.
+ *
+ * Panics if the pattern and the mask have different lengths.
+ */
+pub fn masked_tag<'k>(pattern: &'k [u8], mask: &'k [u8]) -> MaskedTag<'k> {
+    assert_eq!(
+        pattern.len(),
+        mask.len(),
+        "The pattern and mask of a masked tag must have the same length"
+    );
+    MaskedTag { pattern, mask }
+}
+
+impl MaskedTag<'_> {
+    fn matches(&self, bytes: &[u8]) -> bool {
+        bytes
+            .iter()
+            .zip(self.pattern.iter().zip(self.mask))
+            .all(|(byte, (pattern, mask))| byte & mask == pattern & mask)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MaskedTagParser;
+
+impl<'a, E: ParserError> Parser<'a, [u8], &'a [u8], E, MaskedTagParser> for MaskedTag<'_> {
+    fn fab(&self, input: &mut &'a [u8]) -> Result<&'a [u8], E> {
+        match input.split_at_checked(self.pattern.len()) {
+            Some((start, rest)) if self.matches(start) => {
+                *input = rest;
+                Ok(start)
+            }
+            Some(_) => Err(E::from_parser_error(*input, ParserType::Tag)),
+            //The input is shorter than the pattern, so more input could complete it.
+            None if self.matches(input) => Err(E::from_incomplete(*input, ParserType::Tag)),
+            None => Err(E::from_parser_error(*input, ParserType::Tag)),
+        }
+    }
+}
@@ -1,14 +1,78 @@
-use std::marker::PhantomData;
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    marker::PhantomData,
+};
 
 use crate::{sequence::Sequence, Parser, ParserError, ParserType};
 
 #[derive(Clone, Debug)]
 pub struct Alt<T>(pub T);
 #[derive(Clone, Debug)]
+pub struct AltVerbose<T>(pub T);
+#[derive(Clone, Debug)]
+pub struct AltLongest<T>(pub T);
+#[derive(Clone, Debug)]
 pub struct Permutation<T>(pub T);
+#[derive(Clone, Debug)]
+pub struct PermutationOpt<T>(pub T);
+
+/**
+ * Tracks the error of the branch that made the most progress. If the error type supports
+ * location, ties go to the later branch, and the earlier branches that failed at the same
+ * location are kept so their expectations can be merged in once the error is needed.
+ */
+pub(crate) struct Furthest<E> {
+    maxloc: Option<usize>,
+    maxlocerr: Option<E>,
+    ties: Vec<E>,
+}
+
+impl<E: ParserError> Furthest<E> {
+    pub(crate) fn new() -> Self {
+        Furthest {
+            maxloc: None,
+            maxlocerr: None,
+            ties: Vec::new(),
+        }
+    }
+    pub(crate) fn add(&mut self, err: E) {
+        match err.get_loc() {
+            Some(loc) => {
+                if self.maxloc.is_some_and(|val| loc < val) {
+                    return;
+                }
+                match self.maxlocerr.take() {
+                    Some(prev) if self.maxloc == Some(loc) => self.ties.push(prev),
+                    _ => self.ties.clear(),
+                }
+                self.maxloc = Some(loc);
+            }
+            None => self.ties.clear(),
+        }
+        self.maxlocerr = Some(err);
+    }
+    /**
+     * Returns the kept error with the expectations of the tied branches merged into it.
+     */
+    pub(crate) fn finish(self) -> Option<E> {
+        let mut err = self.maxlocerr?;
+        //Each branch that failed here could have matched instead.
+        let mut ties = self.ties.into_iter();
+        if let Some(mut earlier) = ties.next() {
+            for mut tie in ties {
+                tie.merge_expected(earlier);
+                earlier = tie;
+            }
+            err.merge_expected(earlier);
+        }
+        Some(err)
+    }
+}
 
 macro_rules! alt_impl {
     ( $tstruct:ident $( $parser:ident $parserlower:ident $ptype:ident)+ ) => {
+        #[derive(Clone, Debug)]
         pub struct $tstruct<$($ptype,)+> {
             $(
                 $parserlower : PhantomData<$ptype>,
@@ -22,33 +86,29 @@ macro_rules! alt_impl {
             )+{
             fn fab(&self, input: &mut &'a I) -> Result<O, E> {
                 let startloc = *input;
-                let mut maxloc = None;
-                let mut maxlocerr = None;
+                let mut furthest = Furthest::new();
                 let  ($($parserlower,)+) = &self.0;
                 $(
                     match $parserlower.fab(input) {
                         Ok(res) => {
                             return Ok(res);
                         }
-                        Err(err) => {
-                            //If the error type supports location, take the error from the
-                            //parser that made the most progress.
-                            if let Some(loc) = err.get_loc() {
-                                if maxloc.is_none() || maxloc.is_some_and(|val| loc >= val) {
-                                    maxloc = Some(loc);
-                                    maxlocerr = Some(err);
-                                }
-                            } else {
-                                maxlocerr = Some(err);
+                        Err(mut err) => {
+                            //More input might let this branch match, so don't try the others.
+                            if err.is_incomplete() {
+                                *input = startloc;
+                                err.add_context(startloc, ParserType::Alt);
+                                return Err(err);
                             }
+                            furthest.add(err);
                         }
                     }
                     *input = startloc;
                 )+
                 //Alt is only implemented for tuples with at least 1 element, so we will always have some error.
-                let mut maxlocerr = maxlocerr.expect("Something went wrong in the alt parser.");
+                let mut maxlocerr = furthest.finish().expect("Something went wrong in the alt parser.");
                 maxlocerr.add_context(startloc, ParserType::Alt);
-                return Err(maxlocerr);
+                Err(maxlocerr)
             }
         }
 
@@ -67,8 +127,567 @@ alt_impl!(Alt9 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6 P7 p7 T7 P8
 alt_impl!(Alt10 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6 P7 p7 T7 P8 p8 T8 P9 p9 T9 P10 p10 T10);
 alt_impl!(Alt11 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6 P7 p7 T7 P8 p8 T8 P9 p9 T9 P10 p10 T10 P11 p11 T11);
 
+/**
+ * This struct can be constructed through the method `fab_alt`. It is an [`Alt`] that
+ * records the input, output and error types, so that chains of `fab_alt` can be inferred.
+ */
+pub struct ParserAlt<T, I: ?Sized, O, E> {
+    pub alt: Alt<T>,
+    pub phantom_i: PhantomData<I>,
+    pub phantom_o: PhantomData<O>,
+    pub phantom_e: PhantomData<E>,
+}
+impl<T: Clone, I: ?Sized, O, E> Clone for ParserAlt<T, I, O, E> {
+    fn clone(&self) -> Self {
+        ParserAlt {
+            alt: self.alt.clone(),
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+}
+impl<T: Debug, I: ?Sized, O, E> Debug for ParserAlt<T, I, O, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParserAlt")
+            .field("alt", &self.alt)
+            .finish_non_exhaustive()
+    }
+}
+pub struct ParserAltT<AltType> {
+    phantom_alt: PhantomData<AltType>,
+}
+impl<'a, T, I: ?Sized, O, E: ParserError, AltType> Parser<'a, I, O, E, ParserAltT<AltType>>
+    for ParserAlt<T, I, O, E>
+where
+    Alt<T>: Parser<'a, I, O, E, AltType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        self.alt.fab(input)
+    }
+}
+
+macro_rules! alt_chain_impl {
+    ( $( $parser:ident $parserlower:ident)+ ) => {
+        impl<$($parser,)+ I: ?Sized, O, E> ParserAlt<($($parser,)+), I, O, E> {
+            /**
+             * Adds another branch to the end of this alt. This keeps a chain of `fab_alt`
+             * calls flat, so it picks errors exactly like a single alt.
+             */
+            pub fn fab_alt<Next>(self, next: Next) -> ParserAlt<($($parser,)+ Next), I, O, E> {
+                let ($($parserlower,)+) = self.alt.0;
+                ParserAlt {
+                    alt: Alt(($($parserlower,)+ next)),
+                    phantom_i: PhantomData,
+                    phantom_o: PhantomData,
+                    phantom_e: PhantomData,
+                }
+            }
+        }
+    };
+}
+
+alt_chain_impl!(P1 p1 P2 p2);
+alt_chain_impl!(P1 p1 P2 p2 P3 p3);
+alt_chain_impl!(P1 p1 P2 p2 P3 p3 P4 p4);
+alt_chain_impl!(P1 p1 P2 p2 P3 p3 P4 p4 P5 p5);
+alt_chain_impl!(P1 p1 P2 p2 P3 p3 P4 p4 P5 p5 P6 p6);
+alt_chain_impl!(P1 p1 P2 p2 P3 p3 P4 p4 P5 p5 P6 p6 P7 p7);
+alt_chain_impl!(P1 p1 P2 p2 P3 p3 P4 p4 P5 p5 P6 p6 P7 p7 P8 p8);
+alt_chain_impl!(P1 p1 P2 p2 P3 p3 P4 p4 P5 p5 P6 p6 P7 p7 P8 p8 P9 p9);
+alt_chain_impl!(P1 p1 P2 p2 P3 p3 P4 p4 P5 p5 P6 p6 P7 p7 P8 p8 P9 p9 P10 p10);
+
+/**
+ * The output of `alt_either`, holding the output of whichever branch matched. The
+ * 3 branch version nests, returning `Either<O1, Either<O2, O3>>`.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+#[derive(Clone, Debug)]
+pub struct AltEither<T>(pub T);
+
+/**
+ * Wraps the output of a branch of `alt_either` without adding a frame to its errors,
+ * so the errors are the same as those of `alt`.
+ */
+struct EitherBranch<P, F> {
+    parser: P,
+    func: F,
+}
+struct EitherBranchParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+impl<'a, I: ?Sized, M, O, E: ParserError, PType, P, F>
+    Parser<'a, I, O, E, EitherBranchParser<(PType, M)>> for EitherBranch<P, F>
+where
+    P: Parser<'a, I, M, E, PType>,
+    F: Fn(M) -> O,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        self.parser.fab(input).map(&self.func)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AltEither2<T1, T2> {
+    p1: PhantomData<T1>,
+    p2: PhantomData<T2>,
+}
+impl<'a, I: ?Sized + Sequence, O1, O2, E: ParserError, P1, T1, P2, T2>
+    Parser<'a, I, Either<O1, O2>, E, AltEither2<T1, T2>> for AltEither<(P1, P2)>
+where
+    P1: Parser<'a, I, O1, E, T1>,
+    P2: Parser<'a, I, O2, E, T2>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<Either<O1, O2>, E> {
+        let (p1, p2) = &self.0;
+        Alt((
+            EitherBranch {
+                parser: p1,
+                func: Either::Left,
+            },
+            EitherBranch {
+                parser: p2,
+                func: Either::Right,
+            },
+        ))
+        .fab(input)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AltEither3<T1, T2, T3> {
+    p1: PhantomData<T1>,
+    p2: PhantomData<T2>,
+    p3: PhantomData<T3>,
+}
+impl<'a, I: ?Sized + Sequence, O1, O2, O3, E: ParserError, P1, T1, P2, T2, P3, T3>
+    Parser<'a, I, Either<O1, Either<O2, O3>>, E, AltEither3<T1, T2, T3>>
+    for AltEither<(P1, P2, P3)>
+where
+    P1: Parser<'a, I, O1, E, T1>,
+    P2: Parser<'a, I, O2, E, T2>,
+    P3: Parser<'a, I, O3, E, T3>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<Either<O1, Either<O2, O3>>, E> {
+        let (p1, p2, p3) = &self.0;
+        Alt((
+            EitherBranch {
+                parser: p1,
+                func: Either::Left,
+            },
+            EitherBranch {
+                parser: p2,
+                func: |out| Either::Right(Either::Left(out)),
+            },
+            EitherBranch {
+                parser: p3,
+                func: |out| Either::Right(Either::Right(out)),
+            },
+        ))
+        .fab(input)
+    }
+}
+
+/**
+ * This error is used as the cause when alt is given an empty slice or Vec of parsers.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub struct EmptyAltError;
+impl Display for EmptyAltError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Alt was given no parsers")
+    }
+}
+
+impl Error for EmptyAltError {}
+
+/**
+ * Tries each parser in order like the tuple impls of alt, returning the first success.
+ */
+fn alt_slice<'a, I, O, E, PType, P>(parsers: &[P], input: &mut &'a I) -> Result<O, E>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P: Parser<'a, I, O, E, PType>,
+{
+    let startloc = *input;
+    let mut furthest = Furthest::new();
+    for parser in parsers {
+        match parser.fab(input) {
+            Ok(res) => {
+                return Ok(res);
+            }
+            Err(mut err) => {
+                //More input might let this branch match, so don't try the others.
+                if err.is_incomplete() {
+                    *input = startloc;
+                    err.add_context(startloc, ParserType::Alt);
+                    return Err(err);
+                }
+                furthest.add(err);
+            }
+        }
+        *input = startloc;
+    }
+    let mut maxlocerr = furthest
+        .finish()
+        .unwrap_or_else(|| E::from_external_error(startloc, ParserType::Alt, EmptyAltError));
+    maxlocerr.add_context(startloc, ParserType::Alt);
+    Err(maxlocerr)
+}
+
+pub struct AltSlice<PType> {
+    ptype: PhantomData<PType>,
+}
+
+impl<'a, I: ?Sized + Sequence, O, E: ParserError, P, PType> Parser<'a, I, O, E, AltSlice<PType>>
+    for Alt<&[P]>
+where
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        alt_slice(self.0, input)
+    }
+}
+
+impl<'a, I: ?Sized + Sequence, O, E: ParserError, P, PType> Parser<'a, I, O, E, AltSlice<PType>>
+    for Alt<Vec<P>>
+where
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        alt_slice(&self.0, input)
+    }
+}
+
+/**
+ * Picks the error from the branch that made the furthest progress like alt, and attaches
+ * the errors of the other branches to it as alternatives.
+ */
+fn alt_verbose_error<I, E>(mut errors: Vec<E>, startloc: &I) -> E
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+{
+    let mut maxloc = None;
+    let mut maxindex = errors.len().checked_sub(1);
+    for (index, err) in errors.iter().enumerate() {
+        if let Some(loc) = err.get_loc() {
+            if maxloc.is_none() || maxloc.is_some_and(|val| loc >= val) {
+                maxloc = Some(loc);
+                maxindex = Some(index);
+            }
+        }
+    }
+    let mut maxlocerr = match maxindex {
+        Some(index) => errors.remove(index),
+        None => E::from_external_error(startloc, ParserType::Alt, EmptyAltError),
+    };
+    maxlocerr.add_context(startloc, ParserType::Alt);
+    maxlocerr.add_alternatives(errors);
+    maxlocerr
+}
+
+macro_rules! alt_verbose_impl {
+    ( $tstruct:ident $count:literal $( $parser:ident $parserlower:ident $ptype:ident)+ ) => {
+        #[derive(Clone, Debug)]
+        pub struct $tstruct<$($ptype,)+> {
+            $(
+                $parserlower : PhantomData<$ptype>,
+            )+
+        }
+
+        impl<'a, I: ?Sized + Sequence, O, E: ParserError, $($parser, $ptype,)+> Parser<'a, I, O, E, $tstruct<$($ptype,)+>> for AltVerbose<($($parser,)+)>
+            where $(
+                $parser: Parser<'a, I, O, E, $ptype>,
+            )+{
+            fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+                let startloc = *input;
+                let mut errors = Vec::with_capacity($count);
+                let  ($($parserlower,)+) = &self.0;
+                $(
+                    match $parserlower.fab(input) {
+                        Ok(res) => {
+                            return Ok(res);
+                        }
+                        Err(mut err) => {
+                            //More input might let this branch match, so don't try the others.
+                            if err.is_incomplete() {
+                                *input = startloc;
+                                err.add_context(startloc, ParserType::Alt);
+                                return Err(err);
+                            }
+                            errors.push(err);
+                        }
+                    }
+                    *input = startloc;
+                )+
+                Err(alt_verbose_error(errors, startloc))
+            }
+        }
+
+    };
+}
+
+alt_verbose_impl!(AltVerbose1 1 P1 p1 T1);
+alt_verbose_impl!(AltVerbose2 2 P1 p1 T1 P2 p2 T2);
+alt_verbose_impl!(AltVerbose3 3 P1 p1 T1 P2 p2 T2 P3 p3 T3);
+alt_verbose_impl!(AltVerbose4 4 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4);
+alt_verbose_impl!(AltVerbose5 5 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5);
+alt_verbose_impl!(AltVerbose6 6 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6);
+alt_verbose_impl!(AltVerbose7 7 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6 P7 p7 T7);
+alt_verbose_impl!(AltVerbose8 8 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6 P7 p7 T7 P8 p8 T8);
+alt_verbose_impl!(AltVerbose9 9 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6 P7 p7 T7 P8 p8 T8 P9 p9 T9);
+alt_verbose_impl!(AltVerbose10 10 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6 P7 p7 T7 P8 p8 T8 P9 p9 T9 P10 p10 T10);
+alt_verbose_impl!(AltVerbose11 11 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6 P7 p7 T7 P8 p8 T8 P9 p9 T9 P10 p10 T10 P11 p11 T11);
+
+/**
+ * Tries each parser in order like alt_verbose for tuples.
+ */
+fn alt_verbose_slice<'a, I, O, E, PType, P>(parsers: &[P], input: &mut &'a I) -> Result<O, E>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P: Parser<'a, I, O, E, PType>,
+{
+    let startloc = *input;
+    let mut errors = Vec::with_capacity(parsers.len());
+    for parser in parsers {
+        match parser.fab(input) {
+            Ok(res) => {
+                return Ok(res);
+            }
+            Err(mut err) => {
+                //More input might let this branch match, so don't try the others.
+                if err.is_incomplete() {
+                    *input = startloc;
+                    err.add_context(startloc, ParserType::Alt);
+                    return Err(err);
+                }
+                errors.push(err);
+            }
+        }
+        *input = startloc;
+    }
+    Err(alt_verbose_error(errors, startloc))
+}
+
+impl<'a, I: ?Sized + Sequence, O, E: ParserError, P, PType> Parser<'a, I, O, E, AltSlice<PType>>
+    for AltVerbose<&[P]>
+where
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        alt_verbose_slice(self.0, input)
+    }
+}
+
+impl<'a, I: ?Sized + Sequence, O, E: ParserError, P, PType> Parser<'a, I, O, E, AltSlice<PType>>
+    for AltVerbose<Vec<P>>
+where
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        alt_verbose_slice(&self.0, input)
+    }
+}
+
+/**
+ * Tracks the branches of an alt_longest. It keeps the output of the branch that consumed
+ * the most input, and picks the error the same way as alt in case every branch fails.
+ */
+struct Longest<'a, I: ?Sized, O, E> {
+    best: Option<(O, &'a I)>,
+    furthest: Furthest<E>,
+}
+
+impl<'a, I: ?Sized + Sequence, O, E: ParserError> Longest<'a, I, O, E> {
+    fn new() -> Self {
+        Longest {
+            best: None,
+            furthest: Furthest::new(),
+        }
+    }
+    /**
+     * Records the result of the branch at index and restores the input for the next one.
+     * Fails right away if more input might let the branch match.
+     */
+    fn add(&mut self, startloc: &'a I, input: &mut &'a I, res: Result<O, E>) -> Result<(), E> {
+        match res {
+            Ok(out) => {
+                //Ties go to the earlier branch, so only a strictly longer match replaces it.
+                if self
+                    .best
+                    .as_ref()
+                    .is_none_or(|(_, rest)| input.len() < rest.len())
+                {
+                    self.best = Some((out, *input));
+                }
+            }
+            Err(mut err) => {
+                //More input might make this branch the longest, so don't pick one yet.
+                if err.is_incomplete() {
+                    *input = startloc;
+                    err.add_context(startloc, ParserType::Alt);
+                    return Err(err);
+                }
+                self.furthest.add(err);
+            }
+        }
+        *input = startloc;
+        Ok(())
+    }
+    fn finish(self, startloc: &'a I, input: &mut &'a I) -> Result<O, E> {
+        match self.best {
+            Some((out, rest)) => {
+                *input = rest;
+                Ok(out)
+            }
+            None => {
+                let mut err = self.furthest.finish().unwrap_or_else(|| {
+                    E::from_external_error(startloc, ParserType::Alt, EmptyAltError)
+                });
+                err.add_context(startloc, ParserType::Alt);
+                Err(err)
+            }
+        }
+    }
+}
+
+macro_rules! alt_longest_impl {
+    ( $tstruct:ident $( $parser:ident $parserlower:ident $ptype:ident)+ ) => {
+        #[derive(Clone, Debug)]
+        pub struct $tstruct<$($ptype,)+> {
+            $(
+                $parserlower : PhantomData<$ptype>,
+            )+
+        }
+
+        #[allow(unused_assignments)]
+        impl<'a, I: ?Sized + Sequence, O, E: ParserError, $($parser, $ptype,)+> Parser<'a, I, O, E, $tstruct<$($ptype,)+>> for AltLongest<($($parser,)+)>
+            where $(
+                $parser: Parser<'a, I, O, E, $ptype>,
+            )+{
+            fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+                let startloc = *input;
+                let mut longest = Longest::new();
+                let  ($($parserlower,)+) = &self.0;
+                $(
+                    let res = $parserlower.fab(input);
+                    longest.add(startloc, input, res)?;
+                )+
+                longest.finish(startloc, input)
+            }
+        }
+
+    };
+}
+
+alt_longest_impl!(AltLongest1 P1 p1 T1);
+alt_longest_impl!(AltLongest2 P1 p1 T1 P2 p2 T2);
+alt_longest_impl!(AltLongest3 P1 p1 T1 P2 p2 T2 P3 p3 T3);
+alt_longest_impl!(AltLongest4 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4);
+alt_longest_impl!(AltLongest5 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5);
+alt_longest_impl!(AltLongest6 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6);
+alt_longest_impl!(AltLongest7 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6 P7 p7 T7);
+alt_longest_impl!(AltLongest8 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6 P7 p7 T7 P8 p8 T8);
+alt_longest_impl!(AltLongest9 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6 P7 p7 T7 P8 p8 T8 P9 p9 T9);
+alt_longest_impl!(AltLongest10 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6 P7 p7 T7 P8 p8 T8 P9 p9 T9 P10 p10 T10);
+alt_longest_impl!(AltLongest11 P1 p1 T1 P2 p2 T2 P3 p3 T3 P4 p4 T4 P5 p5 T5 P6 p6 T6 P7 p7 T7 P8 p8 T8 P9 p9 T9 P10 p10 T10 P11 p11 T11);
+
+/**
+ * Runs every parser like alt_longest for tuples.
+ */
+fn alt_longest_slice<'a, I, O, E, PType, P>(parsers: &[P], input: &mut &'a I) -> Result<O, E>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P: Parser<'a, I, O, E, PType>,
+{
+    let startloc = *input;
+    let mut longest = Longest::new();
+    for parser in parsers {
+        let res = parser.fab(input);
+        longest.add(startloc, input, res)?;
+    }
+    longest.finish(startloc, input)
+}
+
+impl<'a, I: ?Sized + Sequence, O, E: ParserError, P, PType> Parser<'a, I, O, E, AltSlice<PType>>
+    for AltLongest<&[P]>
+where
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        alt_longest_slice(self.0, input)
+    }
+}
+
+impl<'a, I: ?Sized + Sequence, O, E: ParserError, P, PType> Parser<'a, I, O, E, AltSlice<PType>>
+    for AltLongest<Vec<P>>
+where
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        alt_longest_slice(&self.0, input)
+    }
+}
+
 macro_rules! permutation_impl {
+    //Fills in the members in any order, running sep before each member after the first.
+    (@run $input:ident $sep:ident $($parserlower:ident $rval:ident)+ ) => {{
+        let outer_startloc = *$input;
+        $(
+            let mut $rval = None;
+        )+
+        loop {
+            if $($rval.is_some())&&+ {
+                return Ok(($($rval.expect("Something went wrong in the permutation implementation"),)+));
+            }
+            if $($rval.is_some())||+ {
+                if let Err(mut err) = $sep($input) {
+                    *$input = outer_startloc;
+                    err.add_context(outer_startloc, ParserType::Permutation);
+                    return Err(err);
+                }
+            }
+            let startloc = *$input;
+            let mut furthest = Furthest::new();
+            $(
+                if ($rval.is_none()) {
+                    match $parserlower.fab($input) {
+                        Ok(res) => {
+                            $rval = Some(res);
+                            continue;
+                        }
+                        Err(mut err) => {
+                            //More input might let this branch match, so don't try the others.
+                            if err.is_incomplete() {
+                                *$input = outer_startloc;
+                                err.add_context(outer_startloc, ParserType::Permutation);
+                                return Err(err);
+                            }
+                            furthest.add(err);
+                            *$input = startloc;
+                        }
+                    }
+                }
+            )+
+            //At this point, none of the remaining parsers have made any progress, and there
+            //is at least one of them, so there is an error.
+            *$input = outer_startloc;
+            let mut maxlocerr = furthest.finish().expect("Something went wrong in the permutation parser.");
+            maxlocerr.add_context(outer_startloc, ParserType::Permutation);
+            return Err(maxlocerr);
+        }
+    }};
     ( $tstruct:ident $($parser:ident $parserlower:ident $rval:ident $otype:ident $ptype:ident)+ ) => {
+        #[derive(Clone, Debug)]
         pub struct $tstruct<$($ptype,)+> {
             $(
                 $parserlower : PhantomData<$ptype>,
@@ -81,50 +700,117 @@ macro_rules! permutation_impl {
                 $parser: Parser<'a, I, $otype, E, $ptype>,
             )+{
             fn fab(&self, input: &mut &'a I) -> Result<($($otype,)+), E> {
+                let ($($parserlower,)+) = &self.0;
+                let no_sep = |_: &mut &'a I| Ok::<(), E>(());
+                permutation_impl!(@run input no_sep $($parserlower $rval)+)
+            }
+        }
+
+        #[allow(unused_assignments)]
+        impl<'a, I: ?Sized + Sequence, $($otype, )+ E: ParserError, $($parser, $ptype,)+ S, TS, OS>
+            Parser<'a, I, ($($otype,)+), E, PermutationSeparatedParser<$tstruct<$($ptype,)+>, TS, OS>>
+            for PermutationSeparated<($($parser,)+), S>
+            where $(
+                $parser: Parser<'a, I, $otype, E, $ptype>,
+            )+
+            S: Parser<'a, I, OS, E, TS>,
+        {
+            fn fab(&self, input: &mut &'a I) -> Result<($($otype,)+), E> {
+                let ($($parserlower,)+) = &self.parsers;
+                let sep = |input: &mut &'a I| self.sep.fab(input).map(|_| ());
+                permutation_impl!(@run input sep $($parserlower $rval)+)
+            }
+        }
+    };
+}
+
+/**
+ * This struct can be constructed through the method `separated_by` on [`Permutation`].
+ * It requires the separator between consecutive members.
+ */
+#[derive(Clone, Debug)]
+pub struct PermutationSeparated<T, S> {
+    pub parsers: T,
+    pub sep: S,
+}
+
+pub struct PermutationSeparatedParser<M, TS, OS> {
+    members: PhantomData<M>,
+    sep: PhantomData<TS>,
+    sep_out: PhantomData<OS>,
+}
+
+impl<T> Permutation<T> {
+    /**
+     * Requires `sep` between consecutive members, but not before the first member or after
+     * the last, so `permutation((p1, p2)).separated_by(' ')` matches `a b` and `b a` but not
+     * `ab`. The outputs of the separators are discarded. A separator that isn't followed by
+     * a member fails the whole permutation, so no input is consumed.
+     */
+    pub fn separated_by<S>(self, sep: S) -> PermutationSeparated<T, S> {
+        PermutationSeparated {
+            parsers: self.0,
+            sep,
+        }
+    }
+}
+
+permutation_impl!(SeqAlt1 P1 p1 r1 O1 T1);
+permutation_impl!(SeqAlt2 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2);
+permutation_impl!(SeqAlt3 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3);
+permutation_impl!(SeqAlt4 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4);
+permutation_impl!(SeqAlt5 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5);
+permutation_impl!(SeqAlt6 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6);
+permutation_impl!(SeqAlt7 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7);
+permutation_impl!(SeqAlt8 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8);
+permutation_impl!(SeqAlt9 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9);
+permutation_impl!(SeqAlt10 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10);
+permutation_impl!(SeqAlt11 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11);
+
+macro_rules! permutation_opt_impl {
+    ( $tstruct:ident $($parser:ident $parserlower:ident $rval:ident $otype:ident $ptype:ident)+ ) => {
+        #[derive(Clone, Debug)]
+        pub struct $tstruct<$($ptype,)+> {
+            $(
+                $parserlower : PhantomData<$ptype>,
+            )+
+        }
+
+        impl<'a, I: ?Sized + Sequence, $($otype, )+ E: ParserError, $($parser, $ptype,)+> Parser<'a, I, ($(Option<$otype>,)+), E, $tstruct<$($ptype,)+>> for PermutationOpt<($($parser,)+)>
+            where $(
+                $parser: Parser<'a, I, $otype, E, $ptype>,
+            )+{
+            fn fab(&self, input: &mut &'a I) -> Result<($(Option<$otype>,)+), E> {
                 let outer_startloc = *input;
                 let ($($parserlower,)+) = &self.0;
                 $(
                     let mut $rval = None;
                 )+
-                loop {
+                //Each pass fills in at most one member and each member is filled at most
+                //once, so this loop always terminates even if a member consumes no input.
+                'outer: loop {
                     let startloc = *input;
-                    let mut done = true;
-                    let mut maxloc = None;
-                    let mut maxlocerr = None;
                     $(
                         if ($rval.is_none()) {
-                            done = false;
                             match $parserlower.fab(input) {
                                 Ok(res) => {
                                     $rval = Some(res);
-                                    continue;
+                                    continue 'outer;
+                                }
+                                //More input might let this member match, so fail rather than skip it.
+                                Err(mut err) if err.is_incomplete() => {
+                                    *input = outer_startloc;
+                                    err.add_context(outer_startloc, ParserType::Permutation);
+                                    return Err(err);
                                 }
-                                Err(err) => {
-                                    //If the error type supports location, take the error from the
-                                    //parser that made the most progress.
-                                    if let Some(loc) = err.get_loc() {
-                                        if maxloc.is_none() || maxloc.is_some_and(|val| loc >= val) {
-                                            maxloc = Some(loc);
-                                            maxlocerr = Some(err);
-                                        }
-                                    } else {
-                                        maxlocerr = Some(err);
-                                    }
+                                Err(_) => {
                                     *input = startloc;
                                 }
                             }
                         }
                     )+
-                    //At this point, none of the parsers have made any progress
-                    if (done) {
-                        return Ok(($($rval.expect("Something went wrong in the permutation implementation"),)+));
-                    } else {
-                        *input = outer_startloc;
-                        //If no parsers fail, then done will be true and this line won't run.
-                        let mut maxlocerr = maxlocerr.expect("Something went wrong in the permutation parser.");
-                        maxlocerr.add_context(outer_startloc, ParserType::Permutation);
-                        return Err(maxlocerr);
-                    }
+                    //None of the remaining members matched, so the permutation is finished.
+                    return Ok(($($rval,)+));
                 }
             }
         }
@@ -132,20 +818,21 @@ macro_rules! permutation_impl {
     };
 }
 
-permutation_impl!(SeqAlt1 P1 p1 r1 O1 T1);
-permutation_impl!(SeqAlt2 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2);
-permutation_impl!(SeqAlt3 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3);
-permutation_impl!(SeqAlt4 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4);
-permutation_impl!(SeqAlt5 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5);
-permutation_impl!(SeqAlt6 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6);
-permutation_impl!(SeqAlt7 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7);
-permutation_impl!(SeqAlt8 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8);
-permutation_impl!(SeqAlt9 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9);
-permutation_impl!(SeqAlt10 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10);
-permutation_impl!(SeqAlt11 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11);
+permutation_opt_impl!(SeqAltOpt1 P1 p1 r1 O1 T1);
+permutation_opt_impl!(SeqAltOpt2 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2);
+permutation_opt_impl!(SeqAltOpt3 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3);
+permutation_opt_impl!(SeqAltOpt4 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4);
+permutation_opt_impl!(SeqAltOpt5 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5);
+permutation_opt_impl!(SeqAltOpt6 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6);
+permutation_opt_impl!(SeqAltOpt7 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7);
+permutation_opt_impl!(SeqAltOpt8 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8);
+permutation_opt_impl!(SeqAltOpt9 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9);
+permutation_opt_impl!(SeqAltOpt10 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10);
+permutation_opt_impl!(SeqAltOpt11 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11);
 
 macro_rules! sequence_impl {
     ( $tstruct:ident $( $parser:ident $parserlower:ident $rval:ident $otype:ident $ptype:ident)+ ) => {
+        #[derive(Clone, Debug)]
         pub struct $tstruct<$($ptype,)+> {
             $(
                 $parserlower : PhantomData<$ptype>,
@@ -191,3 +878,53 @@ sequence_impl!(Seq8 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4
 sequence_impl!(Seq9 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9);
 sequence_impl!(Seq10 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10);
 sequence_impl!(Seq11 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11);
+sequence_impl!(Seq12 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12);
+sequence_impl!(Seq13 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13);
+sequence_impl!(Seq14 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14);
+sequence_impl!(Seq15 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15);
+sequence_impl!(Seq16 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15 P16 p16 r16 O16 T16);
+sequence_impl!(Seq17 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15 P16 p16 r16 O16 T16 P17 p17 r17 O17 T17);
+sequence_impl!(Seq18 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15 P16 p16 r16 O16 T16 P17 p17 r17 O17 T17 P18 p18 r18 O18 T18);
+sequence_impl!(Seq19 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15 P16 p16 r16 O16 T16 P17 p17 r17 O17 T17 P18 p18 r18 O18 T18 P19 p19 r19 O19 T19);
+sequence_impl!(Seq20 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15 P16 p16 r16 O16 T16 P17 p17 r17 O17 T17 P18 p18 r18 O18 T18 P19 p19 r19 O19 T19 P20 p20 r20 O20 T20);
+sequence_impl!(Seq21 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15 P16 p16 r16 O16 T16 P17 p17 r17 O17 T17 P18 p18 r18 O18 T18 P19 p19 r19 O19 T19 P20 p20 r20 O20 T20 P21 p21 r21 O21 T21);
+
+/**
+ * This struct can be constructed through the function `separated_pair`.
+ */
+#[derive(Clone, Debug)]
+pub struct SeparatedPair<P1, S, P2> {
+    pub first: P1,
+    pub sep: S,
+    pub second: P2,
+}
+
+pub struct SeparatedPairParser<T1, TS, OS, T2> {
+    first: PhantomData<T1>,
+    sep: PhantomData<TS>,
+    sep_out: PhantomData<OS>,
+    second: PhantomData<T2>,
+}
+
+impl<'a, I, O1, OS, O2, E, P1, S, P2, T1, TS, T2>
+    Parser<'a, I, (O1, O2), E, SeparatedPairParser<T1, TS, OS, T2>> for SeparatedPair<P1, S, P2>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P1: Parser<'a, I, O1, E, T1>,
+    S: Parser<'a, I, OS, E, TS>,
+    P2: Parser<'a, I, O2, E, T2>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<(O1, O2), E> {
+        let startloc = *input;
+        let res = self.first.fab(input).and_then(|first| {
+            self.sep.fab(input)?;
+            Ok((first, self.second.fab(input)?))
+        });
+        res.map_err(|mut err| {
+            err.add_context(startloc, ParserType::Sequence);
+            *input = startloc;
+            err
+        })
+    }
+}
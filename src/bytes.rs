@@ -0,0 +1,89 @@
+use std::{error::Error, fmt::Display, marker::PhantomData};
+
+use crate::{error::get_offset, Parser, ParserError, ParserType};
+
+/**
+ * This error is used as the cause when a parser wrapped by `StrBytes::as_str` consumes
+ * bytes that don't start and end on char boundaries.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub struct CharBoundaryError;
+impl Display for CharBoundaryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Matched bytes are not on char boundaries")
+    }
+}
+
+impl Error for CharBoundaryError {}
+
+/**
+ * A byte level view of a str. Parsing the bytes of the str lets parsers work one u8 at
+ * a time, with byte literals such as `b'a'` and `b"abc"` as tags. Matched bytes can be
+ * converted back into slices of the original str without revalidating them as UTF-8.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct StrBytes<'a> {
+    original: &'a str,
+}
+
+impl<'a> StrBytes<'a> {
+    pub fn new(original: &'a str) -> Self {
+        StrBytes { original }
+    }
+    /**
+     * Returns the bytes of the str, to be used as the input.
+     */
+    pub fn input(&self) -> &'a [u8] {
+        self.original.as_bytes()
+    }
+    /**
+     * Converts a slice of the input back into a slice of the str. Returns None
+     * if the bytes aren't part of the input or don't start and end on char boundaries.
+     */
+    pub fn to_str(&self, bytes: &[u8]) -> Option<&'a str> {
+        let start = get_offset(self.input(), bytes.as_ptr() as usize)?;
+        self.original.get(start..start.checked_add(bytes.len())?)
+    }
+    /**
+     * Creates a parser that returns the part of the str consumed by the underlying
+     * parser. It fails with a [`CharBoundaryError`] cause if the consumed bytes don't
+     * start and end on char boundaries, which can't happen when only ASCII is matched.
+     */
+    pub fn as_str<P>(&self, parser: P) -> BytesAsStr<'a, P> {
+        BytesAsStr {
+            view: *self,
+            parser,
+        }
+    }
+}
+
+/**
+ * This struct can be constructed through the method `as_str` on [`StrBytes`].
+ */
+#[derive(Clone, Debug)]
+pub struct BytesAsStr<'a, P> {
+    pub view: StrBytes<'a>,
+    pub parser: P,
+}
+
+pub struct BytesAsStrParser<O, PType> {
+    phantom_o: PhantomData<O>,
+    phantom_ptype: PhantomData<PType>,
+}
+
+impl<'a, O, E, P, PType> Parser<'a, [u8], &'a str, E, BytesAsStrParser<O, PType>>
+    for BytesAsStr<'a, P>
+where
+    E: ParserError,
+    P: Parser<'a, [u8], O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a [u8]) -> Result<&'a str, E> {
+        let checkpoint = *input;
+        self.parser.fab(input)?;
+        let consumed = &checkpoint[..checkpoint.len() - input.len()];
+        self.view.to_str(consumed).ok_or_else(|| {
+            *input = checkpoint;
+            E::from_external_error(checkpoint, ParserType::Map, CharBoundaryError)
+        })
+    }
+}
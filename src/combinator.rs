@@ -1,6 +1,20 @@
-use std::{error::Error, marker::PhantomData};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    error::Error,
+    fmt::{Debug, Display},
+    marker::PhantomData,
+    rc::Rc,
+    sync::Arc,
+};
 
-use crate::{sequence::Sequence, Parser, ParserError, ParserType};
+use crate::{
+    error::{get_from_start, loc_offset, Expected},
+    repeat::loc,
+    sequence::{InputWrapper, Sequence},
+    Parser, ParserError, ParserType,
+};
 
 pub struct ParserMap<P, I: ?Sized, M, E, F> {
     pub parser: P,
@@ -9,6 +23,26 @@ pub struct ParserMap<P, I: ?Sized, M, E, F> {
     pub phantom_e: PhantomData<E>,
     pub phantom_m: PhantomData<M>,
 }
+//Clone and Debug are implemented by hand rather than derived, since deriving them would
+//require the phantom input and error types to implement them too.
+impl<P: Clone, I: ?Sized, M, E, F: Clone> Clone for ParserMap<P, I, M, E, F> {
+    fn clone(&self) -> Self {
+        ParserMap {
+            parser: self.parser.clone(),
+            func: self.func.clone(),
+            phantom_i: PhantomData,
+            phantom_e: PhantomData,
+            phantom_m: PhantomData,
+        }
+    }
+}
+impl<P: Debug, I: ?Sized, M, E, F> Debug for ParserMap<P, I, M, E, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParserMap")
+            .field("parser", &self.parser)
+            .finish_non_exhaustive()
+    }
+}
 pub struct ParserMapT<PType, M> {
     phantom_ptype: PhantomData<PType>,
     phantom_m: PhantomData<M>,
@@ -31,7 +65,58 @@ where
     }
 }
 
-#[derive(Clone, Debug)]
+/**
+ * This struct can be constructed through the method `fab_map_with_span`. It behaves like
+ * [`ParserMap`], but the function also receives the slice of input that was consumed.
+ */
+pub struct ParserMapWithSpan<P, I: ?Sized, M, E, F> {
+    pub parser: P,
+    pub func: F,
+    pub phantom_i: PhantomData<I>,
+    pub phantom_e: PhantomData<E>,
+    pub phantom_m: PhantomData<M>,
+}
+impl<P: Clone, I: ?Sized, M, E, F: Clone> Clone for ParserMapWithSpan<P, I, M, E, F> {
+    fn clone(&self) -> Self {
+        ParserMapWithSpan {
+            parser: self.parser.clone(),
+            func: self.func.clone(),
+            phantom_i: PhantomData,
+            phantom_e: PhantomData,
+            phantom_m: PhantomData,
+        }
+    }
+}
+impl<P: Debug, I: ?Sized, M, E, F> Debug for ParserMapWithSpan<P, I, M, E, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParserMapWithSpan")
+            .field("parser", &self.parser)
+            .finish_non_exhaustive()
+    }
+}
+pub struct ParserMapWithSpanT<PType, M> {
+    phantom_ptype: PhantomData<PType>,
+    phantom_m: PhantomData<M>,
+}
+impl<'a, P, M, I, O, E: ParserError, PType, F> Parser<'a, I, O, E, ParserMapWithSpanT<PType, M>>
+    for ParserMapWithSpan<P, I, M, E, F>
+where
+    P: Parser<'a, I, M, E, PType>,
+    F: Fn(M, &'a I) -> O,
+    I: ?Sized + Sequence + 'a,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        let checkpoint = *input;
+        match self.parser.fab(input) {
+            Ok(res) => Ok((self.func)(res, checkpoint.subtract(*input))),
+            Err(mut err) => {
+                err.add_context(*input, ParserType::Map);
+                Err(err)
+            }
+        }
+    }
+}
+
 pub struct ParserTryMap<P, I: ?Sized, M, E, F> {
     pub parser: P,
     pub func: F,
@@ -39,6 +124,24 @@ pub struct ParserTryMap<P, I: ?Sized, M, E, F> {
     pub phantom_e: PhantomData<E>,
     pub phantom_m: PhantomData<M>,
 }
+impl<P: Clone, I: ?Sized, M, E, F: Clone> Clone for ParserTryMap<P, I, M, E, F> {
+    fn clone(&self) -> Self {
+        ParserTryMap {
+            parser: self.parser.clone(),
+            func: self.func.clone(),
+            phantom_i: PhantomData,
+            phantom_e: PhantomData,
+            phantom_m: PhantomData,
+        }
+    }
+}
+impl<P: Debug, I: ?Sized, M, E, F> Debug for ParserTryMap<P, I, M, E, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParserTryMap")
+            .field("parser", &self.parser)
+            .finish_non_exhaustive()
+    }
+}
 
 pub struct ParserTryMapOption<PType, M> {
     phantom_ptype: PhantomData<PType>,
@@ -61,7 +164,7 @@ where
                 })
             }
             Err(mut err) => {
-                err.add_context(checkpoint, ParserType::Map);
+                err.add_context(checkpoint, ParserType::TryMap);
                 Err(err)
             }
         }
@@ -76,7 +179,7 @@ impl<'a, P, M, I, O, E: ParserError, PType, FErr, F>
     Parser<'a, I, O, E, ParserTryMapResult<PType, M, FErr>> for ParserTryMap<P, I, M, E, F>
 where
     P: Parser<'a, I, M, E, PType>,
-    FErr: Error + Send + Sync + 'static,
+    FErr: Into<Box<dyn Error + Send + Sync>>,
     F: Fn(M) -> Result<O, FErr>,
     I: ?Sized + Sequence,
 {
@@ -87,11 +190,11 @@ where
                 let func_result = (self.func)(res);
                 func_result.map_err(|err| {
                     *input = checkpoint;
-                    E::from_external_error(*input, ParserType::TryMap, err)
+                    E::from_boxed_error(*input, ParserType::TryMap, err)
                 })
             }
             Err(mut err) => {
-                err.add_context(checkpoint, ParserType::Map);
+                err.add_context(checkpoint, ParserType::TryMap);
                 Err(err)
             }
         }
@@ -109,12 +212,504 @@ where
     P: Parser<'a, I, O, E, ParserType>,
 {
     fn fab(&self, input: &mut &'a I) -> Result<Option<O>, E> {
+        let checkpoint = *input;
+        match self.parser.fab(input) {
+            Ok(out) => Ok(Some(out)),
+            //Restore the input even if the underlying parser didn't, so a failure never
+            //leaves it advanced.
+            Err(err) => {
+                *input = checkpoint;
+                if err.is_incomplete() {
+                    Err(err)
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+}
+/**
+ * This struct can be constructed through the method `fab_opt`. It behaves exactly
+ * like [`Opt`]. The phantom types are needed for type inference in method position.
+ */
+pub struct ParserOpt<P, I: ?Sized, O, E> {
+    pub parser: P,
+    pub phantom_i: PhantomData<I>,
+    pub phantom_o: PhantomData<O>,
+    pub phantom_e: PhantomData<E>,
+}
+impl<P: Clone, I: ?Sized, O, E> Clone for ParserOpt<P, I, O, E> {
+    fn clone(&self) -> Self {
+        ParserOpt {
+            parser: self.parser.clone(),
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+}
+impl<P: Debug, I: ?Sized, O, E> Debug for ParserOpt<P, I, O, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParserOpt")
+            .field("parser", &self.parser)
+            .finish()
+    }
+}
+pub struct ParserOptT<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+impl<'a, P, I: ?Sized, O, E: ParserError, PType> Parser<'a, I, Option<O>, E, ParserOptT<PType>>
+    for ParserOpt<P, I, O, E>
+where
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<Option<O>, E> {
+        let checkpoint = *input;
         match self.parser.fab(input) {
             Ok(out) => Ok(Some(out)),
-            Err(_) => Ok(None),
+            Err(err) => {
+                *input = checkpoint;
+                if err.is_incomplete() {
+                    Err(err)
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+}
+/**
+ * This struct can be constructed through the method `fab_or`. It behaves like
+ * [`ParserOpt`], but returns a clone of the default instead of None.
+ */
+pub struct ParserOr<P, I: ?Sized, O, E> {
+    pub parser: P,
+    pub default: O,
+    pub phantom_i: PhantomData<I>,
+    pub phantom_e: PhantomData<E>,
+}
+impl<P: Clone, I: ?Sized, O: Clone, E> Clone for ParserOr<P, I, O, E> {
+    fn clone(&self) -> Self {
+        ParserOr {
+            parser: self.parser.clone(),
+            default: self.default.clone(),
+            phantom_i: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+}
+impl<P: Debug, I: ?Sized, O: Debug, E> Debug for ParserOr<P, I, O, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParserOr")
+            .field("parser", &self.parser)
+            .field("default", &self.default)
+            .finish()
+    }
+}
+pub struct ParserOrT<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+impl<'a, P, I: ?Sized, O: Clone, E: ParserError, PType> Parser<'a, I, O, E, ParserOrT<PType>>
+    for ParserOr<P, I, O, E>
+where
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        let checkpoint = *input;
+        match self.parser.fab(input) {
+            Ok(out) => Ok(out),
+            Err(err) => {
+                *input = checkpoint;
+                if err.is_incomplete() {
+                    Err(err)
+                } else {
+                    Ok(self.default.clone())
+                }
+            }
+        }
+    }
+}
+/**
+ * This struct can be constructed through the method `fab_or_else`. It behaves like
+ * [`ParserOpt`], but calls the function for the output instead of returning None.
+ */
+pub struct ParserOrElse<P, I: ?Sized, O, E, F> {
+    pub parser: P,
+    pub func: F,
+    pub phantom_i: PhantomData<I>,
+    pub phantom_o: PhantomData<O>,
+    pub phantom_e: PhantomData<E>,
+}
+impl<P: Clone, I: ?Sized, O, E, F: Clone> Clone for ParserOrElse<P, I, O, E, F> {
+    fn clone(&self) -> Self {
+        ParserOrElse {
+            parser: self.parser.clone(),
+            func: self.func.clone(),
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+}
+impl<P: Debug, I: ?Sized, O, E, F> Debug for ParserOrElse<P, I, O, E, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParserOrElse")
+            .field("parser", &self.parser)
+            .finish_non_exhaustive()
+    }
+}
+pub struct ParserOrElseT<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+impl<'a, P, I: ?Sized, O, E: ParserError, PType, F> Parser<'a, I, O, E, ParserOrElseT<PType>>
+    for ParserOrElse<P, I, O, E, F>
+where
+    P: Parser<'a, I, O, E, PType>,
+    F: Fn() -> O,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        let checkpoint = *input;
+        match self.parser.fab(input) {
+            Ok(out) => Ok(out),
+            Err(err) => {
+                *input = checkpoint;
+                if err.is_incomplete() {
+                    Err(err)
+                } else {
+                    Ok((self.func)())
+                }
+            }
+        }
+    }
+}
+/**
+ * This struct can be constructed through the method `fab_complete`. It fails
+ * if the underlying parser doesn't consume all of the input.
+ */
+pub struct Complete<P, I: ?Sized, O, E> {
+    pub parser: P,
+    pub phantom_i: PhantomData<I>,
+    pub phantom_o: PhantomData<O>,
+    pub phantom_e: PhantomData<E>,
+}
+impl<P: Clone, I: ?Sized, O, E> Clone for Complete<P, I, O, E> {
+    fn clone(&self) -> Self {
+        Complete {
+            parser: self.parser.clone(),
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+}
+impl<P: Debug, I: ?Sized, O, E> Debug for Complete<P, I, O, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Complete")
+            .field("parser", &self.parser)
+            .finish()
+    }
+}
+pub struct CompleteParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+impl<'a, P, I, O, E: ParserError, PType> Parser<'a, I, O, E, CompleteParser<PType>>
+    for Complete<P, I, O, E>
+where
+    P: Parser<'a, I, O, E, PType>,
+    I: ?Sized + Sequence,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        let checkpoint = *input;
+        match self.parser.fab(input) {
+            Ok(out) => {
+                if input.is_empty() {
+                    Ok(out)
+                } else {
+                    let mut err = E::from_parser_error(*input, ParserType::Complete);
+                    err.set_expected(Expected::Eof, input.found());
+                    err.add_context(checkpoint, ParserType::Complete);
+                    *input = checkpoint;
+                    Err(err)
+                }
+            }
+            Err(mut err) => {
+                *input = checkpoint;
+                err.add_context(checkpoint, ParserType::Complete);
+                Err(err)
+            }
+        }
+    }
+}
+/**
+ * This struct can be constructed through the method `fab_msg`. If the underlying
+ * parser fails, it adds a frame to the error with the message attached.
+ */
+pub struct WithMessage<P, I: ?Sized, O, E> {
+    pub parser: P,
+    pub message: Cow<'static, str>,
+    pub phantom_i: PhantomData<I>,
+    pub phantom_o: PhantomData<O>,
+    pub phantom_e: PhantomData<E>,
+}
+impl<P: Clone, I: ?Sized, O, E> Clone for WithMessage<P, I, O, E> {
+    fn clone(&self) -> Self {
+        WithMessage {
+            parser: self.parser.clone(),
+            message: self.message.clone(),
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+}
+impl<P: Debug, I: ?Sized, O, E> Debug for WithMessage<P, I, O, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WithMessage")
+            .field("parser", &self.parser)
+            .field("message", &self.message)
+            .finish()
+    }
+}
+pub struct WithMessageParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+impl<'a, P, I, O, E: ParserError, PType> Parser<'a, I, O, E, WithMessageParser<PType>>
+    for WithMessage<P, I, O, E>
+where
+    P: Parser<'a, I, O, E, PType>,
+    I: ?Sized + Sequence,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        let checkpoint = *input;
+        self.parser.fab(input).map_err(|mut err| {
+            *input = checkpoint;
+            err.add_context(checkpoint, ParserType::Message);
+            err.add_message(self.message.clone());
+            err
+        })
+    }
+}
+thread_local! {
+    static DBG_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+/**
+ * This struct can be constructed through the method `fab_dbg`. It prints the input
+ * before running the underlying parser and the result after, indented by how many
+ * fab_dbg parsers are running.
+ */
+pub struct Dbg<P, I: ?Sized, O, E> {
+    pub parser: P,
+    pub label: Cow<'static, str>,
+    pub phantom_i: PhantomData<I>,
+    pub phantom_o: PhantomData<O>,
+    pub phantom_e: PhantomData<E>,
+}
+impl<P: Clone, I: ?Sized, O, E> Clone for Dbg<P, I, O, E> {
+    fn clone(&self) -> Self {
+        Dbg {
+            parser: self.parser.clone(),
+            label: self.label.clone(),
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+}
+impl<P: Debug, I: ?Sized, O, E> Debug for Dbg<P, I, O, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Dbg")
+            .field("parser", &self.parser)
+            .field("label", &self.label)
+            .finish()
+    }
+}
+pub struct DbgParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+/**
+ * Restores the debug depth when dropped, so the indentation stays correct if the parser panics.
+ */
+struct DbgEntry(usize);
+impl Drop for DbgEntry {
+    fn drop(&mut self) {
+        DBG_DEPTH.set(self.0);
+    }
+}
+impl<'a, P, I, O, E: ParserError, PType> Parser<'a, I, O, E, DbgParser<PType>> for Dbg<P, I, O, E>
+where
+    P: Parser<'a, I, O, E, PType>,
+    I: ?Sized + Sequence + Debug,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        const WINDOW: usize = 10;
+        let checkpoint = *input;
+        let depth = DBG_DEPTH.get();
+        let indent = "  ".repeat(depth);
+        eprintln!(
+            "{}{}: {:?}",
+            indent,
+            self.label,
+            get_from_start(checkpoint, WINDOW)
+        );
+        let res = {
+            DBG_DEPTH.set(depth + 1);
+            let _entry = DbgEntry(depth);
+            self.parser.fab(input)
+        };
+        match &res {
+            Ok(_) => eprintln!(
+                "{}{}: ok, consumed {:?}",
+                indent,
+                self.label,
+                get_from_start(checkpoint.subtract(*input), WINDOW)
+            ),
+            Err(err) => match err.get_loc().and_then(|loc| loc_offset(checkpoint, loc)) {
+                Some(offset) => eprintln!("{}{}: failed at +{}", indent, self.label, offset),
+                None => eprintln!("{}{}: failed", indent, self.label),
+            },
         }
+        res
     }
 }
+/**
+ * This struct can be constructed through the method `fab_assert_restores`. It panics if
+ * the underlying parser fails without restoring the input. The check is a debug assertion,
+ * so it is skipped in release builds and the wrapper can be left in place.
+ */
+pub struct AssertRestores<P, I: ?Sized, O, E> {
+    pub parser: P,
+    pub phantom_i: PhantomData<I>,
+    pub phantom_o: PhantomData<O>,
+    pub phantom_e: PhantomData<E>,
+}
+impl<P: Clone, I: ?Sized, O, E> Clone for AssertRestores<P, I, O, E> {
+    fn clone(&self) -> Self {
+        AssertRestores {
+            parser: self.parser.clone(),
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+}
+impl<P: Debug, I: ?Sized, O, E> Debug for AssertRestores<P, I, O, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AssertRestores")
+            .field("parser", &self.parser)
+            .finish()
+    }
+}
+pub struct AssertRestoresParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+impl<'a, P, I, O, E: ParserError, PType> Parser<'a, I, O, E, AssertRestoresParser<PType>>
+    for AssertRestores<P, I, O, E>
+where
+    P: Parser<'a, I, O, E, PType>,
+    I: ?Sized + Sequence,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        let checkpoint = *input;
+        let res = self.parser.fab(input);
+        debug_assert!(
+            res.is_ok() || (loc(*input) == loc(checkpoint) && input.len() == checkpoint.len()),
+            "The parser failed without restoring the input, {} items were left consumed",
+            checkpoint.len().saturating_sub(input.len())
+        );
+        res
+    }
+}
+//Maps the input position to the result and the length consumed on success.
+type MemoCache<O, E> = HashMap<(usize, usize), Result<(O, usize), E>>;
+
+/**
+ * This struct can be constructed through the method `fab_memoize`. It caches the
+ * result of the underlying parser for each input position, so backtracking over the
+ * same input doesn't run it again. This needs the output and error to be Clone.
+ *
+ * The cache is keyed by the address and length of the input, so it must be cleared
+ * with `clear` before parsing a different input.
+ */
+pub struct Memoize<P, I: ?Sized, O, E> {
+    pub parser: P,
+    cache: RefCell<MemoCache<O, E>>,
+    pub phantom_i: PhantomData<I>,
+}
+
+impl<P, I: ?Sized, O, E> Memoize<P, I, O, E> {
+    /**
+     * Constructs a new memoizing parser. Prefer to use the method `fab_memoize` in the
+     * parser trait.
+     */
+    pub fn new(parser: P) -> Self {
+        Memoize {
+            parser,
+            cache: RefCell::new(HashMap::new()),
+            phantom_i: PhantomData,
+        }
+    }
+    /**
+     * Removes all cached results. Call this before parsing a different input.
+     */
+    pub fn clear(&self) {
+        self.cache.borrow_mut().clear();
+    }
+}
+//The clone starts with an empty cache.
+impl<P: Clone, I: ?Sized, O, E> Clone for Memoize<P, I, O, E> {
+    fn clone(&self) -> Self {
+        Memoize::new(self.parser.clone())
+    }
+}
+impl<P: Debug, I: ?Sized, O, E> Debug for Memoize<P, I, O, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Memoize")
+            .field("parser", &self.parser)
+            .finish_non_exhaustive()
+    }
+}
+pub struct MemoizeParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+impl<'a, P, I, O, E, PType> Parser<'a, I, O, E, MemoizeParser<PType>> for Memoize<P, I, O, E>
+where
+    P: Parser<'a, I, O, E, PType>,
+    I: ?Sized + Sequence,
+    O: Clone,
+    E: ParserError + Clone,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        let checkpoint = *input;
+        let key = (loc(checkpoint), checkpoint.len());
+        //The borrow ends before running the parser, since it may reenter this one.
+        let cached = self.cache.borrow().get(&key).cloned();
+        let res = match cached {
+            Some(res) => res,
+            None => {
+                let res = self
+                    .parser
+                    .fab(input)
+                    .map(|out| (out, checkpoint.len() - input.len()));
+                self.cache.borrow_mut().insert(key, res.clone());
+                res
+            }
+        };
+        match res {
+            Ok((out, consumed)) => {
+                let (_, rest) = checkpoint
+                    .try_split_at(consumed)
+                    .expect("The consumed length came from parsing this input");
+                *input = rest;
+                Ok(out)
+            }
+            Err(err) => {
+                *input = checkpoint;
+                Err(err)
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TakeNot<P> {
     pub parser: P,
@@ -137,6 +732,11 @@ where
                 *input = checkpoint;
                 Err(E::from_parser_error(*input, ParserType::TakeNot))
             }
+            //More input might let the parser match, so it isn't known whether to take the item.
+            Err(err) if err.is_incomplete() => {
+                *input = checkpoint;
+                Err(err)
+            }
             Err(_) => {
                 *input = checkpoint;
                 match input.try_split_front() {
@@ -144,13 +744,12 @@ where
                         *input = rest;
                         Ok(first)
                     }
-                    None => Err(E::from_parser_error(*input, ParserType::TakeNot)),
+                    None => Err(E::from_incomplete(*input, ParserType::TakeNot)),
                 }
             }
         }
     }
 }
-#[derive(Clone, Debug)]
 pub struct Value<P, V, I: ?Sized, O, E> {
     pub parser: P,
     pub value: V,
@@ -158,6 +757,25 @@ pub struct Value<P, V, I: ?Sized, O, E> {
     pub phantom_o: PhantomData<O>,
     pub phantom_e: PhantomData<E>,
 }
+impl<P: Clone, V: Clone, I: ?Sized, O, E> Clone for Value<P, V, I, O, E> {
+    fn clone(&self) -> Self {
+        Value {
+            parser: self.parser.clone(),
+            value: self.value.clone(),
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+}
+impl<P: Debug, V: Debug, I: ?Sized, O, E> Debug for Value<P, V, I, O, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Value")
+            .field("parser", &self.parser)
+            .field("value", &self.value)
+            .finish()
+    }
+}
 
 pub struct ValueParser<P, O> {
     pub parser: PhantomData<P>,
@@ -174,9 +792,515 @@ where
         match self.parser.fab(input) {
             Ok(_) => Ok(self.value.clone()),
             Err(mut err) => {
-                err.add_context(*input, ParserType::Map);
+                err.add_context(*input, ParserType::Value);
+                Err(err)
+            }
+        }
+    }
+}
+
+pub struct ParserMapErr<P, I: ?Sized, O, E, F> {
+    pub parser: P,
+    pub func: F,
+    pub phantom_i: PhantomData<I>,
+    pub phantom_o: PhantomData<O>,
+    pub phantom_e: PhantomData<E>,
+}
+impl<P: Clone, I: ?Sized, O, E, F: Clone> Clone for ParserMapErr<P, I, O, E, F> {
+    fn clone(&self) -> Self {
+        ParserMapErr {
+            parser: self.parser.clone(),
+            func: self.func.clone(),
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+}
+impl<P: Debug, I: ?Sized, O, E, F> Debug for ParserMapErr<P, I, O, E, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParserMapErr")
+            .field("parser", &self.parser)
+            .finish_non_exhaustive()
+    }
+}
+
+pub struct ParserMapErrT<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+impl<'a, P, I, O, E, E2, PType, F> Parser<'a, I, O, E2, ParserMapErrT<PType>>
+    for ParserMapErr<P, I, O, E, F>
+where
+    P: Parser<'a, I, O, E, PType>,
+    E: ParserError,
+    E2: ParserError,
+    F: Fn(E) -> E2,
+    I: ?Sized + Sequence,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E2> {
+        let checkpoint = *input;
+        self.parser.fab(input).map_err(|err| {
+            *input = checkpoint;
+            let mut err = (self.func)(err);
+            err.add_context(checkpoint, ParserType::MapErr);
+            err
+        })
+    }
+}
+
+/**
+ * This struct can be constructed through the function `dispatch`. It parses a key once,
+ * then runs the body parser that the table returns for that key.
+ */
+#[derive(Clone, Debug)]
+pub struct Dispatch<KP, F> {
+    pub key_parser: KP,
+    pub table: F,
+}
+pub struct DispatchParser<KType, K, B, BType> {
+    phantom_ktype: PhantomData<KType>,
+    phantom_k: PhantomData<K>,
+    phantom_b: PhantomData<B>,
+    phantom_btype: PhantomData<BType>,
+}
+impl<'a, I, O, E, KP, K, KType, F, B, BType> Parser<'a, I, O, E, DispatchParser<KType, K, B, BType>>
+    for Dispatch<KP, F>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    KP: Parser<'a, I, K, E, KType>,
+    F: Fn(K) -> Option<B>,
+    B: Parser<'a, I, O, E, BType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        let checkpoint = *input;
+        let key = match self.key_parser.fab(input) {
+            Ok(key) => key,
+            Err(mut err) => {
+                *input = checkpoint;
+                err.add_context(checkpoint, ParserType::Dispatch);
+                return Err(err);
+            }
+        };
+        let Some(body) = (self.table)(key) else {
+            *input = checkpoint;
+            return Err(E::from_parser_error(checkpoint, ParserType::Dispatch));
+        };
+        match body.fab(input) {
+            Ok(out) => Ok(out),
+            Err(mut err) => {
+                *input = checkpoint;
+                err.add_context(checkpoint, ParserType::Dispatch);
                 Err(err)
             }
         }
     }
 }
+
+/**
+ * This struct can be constructed through the function `lazy`. It builds the
+ * underlying parser each time it runs.
+ */
+#[derive(Clone, Debug)]
+pub struct Lazy<F> {
+    pub func: F,
+}
+pub struct LazyParser<P, PType> {
+    phantom_p: PhantomData<P>,
+    phantom_ptype: PhantomData<PType>,
+}
+impl<'a, I: ?Sized, O, E: ParserError, P, PType, F> Parser<'a, I, O, E, LazyParser<P, PType>>
+    for Lazy<F>
+where
+    F: Fn() -> P,
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        (self.func)().fab(input)
+    }
+}
+
+/**
+ * This struct can be constructed through the function `inner`. It runs the underlying
+ * parser on the sequence inside a wrapped input.
+ */
+#[derive(Clone, Debug)]
+pub struct Inner<P> {
+    pub parser: P,
+}
+pub struct InnerParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+impl<'a, W, O, E, P, PType> Parser<'a, W, O, E, InnerParser<PType>> for Inner<P>
+where
+    W: ?Sized + InputWrapper,
+    E: ParserError,
+    P: Parser<'a, W::Inner, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a W) -> Result<O, E> {
+        let mut inner = input.inner();
+        let out = self.parser.fab(&mut inner)?;
+        *input = W::wrap(inner);
+        Ok(out)
+    }
+}
+
+/**
+ * This struct can be constructed through the function `success`. It returns a clone
+ * of the value without consuming input.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Success<V> {
+    pub value: V,
+}
+pub struct SuccessParser;
+impl<'a, I: ?Sized, V: Clone, E: ParserError> Parser<'a, I, V, E, SuccessParser> for Success<V> {
+    fn fab(&self, _input: &mut &'a I) -> Result<V, E> {
+        Ok(self.value.clone())
+    }
+}
+
+/**
+ * This struct can be constructed through the function `fail`. It always fails
+ * without consuming input.
+ */
+pub struct Fail<O> {
+    pub phantom_o: PhantomData<O>,
+}
+impl<O> Clone for Fail<O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<O> Copy for Fail<O> {}
+impl<O> Debug for Fail<O> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Fail").finish()
+    }
+}
+pub struct FailParser;
+impl<'a, I: ?Sized + Sequence, O, E: ParserError> Parser<'a, I, O, E, FailParser> for Fail<O> {
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        Err(E::from_parser_error(*input, ParserType::Fail))
+    }
+}
+
+thread_local! {
+    static GUARD_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+/**
+ * This struct can be constructed through the function `depth_guard`. It fails once
+ * `limit` depth guarded parsers are running on this thread.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct DepthGuard<P> {
+    pub parser: P,
+    pub limit: usize,
+}
+pub struct DepthGuardParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+/**
+ * The cause of the error returned by `depth_guard` when input nests deeper than its limit.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub struct DepthLimitError {
+    pub limit: usize,
+}
+impl Display for DepthLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Input nests deeper than the limit of {}", self.limit)
+    }
+}
+
+impl Error for DepthLimitError {}
+
+/**
+ * Decrements the guard depth when dropped, so the count stays correct if the parser panics.
+ */
+struct DepthEntry;
+impl Drop for DepthEntry {
+    fn drop(&mut self) {
+        GUARD_DEPTH.set(GUARD_DEPTH.get() - 1);
+    }
+}
+impl<'a, I: ?Sized + Sequence, O, E: ParserError, P, PType>
+    Parser<'a, I, O, E, DepthGuardParser<PType>> for DepthGuard<P>
+where
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        let depth = GUARD_DEPTH.get();
+        if depth >= self.limit {
+            return Err(E::from_external_error(
+                *input,
+                ParserType::DepthGuard,
+                DepthLimitError { limit: self.limit },
+            ));
+        }
+        GUARD_DEPTH.set(depth + 1);
+        let _entry = DepthEntry;
+        self.parser.fab(input)
+    }
+}
+
+/**
+ * Marker for parsers that are shared through a reference, Rc or Arc. These forward to
+ * the underlying parser, so a parser can be reused without being cloned.
+ */
+pub struct SharedParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+
+impl<'a, I: ?Sized, O, E: ParserError, PType, P> Parser<'a, I, O, E, SharedParser<PType>> for &P
+where
+    P: Parser<'a, I, O, E, PType> + ?Sized,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        (**self).fab(input)
+    }
+}
+
+impl<'a, I: ?Sized, O, E: ParserError, PType, P> Parser<'a, I, O, E, SharedParser<PType>> for Rc<P>
+where
+    P: Parser<'a, I, O, E, PType> + ?Sized,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        (**self).fab(input)
+    }
+}
+
+impl<'a, I: ?Sized, O, E: ParserError, PType, P> Parser<'a, I, O, E, SharedParser<PType>> for Arc<P>
+where
+    P: Parser<'a, I, O, E, PType> + ?Sized,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        (**self).fab(input)
+    }
+}
+
+/**
+ * Parses a length with the length parser and converts it to a usize.
+ */
+fn parse_length<'a, I, E, L, LO, LType>(len_parser: &L, input: &mut &'a I) -> Result<usize, E>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    L: Parser<'a, I, LO, E, LType>,
+    LO: TryInto<usize>,
+    LO::Error: Error + Send + Sync + 'static,
+{
+    let checkpoint = *input;
+    let len = len_parser.fab(input)?;
+    len.try_into().map_err(|err| {
+        *input = checkpoint;
+        E::from_external_error(checkpoint, ParserType::TryMap, err)
+    })
+}
+
+/**
+ * This struct can be constructed through the function `length_take`. It parses a length,
+ * then takes exactly that many items.
+ */
+#[derive(Clone, Debug)]
+pub struct LengthTake<L> {
+    pub len_parser: L,
+}
+pub struct LengthTakeParser<LO, LType> {
+    phantom_lo: PhantomData<LO>,
+    phantom_ltype: PhantomData<LType>,
+}
+impl<'a, I, E, L, LO, LType> Parser<'a, I, &'a I, E, LengthTakeParser<LO, LType>> for LengthTake<L>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    L: Parser<'a, I, LO, E, LType>,
+    LO: TryInto<usize>,
+    LO::Error: Error + Send + Sync + 'static,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<&'a I, E> {
+        let checkpoint = *input;
+        parse_length(&self.len_parser, input)
+            .and_then(|len| crate::take(len).fab(input))
+            .map_err(|mut err| {
+                *input = checkpoint;
+                err.add_context(checkpoint, ParserType::Length);
+                err
+            })
+    }
+}
+
+/**
+ * This struct can be constructed through the function `length_repeat`. It parses a count,
+ * then parses exactly that many items.
+ */
+#[derive(Clone, Debug)]
+pub struct LengthRepeat<L, P> {
+    pub len_parser: L,
+    pub item_parser: P,
+}
+pub struct LengthRepeatParser<LO, LType, PType> {
+    phantom_lo: PhantomData<LO>,
+    phantom_ltype: PhantomData<LType>,
+    phantom_ptype: PhantomData<PType>,
+}
+impl<'a, I, O, E, L, LO, LType, P, PType>
+    Parser<'a, I, Vec<O>, E, LengthRepeatParser<LO, LType, PType>> for LengthRepeat<L, P>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    L: Parser<'a, I, LO, E, LType>,
+    LO: TryInto<usize>,
+    LO::Error: Error + Send + Sync + 'static,
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<Vec<O>, E> {
+        let checkpoint = *input;
+        let res = parse_length(&self.len_parser, input).and_then(|len| {
+            //The count comes from the input, so don't trust it for the allocation.
+            let mut items = Vec::new();
+            for _ in 0..len {
+                items.push(self.item_parser.fab(input)?);
+            }
+            Ok(items)
+        });
+        res.map_err(|mut err| {
+            *input = checkpoint;
+            err.add_context(checkpoint, ParserType::Length);
+            err
+        })
+    }
+}
+
+/**
+ * Runs the shared loop of `escaped` and `escaped_transform`. It alternates between runs of
+ * normal items and escapes until neither makes progress, calling push with the normal
+ * segment before each escape and the escape's output. Returns the trailing normal segment.
+ */
+fn escaped_loop<'a, I, E, N, NO, NType, C, CO, CType, T>(
+    normal: &N,
+    control: &C,
+    input: &mut &'a I,
+    escape: impl Fn(&mut &'a I) -> Result<T, E>,
+    mut push: impl FnMut(&'a I, T),
+) -> Result<&'a I, E>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    N: Parser<'a, I, NO, E, NType>,
+    C: Parser<'a, I, CO, E, CType>,
+{
+    let orig_input = *input;
+    let mut segment_start = *input;
+    loop {
+        let before = *input;
+        if normal.fab(input).is_err() {
+            *input = before;
+        }
+        if loc(*input) != loc(before) {
+            continue;
+        }
+        let escape_start = *input;
+        if control.fab(input).is_err() {
+            *input = escape_start;
+            break;
+        }
+        match escape(input) {
+            //Neither the normal parser nor the escape made progress, so stop rather than looping.
+            Ok(_) if loc(*input) == loc(escape_start) => {
+                *input = escape_start;
+                break;
+            }
+            Ok(val) => {
+                push(segment_start.subtract(escape_start), val);
+                segment_start = *input;
+            }
+            Err(_) => {
+                *input = orig_input;
+                return Err(E::from_parser_error(escape_start, ParserType::Escaped));
+            }
+        }
+    }
+    Ok(segment_start.subtract(*input))
+}
+
+/**
+ * This struct can be constructed through the function `escaped`.
+ */
+#[derive(Clone, Debug)]
+pub struct Escaped<N, C, X> {
+    pub normal: N,
+    pub control: C,
+    pub escapable: X,
+}
+pub struct EscapedParser<NO, NType, CO, CType, XO, XType> {
+    phantom_no: PhantomData<NO>,
+    phantom_ntype: PhantomData<NType>,
+    phantom_co: PhantomData<CO>,
+    phantom_ctype: PhantomData<CType>,
+    phantom_xo: PhantomData<XO>,
+    phantom_xtype: PhantomData<XType>,
+}
+impl<'a, I, E, N, NO, NType, C, CO, CType, X, XO, XType>
+    Parser<'a, I, &'a I, E, EscapedParser<NO, NType, CO, CType, XO, XType>> for Escaped<N, C, X>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    N: Parser<'a, I, NO, E, NType>,
+    C: Parser<'a, I, CO, E, CType>,
+    X: Parser<'a, I, XO, E, XType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<&'a I, E> {
+        let orig_input = *input;
+        escaped_loop(
+            &self.normal,
+            &self.control,
+            input,
+            |input| self.escapable.fab(input),
+            |_, _| {},
+        )?;
+        Ok(orig_input.subtract(*input))
+    }
+}
+
+/**
+ * This struct can be constructed through the function `escaped_transform`.
+ */
+#[derive(Clone, Debug)]
+pub struct EscapedTransform<N, C, X> {
+    pub normal: N,
+    pub control: C,
+    pub transform: X,
+}
+impl<'a, E, N, NO, NType, C, CO, CType, X, XO, XType>
+    Parser<'a, str, Cow<'a, str>, E, EscapedParser<NO, NType, CO, CType, XO, XType>>
+    for EscapedTransform<N, C, X>
+where
+    E: ParserError,
+    N: Parser<'a, str, NO, E, NType>,
+    C: Parser<'a, str, CO, E, CType>,
+    X: Parser<'a, str, XO, E, XType>,
+    String: Extend<XO>,
+{
+    fn fab(&self, input: &mut &'a str) -> Result<Cow<'a, str>, E> {
+        let mut decoded: Option<String> = None;
+        let rest = escaped_loop(
+            &self.normal,
+            &self.control,
+            input,
+            |input| self.transform.fab(input),
+            |segment, val| {
+                let decoded = decoded.get_or_insert_with(String::new);
+                decoded.push_str(segment);
+                decoded.extend(std::iter::once(val));
+            },
+        )?;
+        Ok(match decoded {
+            Some(mut decoded) => {
+                decoded.push_str(rest);
+                Cow::Owned(decoded)
+            }
+            None => Cow::Borrowed(rest),
+        })
+    }
+}
@@ -1,6 +1,9 @@
 use std::{
+    borrow::Cow,
+    collections::BTreeMap,
     error::Error,
-    fmt::{Debug, Display},
+    fmt::{self, Debug, Display},
+    sync::Arc,
 };
 
 use smallvec::{smallvec, SmallVec};
@@ -12,31 +15,295 @@ use crate::{sequence::Sequence, ParserType};
  * that generated the error and its location. This is implemented by `FabError` and
  * `NoContextFabError`.
  *
- * In order to simplify lifetimes used by the error, the parser error
- * stores a pointer to the location the error occured rather than a reference.
- * This doesn't require unsafe to use properly.
+ * Errors record their location by how much input was left where they occured, rather
+ * than by a reference into the input. This keeps the input's lifetime out of the error,
+ * and the location stays meaningful after the input is moved, copied or dropped.
  */
 pub trait ParserError {
-    fn from_parser_error<T: ?Sized + Sequence>(input: *const T, parser_type: ParserType) -> Self;
+    fn from_parser_error<T: ?Sized + Sequence>(input: &T, parser_type: ParserType) -> Self;
     fn from_external_error<T: ?Sized + Sequence, E: Error + Send + Sync + 'static>(
-        input: *const T,
+        input: &T,
         parser_type: ParserType,
         cause: E,
     ) -> Self;
-    fn add_context<T: ?Sized + Sequence>(&mut self, _input: *const T, _parser_type: ParserType) {}
     /**
-     * Get the location of the error. This is used in combinators to recognize the parser that made
-     * the furthest progress.
+     * Creates an error caused by anything that converts into a boxed error. This includes
+     * types implementing `Error`, as well as `Box<dyn Error + Send + Sync>`, `anyhow::Error`
+     * and `String`, which don't implement `Error` themselves. By default the cause is boxed
+     * and wrapped in an error that forwards to the box. Error types that discard the cause
+     * can override this to avoid boxing it.
+     */
+    fn from_boxed_error<T: ?Sized + Sequence, C: Into<Box<dyn Error + Send + Sync>>>(
+        input: &T,
+        parser_type: ParserType,
+        cause: C,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::from_external_error(input, parser_type, BoxedError(cause.into()))
+    }
+    fn add_context<T: ?Sized + Sequence>(&mut self, _input: &T, _parser_type: ParserType) {}
+    /**
+     * Attaches a message to the most recently added frame of the error, replacing any
+     * message already on it. This is used by `fab_msg`.
+     */
+    fn add_message(&mut self, _msg: impl Into<Cow<'static, str>>) {}
+    /**
+     * Attaches the errors of the other branches of an `alt_verbose` to the most recently
+     * added frame of the error. Error types without context discard them.
+     */
+    fn add_alternatives(&mut self, _alternatives: Vec<Self>)
+    where
+        Self: Sized,
+    {
+    }
+    /**
+     * Get the location of the error. This grows as the input is consumed, so it is used in
+     * combinators to recognize the parser that made the furthest progress.
      */
     fn get_loc(&self) -> Option<usize> {
         None
     }
+    /**
+     * Creates an error for a parser that ran out of input before it could decide whether
+     * the input matches, such as a tag that matches a prefix of the input. By default this is
+     * the same as `from_parser_error`. [`Streaming`] records it so that more input can be
+     * requested.
+     */
+    fn from_incomplete<T: ?Sized + Sequence>(input: &T, parser_type: ParserType) -> Self
+    where
+        Self: Sized,
+    {
+        Self::from_parser_error(input, parser_type)
+    }
+    /**
+     * Returns whether this error means more input is needed rather than that the input
+     * doesn't match. Combinators such as `alt`, `opt` and `fab_repeat` propagate
+     * incomplete errors instead of trying other options.
+     */
+    fn is_incomplete(&self) -> bool {
+        false
+    }
+    /**
+     * Records what the parser that created this error expected, and what it found
+     * instead. This is used by tags, so the trace can say `expected 'a', found 'b'`.
+     */
+    fn set_expected(&mut self, _expected: Expected, _found: Option<Found>) {}
+    /**
+     * Combines the expectation of an earlier branch that failed at the same location
+     * into this error, so `alt` can report that any of them would have matched.
+     */
+    fn merge_expected(&mut self, _earlier: Self)
+    where
+        Self: Sized,
+    {
+    }
+    /**
+     * Returns whether `set_expected` records anything, so parsers can skip building
+     * the expectation when it would be discarded.
+     */
+    fn records_expected(&self) -> bool {
+        true
+    }
+}
+
+/**
+ * Lets a boxed error be used where a type implementing `Error` is needed. Its Display,
+ * Debug and source are those of the boxed error.
+ */
+struct BoxedError(Box<dyn Error + Send + Sync>);
+
+impl Debug for BoxedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Display for BoxedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Error for BoxedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
+
+/**
+ * What a parser expected to find, which is recorded in errors with `set_expected`.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expected {
+    Char(char),
+    Str(String),
+    /**
+     * A description of a range of chars, like `'a'..='z'`.
+     */
+    Range(String),
+    /**
+     * A regex pattern, from the parsers built with `re` and `re_captures`.
+     */
+    Pattern(String),
+    OneOf(Vec<Expected>),
+    Eof,
+}
+
+impl Expected {
+    fn into_options(self) -> Vec<Expected> {
+        match self {
+            Expected::OneOf(options) => options,
+            expected => vec![expected],
+        }
+    }
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Char(c) => write!(f, "{:?}", c),
+            Expected::Str(tag) => write!(f, "{:?}", tag),
+            Expected::Range(range) => write!(f, "{}", range),
+            Expected::Pattern(pattern) => write!(f, "match for /{}/", pattern),
+            Expected::OneOf(options) => {
+                write!(f, "one of ")?;
+                for (index, option) in options.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", option)?;
+                }
+                Ok(())
+            }
+            Expected::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+/**
+ * What a parser found instead of what it expected.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Found {
+    Char(char),
+    Eof,
+}
+
+impl Display for Found {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Found::Char(c) => write!(f, "{:?}", c),
+            Found::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+/**
+ * Trait for converting between parser error types. This is used by `fab_convert_err`.
+ *
+ * This is a separate trait rather than `From` so that adding conversions doesn't
+ * break type inference for the `?` operator inside function parsers.
+ */
+pub trait ConvertError<E> {
+    fn convert_error(err: E) -> Self;
+}
+
+impl<E: ParserError> ConvertError<E> for E {
+    fn convert_error(err: E) -> Self {
+        err
+    }
+}
+
+/**
+ * Converting a FabError into a NoContextFabError discards its context.
+ */
+impl ConvertError<FabError> for NoContextFabError {
+    fn convert_error(_err: FabError) -> Self {
+        NoContextFabError
+    }
+}
+
+/**
+ * A NoContextFabError has no location or cause, so the converted FabError starts with an
+ * empty stack. Converting through `fab_convert_err` adds the location of the converting
+ * parser to it.
+ */
+impl ConvertError<NoContextFabError> for FabError {
+    fn convert_error(_err: NoContextFabError) -> Self {
+        FabError {
+            stack: SmallVec::new(),
+            cause: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 struct LocatedError {
+    //The location from input_loc.
     location: usize,
     parser_type: ParserType,
+    message: Option<Cow<'static, str>>,
+    alternatives: Vec<FabError>,
+    //This is boxed so it doesn't make every error larger.
+    expectation: Option<Box<Expectation>>,
+}
+
+#[derive(Debug, Clone)]
+struct Expectation {
+    expected: Expected,
+    found: Option<Found>,
+}
+
+/**
+ * Describes the branch that ended with these frames. This is the outermost message in the
+ * branch if there is one, otherwise what the parser that failed expected, or the parser.
+ */
+fn branch_label(frames: &[LocatedError]) -> String {
+    frames
+        .iter()
+        .rev()
+        .find_map(|item| item.message.as_deref().map(String::from))
+        .or_else(|| {
+            Some(format!(
+                "expected {}",
+                frames[0].expectation.as_ref()?.expected
+            ))
+        })
+        .unwrap_or_else(|| format!("{:?}", frames[0].parser_type))
+}
+
+/**
+ * Writes the cause of an error, followed by each error in its source chain.
+ */
+fn write_cause<W: fmt::Write>(cause: &(dyn Error + 'static), writer: &mut W) -> fmt::Result {
+    writeln!(writer, "From cause [{}]", cause)?;
+    let mut source = cause.source();
+    while let Some(err) = source {
+        writeln!(writer, "Caused by [{}]", err)?;
+        source = err.source();
+    }
+    Ok(())
+}
+
+/**
+ * Writes the message and expectation of a frame after the parser that added it, like
+ * `: expected 'a', found 'b'`.
+ */
+fn write_frame_note<W: fmt::Write>(item: &LocatedError, writer: &mut W) -> fmt::Result {
+    let mut separator = ": ";
+    if let Some(message) = &item.message {
+        write!(writer, "{}{}", separator, message)?;
+        separator = ", ";
+    }
+    if let Some(expectation) = &item.expectation {
+        write!(writer, "{}expected {}", separator, expectation.expected)?;
+        if let Some(found) = &expectation.found {
+            write!(writer, ", found {}", found)?;
+        }
+    }
+    Ok(())
 }
 /**
  * This error type has the FabError trait implemented for it,
@@ -57,17 +324,234 @@ impl Error for NoContextFabError {}
  * It contains no information.
  */
 impl ParserError for NoContextFabError {
-    fn from_parser_error<T: ?Sized + Sequence>(_input: *const T, _parser_type: ParserType) -> Self {
+    fn from_parser_error<T: ?Sized + Sequence>(_input: &T, _parser_type: ParserType) -> Self {
         NoContextFabError
     }
 
     fn from_external_error<T: ?Sized, E: Error + Send + Sync + 'static>(
-        _input: *const T,
+        _input: &T,
         _parser_type: ParserType,
         _cause: E,
     ) -> Self {
         NoContextFabError
     }
+    fn from_boxed_error<T: ?Sized + Sequence, C: Into<Box<dyn Error + Send + Sync>>>(
+        _input: &T,
+        _parser_type: ParserType,
+        _cause: C,
+    ) -> Self {
+        NoContextFabError
+    }
+    fn records_expected(&self) -> bool {
+        false
+    }
+}
+
+/**
+ * This error type stores only the location of the innermost failure and the parser that
+ * failed. It never allocates, but it still reports its location so `alt` keeps the error of
+ * the branch that made the furthest progress. Causes and context are discarded.
+ *
+ * Use this error when FabError's allocations show up in profiles, such as for grammars that
+ * backtrack heavily. `cargo bench --bench alt` compares the error types on an alt heavy
+ * grammar.
+ *
+ * An error converted from a FabError with no frames has no location until a parser adds
+ * context to it, just like the FabError it came from.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OffsetFabError {
+    frame: Option<(usize, ParserType)>,
+}
+impl OffsetFabError {
+    /**
+     * Returns the location of the error as an index into the input, like `FabError::offset`.
+     */
+    pub fn offset<I: ?Sized + Sequence>(&self, original_input: &I) -> Option<usize> {
+        self.frame
+            .and_then(|(location, _)| loc_offset(original_input, location))
+    }
+    /**
+     * Returns the parser that failed, or None if the error has no location.
+     */
+    pub fn parser_type(&self) -> Option<ParserType> {
+        self.frame.map(|(_, parser_type)| parser_type)
+    }
+}
+impl Display for OffsetFabError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.frame {
+            Some((_, parser_type)) => write!(f, "OffsetFabError from parser {:?}", parser_type),
+            None => write!(f, "OffsetFabError"),
+        }
+    }
+}
+impl Error for OffsetFabError {}
+impl ParserError for OffsetFabError {
+    fn from_parser_error<T: ?Sized + Sequence>(input: &T, parser_type: ParserType) -> Self {
+        OffsetFabError {
+            frame: Some((input_loc(input), parser_type)),
+        }
+    }
+    fn from_external_error<T: ?Sized + Sequence, E: Error + Send + Sync + 'static>(
+        input: &T,
+        parser_type: ParserType,
+        _cause: E,
+    ) -> Self {
+        Self::from_parser_error(input, parser_type)
+    }
+    fn from_boxed_error<T: ?Sized + Sequence, C: Into<Box<dyn Error + Send + Sync>>>(
+        input: &T,
+        parser_type: ParserType,
+        _cause: C,
+    ) -> Self {
+        Self::from_parser_error(input, parser_type)
+    }
+    fn get_loc(&self) -> Option<usize> {
+        self.frame.map(|(location, _)| location)
+    }
+    fn add_context<T: ?Sized + Sequence>(&mut self, input: &T, parser_type: ParserType) {
+        if self.frame.is_none() {
+            self.frame = Some((input_loc(input), parser_type));
+        }
+    }
+    fn records_expected(&self) -> bool {
+        false
+    }
+}
+
+/**
+ * Converting a FabError into an OffsetFabError keeps only its innermost frame. A FabError
+ * with no frames converts to an OffsetFabError with no location.
+ */
+impl ConvertError<FabError> for OffsetFabError {
+    fn convert_error(err: FabError) -> Self {
+        OffsetFabError {
+            frame: err
+                .stack
+                .first()
+                .map(|item| (item.location, item.parser_type)),
+        }
+    }
+}
+
+impl ConvertError<OffsetFabError> for FabError {
+    fn convert_error(err: OffsetFabError) -> Self {
+        FabError {
+            stack: err
+                .frame
+                .map(|(location, parser_type)| LocatedError {
+                    location,
+                    parser_type,
+                    message: None,
+                    alternatives: Vec::new(),
+                    expectation: None,
+                })
+                .into_iter()
+                .collect(),
+            cause: None,
+        }
+    }
+}
+
+impl ConvertError<OffsetFabError> for NoContextFabError {
+    fn convert_error(_err: OffsetFabError) -> Self {
+        NoContextFabError
+    }
+}
+
+/**
+ * Wraps an error type for parsing input that arrives in chunks. It records whether the
+ * parse failed because the input ran out, in which case it can be retried once more
+ * input is available, or because the input doesn't match.
+ *
+ * Parsers that reach the end of the input report it with `from_incomplete`, and
+ * combinators propagate it, so `fab_repeat` and `opt` at the end of the buffer fail as
+ * incomplete rather than succeeding early. Use the wrapped error type directly for
+ * complete input.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Streaming<E> {
+    pub error: E,
+    pub incomplete: bool,
+}
+
+impl<E> Streaming<E> {
+    pub fn into_inner(self) -> E {
+        self.error
+    }
+}
+
+impl<E: Display> Display for Streaming<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.incomplete {
+            write!(f, "Incomplete input: {}", self.error)
+        } else {
+            write!(f, "{}", self.error)
+        }
+    }
+}
+
+impl<E: Error> Error for Streaming<E> {}
+
+impl<E: ParserError> ParserError for Streaming<E> {
+    fn from_parser_error<T: ?Sized + Sequence>(input: &T, parser_type: ParserType) -> Self {
+        Streaming {
+            error: E::from_parser_error(input, parser_type),
+            incomplete: false,
+        }
+    }
+    fn from_external_error<T: ?Sized + Sequence, C: Error + Send + Sync + 'static>(
+        input: &T,
+        parser_type: ParserType,
+        cause: C,
+    ) -> Self {
+        Streaming {
+            error: E::from_external_error(input, parser_type, cause),
+            incomplete: false,
+        }
+    }
+    fn from_boxed_error<T: ?Sized + Sequence, C: Into<Box<dyn Error + Send + Sync>>>(
+        input: &T,
+        parser_type: ParserType,
+        cause: C,
+    ) -> Self {
+        Streaming {
+            error: E::from_boxed_error(input, parser_type, cause),
+            incomplete: false,
+        }
+    }
+    fn add_context<T: ?Sized + Sequence>(&mut self, input: &T, parser_type: ParserType) {
+        self.error.add_context(input, parser_type);
+    }
+    fn add_message(&mut self, msg: impl Into<Cow<'static, str>>) {
+        self.error.add_message(msg);
+    }
+    fn add_alternatives(&mut self, alternatives: Vec<Self>) {
+        self.error
+            .add_alternatives(alternatives.into_iter().map(|alt| alt.error).collect());
+    }
+    fn get_loc(&self) -> Option<usize> {
+        self.error.get_loc()
+    }
+    fn from_incomplete<T: ?Sized + Sequence>(input: &T, parser_type: ParserType) -> Self {
+        Streaming {
+            error: E::from_incomplete(input, parser_type),
+            incomplete: true,
+        }
+    }
+    fn is_incomplete(&self) -> bool {
+        self.incomplete
+    }
+    fn set_expected(&mut self, expected: Expected, found: Option<Found>) {
+        self.error.set_expected(expected, found);
+    }
+    fn merge_expected(&mut self, earlier: Self) {
+        self.error.merge_expected(earlier.error);
+    }
+    fn records_expected(&self) -> bool {
+        self.error.records_expected()
+    }
 }
 
 /**
@@ -82,16 +566,19 @@ impl ParserError for NoContextFabError {
  *
  * From cause \[TryReducerFailed\]
  *
+ * If the cause has a source, each error in its chain follows on a `Caused by` line.
+ *
  * There is also a `print_trace_window(input)` method that lets you customize
  * the context window. The default is currently 10, but Fabparse reserves the
  * right to increase this.
  */
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FabError {
     //Use a smallvec for the stack so non-combinator
     //parsers won't need to allocate
     stack: SmallVec<[LocatedError; 1]>,
-    cause: Option<Box<dyn Error>>,
+    //The cause is shared so the error can be cloned.
+    cause: Option<Arc<dyn Error + Send + Sync>>,
 }
 /**
  * This is the default error for Fabparse.
@@ -104,8 +591,9 @@ pub struct FabError {
  *
  * From cause \[TryReducerFailed\]
  *
- * This method requires that you pass in the input that generated the error.
- * If you don't the method may panic or print an incorrect stack trace.
+ * This method requires that you pass in the input that generated the error, or a copy of it.
+ * The error doesn't borrow the input, so this works after the parsed buffer is gone.
+ * Frames that can't be located in the input are printed as "Location unavailable".
  *
  * This error type also has a method print_trace_window(input, window_size)
  * which controls how much context is printed. By default, it will be 10 chars or items
@@ -119,48 +607,105 @@ impl Display for FabError {
         )
     }
 }
-impl Error for FabError {}
+impl Error for FabError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause
+            .as_deref()
+            .map(|cause| cause as &(dyn Error + 'static))
+    }
+}
 
 impl ParserError for FabError {
-    fn from_parser_error<T: ?Sized>(input: *const T, parser_type: ParserType) -> Self {
+    fn from_parser_error<T: ?Sized + Sequence>(input: &T, parser_type: ParserType) -> Self {
         FabError {
             stack: smallvec![LocatedError {
                 parser_type,
-                location: input as *const u8 as usize
+                location: input_loc(input),
+                message: None,
+                alternatives: Vec::new(),
+                expectation: None,
             }],
             cause: None,
         }
     }
-    fn from_external_error<T: ?Sized, E: Error + Send + Sync + 'static>(
-        input: *const T,
+    fn from_external_error<T: ?Sized + Sequence, E: Error + Send + Sync + 'static>(
+        input: &T,
         parser_type: ParserType,
         cause: E,
     ) -> Self {
-        FabError {
-            stack: smallvec![LocatedError {
-                parser_type,
-                location: input as *const u8 as usize
-            }],
-            cause: Some(Box::new(cause)),
-        }
+        let mut err = Self::from_parser_error(input, parser_type);
+        err.cause = Some(Arc::new(cause));
+        err
+    }
+    fn from_boxed_error<T: ?Sized + Sequence, C: Into<Box<dyn Error + Send + Sync>>>(
+        input: &T,
+        parser_type: ParserType,
+        cause: C,
+    ) -> Self {
+        //The box is moved into the Arc as is, so the cause can still be downcast.
+        let mut err = Self::from_parser_error(input, parser_type);
+        err.cause = Some(Arc::from(cause.into()));
+        err
     }
     fn get_loc(&self) -> Option<usize> {
-        return Some(self.stack[0].location);
+        self.stack.first().map(|item| item.location)
     }
-    fn add_context<T: ?Sized + Sequence>(&mut self, input: *const T, parser_type: ParserType) {
+    fn add_context<T: ?Sized + Sequence>(&mut self, input: &T, parser_type: ParserType) {
         self.stack.push(LocatedError {
-            location: input as *const u8 as usize,
+            location: input_loc(input),
             parser_type,
+            message: None,
+            alternatives: Vec::new(),
+            expectation: None,
         })
     }
+    fn add_message(&mut self, msg: impl Into<Cow<'static, str>>) {
+        if let Some(item) = self.stack.last_mut() {
+            item.message = Some(msg.into());
+        }
+    }
+    fn add_alternatives(&mut self, alternatives: Vec<Self>) {
+        if let Some(item) = self.stack.last_mut() {
+            item.alternatives = alternatives;
+        }
+    }
+    fn set_expected(&mut self, expected: Expected, found: Option<Found>) {
+        if let Some(item) = self.stack.first_mut() {
+            item.expectation = Some(Box::new(Expectation { expected, found }));
+        }
+    }
+    fn merge_expected(&mut self, earlier: Self) {
+        let (Some(item), Some(earlier)) =
+            (self.stack.first_mut(), earlier.stack.into_iter().next())
+        else {
+            return;
+        };
+        if item.location != earlier.location {
+            return;
+        }
+        if let (Some(expectation), Some(earlier)) = (&mut item.expectation, earlier.expectation) {
+            let mut options = earlier.expected.into_options();
+            let current = std::mem::replace(&mut expectation.expected, Expected::Eof);
+            for option in current.into_options() {
+                if !options.contains(&option) {
+                    options.push(option);
+                }
+            }
+            //An expectation merged with itself is left as is.
+            expectation.expected = match options.len() {
+                1 => options.remove(0),
+                _ => Expected::OneOf(options),
+            };
+        }
+    }
 }
 /**
  * Gets a slice of window elements from the start of the sequence
  */
-fn get_from_start<I: ?Sized + Sequence>(input: &I, window: usize) -> &I {
+pub(crate) fn get_from_start<I: ?Sized + Sequence>(input: &I, window: usize) -> &I {
     let mut current_start = input;
     for _ in 0..window {
-        if let Some((_, rest)) = current_start.try_split_front() {
+        if let Some((_, rest)) = current_start.try_split_front_ref() {
             current_start = rest;
         } else {
             break;
@@ -180,7 +725,7 @@ fn get_from_end<I: ?Sized + Sequence>(input: &I, window: usize) -> &I {
     let mut count = 0;
     while count < window && end_index > 0 {
         end_index -= 1;
-        if let Some(_) = input.try_split_at(end_index) {
+        if input.try_split_at(end_index).is_some() {
             count += 1;
         }
     }
@@ -189,27 +734,273 @@ fn get_from_end<I: ?Sized + Sequence>(input: &I, window: usize) -> &I {
         .expect("Get from end found a valid split.")
         .1
 }
+/**
+ * Encodes the location of an error at the start of input. This is the length of the input
+ * that is left subtracted from usize::MAX, so it grows as the input is consumed and doesn't
+ * depend on where the input is stored.
+ */
+pub(crate) fn input_loc<T: ?Sized + Sequence>(input: &T) -> usize {
+    usize::MAX - input.len()
+}
+/**
+ * Converts a location from input_loc into an index into the original input. This is in
+ * bytes for str and in elements for \[T\]. Returns None if more input was left at the
+ * location than the original input has.
+ */
+pub(crate) fn loc_offset<I: ?Sized + Sequence>(original_input: &I, loc: usize) -> Option<usize> {
+    original_input.len().checked_sub(usize::MAX - loc)
+}
+/**
+ * Converts a pointer into an index into the sequence. This is in bytes for str and
+ * in elements for \[T\]. Returns None if the pointer isn't within the sequence.
+ */
+pub(crate) fn get_offset<I: ?Sized + Sequence>(input: &I, place: usize) -> Option<usize> {
+    let start = input as *const I as *const u8 as usize;
+    let bytes = place.checked_sub(start)?;
+    let size = std::mem::size_of_val(input);
+    if bytes > size {
+        return None;
+    }
+    if bytes == 0 {
+        return Some(0);
+    }
+    //The sequence isn't empty here, since bytes is in 1..=size.
+    let item_size = size / input.len();
+    if bytes % item_size != 0 {
+        return None;
+    }
+    Some(bytes / item_size)
+}
 /**
  * Gets window elements of the surrounding context, both forwards and backwards.
  * We need to use try split to handle strings correctly, which can only be split at char boundries.
  *
- * Requires place to be value of a valid pointer to within the sequence as usize, otherwise will panic.
+ * Returns None if place isn't within the sequence or isn't a valid split boundary,
+ * such as when the error is traced against a different input than was parsed.
  */
 fn get_surrounding_context<I: ?Sized + Sequence>(
     input: &I,
     place: usize,
     window: usize,
-) -> (&I, &I) {
-    let start = input as *const I as *const u8 as usize;
-    assert!(place >= start);
-    let index = place - start;
-    let (before, after) = input
-        .try_split_at(index)
-        .expect("Place is a valid split boundary");
-    (get_from_end(before, window), get_from_start(after, window))
+) -> Option<(&I, &I)> {
+    let index = loc_offset(input, place)?;
+    let (before, after) = input.try_split_at(index)?;
+    Some((get_from_end(before, window), get_from_start(after, window)))
+}
+
+const TAB_WIDTH: usize = 4;
+
+/**
+ * Gets the 1-based line and column of the offset, along with the text of the line.
+ * Lines are split on \n with a trailing \r removed from the line text. Columns
+ * count chars. An offset inside a multi-byte char points to that char.
+ */
+fn get_line_col(input: &str, offset: usize) -> (usize, usize, &str) {
+    let mut offset = offset.min(input.len());
+    while !input.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &input[..offset];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    let line_end = input[offset..]
+        .find('\n')
+        .map_or(input.len(), |idx| offset + idx);
+    let line = &input[line_start..line_end];
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let line_num = before.matches('\n').count() + 1;
+    let col = input[line_start..offset].chars().count() + 1;
+    (line_num, col, line)
+}
+
+/**
+ * Writes the line containing offset with a caret under its column followed by the note,
+ * as in `FabError::annotate`.
+ */
+pub(crate) fn write_annotation(original_input: &str, offset: usize, note: &str, out: &mut String) {
+    let (line, col, text) = get_line_col(original_input, offset);
+    let label = format!("line {} | ", line);
+    let caret_pos = label.len()
+        + text
+            .chars()
+            .take(col - 1)
+            .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+            .sum::<usize>();
+    out.push_str(&label);
+    out.push_str(&text.replace('\t', &" ".repeat(TAB_WIDTH)));
+    out.push('\n');
+    out.push_str(&" ".repeat(caret_pos));
+    out.push_str("^ ");
+    out.push_str(note);
+    out.push('\n');
 }
 
 impl FabError {
+    /**
+     * Returns the 1-based line and column of the error in the input, with the column
+     * counted in chars. The input must be the input that was parsed.
+     * Returns None if the location isn't within the input.
+     */
+    pub fn line_col(&self, original_input: &str) -> Option<(usize, usize)> {
+        self.line_col_at(original_input, 0)
+    }
+    /**
+     * Like line_col, but for the frame of the error stack at the index frame.
+     * Frame 0 is the parser that failed.
+     */
+    pub fn line_col_at(&self, original_input: &str, frame: usize) -> Option<(usize, usize)> {
+        let (line, col, _) = self.line_info(original_input, frame)?;
+        Some((line, col))
+    }
+    /**
+     * Returns the text of the line containing the error, without the line ending.
+     */
+    pub fn error_line<'b>(&self, original_input: &'b str) -> Option<&'b str> {
+        self.error_line_at(original_input, 0)
+    }
+    /**
+     * Like error_line, but for the frame of the error stack at the index frame.
+     */
+    pub fn error_line_at<'b>(&self, original_input: &'b str, frame: usize) -> Option<&'b str> {
+        let (_, _, text) = self.line_info(original_input, frame)?;
+        Some(text)
+    }
+    /**
+     * Renders each frame of the error stack as the line containing its location
+     * with a caret under the column, starting with the parser that failed. Tabs are
+     * expanded to 4 spaces. Frames outside of the input are skipped.
+     * ```text
+     * line 2 | foo = tru
+     *                ^ from parser Tag
+     * ```
+     */
+    pub fn annotate(&self, original_input: &str) -> String {
+        let mut out = String::new();
+        for item in &self.stack {
+            let Some(offset) = loc_offset(original_input, item.location) else {
+                continue;
+            };
+            let mut note = format!("from parser {:?}", item.parser_type);
+            write_frame_note(item, &mut note).expect("Writing to a String doesn't fail");
+            write_annotation(original_input, offset, &note, &mut out);
+        }
+        if let Some(cause) = &self.cause {
+            write_cause(cause.as_ref(), &mut out).expect("Writing to a String doesn't fail");
+        }
+        out
+    }
+    fn line_info<'b>(
+        &self,
+        original_input: &'b str,
+        frame: usize,
+    ) -> Option<(usize, usize, &'b str)> {
+        let location = self.stack.get(frame)?.location;
+        let offset = loc_offset(original_input, location)?;
+        Some(get_line_col(original_input, offset))
+    }
+    /**
+     * Returns what the parser that failed expected, if it recorded it.
+     */
+    pub fn expected(&self) -> Option<&Expected> {
+        Some(&self.stack.first()?.expectation.as_ref()?.expected)
+    }
+    /**
+     * Returns what the parser that failed found instead of what it expected.
+     */
+    pub fn found(&self) -> Option<Found> {
+        self.stack.first()?.expectation.as_ref()?.found
+    }
+    /**
+     * Returns the location of the error as an index into the input. This is in bytes
+     * for str and in elements for \[T\]. The input must be the input that was parsed,
+     * or a copy of it. Returns None if the location isn't within the input.
+     */
+    pub fn offset<I: ?Sized + Sequence>(&self, original_input: &I) -> Option<usize> {
+        loc_offset(original_input, self.get_loc()?)
+    }
+    /**
+     * Returns the location of every frame of the error stack as an index into the input,
+     * along with the parser that added the frame. The first frame is the parser that failed,
+     * followed by the combinators containing it. Returns None if any location isn't within the input.
+     */
+    pub fn stack_offsets<I: ?Sized + Sequence>(
+        &self,
+        original_input: &I,
+    ) -> Option<Vec<(usize, ParserType)>> {
+        self.stack
+            .iter()
+            .map(|item| Some((loc_offset(original_input, item.location)?, item.parser_type)))
+            .collect()
+    }
+    /**
+     * Returns the offset of every frame into the input along with a description of the
+     * frame, like `Tag: expected 'a', found 'b'`. The offset is None if the location
+     * isn't within the input.
+     */
+    #[cfg_attr(not(feature = "miette"), allow(dead_code))]
+    pub(crate) fn frame_labels<'s, I: ?Sized + Sequence>(
+        &'s self,
+        original_input: &'s I,
+    ) -> impl Iterator<Item = (Option<usize>, String)> + 's {
+        self.stack.iter().map(|item| {
+            let mut label = format!("{:?}", item.parser_type);
+            write_frame_note(item, &mut label).expect("Writing to a String doesn't fail");
+            (loc_offset(original_input, item.location), label)
+        })
+    }
+    /**
+     * Converts each frame of the error stack into a serializable ReportFrame, starting
+     * with the parser that failed.
+     */
+    #[cfg(feature = "serde")]
+    pub(crate) fn report_frames<'s>(
+        &'s self,
+        original_input: &'s str,
+    ) -> impl Iterator<Item = crate::error_report::ReportFrame> + 's {
+        self.stack
+            .iter()
+            .map(|item| crate::error_report::ReportFrame {
+                offset: loc_offset(original_input, item.location),
+                parser: format!("{:?}", item.parser_type),
+                message: item.message.as_ref().map(|message| message.to_string()),
+                expected: item
+                    .expectation
+                    .as_ref()
+                    .map(|expectation| expectation.expected.to_string()),
+                found: item
+                    .expectation
+                    .as_ref()
+                    .and_then(|expectation| expectation.found)
+                    .map(|found| found.to_string()),
+            })
+    }
+    /**
+     * Returns the external error that caused this error, if there is one. This can be
+     * downcast to tell failures such as `DepthLimitError` apart from ordinary mismatches.
+     */
+    pub fn cause(&self) -> Option<&(dyn Error + Send + Sync + 'static)> {
+        self.cause.as_deref()
+    }
+    /**
+     * Returns the cause if it is a `T`, such as the error returned by a `fab_try_map`
+     * function or a [`TryReducerError`](crate::TryReducerError) from a repeat.
+     */
+    pub fn downcast_cause<T: Error + 'static>(&self) -> Option<&T> {
+        self.cause()?.downcast_ref::<T>()
+    }
+    /**
+     * Iterates over the frames of the error stack, starting with the parser that failed.
+     * Each frame has its offset into the input, like `offset`, and the parser that added it.
+     * Unlike `stack_offsets`, a frame whose location isn't within the input has a None offset
+     * instead of discarding the whole stack.
+     */
+    pub fn frames<'s, I: ?Sized + Sequence>(
+        &'s self,
+        original_input: &'s I,
+    ) -> impl Iterator<Item = (Option<usize>, ParserType)> + 's {
+        self.stack
+            .iter()
+            .map(|item| (loc_offset(original_input, item.location), item.parser_type))
+    }
     pub fn print_trace<I: ?Sized + Sequence + Debug>(&self, parser_input: &I) {
         self.print_trace_window(parser_input, 10);
     }
@@ -218,16 +1009,189 @@ impl FabError {
         parser_input: &I,
         window: usize,
     ) {
-        for item in self.stack.iter().rev() {
-            let (before, after) = get_surrounding_context(parser_input, item.location, window);
-            println!(
-                "Location [{:?}]^[{:?}] from parser {:?}",
-                before, after, item.parser_type
-            )
+        print!("{}", self.trace_string_window(parser_input, window));
+    }
+    /**
+     * Renders the trace printed by print_trace to a String.
+     */
+    pub fn trace_string<I: ?Sized + Sequence + Debug>(&self, parser_input: &I) -> String {
+        self.trace_string_window(parser_input, 10)
+    }
+    pub fn trace_string_window<I: ?Sized + Sequence + Debug>(
+        &self,
+        parser_input: &I,
+        window: usize,
+    ) -> String {
+        let mut out = String::new();
+        self.write_trace_window(parser_input, window, &mut out)
+            .expect("Writing to a String doesn't fail");
+        out
+    }
+    /**
+     * Renders the trace like trace_string, but fails if a location isn't within the
+     * input instead of writing "Location unavailable" for it. This catches passing a
+     * different input than the one that was parsed.
+     */
+    pub fn try_trace_string<I: ?Sized + Sequence + Debug>(
+        &self,
+        parser_input: &I,
+    ) -> Result<String, TraceError> {
+        self.try_trace_string_window(parser_input, 10)
+    }
+    pub fn try_trace_string_window<I: ?Sized + Sequence + Debug>(
+        &self,
+        parser_input: &I,
+        window: usize,
+    ) -> Result<String, TraceError> {
+        for (frame, item) in self.stack.iter().enumerate() {
+            let locations = std::iter::once(item.location).chain(
+                item.alternatives
+                    .iter()
+                    .filter_map(|alternative| Some(alternative.stack.first()?.location)),
+            );
+            for location in locations {
+                if get_surrounding_context(parser_input, location, window).is_none() {
+                    return Err(TraceError { frame });
+                }
+            }
+        }
+        Ok(self.trace_string_window(parser_input, window))
+    }
+    /**
+     * Writes the trace printed by print_trace to the writer, one line per frame.
+     */
+    pub fn write_trace<I: ?Sized + Sequence + Debug, W: fmt::Write>(
+        &self,
+        parser_input: &I,
+        writer: &mut W,
+    ) -> fmt::Result {
+        self.write_trace_window(parser_input, 10, writer)
+    }
+    pub fn write_trace_window<I: ?Sized + Sequence + Debug, W: fmt::Write>(
+        &self,
+        parser_input: &I,
+        window: usize,
+        writer: &mut W,
+    ) -> fmt::Result {
+        for (index, item) in self.stack.iter().enumerate().rev() {
+            match get_surrounding_context(parser_input, item.location, window) {
+                Some((before, after)) => write!(
+                    writer,
+                    "Location [{:?}]^[{:?}] from parser {:?}",
+                    before, after, item.parser_type
+                )?,
+                None => write!(
+                    writer,
+                    "Location unavailable from parser {:?}",
+                    item.parser_type
+                )?,
+            }
+            write_frame_note(item, writer)?;
+            writeln!(writer)?;
+            self.write_alternatives(parser_input, window, index, writer)?;
         }
         if let Some(cause) = &self.cause {
-            println!("From cause [{}]", cause);
+            write_cause(cause.as_ref(), writer)?;
+        }
+        Ok(())
+    }
+    /**
+     * Writes the branches of an `alt_verbose` frame grouped by the location where they
+     * failed, starting with the furthest location.
+     */
+    fn write_alternatives<I: ?Sized + Sequence + Debug, W: fmt::Write>(
+        &self,
+        parser_input: &I,
+        window: usize,
+        index: usize,
+        writer: &mut W,
+    ) -> fmt::Result {
+        let alternatives = &self.stack[index].alternatives;
+        if alternatives.is_empty() {
+            return Ok(());
+        }
+        let mut groups: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        if index > 0 {
+            groups
+                .entry(self.stack[0].location)
+                .or_default()
+                .push(branch_label(&self.stack[..index]));
         }
+        for alternative in alternatives {
+            if let Some(item) = alternative.stack.first() {
+                groups
+                    .entry(item.location)
+                    .or_default()
+                    .push(branch_label(&alternative.stack));
+            }
+        }
+        for (location, labels) in groups.iter().rev() {
+            match get_surrounding_context(parser_input, *location, window) {
+                Some((before, after)) => writeln!(
+                    writer,
+                    "  Branches at [{:?}]^[{:?}]: {}",
+                    before,
+                    after,
+                    labels.join(", ")
+                )?,
+                None => writeln!(
+                    writer,
+                    "  Branches at unavailable location: {}",
+                    labels.join(", ")
+                )?,
+            }
+        }
+        Ok(())
+    }
+    /**
+     * Returns an adapter that displays the trace of this error, so it can be embedded
+     * in other messages. The input must be the input that was parsed.
+     */
+    pub fn display_with_input<'a, I: ?Sized + Sequence + Debug>(
+        &'a self,
+        parser_input: &'a I,
+    ) -> TraceDisplay<'a, I> {
+        TraceDisplay {
+            error: self,
+            input: parser_input,
+        }
+    }
+}
+
+/**
+ * This error is returned by `FabError::try_trace_string` when the location of a frame
+ * of the error stack isn't within the input. The frame is the index into the stack,
+ * starting with the parser that failed.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraceError {
+    pub frame: usize,
+}
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The location of frame {} isn't within the input",
+            self.frame
+        )
+    }
+}
+
+impl Error for TraceError {}
+
+/**
+ * This struct can be constructed through `FabError::display_with_input`. It displays
+ * the trace of the error.
+ */
+pub struct TraceDisplay<'a, I: ?Sized> {
+    error: &'a FabError,
+    input: &'a I,
+}
+
+impl<'a, I: ?Sized + Sequence + Debug> Display for TraceDisplay<'a, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.write_trace(self.input, f)
     }
 }
 
@@ -238,17 +1202,53 @@ mod test {
     #[test]
     fn test_get_surrounding_context_success() {
         let input = "abcdefgh";
-        let place = (input as *const str as *const u8 as usize) + 4;
-        let (start, rest) = get_surrounding_context(input, place, 3);
+        let place = input_loc(&input[4..]);
+        let (start, rest) = get_surrounding_context(input, place, 3).unwrap();
         assert_eq!(start, "bcd");
         assert_eq!(rest, "efg");
     }
 
+    #[test]
+    fn test_get_line_col_multibyte() {
+        let input = "héllo\r\nwörld";
+        assert_eq!(get_line_col(input, 0), (1, 1, "héllo"));
+        assert_eq!(get_line_col(input, 3), (1, 3, "héllo"));
+        //Offset 2 is inside the é, so it points to it.
+        assert_eq!(get_line_col(input, 2), (1, 2, "héllo"));
+        assert_eq!(get_line_col(input, 6), (1, 6, "héllo"));
+        assert_eq!(get_line_col(input, 11), (2, 3, "wörld"));
+        assert_eq!(get_line_col(input, input.len()), (2, 6, "wörld"));
+    }
+
+    #[test]
+    fn test_get_offset_slice() {
+        let input = [1u32, 2, 3, 4].as_slice();
+        let start = input.as_ptr() as usize;
+        assert_eq!(get_offset(input, start + 8), Some(2));
+        assert_eq!(get_offset(input, start + 16), Some(4));
+        assert_eq!(get_offset(input, start + 6), None);
+        assert_eq!(get_offset(input, start + 20), None);
+        assert_eq!(get_offset(input, start - 4), None);
+    }
+
+    #[test]
+    fn test_get_surrounding_context_outside() {
+        let input = "aé";
+        //Inside the é.
+        assert_eq!(get_surrounding_context(input, usize::MAX - 1, 3), None);
+        //More input was left than there is.
+        assert_eq!(get_surrounding_context(input, usize::MAX - 4, 3), None);
+        assert_eq!(
+            get_surrounding_context(input, input_loc(""), 3),
+            Some(("aé", ""))
+        );
+    }
+
     #[test]
     fn test_get_surrounding_context_trimmed() {
         let input = "abcd";
-        let place = (input as *const str as *const u8 as usize) + 2;
-        let (start, rest) = get_surrounding_context(input, place, 3);
+        let place = input_loc(&input[2..]);
+        let (start, rest) = get_surrounding_context(input, place, 3).unwrap();
         assert_eq!(start, "ab");
         assert_eq!(rest, "cd");
     }
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{error::write_annotation, FabError};
+
+/**
+ * A plain, serializable snapshot of a FabError for tooling such as editors and test
+ * harnesses. Frames start with the parser that failed. Everything is stored as owned
+ * strings and byte offsets into the input, so a report can be deserialized and rendered
+ * without the parser that produced it.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorReport {
+    pub frames: Vec<ReportFrame>,
+    /**
+     * The external error that caused the failure, formatted with Display.
+     */
+    pub cause: Option<String>,
+    /**
+     * The source chain of the cause, outermost first.
+     */
+    pub caused_by: Vec<String>,
+}
+
+/**
+ * One frame of an ErrorReport. The offset is in bytes into the input, and is None if
+ * the location wasn't within the input. The parser is the ParserType formatted with
+ * Debug, like `Tag` or `Custom("identifier")`.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportFrame {
+    pub offset: Option<usize>,
+    pub parser: String,
+    pub message: Option<String>,
+    pub expected: Option<String>,
+    pub found: Option<String>,
+}
+
+impl FabError {
+    /**
+     * Converts this error into a serializable ErrorReport. The input must be the input
+     * that was parsed.
+     */
+    pub fn to_report(&self, original_input: &str) -> ErrorReport {
+        let mut caused_by = Vec::new();
+        let mut source = self.cause().and_then(|cause| cause.source());
+        while let Some(err) = source {
+            caused_by.push(err.to_string());
+            source = err.source();
+        }
+        ErrorReport {
+            frames: self.report_frames(original_input).collect(),
+            cause: self.cause().map(|cause| cause.to_string()),
+            caused_by,
+        }
+    }
+}
+
+impl ErrorReport {
+    /**
+     * Renders the report against the input that was parsed. This is the same text as
+     * `FabError::annotate`, so a deserialized report renders like the original error.
+     */
+    pub fn render(&self, original_input: &str) -> String {
+        let mut out = String::new();
+        for frame in &self.frames {
+            let Some(offset) = frame.offset else {
+                continue;
+            };
+            write_annotation(original_input, offset, &frame.note(), &mut out);
+        }
+        if let Some(cause) = &self.cause {
+            out.push_str(&format!("From cause [{}]\n", cause));
+            for err in &self.caused_by {
+                out.push_str(&format!("Caused by [{}]\n", err));
+            }
+        }
+        out
+    }
+}
+
+impl ReportFrame {
+    /**
+     * Describes the frame, like `from parser Tag: expected 'a', found 'b'`.
+     */
+    pub fn note(&self) -> String {
+        let mut note = format!("from parser {}", self.parser);
+        let mut separator = ": ";
+        if let Some(message) = &self.message {
+            note.push_str(separator);
+            note.push_str(message);
+            separator = ", ";
+        }
+        if let Some(expected) = &self.expected {
+            note.push_str(&format!("{}expected {}", separator, expected));
+            if let Some(found) = &self.found {
+                note.push_str(&format!(", found {}", found));
+            }
+        }
+        note
+    }
+}
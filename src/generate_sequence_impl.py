@@ -1,6 +1,6 @@
 # A python script to generate the input to the rust Macros. I'm sorry Ferris.
-for i in range(11):
-    print(f'sequence_impl!(SeqAlt{i+1}', end = "")
+for i in range(21):
+    print(f'sequence_impl!(Seq{i+1}', end = "")
     for j in range(1, i+2):
         print(f' P{j} p{j} r{j} O{j} T{j}', end = "")
     print(");")
\ No newline at end of file
@@ -12,10 +12,12 @@
 //!| `'a'` | `let mut input = "def"` | `'a'.fab(&mut input)` | `FabError(...)` | `"def"`|
 //!| `"abc"` | `let mut input = "abcdef"` | `"abc".fab(&mut input)` | `"abc"` | `"def"`|
 //!| `[1, 2]` | `let mut input =  "[1, 2, 3].as_slice()"` | `[1, 2].fab(&mut input)` | `[1, 2]` | `[3]`|
+//!| `let keyword = String::from("abc")` | `let mut input = "abcdef"` | `keyword.fab(&mut input)` | `"abc"` | `"def"`|
 //!| `('a'..='z')` | `let mut input = "zyx"` | `('a'..='z').fab(&mut input)` | `z` | `"yx"`|
 //!| [`take`]`(2)` | `let mut input =  "abc"` | `take(2).fab(&mut input)` | `"ab"` | `"c"`|
 //!| `❘c❘ c=='m'` | `let mut input = "moo"` | `(❘c❘ c=='m').fab(&mut input)` | `'m'` | `"oo"`|
 //!| [`char::is_ascii_digit`] | `let mut input = "123"` | `char::is_ascii_digit.fab(&mut input)` | `'1'` | `"23"`|
+//!| [`char::is_alphabetic`] | `let mut input = "ab1"` | `char::is_alphabetic.fab(&mut input)` | `'a'` | `"b1"`|
 //!| `let parser = ❘c: char❘ if c=='m' {Some(5)} else {None}` | `let mut input = "moo"` | `parser.fab(&mut input)` | `5` | `"oo"`|
 //!| `let parser = ❘c: char❘ if c=='m' {Ok(5)} else {Err(ErrType)}` | `let mut input = "moo"` | `parser.fab(&mut input)` | `5` | `"oo"`|
 //! 
@@ -45,6 +47,7 @@
 //!| `let parser = 'a'.fab_repeat()` | `let mut input = "aabb"` | `parser.fab(&mut input)` | `vec['a','a']` | `"bb"`|
 //!| `let parser = 'a'.fab_repeat()` | `let mut input = "bbbb"` | `parser.fab(&mut input)` | `vec[]` | `"bbbb"`|
 //!| `let parser = 'a'.fab_repeat().as_input_slice()` | `let mut input = "aabb"` | `parser.fab(&mut input)` | `"aa"` | `"bb"`|
+//!| `let parser = char::is_alphabetic.fab_repeat().as_input_slice()` | `let mut input = "ab12"` | `parser.fab(&mut input)` | `"ab"` | `"12"`|
 //!| `let parser = 'a'.fab_repeat().min(1)` | `let mut input = "bbbb"` | `parser.fab(&mut input)` | `FabError(...)` | `"bbbb"`|
 //! 
 //! fab_try_map works both with functions that return Results and ones that return Options.
@@ -63,35 +66,97 @@
 //!| `opt('a')` | `let mut input = "cab"` | `opt('a').fab(&mut input)` | `None` | `"cab"`|
 //!| `take_not('a')` | `let mut input = "cab"` | `take_not('a').fab(&mut input)` | `'c'` | `"ab"`|
 //!| `take_not('a')` | `let mut input = "abc"` | `take_not('a').fab(&mut input)` | `FabError(...)` | `"abc"`|
+//!| `find('c')` | `let mut input = "abcd"` | `find('c').fab(&mut input)` | `'c'` | `"d"`|
+//!| `extract(('b','d'))` | `let mut input = "abcde"` | `extract(('b','d')).fab(&mut input)` | `('b','d')` | `"e"`|
 //! 
 //! Some code is inspired by Winnow by Ed Page + other contributors.
 
+mod attach;
+pub mod binary;
 pub mod branch;
+pub mod bytes;
 pub mod combinator;
 pub mod error;
+#[cfg(feature = "serde")]
+pub mod error_report;
+pub mod located;
+#[cfg(feature = "nom")]
+pub mod nom_compat;
+#[cfg(feature = "regex")]
+pub mod re;
 pub mod repeat;
+pub mod replay;
+#[cfg(feature = "miette")]
+pub mod report;
+pub mod scan;
 pub mod sequence;
+pub mod state;
 pub mod tag;
 pub mod util;
 
 use std::{
+    borrow::Cow,
+    cell::RefCell,
+    error::Error,
     fmt::Debug,
     marker::PhantomData,
+    str::FromStr,
 };
 
-use combinator::{Opt, ParserMap, ParserTryMap, TakeNot, Value};
+use combinator::{
+    AssertRestores, Complete, Dbg, Memoize, ParserMapErr, ParserMapWithSpan, ParserOpt, ParserOr,
+    ParserOrElse, WithMessage,
+};
+pub use branch::Alt;
+pub use branch::ParserAlt;
+pub use branch::Either;
+pub use branch::Permutation;
+pub use combinator::DepthLimitError;
+pub use combinator::Opt;
+pub use combinator::ParserMap;
+pub use combinator::ParserTryMap;
+pub use combinator::TakeNot;
+pub use combinator::Value;
+pub use tag::Take;
+pub use error::ConvertError;
+pub use error::Expected;
 pub use error::FabError;
+pub use error::Found;
 pub use error::ParserError;
 pub use error::NoContextFabError;
+pub use error::OffsetFabError;
+pub use error::Streaming;
+pub use error::TraceError;
+pub use located::Located;
+pub use sequence::InputWrapper;
+pub use sequence::SliceLike;
+pub use repeat::ArrayLenError;
+pub use repeat::FillBorrowedError;
 pub use repeat::TryReducer;
 pub use repeat::TryReducerError;
 pub use repeat::Repeat;
+use repeat::FabIter;
 use repeat::Reducer;
+pub use branch::EmptyAltError;
+pub use bytes::CharBoundaryError;
+pub use bytes::StrBytes;
+#[cfg(feature = "serde")]
+pub use error_report::ErrorReport;
+pub use replay::ReplayError;
+#[cfg(feature = "miette")]
+pub use report::FabReport;
+pub use scan::ExtractError;
+pub use state::State;
+pub use state::Stateful;
 /**
  * This enum represents the kinds of parsers in Fabparse. This is used in errors to 
  * identify the parser that failed.
+ *
+ * User written parsers can identify themselves with `Custom`, for example
+ * `E::from_parser_error(*input, ParserType::Custom("identifier"))`.
  */
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ParserType {
     Tag,
     Alt,
@@ -104,20 +169,47 @@ pub enum ParserType {
     RepeatIter,
     Sequence,
     Permutation,
+    Find,
+    Extract,
+    MapErr,
+    Complete,
+    RepeatTill,
+    Dispatch,
+    Message,
+    Value,
+    Take,
+    Length,
+    Escaped,
+    Precedence,
+    DepthGuard,
+    Fail,
+    Custom(&'static str),
 }
 
 
+/**
+ * A type erased parser. This can be named in structs and function signatures, which is
+ * useful for recursive grammars. Any parser can be converted into one with `fab_boxed`.
+ * It is a parser through the impl for functions.
+ */
+pub type BoxedParser<'a, I, O, E> = Box<dyn Fn(&mut &'a I) -> Result<O, E> + 'a>;
+
 pub trait Parser<'a, I: ?Sized, O, E: ParserError, ParserType> {
     /**
      * Parses the input. This method advances the input reference to the remaining
      * unparsed input. The method is named "fab" instead of "parse" to avoid conflicts
      * with the "parse" method of &str.
+     *
+     * On failure, the input should be left where it was. Combinators that recover from
+     * a failure, such as `opt`, `alt`, `permutation` and `fab_repeat`, restore it
+     * themselves, so a parser that doesn't can't corrupt the parsers after them.
+     * Use `fab_assert_restores` to check a parser in tests.
      */
     fn fab(&self, input: &mut &'a I) -> Result<O, E>;
     /**
      * Returns a parser that replaces the output of the underlying parser with V.
      */
-    fn fab_value<V: Clone>(self, value: V) -> combinator::Value<Self, V, I, O, E>
+    fn fab_value<V: Clone>(self, value: V) -> Value<Self, V, I, O, E>
     where
         Self: Sized,
     {
@@ -146,10 +238,31 @@ pub trait Parser<'a, I: ?Sized, O, E: ParserError, ParserType> {
             phantom_m: PhantomData,
         }
     }
+    /**
+     * Like `fab_map`, but the function also receives the slice of input that the
+     * underlying parser consumed. This is useful for keeping the source text of
+     * what was parsed.
+     */
+    fn fab_map_with_span<F>(self, func: F) -> ParserMapWithSpan<Self, I, O, E, F>
+    where
+        Self: Sized,
+    {
+        ParserMapWithSpan {
+            parser: self,
+            func,
+            phantom_i: PhantomData,
+            phantom_e: PhantomData,
+            phantom_m: PhantomData,
+        }
+    }
     /**
      * This parser first maps the input, and if the result is Option::Some or Result::Ok,
      * it unwraps the input. Othewise, the parser fails. 
      *
+     * The error of a Result is kept as the cause of the parser error. It can be anything
+     * that converts into a `Box<dyn Error + Send + Sync>`, such as a type implementing
+     * `Error`, a boxed error or an `anyhow::Error`. Traces print the cause along with the
+     * errors in its source chain.
      */
     fn fab_try_map<F>(self, func: F) -> ParserTryMap<Self, I, O, E, F>
     where
@@ -163,6 +276,224 @@ pub trait Parser<'a, I: ?Sized, O, E: ParserError, ParserType> {
             phantom_m: PhantomData,
         }
     }
+    /**
+     * Converts the output of the underlying parser, such as the &str from
+     * [`util::num_signed_str`], into T with [`FromStr`]. If the conversion fails,
+     * the parser fails without consuming input and the error from `T::from_str`
+     * is kept as the cause.
+     */
+    #[allow(clippy::type_complexity)]
+    fn fab_parse_to<T>(self) -> ParserTryMap<Self, I, O, E, fn(O) -> Result<T, T::Err>>
+    where
+        Self: Sized,
+        O: AsRef<str>,
+        T: FromStr,
+        T::Err: Error + Send + Sync + 'static,
+    {
+        self.fab_try_map(|out: O| T::from_str(out.as_ref()))
+    }
+    /**
+     * This creates a parser that applies the function to the error of the underlying
+     * parser. The function can change the error type, which allows parsers written
+     * against different error types to be combined.
+     */
+    fn fab_map_err<F>(self, func: F) -> ParserMapErr<Self, I, O, E, F>
+    where
+        Self: Sized,
+    {
+        ParserMapErr {
+            parser: self,
+            func,
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+    /**
+     * Converts the error of the underlying parser into `E2` using the [`ConvertError`] trait.
+     * For example, `parser.fab_convert_err::<FabError>()` lets a parser written against
+     * `NoContextFabError` be used inside a sequence of parsers returning `FabError`.
+     * For other error types, use `fab_map_err` with a conversion function such as `From::from`.
+     */
+    #[allow(clippy::type_complexity)]
+    fn fab_convert_err<E2>(self) -> ParserMapErr<Self, I, O, E, fn(E) -> E2>
+    where
+        Self: Sized,
+        E2: ConvertError<E>,
+    {
+        self.fab_map_err(E2::convert_error)
+    }
+    /**
+     * Makes the underlying parser optional. This is the same as wrapping it with [`opt`],
+     * but reads in the same order as the other methods.
+     * If the underlying parser succeeds with Ok(out), this parser returns Some(out).
+     * Otherwise, this parser succeeds with None and consumes no input.
+     */
+    fn fab_opt(self) -> ParserOpt<Self, I, O, E>
+    where
+        Self: Sized,
+    {
+        ParserOpt {
+            parser: self,
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+    /**
+     * Like [`Parser::fab_opt`], but returns a clone of `default` instead of None, as in
+     * `alt(('-'.fab_value(-1), '+'.fab_value(1))).fab_or(1)`. This parser never fails,
+     * and consumes no input when the default is returned.
+     */
+    fn fab_or(self, default: O) -> ParserOr<Self, I, O, E>
+    where
+        Self: Sized,
+        O: Clone,
+    {
+        ParserOr {
+            parser: self,
+            default,
+            phantom_i: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+    /**
+     * Like [`Parser::fab_or`], but the default is computed by calling `func` each time
+     * the underlying parser fails. This is useful when the default is expensive to build
+     * or isn't Clone.
+     */
+    fn fab_or_else<F>(self, func: F) -> ParserOrElse<Self, I, O, E, F>
+    where
+        Self: Sized,
+        F: Fn() -> O,
+    {
+        ParserOrElse {
+            parser: self,
+            func,
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+    /**
+     * Tries this parser, and if it fails, tries `other` from the same input. This is the
+     * same as `alt((self, other))`, but reads in the same order as the other methods, and
+     * both parsers must have the same output. If both fail, the error is chosen like alt,
+     * from the parser that made the furthest progress.
+     *
+     * Chains such as `a.fab_alt(b).fab_alt(c)` build the flat `alt((a, b, c))`, so their
+     * errors are the same as a single alt. For a fallback that is expensive to build, pass
+     * `lazy(|| ...)` to build it only when this parser fails.
+     */
+    fn fab_alt<P>(self, other: P) -> ParserAlt<(Self, P), I, O, E>
+    where
+        Self: Sized,
+    {
+        ParserAlt {
+            alt: Alt((self, other)),
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+    /**
+     * Requires the underlying parser to consume all of the input. If there is
+     * input left over, this parser fails with an error located at the start of the
+     * unparsed input, and the input is restored.
+     */
+    fn fab_complete(self) -> Complete<Self, I, O, E>
+    where
+        Self: Sized,
+    {
+        Complete {
+            parser: self,
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+    /**
+     * Attaches a message to the error if the underlying parser fails, such as
+     * "while parsing the dependencies table". The message is shown in the error trace.
+     */
+    fn fab_msg(self, message: impl Into<Cow<'static, str>>) -> WithMessage<Self, I, O, E>
+    where
+        Self: Sized,
+    {
+        WithMessage {
+            parser: self,
+            message: message.into(),
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+    /**
+     * Prints the label with the start of the input to stderr, runs the underlying parser,
+     * then prints whether it succeeded and what it consumed. The output, error and input
+     * are exactly those of the underlying parser. Nested fab_dbg parsers are indented,
+     * so the output shows the call tree.
+     */
+    fn fab_dbg(self, label: impl Into<Cow<'static, str>>) -> Dbg<Self, I, O, E>
+    where
+        Self: Sized,
+    {
+        Dbg {
+            parser: self,
+            label: label.into(),
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+    /**
+     * Checks that this parser leaves the input where it was when it fails, panicking in
+     * debug builds if it doesn't. The combinators restore the input after a failure
+     * themselves, so this is for testing hand written parsers, which should restore it
+     * on every error path.
+     */
+    fn fab_assert_restores(self) -> AssertRestores<Self, I, O, E>
+    where
+        Self: Sized,
+    {
+        AssertRestores {
+            parser: self,
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+    /**
+     * Caches the result of this parser at each input position, so a grammar that
+     * backtracks over the same input, such as an alt whose branches share a prefix,
+     * doesn't parse it again. The output and error must be Clone. Call `clear` on the
+     * returned parser before reusing it on a different input.
+     */
+    fn fab_memoize(self) -> Memoize<Self, I, O, E>
+    where
+        Self: Sized,
+    {
+        Memoize::new(self)
+    }
+    /**
+     * Returns an iterator that lazily applies this parser to the input, advancing
+     * it after each successful parse. See [`repeat::FabIter`] for how the iteration ends.
+     */
+    fn fab_iter<'b>(self, input: &'b mut &'a I) -> FabIter<'a, 'b, Self, I, O, E, ParserType>
+    where
+        Self: Sized,
+    {
+        FabIter::new(self, input)
+    }
+    /**
+     * Erases the type of this parser into a [`BoxedParser`].
+     */
+    fn fab_boxed(self) -> BoxedParser<'a, I, O, E>
+    where
+        Self: Sized + 'a,
+    {
+        Box::new(move |input| self.fab(input))
+    }
     /**
      * Repeats the underlying parser, returning the results in a Vec. This
      * parser will accept any number of repetitions, including 0.
@@ -171,15 +502,15 @@ pub trait Parser<'a, I: ?Sized, O, E: ParserError, ParserType> {
      * [`Repeat`] struct for these emthods. 
      *
      */
-    fn fab_repeat(self) -> Repeat<Self, I, O, E, fn(&mut Vec<O>, O) -> (), Vec<O>>
+    #[allow(clippy::type_complexity)]
+    fn fab_repeat(self) -> Repeat<Self, I, O, E, fn(&mut Vec<O>, O), Vec<O>>
     where
         Self: Sized,
-        O: Clone,
     {
         Repeat::new(
             self,
             Reducer {
-                acc: Vec::new(),
+                init: Vec::new,
                 reduce_operator: |vec: &mut Vec<O>, val| vec.push(val),
             },
             0..usize::MAX,
@@ -196,11 +527,49 @@ pub trait Parser<'a, I: ?Sized, O, E: ParserError, ParserType> {
  * When using `FabError`, the error returned will be the error of the parser that made the
  * furthest progress. When using a parser that doesn't provide error locations, or in the event
  * of ties, FunnelParse makes no garuntees as to which child parser's error will be returned.
+ *
+ * This function also takes a slice or Vec of parsers of the same type, for alternatives that are
+ * only known at runtime. If the slice or Vec is empty, the parser fails with an [`EmptyAltError`] cause.
  */
 pub fn alt<T>(parsers: T) -> branch::Alt<T> {
     branch::Alt(parsers)
 }
 
+/**
+ * Like [`alt`], but for 2 or 3 parsers with different output types. The output is an
+ * [`Either`] holding the output of the branch that matched, which saves declaring an enum
+ * when the result is converted with `fab_map` right away. Errors and input restoration
+ * are the same as for `alt`.
+ */
+pub fn alt_either<T>(parsers: T) -> branch::AltEither<T> {
+    branch::AltEither(parsers)
+}
+
+/**
+ * Like [`alt`], but keeps the errors of every branch instead of only the one that made
+ * the furthest progress. With `FabError`, the other branches are attached to the Alt
+ * frame of the error, and the trace lists them grouped by location. This is useful
+ * for messages like "expected one of: let, fn, struct".
+ */
+pub fn alt_verbose<T>(parsers: T) -> branch::AltVerbose<T> {
+    branch::AltVerbose(parsers)
+}
+
+/**
+ * Like [`alt`], but commits to the branch that consumed the most input rather than the
+ * first one that matched, with ties going to the earlier branch. This suits tokenizers,
+ * where `alt_longest(("in", "int", "integer"))` matches all of `integer` regardless of
+ * the order of the branches. Only the output of the longest match is kept, so the output
+ * doesn't need to be Clone.
+ *
+ * Every branch is run on every call, so this is slower than `alt` when an early branch
+ * matches. If none of the branches match, the error is chosen as for `alt`. It also takes
+ * a slice or Vec of parsers.
+ */
+pub fn alt_longest<T>(parsers: T) -> branch::AltLongest<T> {
+    branch::AltLongest(parsers)
+}
+
 /**
  * This function takes in a tuple of 1 to 11 parsers. It returns a parser that
  * succeeds when all of the input parsers have succeeded in any order.
@@ -216,13 +585,273 @@ pub fn permutation<T>(parsers: T) -> branch::Permutation<T> {
     branch::Permutation(parsers)
 }
 
+/**
+ * This function takes in a tuple of 1 to 11 parsers. It returns a parser that matches
+ * each of the input parsers at most once, in any order. None of them are required.
+ * `permutation_opt((parser_1,parser_2))` will return `(Option<output_parser_1>, Option<output_parser_2>)`,
+ * with None for each parser that didn't match.
+ *
+ * This parser never fails. It stops when none of the remaining parsers match.
+ */
+pub fn permutation_opt<T>(parsers: T) -> branch::PermutationOpt<T> {
+    branch::PermutationOpt(parsers)
+}
+
+/**
+ * Creates a parser that runs `first`, `sep` and `second` in sequence, returning the
+ * outputs of `first` and `second` and discarding the output of `sep`. This is useful
+ * for constructs such as `key=value` and `host:port`. Like a tuple of parsers, it consumes
+ * no input on failure.
+ */
+pub fn separated_pair<P1, S, P2>(
+    first: P1,
+    sep: S,
+    second: P2,
+) -> branch::SeparatedPair<P1, S, P2> {
+    branch::SeparatedPair { first, sep, second }
+}
+
+/**
+ * Creates a parser that matches zero or more `parser`s separated by `sep`, such as the
+ * elements of `1, 2, 3`, and returns their outputs in a Vec. The outputs of the separators
+ * are discarded. Like `fab_repeat`, the number of elements can be limited with `min`,
+ * `max` and `bound`.
+ *
+ * By default, a separator that isn't followed by an element is left in the input, so
+ * `[1, 2,]` fails at the `,` when followed by `']'`. Use `allow_trailing` to consume it.
+ */
+pub fn separated<P, S>(parser: P, sep: S) -> repeat::Separated<P, S> {
+    repeat::Separated {
+        parser,
+        sep,
+        bounds: 0..usize::MAX,
+        allow_trailing: false,
+    }
+}
+
+/**
+ * Creates a parser that runs `parser` exactly `buf.len()` times, writing each output into
+ * the corresponding slot of `buf` instead of collecting them into a Vec. The output is `()`,
+ * and the buffer can be taken back with `into_inner` or read after the parser is dropped.
+ *
+ * If there are fewer matches than slots, this parser fails and consumes no input. The slots
+ * before the missing match have already been overwritten, and the rest are left unchanged.
+ * Like `fab_repeat`, it fails rather than looping if `parser` consumes no input. If `parser`
+ * runs the same `fill` again, the nested run fails with a [`FillBorrowedError`] cause.
+ */
+pub fn fill<P, O>(parser: P, buf: &mut [O]) -> repeat::Fill<'_, P, O> {
+    repeat::Fill {
+        parser,
+        buf: RefCell::new(buf),
+    }
+}
+
 /**
  * `take(x: usize) `Constructs a parser that takes `x` items. For strings, this
  * will be characters and for arrays it will be elements. This parser outputs a &str for an input of &str
  * and a &\[T\] for an input of &\[T\]
+ *
+ * `take` also accepts a range such as `take(1..=8)`. This takes as many items as are available
+ * up to the maximum, and fails without consuming input if there are fewer than the minimum.
+ */
+pub fn take<B: tag::TakeBounds>(count: B) -> tag::Take {
+    count.take_bounds()
+}
+
+/**
+ * Repeats the item parser until the terminator parser matches, returning the items in a Vec
+ * along with the output of the terminator. The terminator is checked before each item,
+ * so it is never consumed by the item parser. If the item parser fails before the
+ * terminator matches, this parser fails and consumes no input.
+ *
+ * The repetition can be customized with the methods on [`Repeat`] by using
+ * `item.fab_repeat()...till(terminator)` instead.
+ */
+#[allow(clippy::type_complexity)]
+pub fn repeat_till<P, T, I: ?Sized, O, E>(
+    item: P,
+    terminator: T,
+) -> repeat::RepeatTill<Repeat<P, I, O, E, fn(&mut Vec<O>, O), Vec<O>>, T> {
+    let repeat: Repeat<P, I, O, E, fn(&mut Vec<O>, O), Vec<O>> = Repeat::new(
+        item,
+        Reducer {
+            init: Vec::new,
+            reduce_operator: |vec: &mut Vec<O>, val| vec.push(val),
+        },
+        0..usize::MAX,
+    );
+    repeat.till(terminator)
+}
+/**
+ * Creates a parser that parses a key with `key_parser`, then looks up the body parser
+ * for the key with `table` and runs it. The key is only parsed once, unlike with `alt`.
+ * `table` is a function from the key to `Option<parser>`. All of the body parsers must have
+ * the same type, function pointers such as `fn(&mut &[u8]) -> Result<O, FabError>` work well.
+ *
+ * If `table` returns None, this parser fails at the location of the key.
+ * If the key or body parser fails, this parser fails. In all cases, no input is consumed on failure.
+ */
+pub fn dispatch<KP, F>(key_parser: KP, table: F) -> combinator::Dispatch<KP, F> {
+    combinator::Dispatch { key_parser, table }
+}
+/**
+ * Creates a parser from a function that mutates its captured state, such as a symbol
+ * table or a counter. The function is held in a RefCell. If the parser is called again while
+ * it is already running, such as from a recursive grammar, that call fails with a
+ * [`tag::ReentrantFnMutError`] cause instead of panicking.
+ *
+ * Like other function parsers, it consumes no input on failure.
+ */
+pub fn from_fn_mut<F>(func: F) -> tag::FromFnMut<F> {
+    tag::FromFnMut {
+        func: RefCell::new(func),
+    }
+}
+
+/**
+ * Parses a length with `len_parser`, then takes exactly that many items from the input.
+ * This is bytes for \[u8\] and chars for str. The length can be any output that converts
+ * into a usize. If the body fails, the input is restored to before the length.
+ */
+pub fn length_take<L>(len_parser: L) -> combinator::LengthTake<L> {
+    combinator::LengthTake { len_parser }
+}
+
+/**
+ * Parses a count with `len_parser`, then parses exactly that many items with `item_parser`
+ * into a Vec. If any item fails, the input is restored to before the count.
+ */
+pub fn length_repeat<L, P>(len_parser: L, item_parser: P) -> combinator::LengthRepeat<L, P> {
+    combinator::LengthRepeat {
+        len_parser,
+        item_parser,
+    }
+}
+
+/**
+ * Recognizes runs of `normal` interspersed with escapes, and returns the raw slice.
+ * An escape is `control` followed by `escapable`, for example
+ * `escaped(take_not(|c: char| c == '\\' || c == '"'), '\\', |c: char| "\\\"nrt".contains(c))`.
+ * Parsing stops when neither `normal` nor an escape makes progress, so it may match
+ * nothing. If `escapable` fails after `control`, the error is located at the control
+ * item and no input is consumed.
+ */
+pub fn escaped<N, C, X>(normal: N, control: C, escapable: X) -> combinator::Escaped<N, C, X> {
+    combinator::Escaped {
+        normal,
+        control,
+        escapable,
+    }
+}
+
+/**
+ * Like [`escaped`], but `transform` maps each escape to its replacement, which can be a char,
+ * &str, String or anything else a String can be extended with. The output borrows from
+ * the input unless there were escapes.
+ */
+pub fn escaped_transform<N, C, X>(
+    normal: N,
+    control: C,
+    transform: X,
+) -> combinator::EscapedTransform<N, C, X> {
+    combinator::EscapedTransform {
+        normal,
+        control,
+        transform,
+    }
+}
+
+/**
+ * Creates a parser that calls `func` to build the underlying parser each time it runs,
+ * and then runs it. This allows a parser to refer to parsers that are defined later, or
+ * to itself, as in `alt((number, lazy(|| list)))`. Building the parser doesn't allocate
+ * unless `func` does. Errors and input restoration are exactly those of the underlying parser.
+ */
+pub fn lazy<F>(func: F) -> combinator::Lazy<F> {
+    combinator::Lazy { func }
+}
+
+/**
+ * Creates a parser that always succeeds with a clone of `value` and consumes no input.
+ * This is useful as a default branch, as in `alt((keyword, success("none")))`.
+ * Like any parser that doesn't consume input, it makes `fab_repeat` fail unless
+ * `allow_empty_matches` is set.
+ */
+pub fn success<V>(value: V) -> combinator::Success<V> {
+    combinator::Success { value }
+}
+
+/**
+ * Creates a parser that always fails at the current location with a `ParserType::Fail`
+ * error and consumes no input. The output type is chosen by the caller, as in
+ * `fail::<char>()`. In an `alt`, any branch that made progress before failing takes
+ * priority over this error.
+ */
+pub fn fail<O>() -> combinator::Fail<O> {
+    combinator::Fail {
+        phantom_o: PhantomData,
+    }
+}
+
+/**
+ * Creates a parser that fails instead of running `parser` once `limit` depth guarded parsers
+ * are already running on this thread. Wrapping the recursive step of a grammar with this
+ * bounds how deep untrusted input can nest, so it fails with a `DepthLimitError` cause
+ * rather than overflowing the stack. The input is left unchanged on failure.
  */
-pub fn take(count: usize) -> tag::Take {
-    tag::Take(count)
+pub fn depth_guard<P>(limit: usize, parser: P) -> combinator::DepthGuard<P> {
+    combinator::DepthGuard { parser, limit }
+}
+
+/**
+ * Creates a parser that matches the regex `pattern` at the start of the input and returns
+ * the matched str. A match later in the input doesn't count. The regex is compiled once
+ * here, and this panics if the pattern is invalid; use `re::Re::new` to handle that error.
+ * On failure the error records the pattern as what was expected.
+ */
+#[cfg(feature = "regex")]
+pub fn re(pattern: &str) -> re::Re {
+    re::Re::new(pattern).unwrap_or_else(|err| panic!("Invalid regex {:?}: {}", pattern, err))
+}
+
+/**
+ * Like `re`, but returns the capture groups of the match. The input is advanced past the
+ * whole match.
+ */
+#[cfg(feature = "regex")]
+pub fn re_captures(pattern: &str) -> re::ReCaptures {
+    re::ReCaptures::new(pattern)
+        .unwrap_or_else(|err| panic!("Invalid regex {:?}: {}", pattern, err))
+}
+
+/**
+ * Wraps a nom parser, such as `fn(&str) -> nom::IResult<&str, O>`, so it can be used
+ * inside fabparse combinators. On success the input is advanced to the rest that nom
+ * returned. nom errors become a [`nom_compat::NomErrorCause`] located where nom failed, and
+ * `nom::Err::Incomplete` becomes an incomplete error. No input is consumed on failure.
+ */
+#[cfg(feature = "nom")]
+pub fn from_nom<F>(func: F) -> nom_compat::FromNom<F> {
+    nom_compat::FromNom { func }
+}
+
+/**
+ * Wraps a fabparse parser as a nom parser, so it can be called from nom code. Errors
+ * become a `nom::error::Error` with `ErrorKind::Fail` at the location where fabparse
+ * failed. Incomplete errors become `nom::Err::Incomplete`.
+ */
+#[cfg(feature = "nom")]
+pub fn to_nom<'a, O, PType, P>(parser: P) -> impl Fn(&'a str) -> nom::IResult<&'a str, O>
+where
+    P: Parser<'a, str, O, FabError, PType>,
+{
+    move |input| {
+        let mut rest = input;
+        match parser.fab(&mut rest) {
+            Ok(out) => Ok((rest, out)),
+            Err(err) => Err(nom_compat::to_nom_error(input, err)),
+        }
+    }
 }
 
 /**
@@ -233,6 +862,49 @@ pub fn take(count: usize) -> tag::Take {
 pub fn opt<T>(parser: T) -> combinator::Opt<T> {
     Opt { parser }
 }
+/**
+ * Takes chars from a str while the predicate matches them, and returns the matched slice.
+ * This behaves like `predicate.fab_repeat().as_input_slice()`, including the `min`, `max`
+ * and `bound` methods and the errors, but scans the str directly rather than
+ * parsing one char at a time. The predicate can take `char` or `&char`, so
+ * `take_while(char::is_ascii_whitespace)` works.
+ */
+pub fn take_while<F>(predicate: F) -> repeat::TakeWhile<F> {
+    repeat::TakeWhile {
+        predicate,
+        bounds: 0..usize::MAX,
+    }
+}
+/**
+ * Matches a single char or byte, or one in a range, ignoring ASCII case. For example
+ * `ascii_no_case('a'..='f')` also matches `'A'..='F'`. The item from the input is returned.
+ * Non-ASCII chars are compared exactly.
+ */
+pub fn ascii_no_case<M>(matcher: M) -> tag::AsciiNoCase<M> {
+    tag::AsciiNoCase { matcher }
+}
+/**
+ * Matches the longest of the literals at the start of the input and returns a clone of
+ * its value, as in `literals([("in", Tok::In), ("int", Tok::Int), ("let", Tok::Let)])`.
+ * The literals are built into a trie when this is called, so matching reads the input
+ * once no matter how many literals there are. This is much faster than an `alt` over
+ * many tags, and the order of the literals doesn't matter. It matches both str and
+ * \[u8\] input. If none of the literals match, this fails and consumes no input.
+ */
+pub fn literals<K: AsRef<str>, V>(pairs: impl IntoIterator<Item = (K, V)>) -> tag::Literals<V> {
+    tag::Literals::new(pairs)
+}
+
+/**
+ * Matches the first item of a slice, such as a token, and returns a reference to it in the
+ * input instead of a clone. The matcher is either an item that is compared with `==` or a
+ * `Fn(&T) -> bool` predicate. Items are never cloned, which matters for slices of types
+ * like `String` that are expensive to clone.
+ */
+pub fn item_ref<M>(matcher: M) -> tag::ItemRef<M> {
+    tag::ItemRef { matcher }
+}
+
 /**
  * Creates a parser that takes a single item if the underlying parser fails. If the
  * underlying parser succeeds, this parser fails. For strings, on success this will take a char
@@ -243,3 +915,120 @@ pub fn opt<T>(parser: T) -> combinator::Opt<T> {
 pub fn take_not<T>(parser: T) -> combinator::TakeNot<T> {
     TakeNot { parser }
 }
+
+/**
+ * Creates a parser that recovers from errors in `parser`. If it fails, the error is
+ * captured and the input is skipped up to and including the next match of `sync`, or
+ * to the end if there is none. The output is `Ok` with the output of `parser` or
+ * `Err` with the captured error.
+ *
+ * This fails if `parser` fails on empty input, so repeating it with `fab_repeat` stops
+ * at the end of the input with a list of outputs and recovered errors.
+ */
+pub fn recover<P, S>(parser: P, sync: S) -> scan::Recover<P, S> {
+    scan::Recover { parser, sync }
+}
+
+/**
+ * Creates a parser that skips over input one item at a time until the underlying
+ * parser matches, returning the output of the match. The skipped input is discarded.
+ * The number of items that can be skipped can be bounded with the `max_skip` method.
+ *
+ * If the underlying parser never matches, this parser fails and consumes no input.
+ */
+pub fn find<T>(parser: T) -> scan::Find<T> {
+    scan::Find::new(parser)
+}
+
+/**
+ * This function takes in a tuple of 1 to 11 parsers. It returns a parser that locates
+ * each of them in order like `find`, ignoring any input between the matches. This is
+ * useful for pulling fields out of loosely structured text such as log lines.
+ * The output is a tuple of the outputs of the parsers.
+ *
+ * The number of items skipped before each capture can be bounded with the `max_skip` method.
+ * If a capture can't be found, the error cause is an [`ExtractError`] holding the index of
+ * the missing capture, located where the search for it stopped.
+ */
+pub fn extract<T>(parsers: T) -> scan::Extract<T> {
+    scan::Extract::new(parsers)
+}
+
+/**
+ * Wraps a parser so that a successful parse can be recorded into a [`replay::Trace`]
+ * with `fab_recorded`. The trace records how much input each leaf consumed, which branch
+ * each alt took and how many times each repeat ran. Calling `replay` with the trace and
+ * the same input reproduces the output by slicing the input and re-running only the map
+ * and reduce functions, skipping all of the matching work.
+ *
+ * Replaying on an input of a different length fails with [`ReplayError::LengthMismatch`].
+ * Recording is supported for tags, take, tuples, alt, repeat and the map combinators.
+ */
+pub fn recorded<P, I: ?Sized, O, E>(parser: P) -> replay::Recorded<P, I, O, E> {
+    replay::Recorded {
+        parser,
+        phantom_i: PhantomData,
+        phantom_o: PhantomData,
+        phantom_e: PhantomData,
+    }
+}
+
+/**
+ * Runs the parser on a [`Located`] view of the input, so it and the parsers inside it can
+ * find their absolute offsets with `current_offset` or [`spanned`]. Offsets are relative
+ * to where this parser starts.
+ */
+pub fn with_location<P>(parser: P) -> located::WithLocation<P> {
+    located::WithLocation { parser }
+}
+
+/**
+ * Returns the range of the original input that the parser consumed along with its output.
+ * This runs on a [`Located`] input inside [`with_location`], and fails on any other.
+ */
+pub fn spanned<P>(parser: P) -> located::Spanned<P> {
+    located::Spanned { parser }
+}
+
+/**
+ * Runs the parser on a [`SliceLike`] view of a slice of items, such as tokens, through
+ * which it and the parsers inside it can reach `extras` and the indices of the items.
+ */
+pub fn with_extras<X, P>(extras: X, parser: P) -> sequence::WithExtras<X, P> {
+    sequence::WithExtras { extras, parser }
+}
+
+/**
+ * Runs a parser written for the inner sequence of a wrapped input such as [`Located`],
+ * such as a function taking `&mut &str`, on the wrapped input.
+ */
+pub fn inner<P>(parser: P) -> combinator::Inner<P> {
+    combinator::Inner { parser }
+}
+
+/**
+ * Runs the parser on the input, returning its output along with the remaining input.
+ * The error type is fixed to [`FabError`], so no type annotations are needed.
+ */
+pub fn parse<'a, I, O, PType, P>(parser: P, input: &'a I) -> Result<(O, &'a I), FabError>
+where
+    I: ?Sized + sequence::Sequence,
+    P: Parser<'a, I, O, FabError, PType>,
+{
+    let mut input = input;
+    let out = parser.fab(&mut input)?;
+    Ok((out, input))
+}
+
+/**
+ * Runs the parser on the input like [`parse`], but fails if the parser doesn't consume
+ * all of the input. See [`Parser::fab_complete`].
+ */
+pub fn parse_complete<'a, I, O, PType, P>(parser: P, input: &'a I) -> Result<O, FabError>
+where
+    I: ?Sized + sequence::Sequence,
+    P: Parser<'a, I, O, FabError, PType>,
+{
+    let mut input = input;
+    parser.fab_complete().fab(&mut input)
+}
@@ -0,0 +1,194 @@
+use std::{marker::PhantomData, ops::Range};
+
+use crate::{
+    attach::{attach, with_attached},
+    error::{Expected, Found},
+    sequence::{InputWrapper, Sequence},
+    Parser, ParserError, ParserType,
+};
+
+/**
+ * An input wrapper that knows the absolute offset of the input it wraps within the
+ * original input of a parse. Parsers run on `&mut &Located<str>` like on `&mut &str`,
+ * and any of them can call `current_offset`, however deeply nested. Parsers written
+ * against [`Sequence`] work unchanged, tags of the inner sequence match it, and
+ * [`inner`](crate::inner) runs parsers written for the inner sequence.
+ *
+ * Located inputs are created by [`with_location`](crate::with_location), which records
+ * where the original input starts while its parser runs. Offsets are in bytes for str and
+ * in elements for \[T\].
+ */
+#[repr(transparent)]
+pub struct Located<'a, I: ?Sized> {
+    phantom_a: PhantomData<&'a I>,
+    input: I,
+}
+
+impl<'a, I: ?Sized + Sequence> Located<'a, I> {
+    /**
+     * Returns the offset of the start of this input within the original input. Returns
+     * None outside of the `with_location` parser that created it, such as for an
+     * output that is kept after parsing.
+     */
+    pub fn current_offset(&self) -> Option<usize> {
+        with_attached::<LocatedKey, _, OriginalLen, _>(self, |start, original| {
+            let bytes = self as *const Self as *const u8 as usize - start;
+            if bytes == 0 {
+                return 0;
+            }
+            //The original isn't empty here, since this input starts inside it.
+            bytes / (original.size / original.len)
+        })
+    }
+}
+
+impl<'a, I: ?Sized + Sequence> InputWrapper for Located<'a, I> {
+    type Inner = I;
+
+    fn inner(&self) -> &I {
+        &self.input
+    }
+    fn wrap(inner: &I) -> &Self {
+        //SAFETY: Located is a transparent wrapper around I.
+        unsafe { &*(inner as *const I as *const Self) }
+    }
+}
+
+impl<'a, I: ?Sized + Sequence> Sequence for Located<'a, I> {
+    type Item = I::Item;
+
+    fn try_split_at(&self, mid: usize) -> Option<(&Self, &Self)> {
+        let (front, back) = self.input.try_split_at(mid)?;
+        Some((Self::wrap(front), Self::wrap(back)))
+    }
+    fn try_split_front(&self) -> Option<(I::Item, &Self)> {
+        let (first, rest) = self.input.try_split_front()?;
+        Some((first, Self::wrap(rest)))
+    }
+    fn try_split_front_ref(&self) -> Option<(std::borrow::Cow<'_, I::Item>, &Self)> {
+        let (first, rest) = self.input.try_split_front_ref()?;
+        Some((first, Self::wrap(rest)))
+    }
+    fn len(&self) -> usize {
+        self.input.len()
+    }
+    fn expected_item(item: &I::Item) -> Option<Expected> {
+        I::expected_item(item)
+    }
+    fn expected_tag(&self) -> Option<Expected> {
+        self.input.expected_tag()
+    }
+    fn expected_range(
+        start: std::ops::Bound<&I::Item>,
+        end: std::ops::Bound<&I::Item>,
+    ) -> Option<Expected> {
+        I::expected_range(start, end)
+    }
+    fn found(&self) -> Option<Found> {
+        self.input.found()
+    }
+}
+
+impl<'a, I: ?Sized + std::fmt::Debug> std::fmt::Debug for Located<'a, I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Located").field(&&self.input).finish()
+    }
+}
+
+impl<'a, I: ?Sized + PartialEq> PartialEq for Located<'a, I> {
+    fn eq(&self, other: &Self) -> bool {
+        self.input == other.input
+    }
+}
+
+impl<'a, I: ?Sized + PartialEq> PartialEq<I> for Located<'a, I> {
+    fn eq(&self, other: &I) -> bool {
+        self.input == *other
+    }
+}
+
+impl<'a> PartialEq<Located<'a, str>> for str {
+    fn eq(&self, other: &Located<'a, str>) -> bool {
+        *self == other.input
+    }
+}
+
+impl<'a, T: PartialEq> PartialEq<Located<'a, [T]>> for [T] {
+    fn eq(&self, other: &Located<'a, [T]>) -> bool {
+        *self == other.input
+    }
+}
+
+/**
+ * The key that the original input of a `with_location` parser is attached under.
+ */
+struct LocatedKey;
+
+struct OriginalLen {
+    //The original input in bytes and in items, to convert addresses into offsets.
+    size: usize,
+    len: usize,
+}
+
+/**
+ * This struct can be constructed through the function `with_location`. It runs the
+ * underlying parser on a [`Located`] input, with offsets relative to where it starts.
+ */
+#[derive(Clone, Debug)]
+pub struct WithLocation<P> {
+    pub parser: P,
+}
+
+pub struct WithLocationParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+
+impl<'a, I, O, E, P, PType> Parser<'a, I, O, E, WithLocationParser<PType>> for WithLocation<P>
+where
+    I: ?Sized + Sequence + 'a,
+    E: ParserError,
+    P: Parser<'a, Located<'a, I>, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        let original = OriginalLen {
+            size: std::mem::size_of_val(*input),
+            len: input.len(),
+        };
+        let mut located = Located::wrap(*input);
+        let res =
+            attach::<LocatedKey, _, _, _>(*input, &original, || self.parser.fab(&mut located));
+        *input = located.inner();
+        res
+    }
+}
+
+/**
+ * This struct can be constructed through the function `spanned`. It returns the range of
+ * the original input consumed by the underlying parser.
+ */
+#[derive(Clone, Debug)]
+pub struct Spanned<P> {
+    pub parser: P,
+}
+
+pub struct SpannedParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+
+impl<'a, I, O, E, P, PType> Parser<'a, Located<'a, I>, (Range<usize>, O), E, SpannedParser<PType>>
+    for Spanned<P>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P: Parser<'a, Located<'a, I>, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a Located<'a, I>) -> Result<(Range<usize>, O), E> {
+        //An input that wasn't created by with_location has no offsets.
+        let Some(start) = input.current_offset() else {
+            return Err(E::from_parser_error(*input, ParserType::Function));
+        };
+        let before = input.len();
+        let out = self.parser.fab(input)?;
+        Ok((start..start + (before - input.len()), out))
+    }
+}
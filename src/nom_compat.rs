@@ -0,0 +1,113 @@
+use std::{error::Error, fmt::Display, marker::PhantomData};
+
+use nom::{error::ErrorKind, IResult};
+
+use crate::{FabError, Parser, ParserError, ParserType};
+
+/**
+ * Gives the location and kind of a nom error, so it can be converted into a fabparse
+ * error. This is implemented for `nom::error::Error`. Implement it for custom nom error
+ * types to use them with `from_nom`.
+ */
+pub trait NomError<'a> {
+    /**
+     * Returns the input that was left when the nom parser failed.
+     */
+    fn remaining(&self) -> &'a str;
+    /**
+     * Returns the kind of the failure.
+     */
+    fn kind(&self) -> ErrorKind;
+}
+
+impl<'a> NomError<'a> for nom::error::Error<&'a str> {
+    fn remaining(&self) -> &'a str {
+        self.input
+    }
+    fn kind(&self) -> ErrorKind {
+        self.code
+    }
+}
+
+/**
+ * The cause of an error from a nom parser. `failure` is set if the nom parser returned
+ * `nom::Err::Failure`, which nom uses for errors that shouldn't be backtracked over.
+ * Fabparse combinators backtrack over both kinds.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NomErrorCause {
+    pub kind: ErrorKind,
+    pub failure: bool,
+}
+
+impl Display for NomErrorCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "nom parser failed: {}", self.kind.description())
+    }
+}
+
+impl Error for NomErrorCause {}
+
+/**
+ * This struct can be constructed through the function `from_nom`. It runs a nom
+ * parser on the input.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct FromNom<F> {
+    pub func: F,
+}
+
+pub struct FromNomParser<NomE> {
+    phantom_nom_e: PhantomData<NomE>,
+}
+
+impl<'a, F, O, NomE, E: ParserError> Parser<'a, str, O, E, FromNomParser<NomE>> for FromNom<F>
+where
+    F: Fn(&'a str) -> IResult<&'a str, O, NomE>,
+    NomE: NomError<'a>,
+{
+    fn fab(&self, input: &mut &'a str) -> Result<O, E> {
+        match (self.func)(input) {
+            Ok((rest, out)) => {
+                *input = rest;
+                Ok(out)
+            }
+            Err(nom::Err::Incomplete(_)) => Err(E::from_incomplete(*input, ParserType::Function)),
+            Err(nom::Err::Error(err)) => Err(nom_error(input, &err, false)),
+            Err(nom::Err::Failure(err)) => Err(nom_error(input, &err, true)),
+        }
+    }
+}
+
+fn nom_error<'a, E: ParserError>(input: &'a str, err: &impl NomError<'a>, failure: bool) -> E {
+    //nom reports where it failed with the remaining input. Only its length is used, so an
+    //error that doesn't point into the input is located at the start instead.
+    let remaining = err.remaining();
+    let location = input
+        .len()
+        .checked_sub(remaining.len())
+        .and_then(|start| input.get(start..))
+        .unwrap_or(input);
+    let cause = NomErrorCause {
+        kind: err.kind(),
+        failure,
+    };
+    let mut err = E::from_external_error(location, ParserType::Function, cause);
+    err.add_context(input, ParserType::Function);
+    err
+}
+
+/**
+ * Converts a fabparse error into a nom error at the same location. The kind is
+ * `ErrorKind::Fail`, since nom has no kind for fabparse's parsers.
+ */
+pub(crate) fn to_nom_error(input: &str, err: FabError) -> nom::Err<nom::error::Error<&str>> {
+    if err.is_incomplete() {
+        return nom::Err::Incomplete(nom::Needed::Unknown);
+    }
+    let remaining = err
+        .offset(input)
+        .and_then(|offset| input.get(offset..))
+        .unwrap_or(input);
+    nom::Err::Error(nom::error::Error::new(remaining, ErrorKind::Fail))
+}
@@ -0,0 +1,105 @@
+use regex::{Captures, Regex};
+
+use crate::{error::Expected, sequence::Sequence, Parser, ParserError, ParserType};
+
+/**
+ * Compiles the pattern so it only matches at the start of the input. The pattern is
+ * wrapped in a group so alternations and flags inside it stay inside it.
+ */
+fn anchored(pattern: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!(r"\A(?:{})", pattern))
+}
+
+fn regex_error<E: ParserError>(input: &str, pattern: &str) -> E {
+    let mut err = E::from_parser_error(input, ParserType::Tag);
+    if err.records_expected() {
+        err.set_expected(Expected::Pattern(pattern.to_string()), input.found());
+    }
+    err
+}
+
+/**
+ * This struct can be constructed through the function `re`. It matches a regex at the
+ * start of the input and returns the matched str.
+ */
+#[derive(Clone, Debug)]
+pub struct Re {
+    regex: Regex,
+    pattern: String,
+}
+
+impl Re {
+    /**
+     * Compiles the pattern, returning the regex error if it is invalid.
+     */
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Re {
+            regex: anchored(pattern)?,
+            pattern: pattern.to_string(),
+        })
+    }
+    /**
+     * Returns the pattern this parser was built from.
+     */
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ReParser;
+impl<'a, E: ParserError> Parser<'a, str, &'a str, E, ReParser> for Re {
+    fn fab(&self, input: &mut &'a str) -> Result<&'a str, E> {
+        match self.regex.find(input) {
+            Some(found) => {
+                let (matched, rest) = input.split_at(found.end());
+                *input = rest;
+                Ok(matched)
+            }
+            None => Err(regex_error(input, &self.pattern)),
+        }
+    }
+}
+
+/**
+ * This struct can be constructed through the function `re_captures`. It matches a regex
+ * at the start of the input and returns its capture groups. Group 0 is the matched str.
+ */
+#[derive(Clone, Debug)]
+pub struct ReCaptures {
+    regex: Regex,
+    pattern: String,
+}
+
+impl ReCaptures {
+    /**
+     * Compiles the pattern, returning the regex error if it is invalid.
+     */
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(ReCaptures {
+            regex: anchored(pattern)?,
+            pattern: pattern.to_string(),
+        })
+    }
+    /**
+     * Returns the pattern this parser was built from.
+     */
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ReCapturesParser;
+impl<'a, E: ParserError> Parser<'a, str, Captures<'a>, E, ReCapturesParser> for ReCaptures {
+    fn fab(&self, input: &mut &'a str) -> Result<Captures<'a>, E> {
+        match self.regex.captures(input) {
+            Some(captures) => {
+                let end = captures.get(0).map_or(0, |whole| whole.end());
+                *input = &input[end..];
+                Ok(captures)
+            }
+            None => Err(regex_error(input, &self.pattern)),
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use std::{
+    cell::RefCell,
     convert::Infallible,
     error::Error,
-    fmt::Display,
+    fmt::{Debug, Display},
     marker::PhantomData,
-    ops::{Range, RangeBounds},
+    ops::{ControlFlow, Range, RangeBounds},
 };
 
 use crate::{sequence::Sequence, Parser, ParserError, ParserType};
@@ -29,7 +30,10 @@ impl Error for TryReducerError {}
  * Repeat parsers by default return a Vec. This behavior can be replaced with
  * with the method `parser.reduce(acc, fn)`, where accumulator implements
  * TryReducer. This trait is already implemented for all of `[fn(&mut acc)->(),
- * fn(&mut acc)->Option<()>, fn(&mut acc)->bool, fn(&mut acc)->Result<(),E> ]`
+ * fn(&mut acc)->Option<()>, fn(&mut acc)->bool, fn(&mut acc)->Result<(),E>,
+ * fn(&mut acc)->ControlFlow<()> ]`
+ * It is also implemented for the same functions taking the index of the repetition
+ * before the value, such as `fn(&mut acc, usize, val)->()`.
  *
  * `Acc`: The type of the accumulator
  *
@@ -39,82 +43,449 @@ impl Error for TryReducerError {}
  * You can use any struct defined with your crate.
  *
  * `FErr`: The error type of the accumutation function.
+ *
+ * `try_reduce` is passed the index of the repetition, counting successful parses from 0.
+ * It returns `ControlFlow::Break` to stop the repeat successfully after the value it was
+ * passed, leaving the rest of the input for later parsers.
+ *
+ * `finalize` turns the accumulator into the output once the repeat stops. If it returns
+ * an error, the repeat fails with that error as the cause.
  */
 pub trait TryReducer<'a, Acc, T, FType, FErr, Out, I: ?Sized> {
-    fn try_reduce(&self, acc: &mut Acc, val: T) -> Result<(), FErr>;
-    fn finalize(&self, acc: Acc, orig_input: &'a I, new_input: &'a I) -> Out;
+    fn try_reduce(&self, acc: &mut Acc, index: usize, val: T) -> Result<ControlFlow<()>, FErr>;
+    fn finalize(&self, acc: Acc, orig_input: &'a I, new_input: &'a I) -> Result<Out, FErr>;
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct ResultReducer;
 impl<'a, Acc, T, F, FErr, I: ?Sized> TryReducer<'a, Acc, T, ResultReducer, FErr, Acc, I> for F
 where
     F: Fn(&mut Acc, T) -> Result<(), FErr>,
 {
-    fn try_reduce(&self, acc: &mut Acc, val: T) -> Result<(), FErr> {
-        self(acc, val)
+    fn try_reduce(&self, acc: &mut Acc, _index: usize, val: T) -> Result<ControlFlow<()>, FErr> {
+        self(acc, val).map(ControlFlow::Continue)
     }
-    fn finalize(&self, acc: Acc, _orig_input: &'a I, _new_input: &'a I) -> Acc {
-        acc
+    fn finalize(&self, acc: Acc, _orig_input: &'a I, _new_input: &'a I) -> Result<Acc, FErr> {
+        Ok(acc)
     }
 }
+#[derive(Clone, Copy, Debug)]
 pub struct OptionReducer();
 impl<'a, Acc, T, F, I: ?Sized> TryReducer<'a, Acc, T, OptionReducer, TryReducerError, Acc, I> for F
 where
     F: Fn(&mut Acc, T) -> Option<()>,
 {
-    fn try_reduce(&self, acc: &mut Acc, val: T) -> Result<(), TryReducerError> {
-        self(acc, val).ok_or(TryReducerError).map(|_| ())
+    fn try_reduce(
+        &self,
+        acc: &mut Acc,
+        _index: usize,
+        val: T,
+    ) -> Result<ControlFlow<()>, TryReducerError> {
+        self(acc, val)
+            .ok_or(TryReducerError)
+            .map(ControlFlow::Continue)
     }
-    fn finalize(&self, acc: Acc, _orig_input: &'a I, _new_input: &'a I) -> Acc {
-        acc
+    fn finalize(
+        &self,
+        acc: Acc,
+        _orig_input: &'a I,
+        _new_input: &'a I,
+    ) -> Result<Acc, TryReducerError> {
+        Ok(acc)
     }
 }
+#[derive(Clone, Copy, Debug)]
 pub struct BoolReducer;
 impl<'a, Acc, T, F, I: ?Sized> TryReducer<'a, Acc, T, BoolReducer, TryReducerError, Acc, I> for F
 where
     F: Fn(&mut Acc, T) -> bool,
 {
-    fn try_reduce(&self, acc: &mut Acc, val: T) -> Result<(), TryReducerError> {
+    fn try_reduce(
+        &self,
+        acc: &mut Acc,
+        _index: usize,
+        val: T,
+    ) -> Result<ControlFlow<()>, TryReducerError> {
         if self(acc, val) {
-            Ok(())
+            Ok(ControlFlow::Continue(()))
         } else {
             Err(TryReducerError)
         }
     }
-    fn finalize(&self, acc: Acc, _orig_input: &'a I, _new_input: &'a I) -> Acc {
-        acc
+    fn finalize(
+        &self,
+        acc: Acc,
+        _orig_input: &'a I,
+        _new_input: &'a I,
+    ) -> Result<Acc, TryReducerError> {
+        Ok(acc)
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct InfallibleReducer;
 impl<'a, Acc, T, F, I: ?Sized> TryReducer<'a, Acc, T, InfallibleReducer, Infallible, Acc, I> for F
 where
-    F: Fn(&mut Acc, T) -> (),
+    F: Fn(&mut Acc, T),
+{
+    fn try_reduce(
+        &self,
+        acc: &mut Acc,
+        _index: usize,
+        val: T,
+    ) -> Result<ControlFlow<()>, Infallible> {
+        self(acc, val);
+        Ok(ControlFlow::Continue(()))
+    }
+    fn finalize(&self, acc: Acc, _orig_input: &'a I, _new_input: &'a I) -> Result<Acc, Infallible> {
+        Ok(acc)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ControlFlowReducer;
+impl<'a, Acc, T, F, I: ?Sized> TryReducer<'a, Acc, T, ControlFlowReducer, Infallible, Acc, I> for F
+where
+    F: Fn(&mut Acc, T) -> ControlFlow<()>,
+{
+    fn try_reduce(
+        &self,
+        acc: &mut Acc,
+        _index: usize,
+        val: T,
+    ) -> Result<ControlFlow<()>, Infallible> {
+        Ok(self(acc, val))
+    }
+    fn finalize(&self, acc: Acc, _orig_input: &'a I, _new_input: &'a I) -> Result<Acc, Infallible> {
+        Ok(acc)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EnumeratedResultReducer;
+impl<'a, Acc, T, F, FErr, I: ?Sized> TryReducer<'a, Acc, T, EnumeratedResultReducer, FErr, Acc, I>
+    for F
+where
+    F: Fn(&mut Acc, usize, T) -> Result<(), FErr>,
+{
+    fn try_reduce(&self, acc: &mut Acc, index: usize, val: T) -> Result<ControlFlow<()>, FErr> {
+        self(acc, index, val).map(ControlFlow::Continue)
+    }
+    fn finalize(&self, acc: Acc, _orig_input: &'a I, _new_input: &'a I) -> Result<Acc, FErr> {
+        Ok(acc)
+    }
+}
+#[derive(Clone, Copy, Debug)]
+pub struct EnumeratedOptionReducer;
+impl<'a, Acc, T, F, I: ?Sized>
+    TryReducer<'a, Acc, T, EnumeratedOptionReducer, TryReducerError, Acc, I> for F
+where
+    F: Fn(&mut Acc, usize, T) -> Option<()>,
+{
+    fn try_reduce(
+        &self,
+        acc: &mut Acc,
+        index: usize,
+        val: T,
+    ) -> Result<ControlFlow<()>, TryReducerError> {
+        self(acc, index, val)
+            .ok_or(TryReducerError)
+            .map(ControlFlow::Continue)
+    }
+    fn finalize(
+        &self,
+        acc: Acc,
+        _orig_input: &'a I,
+        _new_input: &'a I,
+    ) -> Result<Acc, TryReducerError> {
+        Ok(acc)
+    }
+}
+#[derive(Clone, Copy, Debug)]
+pub struct EnumeratedBoolReducer;
+impl<'a, Acc, T, F, I: ?Sized>
+    TryReducer<'a, Acc, T, EnumeratedBoolReducer, TryReducerError, Acc, I> for F
+where
+    F: Fn(&mut Acc, usize, T) -> bool,
+{
+    fn try_reduce(
+        &self,
+        acc: &mut Acc,
+        index: usize,
+        val: T,
+    ) -> Result<ControlFlow<()>, TryReducerError> {
+        if self(acc, index, val) {
+            Ok(ControlFlow::Continue(()))
+        } else {
+            Err(TryReducerError)
+        }
+    }
+    fn finalize(
+        &self,
+        acc: Acc,
+        _orig_input: &'a I,
+        _new_input: &'a I,
+    ) -> Result<Acc, TryReducerError> {
+        Ok(acc)
+    }
+}
+#[derive(Clone, Copy, Debug)]
+pub struct EnumeratedInfallibleReducer;
+impl<'a, Acc, T, F, I: ?Sized>
+    TryReducer<'a, Acc, T, EnumeratedInfallibleReducer, Infallible, Acc, I> for F
+where
+    F: Fn(&mut Acc, usize, T),
 {
-    fn try_reduce(&self, acc: &mut Acc, val: T) -> Result<(), Infallible> {
-        Ok(self(acc, val)).map(|_| ())
+    fn try_reduce(
+        &self,
+        acc: &mut Acc,
+        index: usize,
+        val: T,
+    ) -> Result<ControlFlow<()>, Infallible> {
+        self(acc, index, val);
+        Ok(ControlFlow::Continue(()))
     }
-    fn finalize(&self, acc: Acc, _orig_input: &'a I, _new_input: &'a I) -> Acc {
-        acc
+    fn finalize(&self, acc: Acc, _orig_input: &'a I, _new_input: &'a I) -> Result<Acc, Infallible> {
+        Ok(acc)
+    }
+}
+#[derive(Clone, Copy, Debug)]
+pub struct EnumeratedControlFlowReducer;
+impl<'a, Acc, T, F, I: ?Sized>
+    TryReducer<'a, Acc, T, EnumeratedControlFlowReducer, Infallible, Acc, I> for F
+where
+    F: Fn(&mut Acc, usize, T) -> ControlFlow<()>,
+{
+    fn try_reduce(
+        &self,
+        acc: &mut Acc,
+        index: usize,
+        val: T,
+    ) -> Result<ControlFlow<()>, Infallible> {
+        Ok(self(acc, index, val))
+    }
+    fn finalize(&self, acc: Acc, _orig_input: &'a I, _new_input: &'a I) -> Result<Acc, Infallible> {
+        Ok(acc)
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct InputSliceReducer;
 impl<'a, T, I: ?Sized> TryReducer<'a, (), T, InputSliceReducer, Infallible, &'a I, I>
     for InputSliceReducer
 where
     I: Sequence,
 {
-    fn try_reduce(&self, _acc: &mut (), _val: T) -> Result<(), Infallible> {
-        Ok(())
+    fn try_reduce(
+        &self,
+        _acc: &mut (),
+        _index: usize,
+        _val: T,
+    ) -> Result<ControlFlow<()>, Infallible> {
+        Ok(ControlFlow::Continue(()))
     }
-    fn finalize(&self, _acc: (), orig_input: &'a I, new_input: &'a I) -> &'a I {
-        orig_input.subtract(new_input)
+    fn finalize(&self, _acc: (), orig_input: &'a I, new_input: &'a I) -> Result<&'a I, Infallible> {
+        Ok(orig_input.subtract(new_input))
     }
 }
 
-pub struct Reducer<Reduce, Acc: Clone> {
-    pub acc: Acc,
+/**
+ * Counts the number of repetitions.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct CountReducer;
+impl<'a, T, I: ?Sized> TryReducer<'a, usize, T, CountReducer, Infallible, usize, I>
+    for CountReducer
+{
+    fn try_reduce(
+        &self,
+        acc: &mut usize,
+        _index: usize,
+        _val: T,
+    ) -> Result<ControlFlow<()>, Infallible> {
+        *acc += 1;
+        Ok(ControlFlow::Continue(()))
+    }
+    fn finalize(
+        &self,
+        acc: usize,
+        _orig_input: &'a I,
+        _new_input: &'a I,
+    ) -> Result<usize, Infallible> {
+        Ok(acc)
+    }
+}
+
+/**
+ * This error is used as the cause when an array repeat matches a number of items other
+ * than the length of the array, such as when its bounds were changed after `array`.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub struct ArrayLenError {
+    pub len: usize,
+}
+impl Display for ArrayLenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Expected exactly {} items for the array", self.len)
+    }
+}
+
+impl Error for ArrayLenError {}
+
+/**
+ * Accumulates into an array of N items. The accumulator is an array of options,
+ * which avoids needing unsafe code. Accumulating more or fewer than N items fails
+ * with an [`ArrayLenError`].
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct ArrayReducer<const N: usize>;
+impl<'a, T, I: ?Sized, const N: usize>
+    TryReducer<'a, [Option<T>; N], T, ArrayReducer<N>, ArrayLenError, [T; N], I>
+    for ArrayReducer<N>
+{
+    fn try_reduce(
+        &self,
+        acc: &mut [Option<T>; N],
+        index: usize,
+        val: T,
+    ) -> Result<ControlFlow<()>, ArrayLenError> {
+        let slot = acc.get_mut(index).ok_or(ArrayLenError { len: N })?;
+        *slot = Some(val);
+        Ok(ControlFlow::Continue(()))
+    }
+    fn finalize(
+        &self,
+        acc: [Option<T>; N],
+        _orig_input: &'a I,
+        _new_input: &'a I,
+    ) -> Result<[T; N], ArrayLenError> {
+        if acc.iter().any(Option::is_none) {
+            return Err(ArrayLenError { len: N });
+        }
+        Ok(acc.map(|val| val.expect("All items were checked to be present")))
+    }
+}
+
+/**
+ * Accumulates into any container that implements `Extend`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct CollectReducer;
+impl<'a, C, T, I: ?Sized> TryReducer<'a, C, T, CollectReducer, Infallible, C, I> for CollectReducer
+where
+    C: Extend<T>,
+{
+    fn try_reduce(
+        &self,
+        acc: &mut C,
+        _index: usize,
+        val: T,
+    ) -> Result<ControlFlow<()>, Infallible> {
+        acc.extend(std::iter::once(val));
+        Ok(ControlFlow::Continue(()))
+    }
+    fn finalize(&self, acc: C, _orig_input: &'a I, _new_input: &'a I) -> Result<C, Infallible> {
+        Ok(acc)
+    }
+}
+
+/**
+ * Accumulates into an `arrayvec::ArrayVec` with capacity N. Pushing past the capacity
+ * fails the repeat with a `CapacityError` as its cause instead of panicking.
+ */
+#[cfg(feature = "arrayvec")]
+#[derive(Clone, Copy, Debug)]
+pub struct ArrayVecReducer;
+#[cfg(feature = "arrayvec")]
+impl<'a, T, I: ?Sized, const N: usize>
+    TryReducer<
+        'a,
+        arrayvec::ArrayVec<T, N>,
+        T,
+        ArrayVecReducer,
+        arrayvec::CapacityError,
+        arrayvec::ArrayVec<T, N>,
+        I,
+    > for ArrayVecReducer
+{
+    fn try_reduce(
+        &self,
+        acc: &mut arrayvec::ArrayVec<T, N>,
+        _index: usize,
+        val: T,
+    ) -> Result<ControlFlow<()>, arrayvec::CapacityError> {
+        acc.try_push(val).map_err(|err| err.simplify())?;
+        Ok(ControlFlow::Continue(()))
+    }
+    fn finalize(
+        &self,
+        acc: arrayvec::ArrayVec<T, N>,
+        _orig_input: &'a I,
+        _new_input: &'a I,
+    ) -> Result<arrayvec::ArrayVec<T, N>, arrayvec::CapacityError> {
+        Ok(acc)
+    }
+}
+
+/**
+ * Produces the initial value of the accumulator each time a repeat parser runs.
+ * This is implemented for functions `fn()->Acc` and for [`CloneAcc`], which clones a
+ * stored value.
+ */
+pub trait InitAcc<Acc> {
+    fn init_acc(&self) -> Acc;
+}
+
+impl<Acc, F> InitAcc<Acc> for F
+where
+    F: Fn() -> Acc,
+{
+    fn init_acc(&self) -> Acc {
+        self()
+    }
+}
+
+/**
+ * Initializes the accumulator by cloning the stored value. This is used by `reduce`.
+ */
+#[derive(Clone, Debug)]
+pub struct CloneAcc<Acc>(pub Acc);
+
+impl<Acc: Clone> InitAcc<Acc> for CloneAcc<Acc> {
+    fn init_acc(&self) -> Acc {
+        self.0.clone()
+    }
+}
+
+/**
+ * This struct is the parser repeated by `with_spans` on [`Repeat`]. It pairs the output
+ * of the underlying parser with the slice of input that it consumed.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct WithSpan<P> {
+    pub parser: P,
+}
+
+pub struct WithSpanParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+
+impl<'a, I, O, E, P, PType> Parser<'a, I, (O, &'a I), E, WithSpanParser<PType>> for WithSpan<P>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<(O, &'a I), E> {
+        let checkpoint = *input;
+        let out = self.parser.fab(input)?;
+        Ok((out, checkpoint.subtract(*input)))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Reducer<Reduce, Init> {
+    pub init: Init,
     pub reduce_operator: Reduce,
 }
 /**
@@ -122,27 +493,91 @@ pub struct Reducer<Reduce, Acc: Clone> {
  * It can be customized with a min/max number of repititions, or a custom
  * try reduce.
  */
-pub struct Repeat<P, ParI: ?Sized, ParO, ParE, F, Acc: Clone> {
-    parser: P,
-    reducer: Reducer<F, Acc>,
-    bounds: Range<usize>,
+pub struct Repeat<P, ParI: ?Sized, ParO, ParE, F, Acc, Init = fn() -> Acc> {
+    pub(crate) parser: P,
+    pub(crate) reducer: Reducer<F, Init>,
+    pub(crate) bounds: Range<usize>,
+    pub(crate) allow_empty: bool,
     phantom_i: PhantomData<ParI>,
     phantom_o: PhantomData<ParO>,
     phantom_e: PhantomData<ParE>,
+    phantom_acc: PhantomData<Acc>,
 }
 
-impl<P, ParI: ?Sized, ParO, ParE, F, Acc: Clone> Repeat<P, ParI, ParO, ParE, F, Acc> {
+impl<P: Clone, ParI: ?Sized, ParO, ParE, F: Clone, Acc, Init: Clone> Clone
+    for Repeat<P, ParI, ParO, ParE, F, Acc, Init>
+{
+    fn clone(&self) -> Self {
+        Repeat {
+            parser: self.parser.clone(),
+            reducer: self.reducer.clone(),
+            bounds: self.bounds.clone(),
+            allow_empty: self.allow_empty,
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+            phantom_acc: PhantomData,
+        }
+    }
+}
+
+impl<P: Debug, ParI: ?Sized, ParO, ParE, F, Acc, Init> Debug
+    for Repeat<P, ParI, ParO, ParE, F, Acc, Init>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Repeat")
+            .field("parser", &self.parser)
+            .field("bounds", &self.bounds)
+            .field("allow_empty", &self.allow_empty)
+            .finish_non_exhaustive()
+    }
+}
+
+/**
+ * Converts the bounds into the half open range of repetitions that are accepted.
+ */
+fn to_range<B: RangeBounds<usize>>(bounds: B) -> Range<usize> {
+    let lower = match bounds.start_bound() {
+        std::ops::Bound::Included(val) => *val,
+        //The lower bound for a range shound never be usize::MAX.
+        std::ops::Bound::Excluded(val) => {
+            if *val == usize::MAX {
+                panic!("The lower bound for the range shouldn't be usize::MAX")
+            } else {
+                *val + 1
+            }
+        }
+        std::ops::Bound::Unbounded => 0,
+    };
+    let upper = match bounds.end_bound() {
+        //An inclusive bound of usize::MAX makes sense, don't wrap it to 0.
+        std::ops::Bound::Included(val) => {
+            if *val == usize::MAX {
+                *val
+            } else {
+                *val + 1
+            }
+        }
+        std::ops::Bound::Excluded(val) => *val,
+        std::ops::Bound::Unbounded => usize::MAX,
+    };
+    lower..upper
+}
+
+impl<P, ParI: ?Sized, ParO, ParE, F, Acc, Init> Repeat<P, ParI, ParO, ParE, F, Acc, Init> {
     /**
      * Constructs a new repeat parser. Prefer to use the method `fab_repeat` in the parser trait.
      */
-    pub fn new(parser: P, reducer: Reducer<F, Acc>, bounds: Range<usize>) -> Self {
+    pub fn new(parser: P, reducer: Reducer<F, Init>, bounds: Range<usize>) -> Self {
         Repeat {
             parser,
             reducer,
             bounds,
+            allow_empty: false,
             phantom_i: PhantomData,
             phantom_o: PhantomData,
             phantom_e: PhantomData,
+            phantom_acc: PhantomData,
         }
     }
 }
@@ -153,28 +588,54 @@ pub struct RepeatParser<PType, ReducerOut, FErr> {
     ferr: PhantomData<FErr>,
 }
 
-fn loc<I: ?Sized>(seq: &I) -> usize {
+pub(crate) fn loc<I: ?Sized>(seq: &I) -> usize {
     seq as *const I as *const u8 as usize
 }
 
-/**
- * This function is generics soup, but the goal is this:
- * It repeats applying the parser until it fails or exceeds the maximum bound.
- * It accumulates the output of the parser into Acc using F. If F returns an error
- * the parser also fails with that error. In iterator language, this is a TryReduce operator.
- */
-impl<'a, P, I, O, E, PType, F, Acc, FErr, ReducerOut, AccOut>
-    Parser<'a, I, AccOut, E, RepeatParser<PType, ReducerOut, FErr>> for Repeat<P, I, O, E, F, Acc>
+impl<P, I, O, E, F, Acc, Init> Repeat<P, I, O, E, F, Acc, Init>
 where
     E: ParserError,
     I: ?Sized + Sequence,
-    P: Parser<'a, I, O, E, PType>,
-    Acc: Clone,
-    FErr: 'static + Send + Sync + Error,
-    F: TryReducer<'a, Acc, O, ReducerOut, FErr, AccOut, I>,
+    Init: InitAcc<Acc>,
 {
-    fn fab(&self, input: &mut &'a I) -> Result<AccOut, E> {
-        let mut res = self.reducer.acc.clone();
+    /**
+     * Finalizes the accumulator once the repeat has stopped. If that fails, the repeat
+     * fails like it does when a reduction fails.
+     */
+    fn finish<'a, FErr, ReducerOut, AccOut>(
+        &self,
+        res: Acc,
+        orig_input: &'a I,
+        input: &mut &'a I,
+    ) -> Result<AccOut, E>
+    where
+        FErr: 'static + Send + Sync + Error,
+        F: TryReducer<'a, Acc, O, ReducerOut, FErr, AccOut, I>,
+    {
+        self.reducer
+            .reduce_operator
+            .finalize(res, orig_input, input)
+            .map_err(|err| {
+                let mut err = E::from_external_error(*input, ParserType::RepeatIter, err);
+                *input = orig_input;
+                err.add_context(orig_input, ParserType::Repeat);
+                err
+            })
+    }
+    /**
+     * The repeat loop. `step` runs the underlying parser once. This is shared
+     * between parsing and recording a trace.
+     */
+    pub(crate) fn run<'a, FErr, ReducerOut, AccOut>(
+        &self,
+        input: &mut &'a I,
+        mut step: impl FnMut(&mut &'a I) -> Result<O, E>,
+    ) -> Result<AccOut, E>
+    where
+        FErr: 'static + Send + Sync + Error,
+        F: TryReducer<'a, Acc, O, ReducerOut, FErr, AccOut, I>,
+    {
+        let mut res = self.reducer.init.init_acc();
         let mut repetitions: usize = 0;
         let mut last_location = *input;
         let orig_input = *input;
@@ -184,19 +645,20 @@ where
         loop {
             // Break out of the loop early if we hit the repetition limit.
             if repetitions == self.bounds.end - 1 {
-                return Ok(self
-                    .reducer
-                    .reduce_operator
-                    .finalize(res, orig_input, input));
+                return self.finish(res, orig_input, input);
             }
             //This will be used if the try reduce fails to get a
             //correct location of where the parser started.
             let loc_before_iteration = *input;
-            match self.parser.fab(input) {
+            match step(input) {
                 //The parser succeeded, accumulate its output and continue parsing
                 Ok(val) => {
-                    //We made no progress, so return an error rather than looping indefinitely
+                    //We made no progress, so stop if that's allowed. Otherwise return an
+                    //error rather than looping indefinitely.
                     if loc(*input) == loc(last_location) {
+                        if self.allow_empty && self.bounds.contains(&repetitions) {
+                            return self.finish(res, orig_input, input);
+                        }
                         let mut err =
                             E::from_parser_error(loc_before_iteration, ParserType::RepeatIter);
                         *input = orig_input;
@@ -204,31 +666,58 @@ where
                         return Err(err);
                     }
                     last_location = *input;
-                    //The reduce operation can fail, so we need an if let for that case. It accumuates
-                    //results by mutable reference, so there is no need for anything in the Ok case.
-                    if let Err(err) = self.reducer.reduce_operator.try_reduce(&mut res, val) {
-                        let mut err = E::from_external_error(
-                            loc_before_iteration,
-                            ParserType::RepeatIter,
-                            err,
-                        );
-                        *input = orig_input;
-                        //Since the repeat error can occur anywhere in the sequence, add the
-                        //start of the repeat to the context.
-                        err.add_context(orig_input, ParserType::Repeat);
-                        return Err(err);
+                    //The reduce operation can fail or stop the repetition. It accumuates
+                    //results by mutable reference, so there is nothing to do to continue.
+                    match self
+                        .reducer
+                        .reduce_operator
+                        .try_reduce(&mut res, repetitions, val)
+                    {
+                        Ok(ControlFlow::Continue(())) => {}
+                        //The reducer is done, so stop after the value it took if the minimum
+                        //is met. The maximum is checked before each repetition.
+                        Ok(ControlFlow::Break(())) => {
+                            if self.bounds.contains(&(repetitions + 1)) {
+                                return self.finish(res, orig_input, input);
+                            }
+                            let mut err = E::from_parser_error(*input, ParserType::RepeatIter);
+                            *input = orig_input;
+                            err.add_context(orig_input, ParserType::Repeat);
+                            return Err(err);
+                        }
+                        Err(err) => {
+                            let mut err = E::from_external_error(
+                                loc_before_iteration,
+                                ParserType::RepeatIter,
+                                err,
+                            );
+                            *input = orig_input;
+                            //Since the repeat error can occur anywhere in the sequence, add the
+                            //start of the repeat to the context.
+                            err.add_context(orig_input, ParserType::Repeat);
+                            return Err(err);
+                        }
                     }
                 }
-                Err(_) => {
-                    //The underlying parser failed, so return the results up to here.
+                //More input might let the underlying parser match again, so the repetition
+                //can't end here.
+                Err(mut err) if err.is_incomplete() => {
+                    *input = orig_input;
+                    err.add_context(orig_input, ParserType::Repeat);
+                    return Err(err);
+                }
+                Err(mut err) => {
+                    //The underlying parser failed, so return the results up to here. It may
+                    //not have restored the input, so go back to where this iteration started.
                     if self.bounds.contains(&repetitions) {
-                        return Ok(self
-                            .reducer
-                            .reduce_operator
-                            .finalize(res, orig_input, input));
+                        *input = loc_before_iteration;
+                        return self.finish(res, orig_input, input);
                     } else {
+                        //Keep the failure of the repetition that was missing, so the
+                        //trace shows where it failed as well as where the repeat started.
                         *input = orig_input;
-                        return Err(E::from_parser_error(*input, ParserType::Repeat));
+                        err.add_context(orig_input, ParserType::Repeat);
+                        return Err(err);
                     }
                 }
             }
@@ -237,62 +726,213 @@ where
     }
 }
 
-impl<P, ParI: ?Sized, ParO, ParE, F, Acc: Clone> Repeat<P, ParI, ParO, ParE, F, Acc> {
+/**
+ * This function is generics soup, but the goal is this:
+ * It repeats applying the parser until it fails or exceeds the maximum bound.
+ * It accumulates the output of the parser into Acc using F. If F returns an error
+ * the parser also fails with that error. In iterator language, this is a TryReduce operator.
+ */
+impl<'a, P, I, O, E, PType, F, Acc, Init, FErr, ReducerOut, AccOut>
+    Parser<'a, I, AccOut, E, RepeatParser<PType, ReducerOut, FErr>>
+    for Repeat<P, I, O, E, F, Acc, Init>
+where
+    E: ParserError,
+    I: ?Sized + Sequence,
+    P: Parser<'a, I, O, E, PType>,
+    Init: InitAcc<Acc>,
+    FErr: 'static + Send + Sync + Error,
+    F: TryReducer<'a, Acc, O, ReducerOut, FErr, AccOut, I>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<AccOut, E> {
+        self.run(input, |input| self.parser.fab(input))
+    }
+}
+
+impl<P, ParI: ?Sized, ParO, ParE, F, Acc, Init> Repeat<P, ParI, ParO, ParE, F, Acc, Init> {
     /**
      * Sets an inclusive minimum number of repititions for this parser to succeed.
      */
     pub fn min(self, min: usize) -> Self {
-        Repeat::new(self.parser, self.reducer, min..self.bounds.end)
+        Repeat {
+            bounds: min..self.bounds.end,
+            ..self
+        }
     }
     /**
-     * Sets as exclusive maximum limit of the the number of repititions of this parser.
-     * When it hits the limit, it succeeds with its current output.
+     * Sets an inclusive maximum number of repititions of this parser, so `max(2)` matches
+     * at most 2 times. When it hits the limit, it succeeds with its current output.
+     * `max(0)` succeeds immediately with empty output.
      */
     pub fn max(self, max: usize) -> Self {
-        Repeat::new(self.parser, self.reducer, self.bounds.start..max)
+        Repeat {
+            bounds: self.bounds.start..max.saturating_add(1),
+            ..self
+        }
     }
     /**
      * Sets both a minimum and maximum number of repitions for this parser to succeed.
+     * `bound(a..=b)` is the same as `min(a).max(b)`. If the bounds contain no counts,
+     * such as `2..2` or a minimum above the maximum, the parser always fails.
      */
     pub fn bound<B: RangeBounds<usize>>(self, bounds: B) -> Self {
-        let lower = match bounds.start_bound() {
-            std::ops::Bound::Included(val) => *val,
-            //The lower bound for a range shound never be usize::MAX.
-            std::ops::Bound::Excluded(val) => {
-                if *val == usize::MAX {
-                    panic!("The lower bound for the range shouldn't be usize::MAX")
-                } else {
-                    *val + 1
-                }
-            }
-            std::ops::Bound::Unbounded => 0,
-        };
-        let upper = match bounds.end_bound() {
-            //An inclusive bound of usize::MAX makes sense, don't wrap it to 0.
-            std::ops::Bound::Included(val) => {
-                if *val == usize::MAX {
-                    *val
-                } else {
-                    *val + 1
-                }
-            }
-            std::ops::Bound::Excluded(val) => *val,
-            std::ops::Bound::Unbounded => usize::MAX,
-        };
-        Repeat::new(self.parser, self.reducer, lower..upper)
+        Repeat {
+            bounds: to_range(bounds),
+            ..self
+        }
+    }
+    /**
+     * By default, the parser fails if the underlying parser succeeds without consuming
+     * input, since repeating it would loop forever. With this set, such a match stops the
+     * repetition instead, and the parser succeeds with the output so far if the minimum is
+     * met. The output of the empty match is discarded.
+     */
+    pub fn allow_empty_matches(self) -> Self {
+        Repeat {
+            allow_empty: true,
+            ..self
+        }
+    }
+    /**
+     * Replaces the reducer, keeping the other settings.
+     */
+    fn with_reducer<NewF, NewAcc, NewInit>(
+        self,
+        reducer: Reducer<NewF, NewInit>,
+    ) -> Repeat<P, ParI, ParO, ParE, NewF, NewAcc, NewInit> {
+        Repeat {
+            parser: self.parser,
+            reducer,
+            bounds: self.bounds,
+            allow_empty: self.allow_empty,
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+            phantom_acc: PhantomData,
+        }
     }
     /**
      * Returns the slice of the input that this parser matched. &str when parsing &str, &\[T\] when parsing  &\[T\]
      */
     pub fn as_input_slice(self) -> Repeat<P, ParI, ParO, ParE, InputSliceReducer, ()> {
-        Repeat::new(
-            self.parser,
-            Reducer {
-                acc: (),
-                reduce_operator: InputSliceReducer,
+        self.with_reducer(Reducer {
+            init: || (),
+            reduce_operator: InputSliceReducer,
+        })
+    }
+    /**
+     * Returns the number of repetitions that matched, without storing their output.
+     */
+    pub fn count(self) -> Repeat<P, ParI, ParO, ParE, CountReducer, usize> {
+        self.with_reducer(Reducer {
+            init: || 0,
+            reduce_operator: CountReducer,
+        })
+    }
+    /**
+     * Parses exactly N repetitions and outputs them as an array `[O; N]`, without
+     * allocating. If there are fewer than N matches, this parser fails and consumes no input.
+     * This replaces any bounds set on the repeat. If the bounds are changed afterwards so
+     * that more or fewer than N items match, the parser fails with an [`ArrayLenError`]
+     * cause.
+     */
+    #[allow(clippy::type_complexity)]
+    pub fn array<const N: usize>(
+        self,
+    ) -> Repeat<P, ParI, ParO, ParE, ArrayReducer<N>, [Option<ParO>; N]> {
+        Repeat {
+            bounds: N..N + 1,
+            ..self.with_reducer(Reducer {
+                init: || std::array::from_fn(|_| None),
+                reduce_operator: ArrayReducer,
+            })
+        }
+    }
+    /**
+     * Collects the output into any container that implements `Default` and `Extend`, such
+     * as a `String`, `HashSet` or `BTreeMap`, similar to `Iterator::collect`.
+     *
+     * For short repeats, `collect::<SmallVec<[O; N]>>()` keeps up to N items inline
+     * without allocating, and spills to the heap past that. Containers that panic when
+     * full, like `ArrayVec`, should use `array_vec` instead.
+     */
+    pub fn collect<C>(self) -> Repeat<P, ParI, ParO, ParE, CollectReducer, C>
+    where
+        C: Default + Extend<ParO>,
+    {
+        self.with_reducer(Reducer {
+            init: C::default,
+            reduce_operator: CollectReducer,
+        })
+    }
+    /**
+     * Collects the output into an `arrayvec::ArrayVec<O, N>` without allocating. The
+     * maximum number of repetitions is lowered to N, so the repeat stops once the ArrayVec
+     * is full and leaves the rest of the input. A minimum above N can never be met, so the
+     * parser always fails. If the maximum is raised above N afterwards, a match past the
+     * capacity fails the parser with a `CapacityError` cause rather than panicking.
+     */
+    #[cfg(feature = "arrayvec")]
+    #[allow(clippy::type_complexity)]
+    pub fn array_vec<const N: usize>(
+        self,
+    ) -> Repeat<P, ParI, ParO, ParE, ArrayVecReducer, arrayvec::ArrayVec<ParO, N>> {
+        Repeat {
+            bounds: self.bounds.start..self.bounds.end.min(N.saturating_add(1)),
+            ..self.with_reducer(Reducer {
+                init: arrayvec::ArrayVec::new,
+                reduce_operator: ArrayVecReducer,
+            })
+        }
+    }
+    /**
+     * Pushes the outputs, such as chars or &strs, straight into a String without collecting
+     * them into a Vec first. This is the same as `collect::<String>()`. When the chars are
+     * exactly the ones that were matched, `as_input_slice` avoids allocating at all.
+     */
+    pub fn into_string(self) -> Repeat<P, ParI, ParO, ParE, CollectReducer, String>
+    where
+        String: Extend<ParO>,
+    {
+        self.collect()
+    }
+    /**
+     * Pairs each output with the slice of input that its repetition consumed, and collects
+     * them into a `Vec<(O, &I)>`. The slices are contiguous, and together they cover the
+     * input that the repeat matched. The pairs can be reduced with the other output methods
+     * by calling them after this one, since this replaces the reducer.
+     *
+     * For offsets instead of slices, repeat [`spanned(parser)`](crate::spanned) inside
+     * [`with_location`](crate::with_location).
+     */
+    #[allow(clippy::type_complexity)]
+    pub fn with_spans<'a>(
+        self,
+    ) -> Repeat<
+        WithSpan<P>,
+        ParI,
+        (ParO, &'a ParI),
+        ParE,
+        fn(&mut Vec<(ParO, &'a ParI)>, (ParO, &'a ParI)),
+        Vec<(ParO, &'a ParI)>,
+    >
+    where
+        ParI: 'a,
+    {
+        Repeat {
+            parser: WithSpan {
+                parser: self.parser,
+            },
+            reducer: Reducer {
+                init: Vec::new,
+                reduce_operator: |vec, val| vec.push(val),
             },
-            self.bounds,
-        )
+            bounds: self.bounds,
+            allow_empty: self.allow_empty,
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+            phantom_acc: PhantomData,
+        }
     }
     /**
      * By default this parser will output a vec. This method allows that to be replaced
@@ -301,20 +941,527 @@ impl<P, ParI: ?Sized, ParO, ParE, F, Acc: Clone> Repeat<P, ParI, ParO, ParE, F,
      * custom try reduce function. `reduce_fn` can be of the form
      * `fn(&mut acc)->()` if it always succeeds. If if can fail, it can
      * be of the forms `[fn(&mut acc)->Option<()>, fn(&mut acc)->bool, fn(&mut acc)->Result<(),E> ]`
+     * Each of these forms can also take the index of the repetition, starting from 0,
+     * as in `fn(&mut acc, usize, val)->()`.
+     * `reduce_fn` can also return `ControlFlow<()>`. Returning `ControlFlow::Break(())`
+     * stops the repetition after that value and succeeds if the minimum is met, leaving
+     * the rest of the input unconsumed.
      * It can also be a custom struct that implements the TryReducer trait.
+     *
+     * `acc` is cloned each time the parser runs. Use `reduce_with` for accumulators
+     * that aren't Clone or are expensive to clone.
      */
     pub fn reduce<NewAcc: Clone, NewF>(
         self,
         acc: NewAcc,
         reduce_fn: NewF,
-    ) -> Repeat<P, ParI, ParO, ParE, NewF, NewAcc> {
-        Repeat::new(
-            self.parser,
+    ) -> Repeat<P, ParI, ParO, ParE, NewF, NewAcc, CloneAcc<NewAcc>> {
+        self.with_reducer(Reducer {
+            init: CloneAcc(acc),
+            reduce_operator: reduce_fn,
+        })
+    }
+    /**
+     * Like `reduce`, but the accumulator is created by calling `init` each time
+     * the parser runs, so it doesn't need to be Clone.
+     */
+    pub fn reduce_with<NewAcc, NewInit, NewF>(
+        self,
+        init: NewInit,
+        reduce_fn: NewF,
+    ) -> Repeat<P, ParI, ParO, ParE, NewF, NewAcc, NewInit>
+    where
+        NewInit: Fn() -> NewAcc,
+    {
+        self.with_reducer(Reducer {
+            init,
+            reduce_operator: reduce_fn,
+        })
+    }
+}
+
+impl<P, ParI: ?Sized, ParO, ParE, F, Acc, Init> Repeat<P, ParI, ParO, ParE, F, Acc, Init> {
+    /**
+     * Repeats the parser until the terminator matches. The terminator is checked before
+     * each repetition, so it is never consumed by the underlying parser.
+     * See the function `repeat_till`.
+     */
+    pub fn till<T>(self, terminator: T) -> RepeatTill<Self, T> {
+        RepeatTill {
+            repeat: self,
+            terminator,
+        }
+    }
+}
+
+/**
+ * This struct can be constructed through the function `repeat_till` or the method `till`
+ * on `Repeat`. It outputs the accumulated output of the repeat along with the output
+ * of the terminator.
+ */
+#[derive(Clone, Debug)]
+pub struct RepeatTill<R, T> {
+    pub repeat: R,
+    pub terminator: T,
+}
+
+pub struct RepeatTillParser<PType, ReducerOut, FErr, TType, TO> {
+    repeat: PhantomData<RepeatParser<PType, ReducerOut, FErr>>,
+    ttype: PhantomData<TType>,
+    tout: PhantomData<TO>,
+}
+
+impl<'a, P, I, O, E, PType, F, Acc, Init, FErr, ReducerOut, AccOut, T, TType, TO>
+    Parser<'a, I, (AccOut, TO), E, RepeatTillParser<PType, ReducerOut, FErr, TType, TO>>
+    for RepeatTill<Repeat<P, I, O, E, F, Acc, Init>, T>
+where
+    E: ParserError,
+    I: ?Sized + Sequence,
+    P: Parser<'a, I, O, E, PType>,
+    T: Parser<'a, I, TO, E, TType>,
+    Init: InitAcc<Acc>,
+    FErr: 'static + Send + Sync + Error,
+    F: TryReducer<'a, Acc, O, ReducerOut, FErr, AccOut, I>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<(AccOut, TO), E> {
+        let orig_input = *input;
+        //The terminator's output and the input after it, once it has matched.
+        let mut terminated = None;
+        let res = self.repeat.run(input, |input| {
+            let checkpoint = *input;
+            match self.terminator.fab(input) {
+                Ok(out) => {
+                    //Stop the repeat before the terminator so it isn't part of the repeat's output.
+                    terminated = Some((out, *input));
+                    *input = checkpoint;
+                    Err(E::from_parser_error(checkpoint, ParserType::RepeatTill))
+                }
+                Err(_) => {
+                    *input = checkpoint;
+                    self.repeat.parser.fab(input)
+                }
+            }
+        });
+        let acc = match res {
+            Ok(acc) => acc,
+            Err(mut err) => {
+                err.add_context(orig_input, ParserType::RepeatTill);
+                return Err(err);
+            }
+        };
+        match terminated {
+            Some((out, rest)) => {
+                *input = rest;
+                Ok((acc, out))
+            }
+            //The repeat stopped without seeing the terminator, so it must match here.
+            None => match self.terminator.fab(input) {
+                Ok(out) => Ok((acc, out)),
+                Err(mut err) => {
+                    *input = orig_input;
+                    err.add_context(orig_input, ParserType::RepeatTill);
+                    Err(err)
+                }
+            },
+        }
+    }
+}
+
+/**
+ * This struct can be constructed through the function `separated`. It has the same
+ * `min`, `max` and `bound` methods as [`Repeat`], counting elements.
+ */
+#[derive(Clone, Debug)]
+pub struct Separated<P, S> {
+    pub parser: P,
+    pub sep: S,
+    pub bounds: Range<usize>,
+    pub allow_trailing: bool,
+}
+
+impl<P, S> Separated<P, S> {
+    /**
+     * Sets an inclusive minimum number of elements for this parser to succeed.
+     */
+    pub fn min(self, min: usize) -> Self {
+        Separated {
+            bounds: min..self.bounds.end,
+            ..self
+        }
+    }
+    /**
+     * Sets an inclusive maximum number of elements. When it hits the limit, it succeeds
+     * with the elements so far.
+     */
+    pub fn max(self, max: usize) -> Self {
+        Separated {
+            bounds: self.bounds.start..max.saturating_add(1),
+            ..self
+        }
+    }
+    /**
+     * Sets both a minimum and maximum number of elements for this parser to succeed.
+     */
+    pub fn bound<B: RangeBounds<usize>>(self, bounds: B) -> Self {
+        Separated {
+            bounds: to_range(bounds),
+            ..self
+        }
+    }
+    /**
+     * Allows a separator after the last element, as in `[1, 2, 3,]`. The trailing
+     * separator is consumed. Without this, it is left in the input.
+     */
+    pub fn allow_trailing(self) -> Self {
+        Separated {
+            allow_trailing: true,
+            ..self
+        }
+    }
+}
+
+pub struct SeparatedParser<PType, SType, SO> {
+    phantom_ptype: PhantomData<PType>,
+    phantom_stype: PhantomData<SType>,
+    phantom_so: PhantomData<SO>,
+}
+
+impl<'a, P, S, I, O, SO, E, PType, SType>
+    Parser<'a, I, Vec<O>, E, SeparatedParser<PType, SType, SO>> for Separated<P, S>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P: Parser<'a, I, O, E, PType>,
+    S: Parser<'a, I, SO, E, SType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<Vec<O>, E> {
+        let orig_input = *input;
+        if self.bounds.is_empty() {
+            return Err(E::from_parser_error(*input, ParserType::Repeat));
+        }
+        let limit = self.bounds.end - 1;
+        let mut out = Vec::new();
+        //The failure that ended the list, which is kept if there are too few elements.
+        let mut stop_err = None;
+        while out.len() < limit {
+            let checkpoint = *input;
+            if !out.is_empty() {
+                if let Err(err) = self.sep.fab(input) {
+                    *input = checkpoint;
+                    stop_err = Some(err);
+                    break;
+                }
+            }
+            let after_sep = *input;
+            match self.parser.fab(input) {
+                //Neither the separator nor the element made progress, so stop rather than looping.
+                Ok(_) if loc(*input) == loc(checkpoint) => {
+                    let mut err = E::from_parser_error(checkpoint, ParserType::RepeatIter);
+                    *input = orig_input;
+                    err.add_context(orig_input, ParserType::Repeat);
+                    return Err(err);
+                }
+                Ok(val) => out.push(val),
+                Err(err) => {
+                    //A separator without an element after it is only consumed if trailing
+                    //separators are allowed.
+                    *input = if self.allow_trailing {
+                        after_sep
+                    } else {
+                        checkpoint
+                    };
+                    stop_err = Some(err);
+                    break;
+                }
+            }
+        }
+        if stop_err.is_none() && self.allow_trailing && !out.is_empty() {
+            let checkpoint = *input;
+            if let Err(err) = self.sep.fab(input) {
+                *input = checkpoint;
+                stop_err = Some(err);
+            }
+        }
+        let incomplete = stop_err.as_ref().is_some_and(|err| err.is_incomplete());
+        if incomplete || !self.bounds.contains(&out.len()) {
+            *input = orig_input;
+            let mut err =
+                stop_err.unwrap_or_else(|| E::from_parser_error(orig_input, ParserType::Repeat));
+            err.add_context(orig_input, ParserType::Repeat);
+            return Err(err);
+        }
+        Ok(out)
+    }
+}
+
+/**
+ * This error is used as the cause when a `fill` parser runs while its buffer is already
+ * borrowed, such as when the parser it repeats runs the same `fill` again.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub struct FillBorrowedError;
+impl Display for FillBorrowedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Fill buffer was already borrowed")
+    }
+}
+
+impl Error for FillBorrowedError {}
+
+/**
+ * This struct can be constructed through the function `fill`. It holds the buffer that
+ * the outputs are written to, which can be taken back with `into_inner`.
+ */
+#[derive(Debug)]
+pub struct Fill<'b, P, O> {
+    pub parser: P,
+    pub buf: RefCell<&'b mut [O]>,
+}
+
+impl<'b, P, O> Fill<'b, P, O> {
+    /**
+     * Returns the buffer that the outputs were written to.
+     */
+    pub fn into_inner(self) -> &'b mut [O] {
+        self.buf.into_inner()
+    }
+}
+
+pub struct FillParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+
+impl<'a, 'b, P, I, O, E, PType> Parser<'a, I, (), E, FillParser<PType>> for Fill<'b, P, O>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<(), E> {
+        let Ok(buf) = self.buf.try_borrow_mut() else {
+            return Err(E::from_external_error(
+                *input,
+                ParserType::Repeat,
+                FillBorrowedError,
+            ));
+        };
+        let len = buf.len();
+        let slots = RefCell::new(buf);
+        //The outputs are written straight into the buffer by index, so the repeat loop
+        //doesn't need to allocate an accumulator. The loop runs the parser itself, so the
+        //repeat only holds the reducer and bounds.
+        let repeat: Repeat<_, I, O, E, _, ()> = Repeat::new(
+            (),
             Reducer {
-                acc,
-                reduce_operator: reduce_fn,
+                init: || (),
+                reduce_operator: |_: &mut (), index: usize, val: O| {
+                    slots.borrow_mut()[index] = val;
+                },
             },
-            self.bounds,
-        )
+            len..len.saturating_add(1),
+        );
+        repeat.run(input, |input| self.parser.fab(input))
+    }
+}
+
+/**
+ * This struct can be constructed through the method `fab_iter` on any parser.
+ * It lazily applies the parser each time `next` is called, advancing the borrowed
+ * input past each successful parse. Iteration stops at the first failure.
+ *
+ * A failure on empty input is treated as the normal end of the iteration. Otherwise
+ * the error is kept and can be recovered with `finish`. Like `Repeat`, the iterator
+ * stops with an error if the parser succeeds without consuming input.
+ */
+pub struct FabIter<'a, 'b, P, I: ?Sized, O, E, PType> {
+    parser: P,
+    input: &'b mut &'a I,
+    error: Option<E>,
+    done: bool,
+    phantom_o: PhantomData<O>,
+    phantom_ptype: PhantomData<PType>,
+}
+
+impl<'a, 'b, P, I: ?Sized, O, E, PType> FabIter<'a, 'b, P, I, O, E, PType> {
+    /**
+     * Constructs a new iterator. Prefer to use the method `fab_iter` in the parser trait.
+     */
+    pub fn new(parser: P, input: &'b mut &'a I) -> Self {
+        FabIter {
+            parser,
+            input,
+            error: None,
+            done: false,
+            phantom_o: PhantomData,
+            phantom_ptype: PhantomData,
+        }
+    }
+    /**
+     * Consumes the iterator, returning the error that ended the iteration if there was one.
+     */
+    pub fn finish(self) -> Result<(), E> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a, 'b, P, I, O, E, PType> Iterator for FabIter<'a, 'b, P, I, O, E, PType>
+where
+    E: ParserError,
+    I: ?Sized + Sequence,
+    P: Parser<'a, I, O, E, PType>,
+{
+    type Item = O;
+
+    fn next(&mut self) -> Option<O> {
+        if self.done {
+            return None;
+        }
+        let loc_before_iteration = *self.input;
+        match self.parser.fab(self.input) {
+            Ok(val) => {
+                //We made no progress, so stop rather than looping indefinitely
+                if loc(*self.input) == loc(loc_before_iteration) {
+                    self.done = true;
+                    self.error = Some(E::from_parser_error(
+                        loc_before_iteration,
+                        ParserType::RepeatIter,
+                    ));
+                    return None;
+                }
+                Some(val)
+            }
+            Err(err) => {
+                self.done = true;
+                *self.input = loc_before_iteration;
+                if !self.input.is_empty() {
+                    self.error = Some(err);
+                }
+                None
+            }
+        }
+    }
+}
+
+/**
+ * This struct can be constructed through the function `take_while`. It has the same
+ * `min`, `max` and `bound` methods as [`Repeat`].
+ */
+#[derive(Clone, Debug)]
+pub struct TakeWhile<F> {
+    pub predicate: F,
+    pub bounds: Range<usize>,
+}
+
+impl<F> TakeWhile<F> {
+    /**
+     * Sets an inclusive minimum number of chars for this parser to succeed.
+     */
+    pub fn min(self, min: usize) -> Self {
+        TakeWhile {
+            bounds: min..self.bounds.end,
+            ..self
+        }
+    }
+    /**
+     * Sets an inclusive maximum number of chars. When it hits the limit, it succeeds
+     * with the chars so far.
+     */
+    pub fn max(self, max: usize) -> Self {
+        TakeWhile {
+            bounds: self.bounds.start..max.saturating_add(1),
+            ..self
+        }
+    }
+    /**
+     * Sets both a minimum and maximum number of chars for this parser to succeed.
+     */
+    pub fn bound<B: RangeBounds<usize>>(self, bounds: B) -> Self {
+        TakeWhile {
+            bounds: to_range(bounds),
+            ..self
+        }
+    }
+    /**
+     * Returns the number of matching chars at the front of input and their byte length,
+     * stopping at the upper bound.
+     */
+    fn matched_len(&self, input: &str, predicate: impl Fn(char) -> bool) -> (usize, usize) {
+        let limit = self.bounds.end - 1;
+        let mut count = 0;
+        let mut len = 0;
+        //ASCII is checked bytewise, since it doesn't need UTF-8 decoding.
+        for (index, byte) in input.bytes().enumerate() {
+            if count == limit || !byte.is_ascii() {
+                break;
+            }
+            if !predicate(byte as char) {
+                return (count, index);
+            }
+            count += 1;
+            len = index + 1;
+        }
+        for c in input[len..].chars() {
+            if count == limit || !predicate(c) {
+                break;
+            }
+            count += 1;
+            len += c.len_utf8();
+        }
+        (count, len)
+    }
+    /**
+     * Splits off the matching prefix of input if the number of matching chars is
+     * within the bounds.
+     */
+    fn take<'a, E: ParserError>(
+        &self,
+        input: &mut &'a str,
+        predicate: impl Fn(char) -> bool,
+    ) -> Result<&'a str, E> {
+        if self.bounds.is_empty() {
+            return Err(E::from_parser_error(*input, ParserType::Repeat));
+        }
+        let (count, len) = self.matched_len(input, predicate);
+        //The match ran into the end of the input, so more input might extend it.
+        if len == input.len() && count < self.bounds.end - 1 {
+            let err = E::from_incomplete(*input, ParserType::Repeat);
+            if err.is_incomplete() {
+                return Err(err);
+            }
+        }
+        if !self.bounds.contains(&count) {
+            //Like Repeat, report the char that stopped the match under the Repeat frame.
+            let mut err = E::from_parser_error(&input[len..], ParserType::Tag);
+            err.add_context(*input, ParserType::Repeat);
+            return Err(err);
+        }
+        let (matched, rest) = input.split_at(len);
+        *input = rest;
+        Ok(matched)
+    }
+}
+
+pub struct TakeWhileParser<Arg> {
+    phantom_arg: PhantomData<Arg>,
+}
+
+impl<'a, F, E: ParserError> Parser<'a, str, &'a str, E, TakeWhileParser<char>> for TakeWhile<F>
+where
+    F: Fn(char) -> bool,
+{
+    fn fab(&self, input: &mut &'a str) -> Result<&'a str, E> {
+        self.take(input, |c| (self.predicate)(c))
+    }
+}
+
+impl<'a, F, E: ParserError> Parser<'a, str, &'a str, E, TakeWhileParser<&char>> for TakeWhile<F>
+where
+    F: Fn(&char) -> bool,
+{
+    fn fab(&self, input: &mut &'a str) -> Result<&'a str, E> {
+        self.take(input, |c| (self.predicate)(&c))
     }
 }
@@ -0,0 +1,663 @@
+use std::{error::Error, fmt::{Debug, Display}, marker::PhantomData, ops::RangeBounds, slice::Iter};
+
+use crate::{
+    branch::{
+        Alt, Alt1, Alt10, Alt11, Alt2, Alt3, Alt4, Alt5, Alt6, Alt7, Alt8, Alt9, Furthest, Seq1,
+        Seq10, Seq11, Seq12, Seq13, Seq14, Seq15, Seq16, Seq17, Seq18, Seq19, Seq2, Seq20, Seq21,
+        Seq3, Seq4, Seq5, Seq6, Seq7, Seq8, Seq9,
+    },
+    combinator::{
+        ParserMap, ParserMapT, ParserTryMap, ParserTryMapOption, ParserTryMapResult, Value,
+        ValueParser,
+    },
+    repeat::{InitAcc, Repeat, RepeatParser, TryReducer},
+    sequence::Sequence,
+    tag::{
+        ConstArrayParser, FnBoolRefSeqParser, FnBoolSeqParser, ItemSeqParser, RangeSeqParser,
+        SeqSeqParser, Take,
+    },
+    Parser, ParserError, ParserType,
+};
+
+/**
+ * A single event in a trace. Leaf parsers record how much input they consumed,
+ * alt records the index of the branch that succeeded and repeat records the number
+ * of repetitions.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum TraceEvent {
+    Consumed(usize),
+    Branch(usize),
+    Count(usize),
+}
+
+/**
+ * The trace of a successful parse, recorded by [`Recorded::fab_recorded`]. It can be
+ * replayed with [`Recorded::replay`] to reproduce the output without re-matching.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace {
+    input_len: usize,
+    events: Vec<TraceEvent>,
+}
+
+impl Trace {
+    /**
+     * The length of the input the trace was recorded from.
+     */
+    pub fn input_len(&self) -> usize {
+        self.input_len
+    }
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+}
+
+/**
+ * The error returned when a trace can't be replayed. A `LengthMismatch` means the
+ * input has a different length than the one the trace was recorded from. A `TraceMismatch`
+ * means the trace doesn't fit the parser or the input.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum ReplayError {
+    LengthMismatch { expected: usize, found: usize },
+    TraceMismatch,
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::LengthMismatch { expected, found } => write!(
+                f,
+                "Trace was recorded from an input of length {expected}, found length {found}"
+            ),
+            ReplayError::TraceMismatch => f.write_str("Trace doesn't match the parser or input"),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+/**
+ * Trait for parsers that can record a trace of a parse and replay it.
+ * This is implemented for tags, take, tuples, alt, repeat and the map combinators.
+ *
+ * `fab_record` must behave exactly like `fab`, additionally pushing events onto the trace.
+ * On failure it must leave the trace as it found it. `replay_events` re-derives the output
+ * from the events by slicing the input, re-running only the map and reduce functions.
+ */
+pub trait Replay<'a, I: ?Sized, O, E: ParserError, PType>: Parser<'a, I, O, E, PType> {
+    fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<O, E>;
+    fn replay_events(
+        &self,
+        input: &mut &'a I,
+        events: &mut Iter<'_, TraceEvent>,
+    ) -> Result<O, ReplayError>;
+}
+
+/**
+ * Runs a leaf parser, recording how much input it consumed.
+ */
+fn record_leaf<'a, I: ?Sized + Sequence, O, E>(
+    input: &mut &'a I,
+    trace: &mut Vec<TraceEvent>,
+    fab: impl FnOnce(&mut &'a I) -> Result<O, E>,
+) -> Result<O, E> {
+    let before = *input;
+    let out = fab(input)?;
+    trace.push(TraceEvent::Consumed(before.len() - input.len()));
+    Ok(out)
+}
+
+/**
+ * Replays a leaf parser whose output is the single item it consumed.
+ */
+fn replay_item<I: ?Sized + Sequence>(
+    input: &mut &I,
+    events: &mut Iter<'_, TraceEvent>,
+) -> Result<I::Item, ReplayError> {
+    let Some(TraceEvent::Consumed(len)) = events.next() else {
+        return Err(ReplayError::TraceMismatch);
+    };
+    let (item, rest) = input.try_split_front().ok_or(ReplayError::TraceMismatch)?;
+    if input.len() - rest.len() != *len {
+        return Err(ReplayError::TraceMismatch);
+    }
+    *input = rest;
+    Ok(item)
+}
+
+/**
+ * Replays a leaf parser whose output is the slice it consumed.
+ */
+fn replay_slice<'a, I: ?Sized + Sequence>(
+    input: &mut &'a I,
+    events: &mut Iter<'_, TraceEvent>,
+) -> Result<&'a I, ReplayError> {
+    let Some(TraceEvent::Consumed(len)) = events.next() else {
+        return Err(ReplayError::TraceMismatch);
+    };
+    let (start, rest) = input.try_split_at(*len).ok_or(ReplayError::TraceMismatch)?;
+    *input = rest;
+    Ok(start)
+}
+
+impl<'a, Item: 'a + Clone + PartialEq, I, E> Replay<'a, I, Item, E, ItemSeqParser> for Item
+where
+    I: ?Sized + Sequence<Item = Item>,
+    E: ParserError,
+{
+    fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<Item, E> {
+        record_leaf(input, trace, |input| {
+            <Self as Parser<'a, I, Item, E, ItemSeqParser>>::fab(self, input)
+        })
+    }
+    fn replay_events(
+        &self,
+        input: &mut &'a I,
+        events: &mut Iter<'_, TraceEvent>,
+    ) -> Result<Item, ReplayError> {
+        replay_item(input, events)
+    }
+}
+
+impl<'a, I, E> Replay<'a, I, &'a I, E, SeqSeqParser> for &I
+where
+    I: ?Sized + Sequence + PartialEq,
+    E: ParserError,
+{
+    fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<&'a I, E> {
+        record_leaf(input, trace, |input| {
+            <Self as Parser<'a, I, &'a I, E, SeqSeqParser>>::fab(self, input)
+        })
+    }
+    fn replay_events(
+        &self,
+        input: &mut &'a I,
+        events: &mut Iter<'_, TraceEvent>,
+    ) -> Result<&'a I, ReplayError> {
+        replay_slice(input, events)
+    }
+}
+
+impl<'a, E, Item, const N: usize> Replay<'a, [Item], &'a [Item], E, ConstArrayParser> for [Item; N]
+where
+    E: ParserError,
+    Item: Clone + PartialEq,
+{
+    fn fab_record(
+        &self,
+        input: &mut &'a [Item],
+        trace: &mut Vec<TraceEvent>,
+    ) -> Result<&'a [Item], E> {
+        record_leaf(input, trace, |input| {
+            <Self as Parser<'a, [Item], &'a [Item], E, ConstArrayParser>>::fab(self, input)
+        })
+    }
+    fn replay_events(
+        &self,
+        input: &mut &'a [Item],
+        events: &mut Iter<'_, TraceEvent>,
+    ) -> Result<&'a [Item], ReplayError> {
+        replay_slice(input, events)
+    }
+}
+
+impl<'a, I, F, E, Item> Replay<'a, I, Item, E, FnBoolSeqParser> for F
+where
+    I: ?Sized + Sequence<Item = Item>,
+    F: Fn(Item) -> bool,
+    E: ParserError,
+    Item: Clone,
+{
+    fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<Item, E> {
+        record_leaf(input, trace, |input| {
+            <Self as Parser<'a, I, Item, E, FnBoolSeqParser>>::fab(self, input)
+        })
+    }
+    fn replay_events(
+        &self,
+        input: &mut &'a I,
+        events: &mut Iter<'_, TraceEvent>,
+    ) -> Result<Item, ReplayError> {
+        replay_item(input, events)
+    }
+}
+
+impl<'a, I, F, E, Item> Replay<'a, I, Item, E, FnBoolRefSeqParser> for F
+where
+    I: ?Sized + Sequence<Item = Item>,
+    F: Fn(&Item) -> bool,
+    E: ParserError,
+    Item: 'a + Clone,
+{
+    fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<Item, E> {
+        record_leaf(input, trace, |input| {
+            <Self as Parser<'a, I, Item, E, FnBoolRefSeqParser>>::fab(self, input)
+        })
+    }
+    fn replay_events(
+        &self,
+        input: &mut &'a I,
+        events: &mut Iter<'_, TraceEvent>,
+    ) -> Result<Item, ReplayError> {
+        replay_item(input, events)
+    }
+}
+
+impl<'a, Item, I, E, R> Replay<'a, I, Item, E, RangeSeqParser> for R
+where
+    I: ?Sized + Sequence<Item = Item>,
+    E: ParserError,
+    R: RangeBounds<Item>,
+    Item: 'a + Clone + PartialOrd,
+{
+    fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<Item, E> {
+        record_leaf(input, trace, |input| {
+            <Self as Parser<'a, I, Item, E, RangeSeqParser>>::fab(self, input)
+        })
+    }
+    fn replay_events(
+        &self,
+        input: &mut &'a I,
+        events: &mut Iter<'_, TraceEvent>,
+    ) -> Result<Item, ReplayError> {
+        replay_item(input, events)
+    }
+}
+
+impl<'a, I, E: ParserError> Replay<'a, I, &'a I, E, Take> for Take
+where
+    I: ?Sized + Sequence,
+{
+    fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<&'a I, E> {
+        record_leaf(input, trace, |input| {
+            <Self as Parser<'a, I, &'a I, E, Take>>::fab(self, input)
+        })
+    }
+    fn replay_events(
+        &self,
+        input: &mut &'a I,
+        events: &mut Iter<'_, TraceEvent>,
+    ) -> Result<&'a I, ReplayError> {
+        replay_slice(input, events)
+    }
+}
+
+impl<'a, P, M, I, O, E: ParserError, PType, F> Replay<'a, I, O, E, ParserMapT<PType, M>>
+    for ParserMap<P, I, M, E, F>
+where
+    P: Replay<'a, I, M, E, PType>,
+    F: Fn(M) -> O,
+    I: ?Sized + Sequence,
+{
+    fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<O, E> {
+        match self.parser.fab_record(input, trace) {
+            Ok(res) => Ok((self.func)(res)),
+            Err(mut err) => {
+                err.add_context(*input, ParserType::Map);
+                Err(err)
+            }
+        }
+    }
+    fn replay_events(
+        &self,
+        input: &mut &'a I,
+        events: &mut Iter<'_, TraceEvent>,
+    ) -> Result<O, ReplayError> {
+        self.parser.replay_events(input, events).map(&self.func)
+    }
+}
+
+impl<'a, P, M, I: ?Sized + Sequence, O, E: ParserError, PType, F>
+    Replay<'a, I, O, E, ParserTryMapOption<PType, M>> for ParserTryMap<P, I, M, E, F>
+where
+    P: Replay<'a, I, M, E, PType>,
+    F: Fn(M) -> Option<O>,
+{
+    fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<O, E> {
+        let checkpoint = *input;
+        let mark = trace.len();
+        match self.parser.fab_record(input, trace) {
+            Ok(res) => (self.func)(res).ok_or_else(|| {
+                *input = checkpoint;
+                trace.truncate(mark);
+                E::from_parser_error(*input, ParserType::TryMap)
+            }),
+            Err(mut err) => {
+                err.add_context(checkpoint, ParserType::TryMap);
+                Err(err)
+            }
+        }
+    }
+    fn replay_events(
+        &self,
+        input: &mut &'a I,
+        events: &mut Iter<'_, TraceEvent>,
+    ) -> Result<O, ReplayError> {
+        let res = self.parser.replay_events(input, events)?;
+        (self.func)(res).ok_or(ReplayError::TraceMismatch)
+    }
+}
+
+impl<'a, P, M, I, O, E: ParserError, PType, FErr, F>
+    Replay<'a, I, O, E, ParserTryMapResult<PType, M, FErr>> for ParserTryMap<P, I, M, E, F>
+where
+    P: Replay<'a, I, M, E, PType>,
+    FErr: Into<Box<dyn Error + Send + Sync>>,
+    F: Fn(M) -> Result<O, FErr>,
+    I: ?Sized + Sequence,
+{
+    fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<O, E> {
+        let checkpoint = *input;
+        let mark = trace.len();
+        match self.parser.fab_record(input, trace) {
+            Ok(res) => (self.func)(res).map_err(|err| {
+                *input = checkpoint;
+                trace.truncate(mark);
+                E::from_boxed_error(*input, ParserType::TryMap, err)
+            }),
+            Err(mut err) => {
+                err.add_context(checkpoint, ParserType::TryMap);
+                Err(err)
+            }
+        }
+    }
+    fn replay_events(
+        &self,
+        input: &mut &'a I,
+        events: &mut Iter<'_, TraceEvent>,
+    ) -> Result<O, ReplayError> {
+        let res = self.parser.replay_events(input, events)?;
+        (self.func)(res).map_err(|_| ReplayError::TraceMismatch)
+    }
+}
+
+impl<'a, I, O, E: ParserError, ParType, P, V> Replay<'a, I, V, E, ValueParser<ParType, O>>
+    for Value<P, V, I, O, E>
+where
+    P: Replay<'a, I, O, E, ParType>,
+    I: ?Sized + Sequence,
+    V: Clone,
+{
+    fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<V, E> {
+        match self.parser.fab_record(input, trace) {
+            Ok(_) => Ok(self.value.clone()),
+            Err(mut err) => {
+                err.add_context(*input, ParserType::Value);
+                Err(err)
+            }
+        }
+    }
+    fn replay_events(
+        &self,
+        input: &mut &'a I,
+        events: &mut Iter<'_, TraceEvent>,
+    ) -> Result<V, ReplayError> {
+        self.parser.replay_events(input, events)?;
+        Ok(self.value.clone())
+    }
+}
+
+impl<'a, P, I, O, E, PType, F, Acc, Init, FErr, ReducerOut, AccOut>
+    Replay<'a, I, AccOut, E, RepeatParser<PType, ReducerOut, FErr>>
+    for Repeat<P, I, O, E, F, Acc, Init>
+where
+    E: ParserError,
+    I: ?Sized + Sequence,
+    P: Replay<'a, I, O, E, PType>,
+    Init: InitAcc<Acc>,
+    FErr: 'static + Send + Sync + Error,
+    F: TryReducer<'a, Acc, O, ReducerOut, FErr, AccOut, I>,
+{
+    fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<AccOut, E> {
+        //The count isn't known until the repeat finishes, so reserve its slot.
+        let mark = trace.len();
+        trace.push(TraceEvent::Count(0));
+        let mut count = 0;
+        let res = self.run(input, |input| {
+            let iteration_mark = trace.len();
+            let res = self.parser.fab_record(input, trace);
+            match res {
+                Ok(_) => count += 1,
+                Err(_) => trace.truncate(iteration_mark),
+            }
+            res
+        });
+        match res {
+            Ok(out) => {
+                trace[mark] = TraceEvent::Count(count);
+                Ok(out)
+            }
+            Err(err) => {
+                trace.truncate(mark);
+                Err(err)
+            }
+        }
+    }
+    fn replay_events(
+        &self,
+        input: &mut &'a I,
+        events: &mut Iter<'_, TraceEvent>,
+    ) -> Result<AccOut, ReplayError> {
+        let Some(TraceEvent::Count(count)) = events.next() else {
+            return Err(ReplayError::TraceMismatch);
+        };
+        let orig_input = *input;
+        let mut res = self.reducer.init.init_acc();
+        for index in 0..*count {
+            let val = self.parser.replay_events(input, events)?;
+            let flow = self
+                .reducer
+                .reduce_operator
+                .try_reduce(&mut res, index, val)
+                .map_err(|_| ReplayError::TraceMismatch)?;
+            //A reducer that stopped the repeat did so on the last recorded repetition.
+            if flow.is_break() && index + 1 != *count {
+                return Err(ReplayError::TraceMismatch);
+            }
+        }
+        self.reducer
+            .reduce_operator
+            .finalize(res, orig_input, input)
+            .map_err(|_| ReplayError::TraceMismatch)
+    }
+}
+
+macro_rules! sequence_replay_impl {
+    ( $tstruct:ident $( $parser:ident $parserlower:ident $rval:ident $otype:ident $ptype:ident)+ ) => {
+        impl<'a, I: ?Sized + Sequence, $($otype, )+ E: ParserError, $($parser, $ptype,)+> Replay<'a, I, ($($otype,)+), E, $tstruct<$($ptype,)+>> for ($($parser,)+)
+            where $(
+                $parser: Replay<'a, I, $otype, E, $ptype>,
+            )+{
+            fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<($($otype,)+), E> {
+                let startloc = *input;
+                let mark = trace.len();
+                let ($($parserlower,)+) = self;
+                let ($($rval,)+);
+                $(
+                    match $parserlower.fab_record(input, trace) {
+                        Ok(res) => {
+                            $rval = res;
+                        }
+                        Err(mut err) => {
+                            err.add_context(startloc, ParserType::Sequence);
+                            *input = startloc;
+                            trace.truncate(mark);
+                            return Err(err);
+                        }
+                    }
+                )+
+                Ok(($($rval,)+))
+            }
+            fn replay_events(&self, input: &mut &'a I, events: &mut Iter<'_, TraceEvent>) -> Result<($($otype,)+), ReplayError> {
+                let ($($parserlower,)+) = self;
+                Ok(($($parserlower.replay_events(input, events)?,)+))
+            }
+        }
+    };
+}
+
+sequence_replay_impl!(Seq1 P1 p1 r1 O1 T1);
+sequence_replay_impl!(Seq2 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2);
+sequence_replay_impl!(Seq3 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3);
+sequence_replay_impl!(Seq4 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4);
+sequence_replay_impl!(Seq5 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5);
+sequence_replay_impl!(Seq6 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6);
+sequence_replay_impl!(Seq7 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7);
+sequence_replay_impl!(Seq8 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8);
+sequence_replay_impl!(Seq9 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9);
+sequence_replay_impl!(Seq10 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10);
+sequence_replay_impl!(Seq11 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11);
+sequence_replay_impl!(Seq12 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12);
+sequence_replay_impl!(Seq13 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13);
+sequence_replay_impl!(Seq14 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14);
+sequence_replay_impl!(Seq15 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15);
+sequence_replay_impl!(Seq16 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15 P16 p16 r16 O16 T16);
+sequence_replay_impl!(Seq17 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15 P16 p16 r16 O16 T16 P17 p17 r17 O17 T17);
+sequence_replay_impl!(Seq18 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15 P16 p16 r16 O16 T16 P17 p17 r17 O17 T17 P18 p18 r18 O18 T18);
+sequence_replay_impl!(Seq19 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15 P16 p16 r16 O16 T16 P17 p17 r17 O17 T17 P18 p18 r18 O18 T18 P19 p19 r19 O19 T19);
+sequence_replay_impl!(Seq20 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15 P16 p16 r16 O16 T16 P17 p17 r17 O17 T17 P18 p18 r18 O18 T18 P19 p19 r19 O19 T19 P20 p20 r20 O20 T20);
+sequence_replay_impl!(Seq21 P1 p1 r1 O1 T1 P2 p2 r2 O2 T2 P3 p3 r3 O3 T3 P4 p4 r4 O4 T4 P5 p5 r5 O5 T5 P6 p6 r6 O6 T6 P7 p7 r7 O7 T7 P8 p8 r8 O8 T8 P9 p9 r9 O9 T9 P10 p10 r10 O10 T10 P11 p11 r11 O11 T11 P12 p12 r12 O12 T12 P13 p13 r13 O13 T13 P14 p14 r14 O14 T14 P15 p15 r15 O15 T15 P16 p16 r16 O16 T16 P17 p17 r17 O17 T17 P18 p18 r18 O18 T18 P19 p19 r19 O19 T19 P20 p20 r20 O20 T20 P21 p21 r21 O21 T21);
+
+macro_rules! alt_replay_impl {
+    ( $tstruct:ident $( $parser:ident $parserlower:ident $ptype:ident $index:literal)+ ) => {
+        impl<'a, I: ?Sized + Sequence, O, E: ParserError, $($parser, $ptype,)+> Replay<'a, I, O, E, $tstruct<$($ptype,)+>> for Alt<($($parser,)+)>
+            where $(
+                $parser: Replay<'a, I, O, E, $ptype>,
+            )+{
+            fn fab_record(&self, input: &mut &'a I, trace: &mut Vec<TraceEvent>) -> Result<O, E> {
+                let startloc = *input;
+                let mut furthest = Furthest::new();
+                let  ($($parserlower,)+) = &self.0;
+                $(
+                    let mark = trace.len();
+                    trace.push(TraceEvent::Branch($index));
+                    match $parserlower.fab_record(input, trace) {
+                        Ok(res) => {
+                            return Ok(res);
+                        }
+                        Err(mut err) => {
+                            //More input might let this branch match, so don't try the others.
+                            if err.is_incomplete() {
+                                trace.truncate(mark);
+                                *input = startloc;
+                                err.add_context(startloc, ParserType::Alt);
+                                return Err(err);
+                            }
+                            trace.truncate(mark);
+                            furthest.add(err);
+                        }
+                    }
+                    *input = startloc;
+                )+
+                //Alt is only implemented for tuples with at least 1 element, so we will always have some error.
+                let mut maxlocerr = furthest.finish().expect("Something went wrong in the alt parser.");
+                maxlocerr.add_context(startloc, ParserType::Alt);
+                Err(maxlocerr)
+            }
+            fn replay_events(&self, input: &mut &'a I, events: &mut Iter<'_, TraceEvent>) -> Result<O, ReplayError> {
+                let  ($($parserlower,)+) = &self.0;
+                match events.next() {
+                    $(
+                        Some(TraceEvent::Branch($index)) => $parserlower.replay_events(input, events),
+                    )+
+                    _ => Err(ReplayError::TraceMismatch),
+                }
+            }
+        }
+    };
+}
+
+alt_replay_impl!(Alt1 P1 p1 T1 0);
+alt_replay_impl!(Alt2 P1 p1 T1 0 P2 p2 T2 1);
+alt_replay_impl!(Alt3 P1 p1 T1 0 P2 p2 T2 1 P3 p3 T3 2);
+alt_replay_impl!(Alt4 P1 p1 T1 0 P2 p2 T2 1 P3 p3 T3 2 P4 p4 T4 3);
+alt_replay_impl!(Alt5 P1 p1 T1 0 P2 p2 T2 1 P3 p3 T3 2 P4 p4 T4 3 P5 p5 T5 4);
+alt_replay_impl!(Alt6 P1 p1 T1 0 P2 p2 T2 1 P3 p3 T3 2 P4 p4 T4 3 P5 p5 T5 4 P6 p6 T6 5);
+alt_replay_impl!(Alt7 P1 p1 T1 0 P2 p2 T2 1 P3 p3 T3 2 P4 p4 T4 3 P5 p5 T5 4 P6 p6 T6 5 P7 p7 T7 6);
+alt_replay_impl!(Alt8 P1 p1 T1 0 P2 p2 T2 1 P3 p3 T3 2 P4 p4 T4 3 P5 p5 T5 4 P6 p6 T6 5 P7 p7 T7 6 P8 p8 T8 7);
+alt_replay_impl!(Alt9 P1 p1 T1 0 P2 p2 T2 1 P3 p3 T3 2 P4 p4 T4 3 P5 p5 T5 4 P6 p6 T6 5 P7 p7 T7 6 P8 p8 T8 7 P9 p9 T9 8);
+alt_replay_impl!(Alt10 P1 p1 T1 0 P2 p2 T2 1 P3 p3 T3 2 P4 p4 T4 3 P5 p5 T5 4 P6 p6 T6 5 P7 p7 T7 6 P8 p8 T8 7 P9 p9 T9 8 P10 p10 T10 9);
+alt_replay_impl!(Alt11 P1 p1 T1 0 P2 p2 T2 1 P3 p3 T3 2 P4 p4 T4 3 P5 p5 T5 4 P6 p6 T6 5 P7 p7 T7 6 P8 p8 T8 7 P9 p9 T9 8 P10 p10 T10 9 P11 p11 T11 10);
+
+/**
+ * This struct can be constructed through the function `recorded`. It parses like the
+ * underlying parser, and can additionally record a trace of a parse and replay it.
+ */
+pub struct Recorded<P, I: ?Sized, O, E> {
+    pub parser: P,
+    pub phantom_i: PhantomData<I>,
+    pub phantom_o: PhantomData<O>,
+    pub phantom_e: PhantomData<E>,
+}
+impl<P: Clone, I: ?Sized, O, E> Clone for Recorded<P, I, O, E> {
+    fn clone(&self) -> Self {
+        Recorded {
+            parser: self.parser.clone(),
+            phantom_i: PhantomData,
+            phantom_o: PhantomData,
+            phantom_e: PhantomData,
+        }
+    }
+}
+impl<P: Debug, I: ?Sized, O, E> Debug for Recorded<P, I, O, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Recorded")
+            .field("parser", &self.parser)
+            .finish()
+    }
+}
+
+impl<P, I: ?Sized + Sequence, O, E: ParserError> Recorded<P, I, O, E> {
+    /**
+     * Parses the input like `fab`, and on success also returns the trace of the parse.
+     */
+    pub fn fab_recorded<'a, PType>(&self, input: &mut &'a I) -> Result<(O, Trace), E>
+    where
+        P: Replay<'a, I, O, E, PType>,
+    {
+        let input_len = input.len();
+        let mut events = Vec::new();
+        let out = self.parser.fab_record(input, &mut events)?;
+        Ok((out, Trace { input_len, events }))
+    }
+    /**
+     * Reproduces the output of a recorded parse from its trace. The input must be the same
+     * as the input the trace was recorded from. If it has a different length, this returns
+     * `ReplayError::LengthMismatch`.
+     */
+    pub fn replay<'a, PType>(&self, trace: &Trace, input: &'a I) -> Result<O, ReplayError>
+    where
+        P: Replay<'a, I, O, E, PType>,
+    {
+        if input.len() != trace.input_len {
+            return Err(ReplayError::LengthMismatch {
+                expected: trace.input_len,
+                found: input.len(),
+            });
+        }
+        let mut input = input;
+        let mut events = trace.events.iter();
+        let out = self.parser.replay_events(&mut input, &mut events)?;
+        if events.next().is_some() {
+            return Err(ReplayError::TraceMismatch);
+        }
+        Ok(out)
+    }
+}
+
+pub struct RecordedParser<PType> {
+    ptype: PhantomData<PType>,
+}
+
+impl<'a, P, I, O, E, PType> Parser<'a, I, O, E, RecordedParser<PType>> for Recorded<P, I, O, E>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        self.parser.fab(input)
+    }
+}
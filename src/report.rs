@@ -0,0 +1,81 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use miette::{Diagnostic, LabeledSpan, SourceCode};
+
+use crate::FabError;
+
+/**
+ * Pairs a FabError with the input that was parsed, so it can be rendered as a miette
+ * diagnostic. The parser that failed is the primary label, and the combinators
+ * containing it are secondary labels. The cause of the error is the diagnostic source.
+ * Frames outside of the input aren't labeled.
+ */
+#[derive(Debug, Clone)]
+pub struct FabReport<'a> {
+    pub error: FabError,
+    pub source: &'a str,
+}
+
+impl<'a> FabReport<'a> {
+    pub fn new(error: FabError, source: &'a str) -> Self {
+        FabReport { error, source }
+    }
+}
+
+impl FabError {
+    /**
+     * Converts this error into a miette diagnostic. The input must be the input that
+     * was parsed.
+     */
+    pub fn into_report(self, original_input: &str) -> FabReport<'_> {
+        FabReport::new(self, original_input)
+    }
+}
+
+impl Display for FabReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.error.frame_labels(self.source).next() {
+            Some((_, label)) => write!(f, "Parse error in {}", label),
+            None => write!(f, "Parse error"),
+        }
+    }
+}
+
+impl Error for FabReport<'_> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error
+            .cause()
+            .map(|cause| cause as &(dyn Error + 'static))
+    }
+}
+
+impl Diagnostic for FabReport<'_> {
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&self.source)
+    }
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let labels = self.error.frame_labels(self.source).enumerate().filter_map(
+            |(frame, (offset, label))| {
+                let offset = offset?;
+                if frame == 0 {
+                    //Underline the char the parser failed on.
+                    let len = self
+                        .source
+                        .get(offset..)
+                        .and_then(|rest| rest.chars().next())
+                        .map_or(0, char::len_utf8);
+                    Some(LabeledSpan::new_primary_with_span(
+                        Some(label),
+                        (offset, len),
+                    ))
+                } else {
+                    Some(LabeledSpan::new_with_span(Some(label), offset))
+                }
+            },
+        );
+        Some(Box::new(labels))
+    }
+}
@@ -0,0 +1,229 @@
+use std::{error::Error, fmt::Display, marker::PhantomData};
+
+use crate::{sequence::Sequence, Parser, ParserError, ParserType};
+
+/**
+ * This error is used as the cause when an extract parser can't locate one of its
+ * captures. `capture` is the 0-based index of the capture within the tuple.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub struct ExtractError {
+    pub capture: usize,
+}
+impl Display for ExtractError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Capture {} was not found", self.capture)
+    }
+}
+
+impl Error for ExtractError {}
+
+/**
+ * Scans forward one item at a time until the parser succeeds or more than `max_skip`
+ * items have been skipped. On success, the input is left after the match. On failure,
+ * the input is restored and the location where the search stopped is returned.
+ */
+fn scan<'a, I, O, E, PType, P>(parser: &P, input: &mut &'a I, max_skip: usize) -> Result<O, &'a I>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P: Parser<'a, I, O, E, PType>,
+{
+    let start = *input;
+    let mut skipped: usize = 0;
+    loop {
+        let checkpoint = *input;
+        if let Ok(out) = parser.fab(input) {
+            return Ok(out);
+        }
+        *input = checkpoint;
+        if skipped == max_skip {
+            *input = start;
+            return Err(checkpoint);
+        }
+        match input.try_split_front_ref() {
+            Some((_, rest)) => {
+                *input = rest;
+                skipped += 1;
+            }
+            None => {
+                *input = start;
+                return Err(checkpoint);
+            }
+        }
+    }
+}
+
+/**
+ * This struct can be constructed through the function `find`. It skips over input
+ * until the underlying parser matches.
+ */
+#[derive(Clone, Debug)]
+pub struct Find<P> {
+    parser: P,
+    max_skip: usize,
+}
+
+impl<P> Find<P> {
+    pub fn new(parser: P) -> Self {
+        Find {
+            parser,
+            max_skip: usize::MAX,
+        }
+    }
+    /**
+     * Sets the maximum number of items that can be skipped before the parser matches.
+     */
+    pub fn max_skip(self, max_skip: usize) -> Self {
+        Find {
+            parser: self.parser,
+            max_skip,
+        }
+    }
+}
+
+pub struct FindParser<PType> {
+    ptype: PhantomData<PType>,
+}
+
+impl<'a, I, O, E, PType, P> Parser<'a, I, O, E, FindParser<PType>> for Find<P>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        let start = *input;
+        scan(&self.parser, input, self.max_skip).map_err(|stop| {
+            let mut err = E::from_parser_error(stop, ParserType::Find);
+            err.add_context(start, ParserType::Find);
+            err
+        })
+    }
+}
+
+/**
+ * This struct can be constructed through the function `recover`. If the underlying
+ * parser fails, it skips past the next match of `sync` and outputs the error.
+ */
+#[derive(Clone, Debug)]
+pub struct Recover<P, S> {
+    pub parser: P,
+    pub sync: S,
+}
+
+pub struct RecoverParser<PType, SO, SType> {
+    ptype: PhantomData<PType>,
+    so: PhantomData<SO>,
+    stype: PhantomData<SType>,
+}
+
+impl<'a, I, O, E, P, PType, S, SO, SType>
+    Parser<'a, I, Result<O, E>, E, RecoverParser<PType, SO, SType>> for Recover<P, S>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P: Parser<'a, I, O, E, PType>,
+    S: Parser<'a, I, SO, E, SType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<Result<O, E>, E> {
+        let checkpoint = *input;
+        let err = match self.parser.fab(input) {
+            Ok(out) => return Ok(Ok(out)),
+            Err(err) => err,
+        };
+        //Skip from where the parser started, even if it failed without restoring the input.
+        *input = checkpoint;
+        //There is nothing to skip, so fail rather than succeed without progress.
+        if input.is_empty() {
+            return Err(err);
+        }
+        if scan(&self.sync, input, usize::MAX).is_err() {
+            *input = input
+                .try_split_at(input.len())
+                .expect("The end of the input is a valid split")
+                .1;
+        }
+        Ok(Err(err))
+    }
+}
+
+/**
+ * This struct can be constructed through the function `extract`. It locates each of
+ * its parsers in order, ignoring the input between them.
+ */
+#[derive(Clone, Debug)]
+pub struct Extract<T> {
+    parsers: T,
+    max_skip: usize,
+}
+
+impl<T> Extract<T> {
+    pub fn new(parsers: T) -> Self {
+        Extract {
+            parsers,
+            max_skip: usize::MAX,
+        }
+    }
+    /**
+     * Sets the maximum number of items that can be skipped before each capture. This
+     * applies to every gap separately, including the one before the first capture.
+     */
+    pub fn max_skip(self, max_skip: usize) -> Self {
+        Extract {
+            parsers: self.parsers,
+            max_skip,
+        }
+    }
+}
+
+macro_rules! extract_impl {
+    ( $tstruct:ident $( $parser:ident $parserlower:ident $rval:ident $otype:ident $ptype:ident $index:literal)+ ) => {
+        pub struct $tstruct<$($ptype,)+> {
+            $(
+                $parserlower : PhantomData<$ptype>,
+            )+
+        }
+
+        impl<'a, I: ?Sized + Sequence, $($otype, )+ E: ParserError, $($parser, $ptype,)+> Parser<'a, I, ($($otype,)+), E, $tstruct<$($ptype,)+>> for Extract<($($parser,)+)>
+            where $(
+                $parser: Parser<'a, I, $otype, E, $ptype>,
+            )+{
+            fn fab(&self, input: &mut &'a I) -> Result<($($otype,)+), E> {
+                let startloc = *input;
+                let ($($parserlower,)+) = &self.parsers;
+                let ($($rval,)+);
+                $(
+                    match scan($parserlower, input, self.max_skip) {
+                        Ok(res) => {
+                            $rval = res;
+                        }
+                        Err(stop) => {
+                            *input = startloc;
+                            let mut err = E::from_external_error(
+                                stop,
+                                ParserType::Extract,
+                                ExtractError { capture: $index },
+                            );
+                            err.add_context(startloc, ParserType::Extract);
+                            return Err(err);
+                        }
+                    }
+                )+
+                Ok(($($rval,)+))
+            }
+        }
+    };
+}
+
+extract_impl!(Extract1 P1 p1 r1 O1 T1 0);
+extract_impl!(Extract2 P1 p1 r1 O1 T1 0 P2 p2 r2 O2 T2 1);
+extract_impl!(Extract3 P1 p1 r1 O1 T1 0 P2 p2 r2 O2 T2 1 P3 p3 r3 O3 T3 2);
+extract_impl!(Extract4 P1 p1 r1 O1 T1 0 P2 p2 r2 O2 T2 1 P3 p3 r3 O3 T3 2 P4 p4 r4 O4 T4 3);
+extract_impl!(Extract5 P1 p1 r1 O1 T1 0 P2 p2 r2 O2 T2 1 P3 p3 r3 O3 T3 2 P4 p4 r4 O4 T4 3 P5 p5 r5 O5 T5 4);
+extract_impl!(Extract6 P1 p1 r1 O1 T1 0 P2 p2 r2 O2 T2 1 P3 p3 r3 O3 T3 2 P4 p4 r4 O4 T4 3 P5 p5 r5 O5 T5 4 P6 p6 r6 O6 T6 5);
+extract_impl!(Extract7 P1 p1 r1 O1 T1 0 P2 p2 r2 O2 T2 1 P3 p3 r3 O3 T3 2 P4 p4 r4 O4 T4 3 P5 p5 r5 O5 T5 4 P6 p6 r6 O6 T6 5 P7 p7 r7 O7 T7 6);
+extract_impl!(Extract8 P1 p1 r1 O1 T1 0 P2 p2 r2 O2 T2 1 P3 p3 r3 O3 T3 2 P4 p4 r4 O4 T4 3 P5 p5 r5 O5 T5 4 P6 p6 r6 O6 T6 5 P7 p7 r7 O7 T7 6 P8 p8 r8 O8 T8 7);
+extract_impl!(Extract9 P1 p1 r1 O1 T1 0 P2 p2 r2 O2 T2 1 P3 p3 r3 O3 T3 2 P4 p4 r4 O4 T4 3 P5 p5 r5 O5 T5 4 P6 p6 r6 O6 T6 5 P7 p7 r7 O7 T7 6 P8 p8 r8 O8 T8 7 P9 p9 r9 O9 T9 8);
+extract_impl!(Extract10 P1 p1 r1 O1 T1 0 P2 p2 r2 O2 T2 1 P3 p3 r3 O3 T3 2 P4 p4 r4 O4 T4 3 P5 p5 r5 O5 T5 4 P6 p6 r6 O6 T6 5 P7 p7 r7 O7 T7 6 P8 p8 r8 O8 T8 7 P9 p9 r9 O9 T9 8 P10 p10 r10 O10 T10 9);
+extract_impl!(Extract11 P1 p1 r1 O1 T1 0 P2 p2 r2 O2 T2 1 P3 p3 r3 O3 T3 2 P4 p4 r4 O4 T4 3 P5 p5 r5 O5 T5 4 P6 p6 r6 O6 T6 5 P7 p7 r7 O7 T7 6 P8 p8 r8 O8 T8 7 P9 p9 r9 O9 T9 8 P10 p10 r10 O10 T10 9 P11 p11 r11 O11 T11 10);
@@ -1,17 +1,48 @@
+use std::{
+    borrow::Cow,
+    marker::PhantomData,
+    ops::{Bound, Range},
+};
+
+use crate::{
+    attach::{attach, with_attached},
+    error::{Expected, Found},
+    Parser, ParserError,
+};
+
 /**
  * Trait for a sequence. This trait is implemented for slices and for str
+ *
+ * Implementations must keep these invariants, which the parsers rely on:
+ * - `try_split_at(mid)` returns `(front, back)` with `front.len() == mid` and
+ *   `front.len() + back.len() == self.len()`, where `back` is a suffix of `self`.
+ * - `try_split_front` returns a suffix that is strictly shorter than `self`.
+ * - Lengths only shrink as input is consumed. Errors are located and repeats detect a
+ *   lack of progress by comparing the length of the remaining input.
+ *
+ * To parse a stream of tokens with extra data such as the source text, run the parser on
+ * a [`SliceLike`] view of the tokens with [`with_extras`](crate::with_extras).
  */
 pub trait Sequence {
     type Item: Clone;
     /**
-     * Try to split the sequence at an index. If this is out of range
-     * this function will return None.
+     * Try to split the sequence at an index. If this is out of range, or for str isn't
+     * a char boundary, this function will return None rather than panicking.
      */
-    fn try_split_at<'a>(&'a self, mid: usize) -> Option<(&'a Self, &'a Self)>;
+    fn try_split_at(&self, mid: usize) -> Option<(&Self, &Self)>;
     /**
      * Try to split off the first element of a sequence.
      */
-    fn try_split_front<'a>(&'a self) -> Option<(Self::Item, &'a Self)>;
+    fn try_split_front(&self) -> Option<(Self::Item, &Self)>;
+    /**
+     * Like `try_split_front`, but slices borrow the first element instead of cloning it.
+     * Parsers use this to check an item by reference, and only clone it once it has
+     * matched. str has no chars to borrow, so it returns them owned.
+     */
+    fn try_split_front_ref(&self) -> Option<(Cow<'_, Self::Item>, &Self)> {
+        self.try_split_front()
+            .map(|(first, rest)| (Cow::Owned(first), rest))
+    }
     /**
      * Gets the length of a sequence. This will be in bytes for &str,
      * and number of elements for \[T\]
@@ -25,17 +56,45 @@ pub trait Sequence {
      * Subtracts off a later part of the sequence. Useful for extracting 
      * slices of the input.
      */
-    fn subtract<T: ?Sized>(&self, other: &T) -> &Self 
-    where T: Sequence<Item = Self::Item> {
+    fn subtract<T>(&self, other: &T) -> &Self
+    where
+        T: ?Sized + Sequence<Item = Self::Item>,
+    {
         let len = self.len() - other.len();
         self.try_split_at(len).expect("Successful split").0
     }
+    /**
+     * Describes an item for the expectation of an error. This is None for slices, since
+     * their items may not be printable.
+     */
+    fn expected_item(_item: &Self::Item) -> Option<Expected> {
+        None
+    }
+    /**
+     * Describes the sequence for the expectation of an error when it is used as a tag.
+     */
+    fn expected_tag(&self) -> Option<Expected> {
+        None
+    }
+    /**
+     * Describes a range of items for the expectation of an error.
+     */
+    fn expected_range(_start: Bound<&Self::Item>, _end: Bound<&Self::Item>) -> Option<Expected> {
+        None
+    }
+    /**
+     * Describes the first item of the sequence, for what an error found. This is the end
+     * of the input if the sequence is empty.
+     */
+    fn found(&self) -> Option<Found> {
+        self.is_empty().then_some(Found::Eof)
+    }
 }
 
 impl<T: Clone> Sequence for [T] {
     type Item = T;
 
-    fn try_split_at<'a>(&'a self, mid: usize) -> Option<(&'a Self, &'a Self)> {
+    fn try_split_at(&self, mid: usize) -> Option<(&Self, &Self)> {
         if mid > self.len() {
             None
         } else {
@@ -43,13 +102,18 @@ impl<T: Clone> Sequence for [T] {
         }
     }
 
-    fn try_split_front<'a>(&'a self) -> Option<(Self::Item, &'a Self)> {
-        if self.len() == 0 {
+    fn try_split_front(&self) -> Option<(Self::Item, &Self)> {
+        if self.is_empty() {
             None
         } else {
             Some((self[0].clone(), &self[1..]))
         }
     }
+
+    fn try_split_front_ref(&self) -> Option<(Cow<'_, Self::Item>, &Self)> {
+        self.split_first()
+            .map(|(first, rest)| (Cow::Borrowed(first), rest))
+    }
     fn len(&self) -> usize {
         self.len()
     }
@@ -58,7 +122,7 @@ impl<T: Clone> Sequence for [T] {
 impl Sequence for str {
     type Item = char;
 
-    fn try_split_at<'a>(&'a self, mid: usize) -> Option<(&'a Self, &'a Self)> {
+    fn try_split_at(&self, mid: usize) -> Option<(&Self, &Self)> {
         if !self.is_char_boundary(mid) {
             None
         } else {
@@ -66,11 +130,190 @@ impl Sequence for str {
         }
     }
 
-    fn try_split_front<'a>(&'a self) -> Option<(Self::Item, &'a Self)> {
+    fn try_split_front(&self) -> Option<(Self::Item, &Self)> {
         let res = self.chars().next();
         res.map(|char| (char, &self[char.len_utf8()..]))
     }
     fn len(&self) -> usize {
         self.len()
     }
+    fn expected_item(item: &char) -> Option<Expected> {
+        Some(Expected::Char(*item))
+    }
+    fn expected_tag(&self) -> Option<Expected> {
+        Some(Expected::Str(self.to_string()))
+    }
+    fn expected_range(start: Bound<&char>, end: Bound<&char>) -> Option<Expected> {
+        let start = match start {
+            Bound::Included(c) | Bound::Excluded(c) => format!("{:?}", c),
+            Bound::Unbounded => String::new(),
+        };
+        let end = match end {
+            Bound::Included(c) => format!("..={:?}", c),
+            Bound::Excluded(c) => format!("..{:?}", c),
+            Bound::Unbounded => String::from(".."),
+        };
+        Some(Expected::Range(start + &end))
+    }
+    fn found(&self) -> Option<Found> {
+        Some(self.chars().next().map_or(Found::Eof, Found::Char))
+    }
+}
+
+/**
+ * Implemented by inputs that wrap another sequence and carry extra data alongside it,
+ * such as [`Located`](crate::Located). Wrapping and unwrapping are free, since the
+ * wrapper is a reference to the same items. Tags of the inner sequence, such as a `&str`
+ * for a `Located<str>`, match the wrapper, and [`inner`](crate::inner) runs any parser
+ * written for the inner sequence on it.
+ */
+pub trait InputWrapper: Sequence {
+    type Inner: ?Sized + Sequence<Item = Self::Item>;
+    /**
+     * Returns the wrapped sequence.
+     */
+    fn inner(&self) -> &Self::Inner;
+    /**
+     * Wraps part of the inner sequence.
+     */
+    fn wrap(inner: &Self::Inner) -> &Self;
+}
+
+/**
+ * A view of a slice of items, such as tokens from a lexer, that carries extra data such
+ * as the source text or the spans of the tokens. Parsers run on `&mut &SliceLike<T, X>`
+ * like on `&mut &[T]`, so items work as tags and in ranges through [`PartialEq`] and
+ * [`PartialOrd`], and any of them can look up the extras and the position of the items.
+ * Tags of the inner slice match it, and [`inner`](crate::inner) runs parsers written for
+ * the slice.
+ *
+ * SliceLike inputs are created by [`with_extras`](crate::with_extras), which makes the
+ * extras reachable from its input while its parser runs. Positions are indices into the
+ * items that parser started on.
+ */
+#[repr(transparent)]
+pub struct SliceLike<'a, T, X> {
+    phantom_a: PhantomData<&'a X>,
+    items: [T],
+}
+
+impl<'a, T: Clone, X: 'static> SliceLike<'a, T, X> {
+    /**
+     * Calls `func` with the extras and returns its result. Returns None outside of the
+     * `with_extras` parser that created this input.
+     */
+    pub fn extras<R>(&self, func: impl FnOnce(&X) -> R) -> Option<R> {
+        with_attached::<ExtrasKey<X>, _, X, _>(self, |_, extras| func(extras))
+    }
+    /**
+     * Returns the index of the first item of this input within the items. Returns None
+     * outside of the `with_extras` parser that created it.
+     */
+    pub fn offset(&self) -> Option<usize> {
+        with_attached::<ExtrasKey<X>, _, X, _>(self, |start, _| {
+            let bytes = self.items.as_ptr() as usize - start;
+            //Zero sized items all share one address, so they can't be told apart.
+            bytes.checked_div(std::mem::size_of::<T>()).unwrap_or(0)
+        })
+    }
+    /**
+     * Returns the range of indices of this input within the items, such as for the slice
+     * a parser consumed. Returns None outside of the `with_extras` parser that created it.
+     */
+    pub fn range(&self) -> Option<Range<usize>> {
+        let start = self.offset()?;
+        Some(start..start + self.items.len())
+    }
+}
+
+impl<'a, T: Clone, X> InputWrapper for SliceLike<'a, T, X> {
+    type Inner = [T];
+
+    fn inner(&self) -> &[T] {
+        &self.items
+    }
+    fn wrap(inner: &[T]) -> &Self {
+        //SAFETY: SliceLike is a transparent wrapper around [T].
+        unsafe { &*(inner as *const [T] as *const Self) }
+    }
+}
+
+impl<'a, T: Clone, X> Sequence for SliceLike<'a, T, X> {
+    type Item = T;
+
+    fn try_split_at(&self, mid: usize) -> Option<(&Self, &Self)> {
+        let (front, back) = self.items.try_split_at(mid)?;
+        Some((Self::wrap(front), Self::wrap(back)))
+    }
+    fn try_split_front(&self) -> Option<(T, &Self)> {
+        let (first, rest) = self.items.try_split_front()?;
+        Some((first, Self::wrap(rest)))
+    }
+    fn try_split_front_ref(&self) -> Option<(Cow<'_, T>, &Self)> {
+        let (first, rest) = self.items.try_split_front_ref()?;
+        Some((first, Self::wrap(rest)))
+    }
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+impl<'a, T: std::fmt::Debug, X> std::fmt::Debug for SliceLike<'a, T, X> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SliceLike").field(&&self.items).finish()
+    }
+}
+
+impl<'a, T: PartialEq, X> PartialEq for SliceLike<'a, T, X> {
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items
+    }
+}
+
+impl<'a, T: PartialEq, X> PartialEq<[T]> for SliceLike<'a, T, X> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.items == *other
+    }
+}
+
+impl<'a, T: PartialEq, X> PartialEq<SliceLike<'a, T, X>> for [T] {
+    fn eq(&self, other: &SliceLike<'a, T, X>) -> bool {
+        *self == other.items
+    }
+}
+
+/**
+ * The key that the extras of a `with_extras` parser are attached under.
+ */
+struct ExtrasKey<X>(PhantomData<X>);
+
+/**
+ * This struct can be constructed through the function `with_extras`. It runs the
+ * underlying parser on a [`SliceLike`] input that carries the extras.
+ */
+#[derive(Clone, Debug)]
+pub struct WithExtras<X, P> {
+    pub extras: X,
+    pub parser: P,
+}
+
+pub struct WithExtrasParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+
+impl<'a, T, X, O, E, P, PType> Parser<'a, [T], O, E, WithExtrasParser<PType>> for WithExtras<X, P>
+where
+    T: Clone + 'a,
+    X: 'static,
+    E: ParserError,
+    P: Parser<'a, SliceLike<'a, T, X>, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a [T]) -> Result<O, E> {
+        let mut slice_like = SliceLike::wrap(*input);
+        let res = attach::<ExtrasKey<X>, _, _, _>(*input, &self.extras, || {
+            self.parser.fab(&mut slice_like)
+        });
+        *input = slice_like.inner();
+        res
+    }
 }
@@ -0,0 +1,334 @@
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    marker::PhantomData,
+};
+
+use crate::{
+    attach::{attach, with_attached},
+    error::{Expected, Found},
+    scan::Recover,
+    sequence::{InputWrapper, Sequence},
+    Parser, ParserError,
+};
+
+/**
+ * Holds user state for a parse, such as an indentation stack or a set of declared names.
+ * Parsers created with `with_state` run on a [`Stateful`] input that can reach the state.
+ *
+ * State isn't restored when a combinator backtracks. Parsers that change the state should
+ * only do so once they have succeeded, or undo their changes on failure.
+ */
+#[derive(Debug, Default)]
+pub struct State<S> {
+    state: RefCell<S>,
+}
+
+impl<S> State<S> {
+    pub fn new(state: S) -> Self {
+        State {
+            state: RefCell::new(state),
+        }
+    }
+    /**
+     * Borrows the state. This panics if the state is being changed through `Stateful::state`.
+     */
+    pub fn borrow(&self) -> Ref<'_, S> {
+        self.state.borrow()
+    }
+    /**
+     * Mutably borrows the state. This panics if the state is already borrowed.
+     */
+    pub fn borrow_mut(&self) -> RefMut<'_, S> {
+        self.state.borrow_mut()
+    }
+    pub fn into_inner(self) -> S {
+        self.state.into_inner()
+    }
+    /**
+     * Creates a parser that runs `parser` on a [`Stateful`] input, through which any
+     * parser inside it can reach this state.
+     */
+    pub fn with_state<P>(&self, parser: P) -> WithState<'_, S, P> {
+        WithState {
+            state: self,
+            parser,
+        }
+    }
+}
+
+/**
+ * An input wrapper that carries user state through a parse. Parsers run on
+ * `&mut &Stateful<str, S>` like on `&mut &str`, and any of them can reach the state with
+ * `state`, however deeply nested. Parsers written against [`Sequence`] work unchanged,
+ * tags of the inner sequence match it, and [`inner`](crate::inner) runs parsers written
+ * for the inner sequence.
+ *
+ * Stateful inputs are created by the `with_state` method on a [`State`], which makes the
+ * state reachable from its input while its parser runs.
+ */
+#[repr(transparent)]
+pub struct Stateful<'a, I: ?Sized, S> {
+    phantom_a: PhantomData<&'a I>,
+    phantom_s: PhantomData<S>,
+    input: I,
+}
+
+impl<'a, I: ?Sized + Sequence, S: 'static> Stateful<'a, I, S> {
+    /**
+     * Calls `func` with the state and returns its result. Returns None outside of the
+     * `with_state` parser that created this input, or if the state is already borrowed,
+     * such as from a nested call.
+     */
+    pub fn state<R>(&self, func: impl FnOnce(&mut S) -> R) -> Option<R> {
+        with_attached::<StateKey<S>, _, RefCell<S>, _>(self, |_, state| {
+            let mut state = state.try_borrow_mut().ok()?;
+            Some(func(&mut state))
+        })
+        .flatten()
+    }
+}
+
+impl<'a, I: ?Sized + Sequence, S> InputWrapper for Stateful<'a, I, S> {
+    type Inner = I;
+
+    fn inner(&self) -> &I {
+        &self.input
+    }
+    fn wrap(inner: &I) -> &Self {
+        //SAFETY: Stateful is a transparent wrapper around I.
+        unsafe { &*(inner as *const I as *const Self) }
+    }
+}
+
+impl<'a, I: ?Sized + Sequence, S> Sequence for Stateful<'a, I, S> {
+    type Item = I::Item;
+
+    fn try_split_at(&self, mid: usize) -> Option<(&Self, &Self)> {
+        let (front, back) = self.input.try_split_at(mid)?;
+        Some((Self::wrap(front), Self::wrap(back)))
+    }
+    fn try_split_front(&self) -> Option<(I::Item, &Self)> {
+        let (first, rest) = self.input.try_split_front()?;
+        Some((first, Self::wrap(rest)))
+    }
+    fn try_split_front_ref(&self) -> Option<(std::borrow::Cow<'_, I::Item>, &Self)> {
+        let (first, rest) = self.input.try_split_front_ref()?;
+        Some((first, Self::wrap(rest)))
+    }
+    fn len(&self) -> usize {
+        self.input.len()
+    }
+    fn expected_item(item: &I::Item) -> Option<Expected> {
+        I::expected_item(item)
+    }
+    fn expected_tag(&self) -> Option<Expected> {
+        self.input.expected_tag()
+    }
+    fn expected_range(
+        start: std::ops::Bound<&I::Item>,
+        end: std::ops::Bound<&I::Item>,
+    ) -> Option<Expected> {
+        I::expected_range(start, end)
+    }
+    fn found(&self) -> Option<Found> {
+        self.input.found()
+    }
+}
+
+impl<'a, I: ?Sized + std::fmt::Debug, S> std::fmt::Debug for Stateful<'a, I, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Stateful").field(&&self.input).finish()
+    }
+}
+
+impl<'a, I: ?Sized + PartialEq, S> PartialEq for Stateful<'a, I, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.input == other.input
+    }
+}
+
+impl<'a, I: ?Sized + PartialEq, S> PartialEq<I> for Stateful<'a, I, S> {
+    fn eq(&self, other: &I) -> bool {
+        self.input == *other
+    }
+}
+
+impl<'a, S> PartialEq<Stateful<'a, str, S>> for str {
+    fn eq(&self, other: &Stateful<'a, str, S>) -> bool {
+        *self == other.input
+    }
+}
+
+impl<'a, T: PartialEq, S> PartialEq<Stateful<'a, [T], S>> for [T] {
+    fn eq(&self, other: &Stateful<'a, [T], S>) -> bool {
+        *self == other.input
+    }
+}
+
+/**
+ * The key that the state of a `with_state` parser is attached under.
+ */
+struct StateKey<S>(PhantomData<S>);
+
+/**
+ * A `State<Vec<E>>` can collect diagnostics, so that one parse reports several errors.
+ * Parsers created with its `recover` and `opt` methods push errors onto it and continue,
+ * and the errors keep their locations. Once parsing is done, take them with `into_inner`.
+ * Nothing is allocated until an error is pushed.
+ */
+impl<E> State<Vec<E>> {
+    /**
+     * Like [`recover`](crate::recover), but a recovered error is pushed onto the errors
+     * and the output is None.
+     */
+    pub fn recover<P, S>(&self, parser: P, sync: S) -> ReportRecover<'_, E, P, S> {
+        ReportRecover {
+            errors: self,
+            recover: Recover { parser, sync },
+        }
+    }
+    /**
+     * Like [`opt`](crate::opt), but if the parser fails its error is pushed onto the
+     * errors. No input is consumed on failure.
+     */
+    pub fn opt<P>(&self, parser: P) -> ReportOpt<'_, E, P> {
+        ReportOpt {
+            errors: self,
+            parser,
+        }
+    }
+    /**
+     * Pushes the error, or returns it if the errors are borrowed.
+     */
+    fn report(&self, err: E) -> Result<(), E> {
+        match self.state.try_borrow_mut() {
+            Ok(mut errors) => {
+                errors.push(err);
+                Ok(())
+            }
+            Err(_) => Err(err),
+        }
+    }
+}
+
+/**
+ * This struct can be constructed through the method `recover` on a `State<Vec<E>>`.
+ */
+#[derive(Debug)]
+pub struct ReportRecover<'s, E, P, S> {
+    pub errors: &'s State<Vec<E>>,
+    pub recover: Recover<P, S>,
+}
+
+impl<'s, E, P: Clone, S: Clone> Clone for ReportRecover<'s, E, P, S> {
+    fn clone(&self) -> Self {
+        ReportRecover {
+            errors: self.errors,
+            recover: self.recover.clone(),
+        }
+    }
+}
+
+pub struct ReportRecoverParser<PType, SO, SType> {
+    phantom_ptype: PhantomData<PType>,
+    phantom_so: PhantomData<SO>,
+    phantom_stype: PhantomData<SType>,
+}
+
+impl<'a, 's, I, O, E, P, PType, S, SO, SType>
+    Parser<'a, I, Option<O>, E, ReportRecoverParser<PType, SO, SType>>
+    for ReportRecover<'s, E, P, S>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P: Parser<'a, I, O, E, PType>,
+    S: Parser<'a, I, SO, E, SType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<Option<O>, E> {
+        match self.recover.fab(input)? {
+            Ok(out) => Ok(Some(out)),
+            Err(err) => self.errors.report(err).map(|_| None),
+        }
+    }
+}
+
+/**
+ * This struct can be constructed through the method `opt` on a `State<Vec<E>>`.
+ */
+#[derive(Debug)]
+pub struct ReportOpt<'s, E, P> {
+    pub errors: &'s State<Vec<E>>,
+    pub parser: P,
+}
+
+impl<'s, E, P: Clone> Clone for ReportOpt<'s, E, P> {
+    fn clone(&self) -> Self {
+        ReportOpt {
+            errors: self.errors,
+            parser: self.parser.clone(),
+        }
+    }
+}
+
+pub struct ReportOptParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+
+impl<'a, 's, I, O, E, P, PType> Parser<'a, I, Option<O>, E, ReportOptParser<PType>>
+    for ReportOpt<'s, E, P>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    P: Parser<'a, I, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<Option<O>, E> {
+        let checkpoint = *input;
+        match self.parser.fab(input) {
+            Ok(out) => Ok(Some(out)),
+            Err(err) => {
+                *input = checkpoint;
+                self.errors.report(err).map(|_| None)
+            }
+        }
+    }
+}
+
+/**
+ * This struct can be constructed through the method `with_state` on [`State`].
+ */
+#[derive(Debug)]
+pub struct WithState<'s, S, P> {
+    pub state: &'s State<S>,
+    pub parser: P,
+}
+
+impl<'s, S, P: Clone> Clone for WithState<'s, S, P> {
+    fn clone(&self) -> Self {
+        WithState {
+            state: self.state,
+            parser: self.parser.clone(),
+        }
+    }
+}
+
+pub struct WithStateParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+
+impl<'a, 's, I, O, E, S, P, PType> Parser<'a, I, O, E, WithStateParser<PType>>
+    for WithState<'s, S, P>
+where
+    I: ?Sized + Sequence + 'a,
+    E: ParserError,
+    S: 'static,
+    P: Parser<'a, Stateful<'a, I, S>, O, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        let mut stateful = Stateful::wrap(*input);
+        let res = attach::<StateKey<S>, _, _, _>(*input, &self.state.state, || {
+            self.parser.fab(&mut stateful)
+        });
+        *input = stateful.inner();
+        res
+    }
+}
@@ -1,27 +1,60 @@
-use std::{error::Error, ops::RangeBounds};
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt::Display,
+    ops::{
+        Bound, Range, RangeBounds, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive,
+    },
+};
 
-use crate::{sequence::Sequence, Parser, ParserError, ParserType};
+use crate::{
+    error::Expected,
+    sequence::{InputWrapper, Sequence},
+    Parser, ParserError, ParserType,
+};
 
+/**
+ * Records what a tag expected in its error, along with what it found in the input.
+ * The expectation is only built if the error records it.
+ */
+fn with_expected<I: ?Sized + Sequence, E: ParserError>(
+    mut err: E,
+    expected: impl FnOnce() -> Option<Expected>,
+    input: &I,
+) -> E {
+    if !err.records_expected() {
+        return err;
+    }
+    if let Some(expected) = expected() {
+        err.set_expected(expected, input.found());
+    }
+    err
+}
+
+#[derive(Clone, Copy, Debug)]
 pub struct ItemSeqParser;
-impl<'a, Item: PartialEq, I, E> Parser<'a, I, Item, E, ItemSeqParser> for Item
+impl<'a, Item: 'a + Clone + PartialEq, I, E> Parser<'a, I, Item, E, ItemSeqParser> for Item
 where
     I: ?Sized + Sequence<Item = Item>,
     E: ParserError,
 {
     fn fab(&self, input: &mut &'a I) -> Result<Item, E> {
-        if let Some((start, rest)) = input.try_split_front() {
-            if start == *self {
+        if let Some((start, rest)) = input.try_split_front_ref() {
+            if *start == *self {
                 *input = rest;
-                Ok(start)
+                Ok(start.into_owned())
             } else {
-                Err(E::from_parser_error(*input, ParserType::Tag))
+                let err = E::from_parser_error(*input, ParserType::Tag);
+                Err(with_expected(err, || I::expected_item(self), *input))
             }
         } else {
-            Err(E::from_parser_error(*input, ParserType::Tag))
+            let err = E::from_incomplete(*input, ParserType::Tag);
+            Err(with_expected(err, || I::expected_item(self), *input))
         }
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct SeqSeqParser;
 
 impl<'a, I, E> Parser<'a, I, &'a I, E, SeqSeqParser> for &I
@@ -30,19 +63,47 @@ where
     E: ParserError,
 {
     fn fab(&self, input: &mut &'a I) -> Result<&'a I, E> {
-        if let Some((start, rest)) = input.try_split_at(self.len()) {
+        let err = if let Some((start, rest)) = input.try_split_at(self.len()) {
             if start == *self {
                 *input = rest;
-                Ok(start)
-            } else {
-                Err(E::from_parser_error(*input, ParserType::Tag))
+                return Ok(start);
             }
+            E::from_parser_error(*input, ParserType::Tag)
+        } else if self
+            .try_split_at(input.len())
+            .is_some_and(|(start, _)| start == *input)
+        {
+            //The input is a prefix of the tag, so more input could complete it.
+            E::from_incomplete(*input, ParserType::Tag)
         } else {
-            Err(E::from_parser_error(*input, ParserType::Tag))
-        }
+            E::from_parser_error(*input, ParserType::Tag)
+        };
+        Err(with_expected(err, || self.expected_tag(), *input))
+    }
+}
+
+/**
+ * Lets a tag of the inner sequence, such as a `&str`, match a wrapped input such as a
+ * `Located<str>`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct WrappedSeqParser;
+
+impl<'a, W, E> Parser<'a, W, &'a W, E, WrappedSeqParser> for &W::Inner
+where
+    W: ?Sized + InputWrapper,
+    W::Inner: PartialEq,
+    E: ParserError,
+{
+    fn fab(&self, input: &mut &'a W) -> Result<&'a W, E> {
+        let mut inner = input.inner();
+        let out = Parser::<'a, W::Inner, &'a W::Inner, E, SeqSeqParser>::fab(self, &mut inner)?;
+        *input = W::wrap(inner);
+        Ok(W::wrap(out))
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct ConstArrayParser;
 
 impl<'a, E, Item, const N: usize> Parser<'a, [Item], &'a [Item], E, ConstArrayParser> for [Item; N]
@@ -55,6 +116,52 @@ where
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct OwnedSeqParser;
+
+/**
+ * An owned String tag matches its contents, so a parser built from a runtime
+ * keyword doesn't borrow from it.
+ */
+impl<'a, E> Parser<'a, str, &'a str, E, OwnedSeqParser> for String
+where
+    E: ParserError,
+{
+    fn fab(&self, input: &mut &'a str) -> Result<&'a str, E> {
+        self.as_str().fab(input)
+    }
+}
+
+/**
+ * An owned Vec tag matches its contents against slice input.
+ */
+impl<'a, E, Item> Parser<'a, [Item], &'a [Item], E, OwnedSeqParser> for Vec<Item>
+where
+    E: ParserError,
+    Item: Clone + PartialEq,
+{
+    fn fab(&self, input: &mut &'a [Item]) -> Result<&'a [Item], E> {
+        self.as_slice().fab(input)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StrByteSeqParser;
+
+/**
+ * A str tag can match against byte input, comparing its UTF-8 bytes. This is useful for
+ * ASCII keywords in binary protocols.
+ */
+impl<'a, E> Parser<'a, [u8], &'a [u8], E, StrByteSeqParser> for &str
+where
+    E: ParserError,
+{
+    fn fab(&self, input: &mut &'a [u8]) -> Result<&'a [u8], E> {
+        self.as_bytes().fab(input)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
 pub struct FnBoolSeqParser;
 impl<'a, I, F, E, Item> Parser<'a, I, Item, E, FnBoolSeqParser> for F
 where
@@ -72,33 +179,35 @@ where
                 Err(E::from_parser_error(*input, ParserType::Tag))
             }
         } else {
-            Err(E::from_parser_error(*input, ParserType::Tag))
+            Err(E::from_incomplete(*input, ParserType::Tag))
         }
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct FnBoolRefSeqParser;
 impl<'a, I, F, E, Item> Parser<'a, I, Item, E, FnBoolRefSeqParser> for F
 where
     I: ?Sized + Sequence<Item = Item>,
     F: Fn(&Item) -> bool,
     E: ParserError,
-    Item: Clone,
+    Item: 'a + Clone,
 {
     fn fab(&self, input: &mut &'a I) -> Result<Item, E> {
-        if let Some((first, rest)) = input.try_split_front() {
+        if let Some((first, rest)) = input.try_split_front_ref() {
             if self(&first) {
                 *input = rest;
-                Ok(first)
+                Ok(first.into_owned())
             } else {
                 Err(E::from_parser_error(*input, ParserType::Tag))
             }
         } else {
-            Err(E::from_parser_error(*input, ParserType::Tag))
+            Err(E::from_incomplete(*input, ParserType::Tag))
         }
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct FnOptionSeqParser;
 impl<'a, I, F, E, Item, FnOut> Parser<'a, I, FnOut, E, FnOptionSeqParser> for F
 where
@@ -116,21 +225,22 @@ where
                 Err(E::from_parser_error(*input, ParserType::Tag))
             }
         } else {
-            Err(E::from_parser_error(*input, ParserType::Tag))
+            Err(E::from_incomplete(*input, ParserType::Tag))
         }
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct FnOptionRefSeqParser;
 impl<'a, I, F, E, Item, FnOut> Parser<'a, I, FnOut, E, FnOptionRefSeqParser> for F
 where
     I: ?Sized + Sequence<Item = Item>,
     F: Fn(&Item) -> Option<FnOut>,
     E: ParserError,
-    Item: Clone,
+    Item: 'a + Clone,
 {
     fn fab(&self, input: &mut &'a I) -> Result<FnOut, E> {
-        if let Some((first, rest)) = input.try_split_front() {
+        if let Some((first, rest)) = input.try_split_front_ref() {
             if let Some(out) = self(&first) {
                 *input = rest;
                 Ok(out)
@@ -138,11 +248,12 @@ where
                 Err(E::from_parser_error(*input, ParserType::Tag))
             }
         } else {
-            Err(E::from_parser_error(*input, ParserType::Tag))
+            Err(E::from_incomplete(*input, ParserType::Tag))
         }
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct FnResultSeqParser;
 impl<'a, I, F, E, Item, FnOut, FnErr> Parser<'a, I, FnOut, E, FnResultSeqParser> for F
 where
@@ -162,22 +273,23 @@ where
                 Err(err) => Err(E::from_external_error(*input, ParserType::Tag, err)),
             }
         } else {
-            Err(E::from_parser_error(*input, ParserType::Tag))
+            Err(E::from_incomplete(*input, ParserType::Tag))
         }
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct FnResultRefSeqParser;
 impl<'a, I, F, E, Item, FnOut, FnErr> Parser<'a, I, FnOut, E, FnResultRefSeqParser> for F
 where
     I: ?Sized + Sequence<Item = Item>,
     F: Fn(&Item) -> Result<FnOut, FnErr>,
     E: ParserError,
-    Item: Clone,
+    Item: 'a + Clone,
     FnErr: 'static + Error + Send + Sync,
 {
     fn fab(&self, input: &mut &'a I) -> Result<FnOut, E> {
-        if let Some((first, rest)) = input.try_split_front() {
+        if let Some((first, rest)) = input.try_split_front_ref() {
             match self(&first) {
                 Ok(out) => {
                     *input = rest;
@@ -186,34 +298,325 @@ where
                 Err(err) => Err(E::from_external_error(*input, ParserType::Tag, err)),
             }
         } else {
-            Err(E::from_parser_error(*input, ParserType::Tag))
+            Err(E::from_incomplete(*input, ParserType::Tag))
         }
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct RangeSeqParser;
 impl<'a, Item, I, E, R> Parser<'a, I, Item, E, RangeSeqParser> for R
 where
     I: ?Sized + Sequence<Item = Item>,
     E: ParserError,
     R: RangeBounds<Item>,
-    Item: PartialOrd,
+    Item: 'a + Clone + PartialOrd,
 {
     fn fab(&self, input: &mut &'a I) -> Result<Item, E> {
-        if let Some((start, rest)) = input.try_split_front() {
+        if let Some((start, rest)) = input.try_split_front_ref() {
             if self.contains(&start) {
                 *input = rest;
-                Ok(start)
+                Ok(start.into_owned())
             } else {
-                Err(E::from_parser_error(*input, ParserType::Tag))
+                let err = E::from_parser_error(*input, ParserType::Tag);
+                let expected = || I::expected_range(self.start_bound(), self.end_bound());
+                Err(with_expected(err, expected, *input))
             }
         } else {
-            Err(E::from_parser_error(*input, ParserType::Tag))
+            let err = E::from_incomplete(*input, ParserType::Tag);
+            let expected = || I::expected_range(self.start_bound(), self.end_bound());
+            Err(with_expected(err, expected, *input))
+        }
+    }
+}
+
+/**
+ * This struct can be constructed through the function `item_ref`.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ItemRef<M> {
+    pub matcher: M,
+}
+
+/**
+ * Takes the first item of a slice if it matches, returning a reference to it.
+ */
+fn split_item_ref<'a, T, E>(
+    input: &mut &'a [T],
+    matches: impl Fn(&T) -> bool,
+) -> Result<&'a T, E>
+where
+    T: Clone,
+    E: ParserError,
+{
+    match input.split_first() {
+        Some((first, rest)) if matches(first) => {
+            *input = rest;
+            Ok(first)
+        }
+        Some(_) => Err(E::from_parser_error(*input, ParserType::Tag)),
+        None => Err(E::from_incomplete(*input, ParserType::Tag)),
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ItemRefParser;
+impl<'a, T, E> Parser<'a, [T], &'a T, E, ItemRefParser> for ItemRef<T>
+where
+    T: Clone + PartialEq,
+    E: ParserError,
+{
+    fn fab(&self, input: &mut &'a [T]) -> Result<&'a T, E> {
+        split_item_ref(input, |item| *item == self.matcher)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FnBoolItemRefParser;
+impl<'a, T, F, E> Parser<'a, [T], &'a T, E, FnBoolItemRefParser> for ItemRef<F>
+where
+    T: Clone,
+    F: Fn(&T) -> bool,
+    E: ParserError,
+{
+    fn fab(&self, input: &mut &'a [T]) -> Result<&'a T, E> {
+        split_item_ref(input, &self.matcher)
+    }
+}
+
+/**
+ * Items that have an ASCII case, used by `ascii_no_case`.
+ */
+pub trait AsciiItem: Copy + PartialOrd {
+    fn to_ascii_lower(self) -> Self;
+    fn to_ascii_upper(self) -> Self;
+}
+impl AsciiItem for char {
+    fn to_ascii_lower(self) -> Self {
+        self.to_ascii_lowercase()
+    }
+    fn to_ascii_upper(self) -> Self {
+        self.to_ascii_uppercase()
+    }
+}
+impl AsciiItem for u8 {
+    fn to_ascii_lower(self) -> Self {
+        self.to_ascii_lowercase()
+    }
+    fn to_ascii_upper(self) -> Self {
+        self.to_ascii_uppercase()
+    }
+}
+
+/**
+ * This struct can be constructed through the function `ascii_no_case`.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub struct AsciiNoCase<M> {
+    pub matcher: M,
+}
+
+/**
+ * Takes the first item if it or its other ASCII case matches, returning the item from
+ * the input.
+ */
+fn no_case_item<I, E, Item>(
+    input: &mut &I,
+    matches: impl Fn(&Item) -> bool,
+) -> Result<Item, E>
+where
+    I: ?Sized + Sequence<Item = Item>,
+    E: ParserError,
+    Item: AsciiItem,
+{
+    match input.try_split_front() {
+        Some((start, rest))
+            if matches(&start)
+                || matches(&start.to_ascii_lower())
+                || matches(&start.to_ascii_upper()) =>
+        {
+            *input = rest;
+            Ok(start)
         }
+        Some(_) => Err(E::from_parser_error(*input, ParserType::Tag)),
+        None => Err(E::from_incomplete(*input, ParserType::Tag)),
     }
 }
 
-pub struct Take(pub usize);
+#[derive(Clone, Copy, Debug)]
+pub struct AsciiNoCaseItemParser;
+impl<'a, Item, I, E> Parser<'a, I, Item, E, AsciiNoCaseItemParser> for AsciiNoCase<Item>
+where
+    I: ?Sized + Sequence<Item = Item>,
+    E: ParserError,
+    Item: AsciiItem,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<Item, E> {
+        no_case_item(input, |item| *item == self.matcher)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AsciiNoCaseRangeParser;
+impl<'a, Item, I, E, R> Parser<'a, I, Item, E, AsciiNoCaseRangeParser> for AsciiNoCase<R>
+where
+    I: ?Sized + Sequence<Item = Item>,
+    E: ParserError,
+    R: RangeBounds<Item>,
+    Item: AsciiItem,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<Item, E> {
+        no_case_item(input, |item| self.matcher.contains(item))
+    }
+}
+
+/**
+ * This struct can be constructed through the function `literals`. It holds the literals
+ * in a trie over their bytes, which is built once when it is constructed.
+ */
+#[derive(Clone, Debug)]
+pub struct Literals<V> {
+    nodes: Vec<TrieNode>,
+    patterns: Vec<String>,
+    values: Vec<V>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct TrieNode {
+    //Sorted by byte, so the next node is found with a binary search.
+    children: Vec<(u8, usize)>,
+    //The index of the literal that ends at this node.
+    literal: Option<usize>,
+}
+
+impl<V> Literals<V> {
+    /**
+     * Builds the trie from pairs of a literal and its value. If a literal appears more
+     * than once, the last value is kept.
+     */
+    pub fn new<K: AsRef<str>>(pairs: impl IntoIterator<Item = (K, V)>) -> Self {
+        let mut literals = Literals {
+            nodes: vec![TrieNode::default()],
+            patterns: Vec::new(),
+            values: Vec::new(),
+        };
+        for (pattern, value) in pairs {
+            literals.insert(pattern.as_ref(), value);
+        }
+        literals
+    }
+    fn insert(&mut self, pattern: &str, value: V) {
+        let mut node = 0;
+        for &byte in pattern.as_bytes() {
+            node = match self.nodes[node]
+                .children
+                .binary_search_by_key(&byte, |&(b, _)| b)
+            {
+                Ok(index) => self.nodes[node].children[index].1,
+                Err(index) => {
+                    let child = self.nodes.len();
+                    self.nodes.push(TrieNode::default());
+                    self.nodes[node].children.insert(index, (byte, child));
+                    child
+                }
+            };
+        }
+        match self.nodes[node].literal {
+            Some(index) => self.values[index] = value,
+            None => {
+                self.nodes[node].literal = Some(self.values.len());
+                self.patterns.push(pattern.to_string());
+                self.values.push(value);
+            }
+        }
+    }
+    /**
+     * Walks the trie along bytes. Returns the length and index of the longest literal
+     * that bytes starts with, and whether a longer literal could match if there were
+     * more bytes.
+     */
+    fn longest(&self, bytes: &[u8]) -> (Option<(usize, usize)>, bool) {
+        let mut node = &self.nodes[0];
+        let mut found = node.literal.map(|index| (0, index));
+        for (depth, byte) in bytes.iter().enumerate() {
+            match node.children.binary_search_by_key(byte, |&(b, _)| b) {
+                Ok(index) => node = &self.nodes[node.children[index].1],
+                Err(_) => return (found, false),
+            }
+            if let Some(index) = node.literal {
+                found = Some((depth + 1, index));
+            }
+        }
+        (found, !node.children.is_empty())
+    }
+    fn fab_bytes<I, E>(&self, input: &mut &I, bytes: &[u8]) -> Result<V, E>
+    where
+        I: ?Sized + Sequence,
+        E: ParserError,
+        V: Clone,
+    {
+        let (found, partial) = self.longest(bytes);
+        if partial {
+            //The input ended inside a literal, so more input might make a longer match.
+            let err = E::from_incomplete(*input, ParserType::Tag);
+            if err.is_incomplete() || found.is_none() {
+                return Err(with_expected(err, || self.expected(), *input));
+            }
+        }
+        match found {
+            Some((len, index)) => {
+                let (_, rest) = input
+                    .try_split_at(len)
+                    .expect("A whole literal ends on a char boundary");
+                *input = rest;
+                Ok(self.values[index].clone())
+            }
+            None => {
+                let err = E::from_parser_error(*input, ParserType::Tag);
+                Err(with_expected(err, || self.expected(), *input))
+            }
+        }
+    }
+    fn expected(&self) -> Option<Expected> {
+        let mut options: Vec<_> = self.patterns.iter().cloned().map(Expected::Str).collect();
+        match options.len() {
+            0 => None,
+            1 => options.pop(),
+            _ => Some(Expected::OneOf(options)),
+        }
+    }
+}
+
+impl<K: AsRef<str>, V> FromIterator<(K, V)> for Literals<V> {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        Literals::new(iter)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LiteralsParser;
+
+impl<'a, E: ParserError, V: Clone> Parser<'a, str, V, E, LiteralsParser> for Literals<V> {
+    fn fab(&self, input: &mut &'a str) -> Result<V, E> {
+        self.fab_bytes(input, input.as_bytes())
+    }
+}
+
+impl<'a, E: ParserError, V: Clone> Parser<'a, [u8], V, E, LiteralsParser> for Literals<V> {
+    fn fab(&self, input: &mut &'a [u8]) -> Result<V, E> {
+        self.fab_bytes(input, input)
+    }
+}
+
+/**
+ * This struct can be constructed through the function `take`. It takes between
+ * `min` and `max` items, inclusive, as many as are available.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub struct Take {
+    pub min: usize,
+    pub max: usize,
+}
 impl<'a, I, E: ParserError> Parser<'a, I, &'a I, E, Take> for Take
 where
     I: ?Sized + Sequence,
@@ -221,14 +624,19 @@ where
     fn fab(&self, input: &mut &'a I) -> Result<&'a I, E> {
         let orig = *input;
         let orig_len: usize = input.len();
-        for _ in 0..self.0 {
+        let mut count = 0;
+        while count < self.max {
             if let Some((_first, rest)) = input.try_split_front() {
                 *input = rest;
+                count += 1;
             } else {
-                *input = orig;
-                return Err(E::from_parser_error(*input, ParserType::Tag));
+                break;
             }
         }
+        if count < self.min {
+            *input = orig;
+            return Err(E::from_incomplete(*input, ParserType::Take));
+        }
         let pos = orig_len - input.len();
         *input = orig;
         let (res, rest) = input
@@ -239,6 +647,55 @@ where
     }
 }
 
+/**
+ * Trait for the lengths accepted by `take`. This is implemented for usize, which takes
+ * an exact number of items, and for all of the range types over usize.
+ */
+pub trait TakeBounds {
+    fn take_bounds(self) -> Take;
+}
+
+impl TakeBounds for usize {
+    fn take_bounds(self) -> Take {
+        Take {
+            min: self,
+            max: self,
+        }
+    }
+}
+
+fn range_take_bounds<B: RangeBounds<usize>>(bounds: B) -> Take {
+    let min = match bounds.start_bound() {
+        Bound::Included(val) => *val,
+        Bound::Excluded(val) => val
+            .checked_add(1)
+            .expect("The lower bound for the range shouldn't be usize::MAX"),
+        Bound::Unbounded => 0,
+    };
+    let max = match bounds.end_bound() {
+        Bound::Included(val) => *val,
+        //An empty range can never be satisfied, so make the minimum larger than the maximum.
+        Bound::Excluded(0) => return Take { min: 1, max: 0 },
+        Bound::Excluded(val) => *val - 1,
+        Bound::Unbounded => usize::MAX,
+    };
+    Take { min, max }
+}
+
+macro_rules! take_bounds_impl {
+    ( $($range:ty)+ ) => {
+        $(
+            impl TakeBounds for $range {
+                fn take_bounds(self) -> Take {
+                    range_take_bounds(self)
+                }
+            }
+        )+
+    };
+}
+
+take_bounds_impl!(Range<usize> RangeInclusive<usize> RangeFrom<usize> RangeTo<usize> RangeToInclusive<usize> RangeFull);
+
 pub struct ParserFunction;
 
 impl<'c, I: ?Sized + Sequence, O, E: ParserError, F> Parser<'c, I, O, E, ParserFunction> for F
@@ -254,3 +711,59 @@ where
         })
     }
 }
+
+/**
+ * This error is used as the cause when a parser constructed with `from_fn_mut` is called
+ * again while it is already running, such as from a recursive grammar.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub struct ReentrantFnMutError;
+impl Display for ReentrantFnMutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FnMut parser was called while it was already running")
+    }
+}
+
+impl Error for ReentrantFnMutError {}
+
+/**
+ * This struct can be constructed through the function `from_fn_mut`. It holds
+ * the function in a RefCell so it can mutate its captured state.
+ */
+#[derive(Debug)]
+pub struct FromFnMut<F> {
+    pub func: RefCell<F>,
+}
+
+impl<F> FromFnMut<F> {
+    /**
+     * Returns the underlying function, along with any state it captured.
+     */
+    pub fn into_inner(self) -> F {
+        self.func.into_inner()
+    }
+}
+
+pub struct ParserFunctionMut;
+
+impl<'c, I: ?Sized + Sequence, O, E: ParserError, F> Parser<'c, I, O, E, ParserFunctionMut>
+    for FromFnMut<F>
+where
+    F: FnMut(&mut &'c I) -> Result<O, E>,
+{
+    fn fab(&self, input: &mut &'c I) -> Result<O, E> {
+        let checkpoint = *input;
+        let Ok(mut func) = self.func.try_borrow_mut() else {
+            return Err(E::from_external_error(
+                checkpoint,
+                ParserType::Function,
+                ReentrantFnMutError,
+            ));
+        };
+        func(input).map_err(|mut err| {
+            *input = checkpoint;
+            err.add_context(checkpoint, ParserType::Function);
+            err
+        })
+    }
+}
@@ -1,4 +1,18 @@
-use crate::{opt, sequence::Sequence, Parser, ParserError};
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::Display,
+    marker::PhantomData,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
+
+use crate::{
+    alt,
+    error::{Expected, Found},
+    opt,
+    sequence::Sequence,
+    Parser, ParserError, ParserType,
+};
 
 /**
  * This string can be parsed as an unsigned number
@@ -27,25 +41,1760 @@ where
         .fab(input)?;
     Ok(orig_input.subtract(*input))
 }
+fn is_space(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
 /**
- * This string can be parsed as a float or double
+ * Matches zero or more spaces and tabs, without allocating.
  */
-pub fn float_str<'a, E: ParserError>(input: &mut &'a str) -> Result<&'a str, E>
+pub fn space0<'a, E: ParserError>(input: &mut &'a str) -> Result<&'a str, E> {
+    is_space.fab_repeat().as_input_slice().fab(input)
+}
+
+/**
+ * Matches one or more spaces and tabs, without allocating.
+ */
+pub fn space1<'a, E: ParserError>(input: &mut &'a str) -> Result<&'a str, E> {
+    is_space.fab_repeat().min(1).as_input_slice().fab(input)
+}
+
+/**
+ * Matches zero or more whitespace chars, including newlines, without allocating.
+ */
+pub fn multispace0<'a, E: ParserError>(input: &mut &'a str) -> Result<&'a str, E> {
+    char::is_whitespace.fab_repeat().as_input_slice().fab(input)
+}
+
+/**
+ * Matches one or more whitespace chars, including newlines, without allocating.
+ */
+pub fn multispace1<'a, E: ParserError>(input: &mut &'a str) -> Result<&'a str, E> {
+    char::is_whitespace
+        .fab_repeat()
+        .min(1)
+        .as_input_slice()
+        .fab(input)
+}
+
+/**
+ * This struct can be constructed through the function `ws`.
+ */
+#[derive(Clone, Debug)]
+pub struct Ws<P> {
+    pub parser: P,
+}
+
+/**
+ * Skips whitespace, including newlines, before and after the underlying parser and
+ * returns only its output. If the underlying parser fails, its error points at the
+ * char it failed on and no input is consumed.
+ */
+pub fn ws<P>(parser: P) -> Ws<P> {
+    Ws { parser }
+}
+
+pub struct WsParser<PType> {
+    phantom_ptype: PhantomData<PType>,
+}
+
+impl<'a, O, E, P, PType> Parser<'a, str, O, E, WsParser<PType>> for Ws<P>
 where
+    E: ParserError,
+    P: Parser<'a, str, O, E, PType>,
 {
+    fn fab(&self, input: &mut &'a str) -> Result<O, E> {
+        let start = *input;
+        multispace0::<E>(input)?;
+        match self.parser.fab(input) {
+            Ok(out) => {
+                multispace0::<E>(input)?;
+                Ok(out)
+            }
+            Err(err) => {
+                *input = start;
+                Err(err)
+            }
+        }
+    }
+}
+
+/**
+ * Matches a line ending, either `\n` or `\r\n`.
+ */
+pub fn line_ending<'a, E: ParserError>(input: &mut &'a str) -> Result<&'a str, E> {
+    alt(("\n", "\r\n")).fab(input)
+}
+
+/**
+ * Matches everything up to the next line ending or the end of the input, which may be
+ * empty. A `\r` that isn't followed by `\n` is part of the line.
+ */
+pub fn not_line_ending<'a, E: ParserError>(input: &mut &'a str) -> Result<&'a str, E> {
+    let end = match input.find('\n') {
+        Some(index) if input[..index].ends_with('\r') => index - 1,
+        Some(index) => index,
+        None => input.len(),
+    };
+    let (line, rest) = input.split_at(end);
+    *input = rest;
+    Ok(line)
+}
+
+/**
+ * Matches the rest of the line and its line ending, and returns the line without the
+ * line ending. The last line doesn't need a line ending. This fails at the end of the
+ * input, so `rest_of_line.fab_repeat()` returns every line of a document.
+ */
+pub fn rest_of_line<'a, E: ParserError>(input: &mut &'a str) -> Result<&'a str, E> {
+    if input.is_empty() {
+        return Err(E::from_parser_error(*input, ParserType::Tag));
+    }
+    let line = not_line_ending(input)?;
+    opt(line_ending).fab(input)?;
+    Ok(line)
+}
+
+/**
+ * This struct can be constructed through the function `line_comment`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct LineComment<'k> {
+    pub prefix: &'k str,
+}
+
+/**
+ * Matches a comment that starts with the prefix, such as `//` or `#`, and runs to the end
+ * of the line. The comment is returned with its prefix. The line ending isn't consumed,
+ * and the comment can end at the end of the input.
+ */
+pub fn line_comment(prefix: &str) -> LineComment<'_> {
+    LineComment { prefix }
+}
+
+pub struct CommentParser;
+
+impl<'a, E: ParserError> Parser<'a, str, &'a str, E, CommentParser> for LineComment<'_> {
+    fn fab(&self, input: &mut &'a str) -> Result<&'a str, E> {
+        let checkpoint = *input;
+        self.prefix.fab(input)?;
+        not_line_ending::<E>(input)?;
+        Ok(checkpoint.subtract(*input))
+    }
+}
+
+/**
+ * This struct can be constructed through the function `block_comment`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct BlockComment<'k> {
+    pub open: &'k str,
+    pub close: &'k str,
+    pub nested: bool,
+}
+
+/**
+ * Matches a comment from the open delimiter up to and including the close delimiter, such
+ * as `/* ... */`, and returns it with its delimiters. If nested is true, each open delimiter
+ * inside the comment must be matched by its own close delimiter. If the comment isn't
+ * closed, the error is located at its open delimiter and no input is consumed.
+ */
+pub fn block_comment<'k>(open: &'k str, close: &'k str, nested: bool) -> BlockComment<'k> {
+    BlockComment {
+        open,
+        close,
+        nested,
+    }
+}
+
+impl BlockComment<'_> {
+    /**
+     * Returns the length of the comment at the start of input, which starts after the
+     * open delimiter, or None if it isn't closed.
+     */
+    fn body_len(&self, body: &str) -> Option<usize> {
+        let mut depth = 1;
+        let mut index = 0;
+        while index < body.len() {
+            let rest = &body[index..];
+            if rest.starts_with(self.close) {
+                index += self.close.len();
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            } else if self.nested && rest.starts_with(self.open) {
+                index += self.open.len();
+                depth += 1;
+            } else {
+                index += rest.chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        None
+    }
+}
+
+impl<'a, E: ParserError> Parser<'a, str, &'a str, E, CommentParser> for BlockComment<'_> {
+    fn fab(&self, input: &mut &'a str) -> Result<&'a str, E> {
+        let checkpoint = *input;
+        self.open.fab(input)?;
+        match self.body_len(input) {
+            Some(len) => {
+                *input = &input[len..];
+                Ok(checkpoint.subtract(*input))
+            }
+            None => {
+                *input = checkpoint;
+                let mut err = E::from_parser_error(checkpoint, ParserType::Tag);
+                err.set_expected(Expected::Str(self.close.to_string()), Some(Found::Eof));
+                err.add_message("unterminated block comment");
+                Err(err)
+            }
+        }
+    }
+}
+
+/**
+ * A delimiter for `balanced`, either a char or a str.
+ */
+pub trait Delimiter<'k> {
+    fn into_delimiter(self) -> Cow<'k, str>;
+}
+
+impl<'k> Delimiter<'k> for char {
+    fn into_delimiter(self) -> Cow<'k, str> {
+        Cow::Owned(self.to_string())
+    }
+}
+
+impl<'k> Delimiter<'k> for &'k str {
+    fn into_delimiter(self) -> Cow<'k, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+/**
+ * This struct can be constructed through the function `balanced`.
+ */
+#[derive(Clone, Debug)]
+pub struct Balanced<'k> {
+    pub open: Cow<'k, str>,
+    pub close: Cow<'k, str>,
+    pub quotes: &'k str,
+    pub outer: bool,
+}
+
+/**
+ * Matches the open delimiter, then everything up to the close delimiter that matches it,
+ * counting nested pairs of delimiters. It returns the text between the delimiters, and
+ * consumes the close delimiter. For example, `balanced('{', '}')` returns ` a { b } c `
+ * from `{ a { b } c }`. The delimiters can be chars or strs.
+ *
+ * If the delimiters aren't balanced, the error is located at the open delimiter and no
+ * input is consumed.
+ */
+pub fn balanced<'k>(open: impl Delimiter<'k>, close: impl Delimiter<'k>) -> Balanced<'k> {
+    Balanced {
+        open: open.into_delimiter(),
+        close: close.into_delimiter(),
+        quotes: "",
+        outer: false,
+    }
+}
+
+impl<'k> Balanced<'k> {
+    /**
+     * Ignores delimiters inside quoted strings. Each char of `quotes` starts a string that
+     * runs to the next copy of the same char, such as `"\"'"` for double and single quoted
+     * strings. A backslash inside a string escapes the char after it. A string that isn't
+     * closed makes the delimiters unbalanced.
+     */
+    pub fn skip_quoted(self, quotes: &'k str) -> Self {
+        Balanced { quotes, ..self }
+    }
+    /**
+     * Returns the whole match, including the outer delimiters, instead of the text
+     * between them.
+     */
+    pub fn outer(self) -> Self {
+        Balanced {
+            outer: true,
+            ..self
+        }
+    }
+    /**
+     * Returns the length of the text before the matching close delimiter, where body
+     * starts after the open delimiter, or None if it isn't closed.
+     */
+    fn inner_len(&self, body: &str) -> Option<usize> {
+        let mut depth = 1;
+        let mut index = 0;
+        while index < body.len() {
+            let rest = &body[index..];
+            let next = rest.chars().next().map_or(1, char::len_utf8);
+            if rest.starts_with(self.close.as_ref()) {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+                index += self.close.len();
+            } else if !self.open.is_empty() && rest.starts_with(self.open.as_ref()) {
+                index += self.open.len();
+                depth += 1;
+            } else if let Some(quote) = rest.chars().next().filter(|c| self.quotes.contains(*c)) {
+                index += next + quoted_len(&rest[next..], quote)?;
+            } else {
+                index += next;
+            }
+        }
+        None
+    }
+}
+
+/**
+ * Returns the length of a quoted string up to and including its closing quote, where
+ * body starts after the opening quote.
+ */
+fn quoted_len(body: &str, quote: char) -> Option<usize> {
+    let mut chars = body.char_indices();
+    while let Some((index, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == quote {
+            return Some(index + c.len_utf8());
+        }
+    }
+    None
+}
+
+pub struct BalancedParser;
+
+impl<'a, E: ParserError> Parser<'a, str, &'a str, E, BalancedParser> for Balanced<'_> {
+    fn fab(&self, input: &mut &'a str) -> Result<&'a str, E> {
+        let checkpoint = *input;
+        self.open.as_ref().fab(input)?;
+        match self.inner_len(input) {
+            Some(len) => {
+                let inner = &input[..len];
+                *input = &input[len + self.close.len()..];
+                if self.outer {
+                    Ok(checkpoint.subtract(*input))
+                } else {
+                    Ok(inner)
+                }
+            }
+            None => {
+                *input = checkpoint;
+                let mut err = E::from_parser_error(checkpoint, ParserType::Tag);
+                err.set_expected(Expected::Str(self.close.to_string()), Some(Found::Eof));
+                err.add_message("unbalanced delimiters");
+                Err(err)
+            }
+        }
+    }
+}
+
+/**
+ * This struct can be constructed through the function `skip_ws_and_comments`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct SkipWsAndComments<'k> {
+    pub line: Option<LineComment<'k>>,
+    pub block: Option<BlockComment<'k>>,
+}
+
+/**
+ * Skips any mix of whitespace and comments, and returns the skipped str, which may be
+ * empty. Either kind of comment can be left out with None, as in
+ * `skip_ws_and_comments(line_comment("#"), None)`. This doesn't allocate.
+ *
+ * A comment that starts but isn't closed is an error rather than being left in the input.
+ */
+pub fn skip_ws_and_comments<'k>(
+    line: impl Into<Option<LineComment<'k>>>,
+    block: impl Into<Option<BlockComment<'k>>>,
+) -> SkipWsAndComments<'k> {
+    SkipWsAndComments {
+        line: line.into(),
+        block: block.into(),
+    }
+}
+
+impl<'a, E: ParserError> Parser<'a, str, &'a str, E, CommentParser> for SkipWsAndComments<'_> {
+    fn fab(&self, input: &mut &'a str) -> Result<&'a str, E> {
+        let checkpoint = *input;
+        loop {
+            let before = input.len();
+            multispace0::<E>(input)?;
+            let res = match (self.line, self.block) {
+                (Some(line), _) if input.starts_with(line.prefix) => line.fab(input),
+                (_, Some(block)) if input.starts_with(block.open) => block.fab(input),
+                _ => Ok(""),
+            };
+            if let Err(err) = res {
+                *input = checkpoint;
+                return Err(err);
+            }
+            //Each pass must consume something, so this stops even if a delimiter is empty.
+            if input.len() == before {
+                return Ok(checkpoint.subtract(*input));
+            }
+        }
+    }
+}
+
+/**
+ * Matches an ASCII identifier. The first char is a letter or `_`, and the rest are
+ * letters, digits or `_`. This doesn't allocate.
+ */
+pub fn identifier<'a, E: ParserError>(input: &mut &'a str) -> Result<&'a str, E> {
+    identifier_with(
+        input,
+        |c| c.is_ascii_alphabetic(),
+        |c| c.is_ascii_alphanumeric(),
+    )
+}
+
+/**
+ * Matches a Unicode identifier. The first char is alphabetic or `_`, and the rest are
+ * alphanumeric or `_`. This approximates the Unicode XID rules without the extra tables.
+ * With the `unicode` feature, this follows UAX #31 instead, using [`xid_start`] and
+ * [`xid_continue`].
+ */
+#[cfg(not(feature = "unicode"))]
+pub fn unicode_identifier<'a, E: ParserError>(input: &mut &'a str) -> Result<&'a str, E> {
+    identifier_with(input, char::is_alphabetic, char::is_alphanumeric)
+}
+
+/**
+ * Matches a Unicode identifier following UAX #31, like Rust identifiers. The first char
+ * is XID_Start or `_`, and the rest are XID_Continue.
+ */
+#[cfg(feature = "unicode")]
+pub fn unicode_identifier<'a, E: ParserError>(input: &mut &'a str) -> Result<&'a str, E> {
+    identifier_with(
+        input,
+        unicode_ident::is_xid_start,
+        unicode_ident::is_xid_continue,
+    )
+}
+
+/**
+ * Matches a char with the Unicode XID_Start property, which can start an identifier.
+ */
+#[cfg(feature = "unicode")]
+pub fn xid_start<E: ParserError>(input: &mut &str) -> Result<char, E> {
+    unicode_ident::is_xid_start.fab(input)
+}
+
+/**
+ * Matches a char with the Unicode XID_Continue property, which can continue an identifier.
+ * This includes combining marks and digits.
+ */
+#[cfg(feature = "unicode")]
+pub fn xid_continue<E: ParserError>(input: &mut &str) -> Result<char, E> {
+    unicode_ident::is_xid_continue.fab(input)
+}
+
+fn identifier_with<'a, E: ParserError>(
+    input: &mut &'a str,
+    is_start: fn(char) -> bool,
+    is_continue: fn(char) -> bool,
+) -> Result<&'a str, E> {
     let orig_input = *input;
-    let _sign = opt('-').fab(input)?;
-    let _whole_part = char::is_ascii_digit
+    (|c: char| c == '_' || is_start(c)).fab(input)?;
+    (|c: char| c == '_' || is_continue(c))
         .fab_repeat()
-        .min(1)
         .as_input_slice()
         .fab(input)?;
-    let _decimal_part = opt((
-        '.',
-        char::is_ascii_digit
-        .fab_repeat()
-        .min(1)
-        .as_input_slice()
-    )).fab(input)?;
     Ok(orig_input.subtract(*input))
-}
\ No newline at end of file
+}
+
+/**
+ * This struct can be constructed through the function `keyword`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Keyword<'k> {
+    pub word: &'k str,
+}
+
+/**
+ * Matches the word like a str tag, but only if the word isn't followed by a char that
+ * could continue an identifier, so `keyword("if")` matches `if x` and `if(` but not `ifoo`.
+ * Chars that continue an identifier are `_` and Unicode alphanumerics, so `ifé` is rejected
+ * too. The char after the word isn't consumed, and at the end of input the word matches.
+ * On failure no input is consumed.
+ */
+pub fn keyword(word: &str) -> Keyword<'_> {
+    Keyword { word }
+}
+
+pub struct KeywordParser;
+
+impl<'a, E: ParserError> Parser<'a, str, &'a str, E, KeywordParser> for Keyword<'_> {
+    fn fab(&self, input: &mut &'a str) -> Result<&'a str, E> {
+        let checkpoint = *input;
+        let word = self.word.fab(input)?;
+        if input.starts_with(|c: char| c == '_' || c.is_alphanumeric()) {
+            let mut err = E::from_parser_error(*input, ParserType::Tag);
+            err.add_message(format!("expected {:?} to end", self.word));
+            *input = checkpoint;
+            return Err(err);
+        }
+        Ok(word)
+    }
+}
+
+/**
+ * Parses the keyword `true` or `false` into a bool. See [`keyword`] for how the end
+ * of the word is checked.
+ */
+pub fn bool_lit<E: ParserError>(input: &mut &str) -> Result<bool, E> {
+    alt((
+        keyword("true").fab_value(true),
+        keyword("false").fab_value(false),
+    ))
+    .fab(input)
+}
+
+/**
+ * This string can be parsed as a float or double. It matches an optional sign, digits with
+ * an optional fraction, and an optional exponent, such as `-1.5`, `.5`, `5.` or `2.5E-3`.
+ * An exponent marker without digits, as in `1e`, fails. `inf` and `NaN` aren't matched,
+ * so combine this with tags using `alt` to accept them.
+ */
+pub fn float_str<'a, E: ParserError>(input: &mut &'a str) -> Result<&'a str, E>
+where
+{
+    let orig_input = *input;
+    let res = float_parts(input);
+    if res.is_err() {
+        *input = orig_input;
+    }
+    res.map(|_| orig_input.subtract(*input))
+}
+
+fn float_parts<E: ParserError>(input: &mut &str) -> Result<(), E> {
+    let digits = || char::is_ascii_digit.fab_repeat().as_input_slice();
+    let _sign = opt(alt(('-', '+'))).fab(input)?;
+    let whole_part = digits().fab(input)?;
+    let decimal_part = opt(('.', digits())).fab(input)?;
+    if whole_part.is_empty() && decimal_part.is_none_or(|(_, part)| part.is_empty()) {
+        return Err(E::from_parser_error(*input, ParserType::Tag));
+    }
+    if opt(alt(('e', 'E'))).fab(input)?.is_some() {
+        let _exponent = (opt(alt(('-', '+'))), digits().min(1)).fab(input)?;
+    }
+    Ok(())
+}
+
+/**
+ * This struct can be constructed through the function `float`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Float;
+
+/**
+ * Parses a float matched by [`float_str`] into an f64. If the conversion fails, its
+ * error is the cause and no input is consumed.
+ */
+pub fn float() -> Float {
+    Float
+}
+
+impl<'a, E: ParserError> Parser<'a, str, f64, E, Float> for Float {
+    fn fab(&self, input: &mut &'a str) -> Result<f64, E> {
+        float_str.fab_parse_to::<f64>().fab(input)
+    }
+}
+
+/**
+ * This error is used as the cause when a number parsed by `uint` or `int` doesn't
+ * fit in the numeric type.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub struct IntOverflowError;
+impl Display for IntOverflowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Number doesn't fit in the integer type")
+    }
+}
+
+impl Error for IntOverflowError {}
+
+/**
+ * Trait for the integer types that can be parsed by `uint`, `int` and the prefixed
+ * integer parsers.
+ */
+pub trait Integer: Copy {
+    const ZERO: Self;
+    const SIGNED: bool;
+    /**
+     * Appends a digit in the radix to the number, subtracting it if the number is negative.
+     * Returns None on overflow.
+     */
+    fn push_digit(self, radix: u8, digit: u8, negative: bool) -> Option<Self>;
+}
+
+macro_rules! integer_impl {
+    ( $signed:literal $($num:ident)+ ) => {
+        $(
+            impl Integer for $num {
+                const ZERO: Self = 0;
+                const SIGNED: bool = $signed;
+                fn push_digit(self, radix: u8, digit: u8, negative: bool) -> Option<Self> {
+                    let shifted = self.checked_mul(radix as $num)?;
+                    if negative {
+                        shifted.checked_sub(digit as $num)
+                    } else {
+                        shifted.checked_add(digit as $num)
+                    }
+                }
+            }
+        )+
+    };
+}
+
+integer_impl!(false u8 u16 u32 u64 u128 usize);
+integer_impl!(true i8 i16 i32 i64 i128 isize);
+
+/**
+ * Inputs that the integer parsers can read ASCII digits from. This is implemented for str
+ * and \[u8\], so numbers in byte input are parsed without converting it to a str.
+ */
+pub trait DigitInput: Sequence {
+    fn ascii_bytes(&self) -> &[u8];
+    /**
+     * Splits off the first len bytes, which must be ASCII.
+     */
+    fn skip_ascii(&self, len: usize) -> &Self {
+        self.try_split_at(len)
+            .expect("Skipped bytes are ASCII, so this is a valid split")
+            .1
+    }
+}
+
+impl DigitInput for str {
+    fn ascii_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl DigitInput for [u8] {
+    fn ascii_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+/**
+ * Parses ASCII digits in the radix into the number. If underscores is set, single
+ * underscores are accepted between digits. The input is only advanced on success.
+ */
+fn parse_digits<'a, I: ?Sized + DigitInput, T: Integer, E: ParserError>(
+    input: &mut &'a I,
+    start: &'a I,
+    radix: u8,
+    underscores: bool,
+    negative: bool,
+) -> Result<T, E> {
+    let bytes = input.ascii_bytes();
+    let mut value = T::ZERO;
+    let mut consumed = 0;
+    while consumed < bytes.len() {
+        let mut index = consumed;
+        if underscores && consumed > 0 && bytes[index] == b'_' {
+            index += 1;
+        }
+        let Some(digit) = bytes
+            .get(index)
+            .and_then(|byte| (*byte as char).to_digit(radix as u32))
+        else {
+            break;
+        };
+        value = value
+            .push_digit(radix, digit as u8, negative)
+            .ok_or_else(|| E::from_external_error(start, ParserType::TryMap, IntOverflowError))?;
+        consumed = index + 1;
+    }
+    if consumed == 0 {
+        return Err(E::from_parser_error(*input, ParserType::Tag));
+    }
+    *input = input.skip_ascii(consumed);
+    Ok(value)
+}
+
+/**
+ * This struct can be constructed through the function `uint`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct UInt<T> {
+    phantom_t: PhantomData<T>,
+}
+
+/**
+ * This struct can be constructed through the function `int`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Int<T> {
+    phantom_t: PhantomData<T>,
+}
+
+/**
+ * Parses ASCII digits directly into the integer type, without a sign. If the number
+ * doesn't fit, this fails with an [`IntOverflowError`] cause and consumes no input.
+ * This parses both str and \[u8\] input.
+ */
+pub fn uint<T: Integer>() -> UInt<T> {
+    UInt {
+        phantom_t: PhantomData,
+    }
+}
+
+/**
+ * Parses an integer with an optional leading `+` or `-` directly into the integer type.
+ * A `-` is only accepted for signed types. If the number doesn't fit, this fails with
+ * an [`IntOverflowError`] cause and consumes no input. This parses both str and \[u8\]
+ * input.
+ */
+pub fn int<T: Integer>() -> Int<T> {
+    Int {
+        phantom_t: PhantomData,
+    }
+}
+
+pub struct IntParser;
+
+impl<'a, I: ?Sized + DigitInput, T: Integer, E: ParserError> Parser<'a, I, T, E, IntParser>
+    for UInt<T>
+{
+    fn fab(&self, input: &mut &'a I) -> Result<T, E> {
+        let start = *input;
+        parse_digits(input, start, 10, false, false)
+    }
+}
+
+impl<'a, I: ?Sized + DigitInput, T: Integer, E: ParserError> Parser<'a, I, T, E, IntParser>
+    for Int<T>
+{
+    fn fab(&self, input: &mut &'a I) -> Result<T, E> {
+        let start = *input;
+        let negative = match start.ascii_bytes().first() {
+            Some(b'+') => false,
+            Some(b'-') if T::SIGNED => true,
+            _ => return parse_digits(input, start, 10, false, false),
+        };
+        *input = start.skip_ascii(1);
+        parse_digits(input, start, 10, false, negative).inspect_err(|_| {
+            *input = start;
+        })
+    }
+}
+
+/**
+ * This struct can be constructed through the functions `hex_uint`, `oct_uint` and `bin_uint`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct RadixUInt<T> {
+    pub radix: u8,
+    pub prefix: char,
+    phantom_t: PhantomData<T>,
+}
+
+/**
+ * This struct can be constructed through the function `prefixed_int`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct PrefixedInt<T> {
+    phantom_t: PhantomData<T>,
+}
+
+fn radix_uint<T: Integer>(radix: u8, prefix: char) -> RadixUInt<T> {
+    RadixUInt {
+        radix,
+        prefix,
+        phantom_t: PhantomData,
+    }
+}
+
+/**
+ * Parses a hexadecimal number with a `0x` prefix, such as `0xFF_FF`. Prefixes and digits
+ * can be either case, and single underscores are accepted between digits. If there are
+ * no digits or the number doesn't fit, this fails and consumes no input. Overflow has an
+ * [`IntOverflowError`] cause.
+ */
+pub fn hex_uint<T: Integer>() -> RadixUInt<T> {
+    radix_uint(16, 'x')
+}
+
+/**
+ * Parses an octal number with a `0o` prefix, such as `0o755`. This otherwise behaves
+ * like [`hex_uint`].
+ */
+pub fn oct_uint<T: Integer>() -> RadixUInt<T> {
+    radix_uint(8, 'o')
+}
+
+/**
+ * Parses a binary number with a `0b` prefix, such as `0b1010`. This otherwise behaves
+ * like [`hex_uint`].
+ */
+pub fn bin_uint<T: Integer>() -> RadixUInt<T> {
+    radix_uint(2, 'b')
+}
+
+/**
+ * Parses a number in the radix given by its prefix, `0x`, `0o` or `0b`, like [`hex_uint`].
+ * Numbers without a prefix are parsed as decimal like [`uint`].
+ */
+pub fn prefixed_int<T: Integer>() -> PrefixedInt<T> {
+    PrefixedInt {
+        phantom_t: PhantomData,
+    }
+}
+
+impl<'a, T: Integer, E: ParserError> Parser<'a, str, T, E, IntParser> for RadixUInt<T> {
+    fn fab(&self, input: &mut &'a str) -> Result<T, E> {
+        let start = *input;
+        let mut chars = start.chars();
+        if chars.next() != Some('0')
+            || !chars
+                .next()
+                .is_some_and(|c| c.eq_ignore_ascii_case(&self.prefix))
+        {
+            return Err(E::from_parser_error(start, ParserType::Tag));
+        }
+        *input = &start[2..];
+        parse_digits(input, start, self.radix, true, false).inspect_err(|_| {
+            *input = start;
+        })
+    }
+}
+
+impl<'a, T: Integer, E: ParserError> Parser<'a, str, T, E, IntParser> for PrefixedInt<T> {
+    fn fab(&self, input: &mut &'a str) -> Result<T, E> {
+        let radix = match input.as_bytes() {
+            [b'0', b'x' | b'X', ..] => hex_uint(),
+            [b'0', b'o' | b'O', ..] => oct_uint(),
+            [b'0', b'b' | b'B', ..] => bin_uint(),
+            _ => return uint().fab(input),
+        };
+        radix.fab(input)
+    }
+}
+
+/**
+ * This error is used as the cause when `hex_bytes` or `hex_bytes_exact` fails on hex digits.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum HexBytesError {
+    /**
+     * A byte has only one hex digit. The error is located at that digit.
+     */
+    OddDigits,
+    /**
+     * `hex_bytes_exact` found a different number of bytes. The error is located where the
+     * next byte was expected if there are too few, or at the first extra byte.
+     */
+    Length,
+}
+impl Display for HexBytesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HexBytesError::OddDigits => f.write_str("Odd number of hex digits"),
+            HexBytesError::Length => f.write_str("Wrong number of hex bytes"),
+        }
+    }
+}
+
+impl Error for HexBytesError {}
+
+/**
+ * This struct can be constructed through the function `hex_bytes`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct HexBytes {
+    pub separated: bool,
+    pub non_empty: bool,
+}
+
+/**
+ * This struct can be constructed through the function `hex_bytes_exact`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct HexBytesExact<const N: usize> {
+    pub separated: bool,
+}
+
+/**
+ * Parses pairs of hex digits, such as `deadbeef`, into bytes. The digits can be either
+ * case. It stops before anything that doesn't start another pair, and matches no bytes
+ * if the input doesn't start with a hex digit. If a byte has only one digit, this fails
+ * with a [`HexBytesError::OddDigits`] cause and consumes no input.
+ *
+ * Use `separated` to allow separators between bytes, and `non_empty` to require at
+ * least one byte.
+ */
+pub fn hex_bytes() -> HexBytes {
+    HexBytes {
+        separated: false,
+        non_empty: false,
+    }
+}
+
+/**
+ * Parses exactly N bytes of hex digits into an array, like [`hex_bytes`]. If there are
+ * fewer or more bytes, this fails with a [`HexBytesError::Length`] cause and consumes
+ * no input.
+ */
+pub fn hex_bytes_exact<const N: usize>() -> HexBytesExact<N> {
+    HexBytesExact { separated: false }
+}
+
+impl HexBytes {
+    /**
+     * Allows a `:`, `-` or space between bytes, as in the MAC address `00:1a:2b:3c:4d:5e`.
+     * The bytes must all be separated by the same char. A separator that isn't followed
+     * by a byte isn't consumed.
+     */
+    pub fn separated(self) -> Self {
+        HexBytes {
+            separated: true,
+            ..self
+        }
+    }
+    /**
+     * Fails rather than returning no bytes if the input doesn't start with a hex digit.
+     */
+    pub fn non_empty(self) -> Self {
+        HexBytes {
+            non_empty: true,
+            ..self
+        }
+    }
+}
+
+impl<const N: usize> HexBytesExact<N> {
+    /**
+     * Allows separators between bytes, like [`HexBytes::separated`].
+     */
+    pub fn separated(self) -> Self {
+        HexBytesExact { separated: true }
+    }
+}
+
+/**
+ * Parses up to max pairs of hex digits from the start of input, passing each byte and the
+ * input at the start of its pair to push. Returns the number of bytes and the rest of the
+ * input, or the location of a digit without a pair.
+ */
+fn hex_pairs<'a>(
+    input: &'a str,
+    separated: bool,
+    max: usize,
+    mut push: impl FnMut(u8, &'a str),
+) -> Result<(usize, &'a str), &'a str> {
+    let bytes = input.as_bytes();
+    let digit = |index: usize| bytes.get(index).and_then(|b| (*b as char).to_digit(16));
+    let mut separator = None;
+    let mut count = 0;
+    let mut index = 0;
+    while count < max {
+        let mut start = index;
+        let mut sep = None;
+        if separated && count > 0 {
+            if let Some(&b @ (b':' | b'-' | b' ')) = bytes.get(index) {
+                if separator.is_none_or(|prev| prev == b) {
+                    sep = Some(b);
+                    start += 1;
+                }
+            }
+        }
+        let Some(high) = digit(start) else {
+            break;
+        };
+        let Some(low) = digit(start + 1) else {
+            return Err(&input[start..]);
+        };
+        push((high * 16 + low) as u8, &input[start..]);
+        separator = separator.or(sep);
+        count += 1;
+        index = start + 2;
+    }
+    Ok((count, &input[index..]))
+}
+
+pub struct HexBytesParser;
+
+impl<'a, E: ParserError> Parser<'a, str, Vec<u8>, E, HexBytesParser> for HexBytes {
+    fn fab(&self, input: &mut &'a str) -> Result<Vec<u8>, E> {
+        let start = *input;
+        let mut out = Vec::new();
+        match hex_pairs(start, self.separated, usize::MAX, |byte, _| out.push(byte)) {
+            Ok((0, _)) if self.non_empty => Err(E::from_parser_error(start, ParserType::Tag)),
+            Ok((_, rest)) => {
+                *input = rest;
+                Ok(out)
+            }
+            Err(dangling) => Err(E::from_external_error(
+                dangling,
+                ParserType::Tag,
+                HexBytesError::OddDigits,
+            )),
+        }
+    }
+}
+
+impl<'a, E: ParserError, const N: usize> Parser<'a, str, [u8; N], E, HexBytesParser>
+    for HexBytesExact<N>
+{
+    fn fab(&self, input: &mut &'a str) -> Result<[u8; N], E> {
+        let start = *input;
+        let mut out = [0; N];
+        let mut index = 0;
+        let mut extra = None;
+        //One more byte than needed is parsed, so that too many bytes can be detected.
+        let res = hex_pairs(start, self.separated, N.saturating_add(1), |byte, at| {
+            match out.get_mut(index) {
+                Some(slot) => *slot = byte,
+                None => extra = Some(at),
+            }
+            index += 1;
+        });
+        match res {
+            Ok((count, rest)) if count == N => {
+                *input = rest;
+                Ok(out)
+            }
+            Ok((_, rest)) => Err(E::from_external_error(
+                extra.unwrap_or(rest),
+                ParserType::Tag,
+                HexBytesError::Length,
+            )),
+            Err(dangling) => Err(E::from_external_error(
+                dangling,
+                ParserType::Tag,
+                HexBytesError::OddDigits,
+            )),
+        }
+    }
+}
+
+/**
+ * This error is used as the cause when `ipv4_addr` or `ipv6_addr` finds a malformed
+ * address.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum IpAddrError {
+    /**
+     * The address has too many or too few groups. The error is located at the extra
+     * group, or where the next group was expected if there are too few.
+     */
+    GroupCount,
+    /**
+     * The address has more than one `::`. The error is located at the second one.
+     */
+    DoubleColon,
+    /**
+     * A group of an IPv6 address has more than 4 hex digits. The error is located at the
+     * start of the group.
+     */
+    GroupLength,
+    /**
+     * An octet of an IPv4 address has a leading zero, such as `010`, which some parsers
+     * read as octal. The error is located at the start of the octet.
+     */
+    LeadingZero,
+}
+impl Display for IpAddrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IpAddrError::GroupCount => f.write_str("Wrong number of groups in IPv6 address"),
+            IpAddrError::DoubleColon => f.write_str("IPv6 address has more than one '::'"),
+            IpAddrError::GroupLength => {
+                f.write_str("IPv6 address group has more than 4 hex digits")
+            }
+            IpAddrError::LeadingZero => f.write_str("IPv4 address octet has a leading zero"),
+        }
+    }
+}
+
+impl Error for IpAddrError {}
+
+/**
+ * This struct can be constructed through the function `ipv4_addr`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Ipv4AddrParser;
+
+/**
+ * This struct can be constructed through the function `ipv6_addr`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct Ipv6AddrParser;
+
+/**
+ * This struct can be constructed through the function `socket_addr`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct SocketAddrParser;
+
+/**
+ * Parses an IPv4 address in dotted decimal, such as `192.0.2.1`. If an octet is over 255,
+ * this fails with an [`IntOverflowError`] cause, and if an octet other than `0` starts
+ * with a zero, such as `010`, it fails with an [`IpAddrError::LeadingZero`] cause, like
+ * [`Ipv4Addr::from_str`](std::str::FromStr::from_str). It consumes no input on failure.
+ */
+pub fn ipv4_addr() -> Ipv4AddrParser {
+    Ipv4AddrParser
+}
+
+/**
+ * Parses an IPv6 address, such as `2001:db8::1`, `::` or `::ffff:192.0.2.1`. Groups are 1
+ * to 4 hex digits of either case, at most one `::` stands for the missing groups, and the
+ * last 2 groups can be written as an IPv4 address. A zone like `%eth0` isn't part of
+ * [`Ipv6Addr`], so it is left in the input.
+ *
+ * Too many or too few groups fail with an [`IpAddrError::GroupCount`] cause, a second `::`
+ * with an [`IpAddrError::DoubleColon`] cause, and a group of more than 4 digits with an
+ * [`IpAddrError::GroupLength`] cause. It consumes no input on failure.
+ */
+pub fn ipv6_addr() -> Ipv6AddrParser {
+    Ipv6AddrParser
+}
+
+/**
+ * Parses an IP address and port, such as `192.0.2.1:80` or `[::1]:8080`. IPv6 addresses
+ * must be in brackets, since their colons would be ambiguous with the port. The address
+ * is parsed like [`ipv4_addr`] or [`ipv6_addr`], and a port over 65535 fails with an
+ * [`IntOverflowError`] cause. It consumes no input on failure.
+ */
+pub fn socket_addr() -> SocketAddrParser {
+    SocketAddrParser
+}
+
+impl<'a, E: ParserError> Parser<'a, str, Ipv4Addr, E, Ipv4AddrParser> for Ipv4AddrParser {
+    fn fab(&self, input: &mut &'a str) -> Result<Ipv4Addr, E> {
+        let octet = ipv4_octet::<E>;
+        let (a, _, b, _, c, _, d) = (octet, '.', octet, '.', octet, '.', octet).fab(input)?;
+        Ok(Ipv4Addr::new(a, b, c, d))
+    }
+}
+
+/**
+ * Parses an octet of an IPv4 address, rejecting leading zeros.
+ */
+fn ipv4_octet<E: ParserError>(input: &mut &str) -> Result<u8, E> {
+    if input.starts_with('0') && input[1..].starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(E::from_external_error(
+            *input,
+            ParserType::Tag,
+            IpAddrError::LeadingZero,
+        ));
+    }
+    uint::<u8>().fab(input)
+}
+
+impl<'a, E: ParserError> Parser<'a, str, Ipv6Addr, E, Ipv6AddrParser> for Ipv6AddrParser {
+    fn fab(&self, input: &mut &'a str) -> Result<Ipv6Addr, E> {
+        let start = *input;
+        ipv6_groups(input)
+            .map(Ipv6Addr::from)
+            .inspect_err(|_| *input = start)
+    }
+}
+
+/**
+ * Parses the groups of an IPv6 address, filling in the groups that `::` stands for.
+ * The input is left partly consumed on failure.
+ */
+fn ipv6_groups<E: ParserError>(input: &mut &str) -> Result<[u16; 8], E> {
+    let start = *input;
+    let mut groups = Vec::with_capacity(8);
+    //The number of groups before the `::`, if there is one.
+    let mut gap = None;
+    if input.starts_with("::") {
+        gap = Some(0);
+        *input = &input[2..];
+    }
+    loop {
+        //The `::` stands for at least one group.
+        let max = if gap.is_some() { 7 } else { 8 };
+        let digits = input.bytes().take_while(u8::is_ascii_hexdigit).count();
+        if digits == 0 {
+            if groups.is_empty() && gap.is_none() {
+                return Err(E::from_parser_error(start, ParserType::Tag));
+            }
+            break;
+        }
+        //An embedded IPv4 address is the last 2 groups.
+        if input[digits..].starts_with('.') {
+            if groups.len() + 2 > max {
+                return Err(group_count_error(input));
+            }
+            let [a, b, c, d] = ipv4_addr().fab(input)?.octets();
+            groups.extend([u16::from_be_bytes([a, b]), u16::from_be_bytes([c, d])]);
+            break;
+        }
+        if groups.len() == max {
+            return Err(group_count_error(input));
+        }
+        if digits > 4 {
+            return Err(E::from_external_error(
+                *input,
+                ParserType::Tag,
+                IpAddrError::GroupLength,
+            ));
+        }
+        let group_start = *input;
+        groups.push(parse_digits::<_, u16, E>(
+            input,
+            group_start,
+            16,
+            false,
+            false,
+        )?);
+        if input.starts_with("::") {
+            if gap.is_some() {
+                return Err(E::from_external_error(
+                    *input,
+                    ParserType::Tag,
+                    IpAddrError::DoubleColon,
+                ));
+            }
+            gap = Some(groups.len());
+            *input = &input[2..];
+        } else if input.starts_with(':') && input[1..].starts_with(|c: char| c.is_ascii_hexdigit())
+        {
+            *input = &input[1..];
+        } else {
+            break;
+        }
+    }
+    let mut out = [0; 8];
+    match gap {
+        Some(gap) if groups.len() <= 7 => {
+            let tail = &groups[gap..];
+            out[..gap].copy_from_slice(&groups[..gap]);
+            out[8 - tail.len()..].copy_from_slice(tail);
+        }
+        None if groups.len() == 8 => out.copy_from_slice(&groups),
+        _ => return Err(group_count_error(input)),
+    }
+    Ok(out)
+}
+
+/**
+ * Returns an error with an [`IpAddrError::GroupCount`] cause located at input.
+ */
+fn group_count_error<E: ParserError>(input: &str) -> E {
+    E::from_external_error(input, ParserType::Tag, IpAddrError::GroupCount)
+}
+
+impl<'a, E: ParserError> Parser<'a, str, SocketAddr, E, SocketAddrParser> for SocketAddrParser {
+    fn fab(&self, input: &mut &'a str) -> Result<SocketAddr, E> {
+        let ip = alt((
+            ipv4_addr().fab_map(IpAddr::V4),
+            ('[', ipv6_addr(), ']').fab_map(|(_, ip, _): (char, Ipv6Addr, char)| IpAddr::V6(ip)),
+        ));
+        let (ip, _, port) = (ip, ':', uint::<u16>()).fab(input)?;
+        Ok(SocketAddr::new(ip, port))
+    }
+}
+
+/**
+ * A numeric literal recognized by [`rust_number`]. The literal isn't converted, since the
+ * suffix decides its type.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumberLiteral<'a> {
+    /**
+     * The digits after any radix prefix and before any suffix, including `_` separators.
+     * For decimal literals this includes the fraction and exponent.
+     */
+    pub digits: &'a str,
+    /**
+     * The radix given by the prefix, or 10 without one.
+     */
+    pub radix: u32,
+    /**
+     * The type suffix, such as `u8` or `f32`, without the `_` before it.
+     */
+    pub suffix: Option<&'a str>,
+}
+
+const INT_SUFFIXES: [&str; 12] = [
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+const FLOAT_SUFFIXES: [&str; 2] = ["f32", "f64"];
+
+/**
+ * Returns the length of the digits in the radix at the start of text. Single underscores
+ * are accepted between digits, but not before the first or after the last.
+ */
+fn separated_digits(text: &str, radix: u32) -> usize {
+    let bytes = text.as_bytes();
+    let is_digit = |index: usize| {
+        bytes
+            .get(index)
+            .is_some_and(|byte| (*byte as char).is_digit(radix))
+    };
+    let mut len = 0;
+    while is_digit(len) {
+        len += 1;
+        if bytes.get(len) == Some(&b'_') && is_digit(len + 1) {
+            len += 1;
+        }
+    }
+    len
+}
+
+/**
+ * Returns the length of the fraction and exponent of a decimal literal at the start of
+ * text, or None if there is an exponent marker without digits.
+ */
+fn fraction_exponent(text: &str) -> Option<usize> {
+    let mut len = 0;
+    if let Some(fraction) = text.strip_prefix('.') {
+        let digits = separated_digits(fraction, 10);
+        if digits > 0 {
+            len += 1 + digits;
+        }
+    }
+    if let Some(exponent) = text[len..].strip_prefix(['e', 'E']) {
+        let sign = usize::from(exponent.starts_with(['+', '-']));
+        let digits = separated_digits(&exponent[sign..], 10);
+        if digits == 0 {
+            return None;
+        }
+        len += 1 + sign + digits;
+    }
+    Some(len)
+}
+
+/**
+ * Parses a Rust style numeric literal, such as `1_000_000`, `0xFF_u8`, `2.5e-3` or
+ * `3.14_f32`. Decimal, `0x`, `0o` and `0b` literals are accepted, with single `_`
+ * separators between digits. Decimal literals can have a fraction and an exponent.
+ *
+ * The literal can end in a type suffix such as `u8`, `i64` or `f32`, optionally after a
+ * `_`. Float suffixes are only recognized on decimal literals, since `f` is a hex digit.
+ * Leading, trailing or doubled separators, as in `_1`, `1_` or `1__2`, fail and consume
+ * no input. A `.` without digits after it isn't part of the literal, so `1.max` matches `1`.
+ */
+pub fn rust_number<'a, E: ParserError>(input: &mut &'a str) -> Result<NumberLiteral<'a>, E> {
+    let start = *input;
+    let (radix, body) = match start.as_bytes() {
+        [b'0', b'x' | b'X', ..] => (16, &start[2..]),
+        [b'0', b'o' | b'O', ..] => (8, &start[2..]),
+        [b'0', b'b' | b'B', ..] => (2, &start[2..]),
+        _ => (10, start),
+    };
+    let fail = || E::from_parser_error(start, ParserType::Tag);
+    let mut len = separated_digits(body, radix);
+    if len == 0 {
+        return Err(fail());
+    }
+    if radix == 10 {
+        len += fraction_exponent(&body[len..]).ok_or_else(fail)?;
+    }
+    let (digits, rest) = body.split_at(len);
+    let separator = usize::from(rest.starts_with('_'));
+    let float_suffixes: &[&str] = if radix == 10 { &FLOAT_SUFFIXES } else { &[] };
+    let is_suffix = |suffix: &&&str| {
+        rest[separator..]
+            .strip_prefix(**suffix)
+            .is_some_and(|after| {
+                !after.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+            })
+    };
+    let suffix = INT_SUFFIXES.iter().chain(float_suffixes).find(is_suffix);
+    let rest = match suffix {
+        Some(suffix) => &rest[separator + suffix.len()..],
+        //A separator must be followed by digits or a suffix.
+        None if separator == 1 => return Err(fail()),
+        None => rest,
+    };
+    *input = rest;
+    Ok(NumberLiteral {
+        digits,
+        radix,
+        suffix: suffix.copied(),
+    })
+}
+
+/**
+ * This error is used as the cause when a quoted string is invalid.
+ */
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum QuotedStringError {
+    /**
+     * The string has no closing quote. The error is located at the opening quote.
+     */
+    Unterminated,
+    /**
+     * The escape isn't one of the supported escapes. The error is located at the `\`.
+     */
+    InvalidEscape,
+    /**
+     * A `\u{...}` escape doesn't contain 1 to 6 hex digits of a Unicode scalar value.
+     * The error is located at the `\`.
+     */
+    InvalidUnicode,
+}
+impl Display for QuotedStringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuotedStringError::Unterminated => f.write_str("Quoted string has no closing quote"),
+            QuotedStringError::InvalidEscape => f.write_str("Invalid escape in quoted string"),
+            QuotedStringError::InvalidUnicode => {
+                f.write_str("Invalid unicode escape in quoted string")
+            }
+        }
+    }
+}
+
+impl Error for QuotedStringError {}
+
+/**
+ * This struct can be constructed through the function `quoted`.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct QuotedString {
+    pub quote: char,
+}
+
+/**
+ * Parses a double quoted string, like [`quoted`] with `'"'`.
+ */
+pub fn quoted_string<'a, E: ParserError>(input: &mut &'a str) -> Result<Cow<'a, str>, E> {
+    quoted('"').fab(input)
+}
+
+/**
+ * Parses a string surrounded by the quote char, and returns its contents with escapes
+ * decoded. The contents are borrowed from the input unless they contain escapes.
+ *
+ * The escapes are `\n`, `\r`, `\t`, `\0`, `\\`, `\"`, `\'` and `\u{...}`
+ * with 1 to 6 hex digits. On failure the cause is a [`QuotedStringError`] and no input
+ * is consumed.
+ */
+pub fn quoted(quote: char) -> QuotedString {
+    QuotedString { quote }
+}
+
+/**
+ * Decodes the escape at the start of escape, which starts after the `\`.
+ * Returns the char and the length of the escape.
+ */
+fn decode_escape(escape: &str) -> Result<(char, usize), QuotedStringError> {
+    let kind = escape
+        .chars()
+        .next()
+        .ok_or(QuotedStringError::InvalidEscape)?;
+    let decoded = match kind {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        '0' => '\0',
+        '\\' | '"' | '\'' => kind,
+        'u' => {
+            let digits = escape
+                .strip_prefix("u{")
+                .and_then(|rest| rest.split_once('}'))
+                .map(|(digits, _)| digits)
+                .filter(|digits| (1..=6).contains(&digits.len()))
+                .ok_or(QuotedStringError::InvalidUnicode)?;
+            let decoded = u32::from_str_radix(digits, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or(QuotedStringError::InvalidUnicode)?;
+            return Ok((decoded, digits.len() + 3));
+        }
+        _ => return Err(QuotedStringError::InvalidEscape),
+    };
+    Ok((decoded, kind.len_utf8()))
+}
+
+pub struct QuotedStringParser;
+
+impl<'a, E: ParserError> Parser<'a, str, Cow<'a, str>, E, QuotedStringParser> for QuotedString {
+    fn fab(&self, input: &mut &'a str) -> Result<Cow<'a, str>, E> {
+        let start = *input;
+        let Some(body) = start.strip_prefix(self.quote) else {
+            return Err(E::from_parser_error(start, ParserType::Tag));
+        };
+        let mut decoded: Option<String> = None;
+        let mut segment_start = 0;
+        let mut index = 0;
+        while let Some(c) = body[index..].chars().next() {
+            if c == self.quote {
+                let segment = &body[segment_start..index];
+                *input = &body[index + c.len_utf8()..];
+                return Ok(match decoded {
+                    Some(mut decoded) => {
+                        decoded.push_str(segment);
+                        Cow::Owned(decoded)
+                    }
+                    None => Cow::Borrowed(segment),
+                });
+            }
+            if c == '\\' {
+                let (escaped, len) = decode_escape(&body[index + 1..])
+                    .map_err(|err| E::from_external_error(&body[index..], ParserType::Tag, err))?;
+                let decoded = decoded.get_or_insert_with(String::new);
+                decoded.push_str(&body[segment_start..index]);
+                decoded.push(escaped);
+                index += 1 + len;
+                segment_start = index;
+            } else {
+                index += c.len_utf8();
+            }
+        }
+        Err(E::from_external_error(
+            start,
+            ParserType::Tag,
+            QuotedStringError::Unterminated,
+        ))
+    }
+}
+
+/**
+ * The associativity of an infix operator, which decides how a chain of operators with
+ * the same binding power is grouped. With `Left`, `1-2-3` is `(1-2)-3`, and with
+ * `Right`, `2^3^2` is `2^(3^2)`.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
+}
+
+/**
+ * An infix operator for [`precedence`]. Operators with a higher binding power bind
+ * tighter. The fold combines the operands on either side of the operator.
+ */
+#[derive(Clone, Debug)]
+pub struct Infix<P, O> {
+    pub op: P,
+    pub power: u32,
+    pub assoc: Assoc,
+    pub fold: fn(O, O) -> O,
+}
+
+impl<P, O> Infix<P, O> {
+    /**
+     * Constructs a left associative infix operator.
+     */
+    pub fn left(op: P, power: u32, fold: fn(O, O) -> O) -> Self {
+        Infix {
+            op,
+            power,
+            assoc: Assoc::Left,
+            fold,
+        }
+    }
+    /**
+     * Constructs a right associative infix operator.
+     */
+    pub fn right(op: P, power: u32, fold: fn(O, O) -> O) -> Self {
+        Infix {
+            op,
+            power,
+            assoc: Assoc::Right,
+            fold,
+        }
+    }
+}
+
+/**
+ * A prefix operator for [`precedence`], such as negation. Its operand only includes
+ * infix and postfix operators that bind at least as tightly as it does.
+ */
+#[derive(Clone, Debug)]
+pub struct Prefix<P, O> {
+    pub op: P,
+    pub power: u32,
+    pub fold: fn(O) -> O,
+}
+
+impl<P, O> Prefix<P, O> {
+    pub fn new(op: P, power: u32, fold: fn(O) -> O) -> Self {
+        Prefix { op, power, fold }
+    }
+}
+
+/**
+ * A postfix operator for [`precedence`], such as factorial. It applies to the
+ * expression before it when it binds at least as tightly as the operator before that.
+ */
+#[derive(Clone, Debug)]
+pub struct Postfix<P, O> {
+    pub op: P,
+    pub power: u32,
+    pub fold: fn(O) -> O,
+}
+
+impl<P, O> Postfix<P, O> {
+    pub fn new(op: P, power: u32, fold: fn(O) -> O) -> Self {
+        Postfix { op, power, fold }
+    }
+}
+
+/**
+ * This struct can be constructed through the function `precedence`. Prefix and
+ * postfix operators can be added with the methods `prefix` and `postfix`.
+ */
+#[derive(Clone, Debug)]
+pub struct Precedence<A, P, O> {
+    pub atom: A,
+    pub infix: Vec<Infix<P, O>>,
+    pub prefix: Vec<Prefix<P, O>>,
+    pub postfix: Vec<Postfix<P, O>>,
+}
+
+/**
+ * Parses expressions of atoms joined by infix operators, using precedence climbing.
+ * The output is built with the fold functions of the operators, so it can be an AST
+ * or the evaluated value.
+ *
+ * The operators are tried in order and the first one that matches is used, so an
+ * operator that is a prefix of another, like `*` and `**`, should come after it.
+ * If an operator matches but its operand fails to parse, the whole expression fails
+ * with a frame at the operator.
+ */
+pub fn precedence<A, P, O>(atom: A, infix: Vec<Infix<P, O>>) -> Precedence<A, P, O> {
+    Precedence {
+        atom,
+        infix,
+        prefix: Vec::new(),
+        postfix: Vec::new(),
+    }
+}
+
+impl<A, P, O> Precedence<A, P, O> {
+    /**
+     * Sets the prefix operators.
+     */
+    pub fn prefix(self, prefix: Vec<Prefix<P, O>>) -> Self {
+        Precedence { prefix, ..self }
+    }
+    /**
+     * Sets the postfix operators.
+     */
+    pub fn postfix(self, postfix: Vec<Postfix<P, O>>) -> Self {
+        Precedence { postfix, ..self }
+    }
+    /**
+     * Parses an expression whose operators all have a binding power of at least min_power.
+     */
+    fn expr<'a, I, E, AType, PType, PO>(&self, input: &mut &'a I, min_power: u32) -> Result<O, E>
+    where
+        I: ?Sized + Sequence,
+        E: ParserError,
+        A: Parser<'a, I, O, E, AType>,
+        P: Parser<'a, I, PO, E, PType>,
+    {
+        let mut lhs = self.operand(input)?;
+        loop {
+            let op_start = *input;
+            if let Some(postfix) = find_op(&self.postfix, |postfix| &postfix.op, input)? {
+                if postfix.power < min_power {
+                    *input = op_start;
+                    return Ok(lhs);
+                }
+                lhs = (postfix.fold)(lhs);
+                continue;
+            }
+            let Some(infix) = find_op(&self.infix, |infix| &infix.op, input)? else {
+                return Ok(lhs);
+            };
+            if infix.power < min_power {
+                *input = op_start;
+                return Ok(lhs);
+            }
+            let rhs_power = match infix.assoc {
+                Assoc::Left => infix.power.saturating_add(1),
+                Assoc::Right => infix.power,
+            };
+            let rhs = self.expr(input, rhs_power).map_err(|mut err| {
+                err.add_context(op_start, ParserType::Precedence);
+                err
+            })?;
+            lhs = (infix.fold)(lhs, rhs);
+        }
+    }
+    /**
+     * Parses an atom, or a prefix operator and its operand.
+     */
+    fn operand<'a, I, E, AType, PType, PO>(&self, input: &mut &'a I) -> Result<O, E>
+    where
+        I: ?Sized + Sequence,
+        E: ParserError,
+        A: Parser<'a, I, O, E, AType>,
+        P: Parser<'a, I, PO, E, PType>,
+    {
+        let op_start = *input;
+        match find_op(&self.prefix, |prefix| &prefix.op, input)? {
+            Some(prefix) => {
+                let operand = self.expr(input, prefix.power).map_err(|mut err| {
+                    err.add_context(op_start, ParserType::Precedence);
+                    err
+                })?;
+                Ok((prefix.fold)(operand))
+            }
+            None => self.atom.fab(input),
+        }
+    }
+}
+
+/**
+ * Returns the first operator that matches the input, consuming it.
+ */
+fn find_op<'a, 't, T, I, PO, E, P, PType>(
+    ops: &'t [T],
+    get_op: impl Fn(&T) -> &P,
+    input: &mut &'a I,
+) -> Result<Option<&'t T>, E>
+where
+    I: ?Sized,
+    E: ParserError,
+    P: Parser<'a, I, PO, E, PType>,
+{
+    let checkpoint = *input;
+    for op in ops {
+        match get_op(op).fab(input) {
+            Ok(_) => return Ok(Some(op)),
+            //More input might complete the operator, so it isn't known which one matches.
+            Err(err) if err.is_incomplete() => {
+                *input = checkpoint;
+                return Err(err);
+            }
+            Err(_) => *input = checkpoint,
+        }
+    }
+    Ok(None)
+}
+
+pub struct PrecedenceParser<AType, PType, PO> {
+    phantom_atype: PhantomData<AType>,
+    phantom_ptype: PhantomData<PType>,
+    phantom_po: PhantomData<PO>,
+}
+
+impl<'a, I, O, E, A, AType, P, PType, PO> Parser<'a, I, O, E, PrecedenceParser<AType, PType, PO>>
+    for Precedence<A, P, O>
+where
+    I: ?Sized + Sequence,
+    E: ParserError,
+    A: Parser<'a, I, O, E, AType>,
+    P: Parser<'a, I, PO, E, PType>,
+{
+    fn fab(&self, input: &mut &'a I) -> Result<O, E> {
+        let start = *input;
+        self.expr(input, 0).map_err(|mut err| {
+            *input = start;
+            err.add_context(start, ParserType::Precedence);
+            err
+        })
+    }
+}
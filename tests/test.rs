@@ -1,6 +1,42 @@
-use std::{collections::HashMap, error::Error, fmt, str::FromStr};
+use std::{
+    borrow::Cow,
+    cell::{Cell, OnceCell},
+    collections::{BTreeMap, HashMap, HashSet},
+    error::Error,
+    fmt,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    ops::ControlFlow,
+    rc::Rc,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
 
-use fabparse::{opt, take, take_not, FabError, Parser};
+use fabparse::{
+    alt, alt_either, alt_longest, alt_verbose, ascii_no_case,
+    binary::{self, masked_tag},
+    depth_guard, dispatch, escaped, escaped_transform, extract, fail, fill, find, from_fn_mut,
+    inner, item_ref, lazy, length_repeat, length_take, literals, opt, permutation, permutation_opt,
+    recover, repeat_till, separated, separated_pair,
+    sequence::Sequence,
+    spanned, success, take, take_not, take_while,
+    util::{
+        self, balanced, bin_uint, block_comment, bool_lit, float, float_str, hex_bytes,
+        hex_bytes_exact, hex_uint, int, ipv4_addr, ipv6_addr, keyword, line_comment, line_ending,
+        multispace0, multispace1, not_line_ending, num_signed_str, num_unsigned_str, oct_uint,
+        precedence, prefixed_int, quoted, quoted_string, rest_of_line, rust_number,
+        skip_ws_and_comments, socket_addr, space0, space1, uint, unicode_identifier, ws,
+        HexBytesError, Infix, IntOverflowError, IntParser, IpAddrError, NumberLiteral, Postfix,
+        Prefix, QuotedStringError, RadixUInt,
+    },
+    with_extras, with_location, Alt, ArrayLenError, BoxedParser, ConvertError, DepthLimitError,
+    Either, Expected, FabError, FillBorrowedError, Found, InputWrapper, Located, NoContextFabError,
+    OffsetFabError, Opt, Parser, ParserError, ParserMap, ParserType, Repeat, ReplayError,
+    SliceLike, State, Stateful, StrBytes, Streaming, Take, TraceError, TryReducerError, Value,
+};
 #[test]
 fn char_tag_parser_success() {
     let mut input = "abc";
@@ -185,6 +221,80 @@ fn fnresult_str_parser_error() {
     assert_eq!("cde", input);
 }
 
+static COUNTED_CLONES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, PartialEq, PartialOrd)]
+struct Counted(u32);
+
+impl Clone for Counted {
+    fn clone(&self) -> Self {
+        COUNTED_CLONES.fetch_add(1, Ordering::SeqCst);
+        Counted(self.0)
+    }
+}
+
+#[test]
+fn slice_ref_parsers_clone_on_success() {
+    let items = [Counted(1), Counted(2)];
+    let mut input = items.as_slice();
+    let res: Result<_, FabError> = Counted(9).fab(&mut input);
+    assert!(res.is_err());
+    let res: Result<_, FabError> = (|c: &Counted| c.0 == 2).fab(&mut input);
+    assert!(res.is_err());
+    let res: Result<_, FabError> = (Counted(0)..Counted(1)).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!(0, COUNTED_CLONES.load(Ordering::SeqCst));
+
+    let res: Result<_, FabError> = (|c: &Counted| c.0 == 1).fab(&mut input);
+    assert_eq!(Counted(1), res.unwrap());
+    assert_eq!(1, COUNTED_CLONES.load(Ordering::SeqCst));
+    let res: Result<_, FabError> = (|c: &Counted| (c.0 == 2).then_some(c.0)).fab(&mut input);
+    assert_eq!(2, res.unwrap());
+    assert_eq!(1, COUNTED_CLONES.load(Ordering::SeqCst));
+    assert!(input.is_empty());
+}
+
+#[test]
+fn item_ref_tokens() {
+    let tokens: Vec<String> = ["let", "x", "=", "1"].map(String::from).to_vec();
+    let mut input = tokens.as_slice();
+    let res: Result<_, FabError> = item_ref(String::from("let")).fab(&mut input);
+    assert!(std::ptr::eq(&tokens[0], res.unwrap()));
+
+    let ident = item_ref(|t: &String| t.chars().all(char::is_alphabetic));
+    let res: Result<_, FabError> = (ident, item_ref(String::from("="))).fab(&mut input);
+    let (name, _) = res.unwrap();
+    assert_eq!("x", name);
+    assert_eq!(["1"].as_slice(), input);
+
+    let res: Result<_, FabError> = ident.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!(["1"].as_slice(), input);
+    let mut input = [].as_slice();
+    let res: Result<&String, Streaming<FabError>> = ident.fab(&mut input);
+    assert!(res.unwrap_err().is_incomplete());
+
+    let mut input = tokens.as_slice();
+    let res: Result<_, FabError> = item_ref(|t: &String| t != "=").fab_repeat().fab(&mut input);
+    assert_eq!(vec!["let", "x"], res.unwrap());
+    assert_eq!(["=", "1"].as_slice(), input);
+}
+
+#[test]
+fn str_item_parsers_unchanged() {
+    let mut input = "b1x";
+    let res: Result<_, FabError> = ('a'..='c', char::is_ascii_digit, 'x').fab(&mut input);
+    assert_eq!(('b', '1', 'x'), res.unwrap());
+    assert_eq!("", input);
+    let mut input = "z";
+    let res: Result<_, FabError> = ('a'..='c').fab(&mut input);
+    assert_eq!(
+        Some(&Expected::Range("'a'..='c'".to_string())),
+        res.unwrap_err().expected()
+    );
+    assert_eq!("z", input);
+}
+
 #[test]
 fn strtag_unicode_success() {
     let mut input = "😀🇷🇺";
@@ -229,6 +339,76 @@ fn take_map_fail() {
     assert_eq!("abc", input);
 }
 
+#[derive(Clone, Debug, PartialEq)]
+enum TokenKind {
+    Keyword,
+    Identifier,
+    Empty,
+}
+
+#[derive(Debug, PartialEq)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+}
+
+fn token<'a>(input: &mut &'a str) -> Result<Token<'a>, FabError> {
+    alt((
+        alt((keyword("let"), keyword("fn"))).fab_value(TokenKind::Keyword),
+        util::identifier.fab_value(TokenKind::Identifier),
+        "".fab_value(TokenKind::Empty),
+    ))
+    .fab_map_with_span(|kind, text| Token { kind, text })
+    .fab(input)
+}
+
+#[test]
+fn map_with_span_tokens() {
+    let mut input = "let x1 = 5";
+    let res: Result<_, FabError> = (token, ' ', token).fab(&mut input);
+    let (first, _, second) = res.unwrap();
+    assert_eq!(
+        Token {
+            kind: TokenKind::Keyword,
+            text: "let"
+        },
+        first
+    );
+    assert_eq!(
+        Token {
+            kind: TokenKind::Identifier,
+            text: "x1"
+        },
+        second
+    );
+    assert_eq!(" = 5", input);
+    let res = token(&mut input);
+    assert_eq!(
+        Token {
+            kind: TokenKind::Empty,
+            text: ""
+        },
+        res.unwrap()
+    );
+    assert_eq!(" = 5", input);
+}
+
+#[test]
+fn map_with_span_fail() {
+    let mut input = "abc";
+    let res: Result<_, FabError> = ('a', 'c')
+        .fab_map_with_span(|_, text: &str| text.len())
+        .fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!("abc", input);
+    assert_eq!(Some(1), err.offset("abc"));
+    let mut input: &[u8] = &[1, 2, 3];
+    let res: Result<_, FabError> = ([1, 2].as_slice(), 3u8)
+        .fab_map_with_span(|_, bytes: &[u8]| bytes.len())
+        .fab(&mut input);
+    assert_eq!(3, res.unwrap());
+}
+
 #[derive(Debug)]
 struct TestError;
 
@@ -295,6 +475,60 @@ fn try_map_result_fail() {
     assert_eq!("abc", input);
 }
 
+fn parse_port(digits: &str) -> Result<u16, Box<dyn Error + Send + Sync>> {
+    let port = u16::from_str(digits)?;
+    if port == 0 {
+        return Err("port 0 is reserved".into());
+    }
+    Ok(port)
+}
+
+#[test]
+fn try_map_boxed_error() {
+    let digits = || take_while(|c: char| c.is_ascii_digit()).min(1);
+    let mut input = "8080";
+    let res: Result<_, FabError> = digits().fab_try_map(parse_port).fab(&mut input);
+    assert_eq!(8080, res.unwrap());
+    let text = "99999:";
+    let mut input = text;
+    let res: Result<_, FabError> = digits().fab_try_map(parse_port).fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(text, input);
+    assert!(err.downcast_cause::<std::num::ParseIntError>().is_some());
+    assert!(err
+        .trace_string(text)
+        .contains("From cause [number too large"));
+    let mut input = "0";
+    let res: Result<_, NoContextFabError> = digits().fab_try_map(parse_port).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("0", input);
+}
+
+#[test]
+fn try_map_anyhow_context() {
+    use anyhow::Context;
+    let text = "port=99999";
+    let mut input = text;
+    let res: Result<_, FabError> = (
+        "port=",
+        take_while(|c: char| c.is_ascii_digit()).fab_try_map(|digits: &str| {
+            u16::from_str(digits).with_context(|| format!("invalid port {digits}"))
+        }),
+    )
+        .fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(text, input);
+    err.print_trace(text);
+    let trace = err.trace_string(text);
+    assert!(trace.contains("From cause [invalid port 99999]\n"));
+    assert!(trace.contains("Caused by [number too large to fit in target type]\n"));
+    let cause = err.cause().unwrap();
+    assert_eq!(
+        "number too large to fit in target type",
+        cause.source().unwrap().to_string()
+    );
+}
+
 #[test]
 fn try_map_option_result_fail() {
     let mut input = "abc";
@@ -337,6 +571,103 @@ fn opt_success() {
     assert_eq!("abc", input);
 }
 
+/**
+ * Matches 'x', but consumes the first char even when it fails. Parsers shouldn't leave
+ * the input advanced on failure, so this checks that the combinators don't rely on it.
+ */
+struct Leaky;
+
+impl<'a, E: ParserError> Parser<'a, str, char, E, Leaky> for Leaky {
+    fn fab(&self, input: &mut &'a str) -> Result<char, E> {
+        let mut chars = input.chars();
+        let first = chars.next();
+        *input = chars.as_str();
+        match first {
+            Some('x') => Ok('x'),
+            _ => Err(E::from_parser_error(*input, ParserType::Custom("leaky"))),
+        }
+    }
+}
+
+#[test]
+fn rollback_opt() {
+    let mut input = "ab";
+    let res: Result<_, FabError> = opt(Leaky).fab(&mut input);
+    assert_eq!(None, res.unwrap());
+    assert_eq!("ab", input);
+    let res: Result<_, FabError> = Leaky.fab_opt().fab(&mut input);
+    assert_eq!(None, res.unwrap());
+    assert_eq!("ab", input);
+    let res: Result<_, FabError> = Leaky.fab_or('-').fab(&mut input);
+    assert_eq!('-', res.unwrap());
+    assert_eq!("ab", input);
+    let res: Result<_, FabError> = Leaky.fab_or_else(|| '-').fab(&mut input);
+    assert_eq!('-', res.unwrap());
+    assert_eq!("ab", input);
+    let res: Result<_, FabError> = (opt(Leaky), "ab").fab(&mut input);
+    assert_eq!((None, "ab"), res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn rollback_alt() {
+    let mut input = "abc";
+    let res: Result<_, FabError> = alt((Leaky.fab_value("x"), "ab")).fab(&mut input);
+    assert_eq!("ab", res.unwrap());
+    assert_eq!("c", input);
+    let res: Result<_, FabError> = alt((Leaky, 'd')).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("c", input);
+}
+
+#[test]
+fn rollback_permutation() {
+    let mut input = "abx!";
+    let res: Result<_, FabError> = permutation((Leaky, "ab")).fab(&mut input);
+    assert_eq!(('x', "ab"), res.unwrap());
+    assert_eq!("!", input);
+    let mut input = "ab!";
+    let res: Result<_, FabError> = permutation_opt((Leaky, "ab")).fab(&mut input);
+    assert_eq!((None, Some("ab")), res.unwrap());
+    assert_eq!("!", input);
+}
+
+#[test]
+fn rollback_repeat() {
+    let mut input = "xxa!";
+    let res: Result<Vec<char>, FabError> = Leaky.fab_repeat().fab(&mut input);
+    assert_eq!(vec!['x', 'x'], res.unwrap());
+    assert_eq!("a!", input);
+    let mut input = "xx;";
+    let res: Result<(Vec<char>, char), FabError> = repeat_till(Leaky, ';').fab(&mut input);
+    assert_eq!((vec!['x', 'x'], ';'), res.unwrap());
+    assert_eq!("", input);
+    let mut input = "xxa";
+    let mut iter = Leaky.fab_iter(&mut input);
+    assert_eq!(vec!['x', 'x'], iter.by_ref().collect::<Vec<_>>());
+    let res: Result<_, FabError> = iter.finish();
+    assert!(res.is_err());
+    assert_eq!("a", input);
+}
+
+#[test]
+fn assert_restores_ok() {
+    let mut input = "ac";
+    let res: Result<_, FabError> = ("a", "b").fab_assert_restores().fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("ac", input);
+    let res: Result<_, FabError> = opt(Leaky).fab_assert_restores().fab(&mut input);
+    assert_eq!(None, res.unwrap());
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "failed without restoring the input")]
+fn assert_restores_leaky() {
+    let mut input = "ab";
+    let _: Result<_, FabError> = Leaky.fab_assert_restores().fab(&mut input);
+}
+
 #[test]
 fn tuple_success() {
     let mut input = "abc";
@@ -431,12 +762,18 @@ fn repeat_min_fail() {
     let res: Result<_, FabError> = 'a'.fab_repeat().min(3).fab(&mut input);
     assert!(res.is_err());
     assert_eq!("aac", input);
+    let err = res.unwrap_err();
+    assert_eq!(
+        "Location [\"\"]^[\"aac\"] from parser Repeat\n\
+         Location [\"aa\"]^[\"c\"] from parser Tag: expected 'a', found 'c'\n",
+        err.trace_string(input)
+    );
 }
 
 #[test]
 fn repeat_max_success() {
     let mut input = "aac";
-    let res: Result<_, FabError> = 'a'.fab_repeat().max(3).fab(&mut input);
+    let res: Result<_, FabError> = 'a'.fab_repeat().max(2).fab(&mut input);
     assert_eq!(vec!['a', 'a'], res.unwrap());
     assert_eq!("c", input);
 }
@@ -444,12 +781,12 @@ fn repeat_max_success() {
 #[test]
 fn repeat_max() {
     let mut input = "aac";
-    let res: Result<_, FabError> = 'a'.fab_repeat().max(2).fab(&mut input);
+    let res: Result<_, FabError> = 'a'.fab_repeat().max(1).fab(&mut input);
     assert_eq!(vec!['a'], res.unwrap());
     assert_eq!("ac", input);
 }
 
-fn char_num<'a>(input: &mut &'a str) -> Result<(char, u32), FabError> {
+fn char_num(input: &mut &str) -> Result<(char, u32), FabError> {
     ('a'..='z', ('0'..='9').fab_try_map(|c: char| c.to_digit(10))).fab(input)
 }
 
@@ -521,7 +858,19 @@ fn test_error_trace() {
         .fab(&mut input);
     assert!(res.is_err());
     assert_eq!("a1b2c3", input);
-    res.unwrap_err().print_trace(input);
+    let err = res.unwrap_err();
+    err.print_trace(input);
+    let trace = err.trace_string(input);
+    assert_eq!(
+        "Location [\"\"]^[\"a1b2c3\"] from parser Repeat\n\
+         Location [\"a1b2\"]^[\"c3\"] from parser RepeatIter\n\
+         From cause [TryReducerFailed]\n",
+        trace
+    );
+    assert_eq!(trace, format!("{}", err.display_with_input(input)));
+    let mut written = String::new();
+    err.write_trace(input, &mut written).unwrap();
+    assert_eq!(trace, written);
 }
 
 #[test]
@@ -531,3 +880,4988 @@ fn repeat_as_input_slice() {
     assert_eq!("aa", res.unwrap());
     assert_eq!("c", input);
 }
+
+fn log_timestamp<'a>(input: &mut &'a str) -> Result<&'a str, FabError> {
+    let orig_input = *input;
+    (
+        take(4),
+        '-',
+        take(2),
+        '-',
+        take(2),
+        'T',
+        take(2),
+        ':',
+        take(2),
+        ':',
+        take(2),
+    )
+        .fab(input)?;
+    Ok(orig_input.subtract(*input))
+}
+
+fn log_level<'a>(input: &mut &'a str) -> Result<&'a str, FabError> {
+    ("level=", alt(("INFO", "WARN", "ERROR")))
+        .fab_map(|(_, level)| level)
+        .fab(input)
+}
+
+fn log_duration(input: &mut &str) -> Result<u32, FabError> {
+    (num_unsigned_str, "ms")
+        .fab_try_map(|(digits, _): (&str, &str)| digits.parse::<u32>().ok())
+        .fab(input)
+}
+
+#[test]
+fn find_success() {
+    let mut input = "xxabyy";
+    let res: Result<_, FabError> = find("ab").fab(&mut input);
+    assert_eq!("ab", res.unwrap());
+    assert_eq!("yy", input);
+}
+
+#[test]
+fn find_max_skip_fail() {
+    let mut input = "xxxab";
+    let res: Result<_, FabError> = find("ab").max_skip(2).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("xxxab", input);
+}
+
+#[test]
+fn extract_log_line_success() {
+    let mut input = "[2024-01-05T10:00:00] worker-3 level=WARN: request took 35ms (retrying)";
+    let res = extract((log_timestamp, log_level, log_duration)).fab(&mut input);
+    assert_eq!(("2024-01-05T10:00:00", "WARN", 35), res.unwrap());
+    assert_eq!(" (retrying)", input);
+}
+
+#[test]
+fn extract_log_line_messy_success() {
+    let mut input = "ts=2023-12-31T23:59:59 pid=4 ;; level=ERROR ;; elapsed=1200ms";
+    let res = extract((log_timestamp, log_level, log_duration)).fab(&mut input);
+    assert_eq!(("2023-12-31T23:59:59", "ERROR", 1200), res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn extract_missing_capture_fail() {
+    let mut input = "[2024-01-05T10:00:00] worker-3 level=INFO: no duration here";
+    let res = extract((log_timestamp, log_level, log_duration)).fab(&mut input);
+    let err = res.unwrap_err();
+    assert!(err.to_string().contains("ExtractError { capture: 2 }"));
+    assert_eq!(
+        "[2024-01-05T10:00:00] worker-3 level=INFO: no duration here",
+        input
+    );
+}
+
+#[test]
+fn extract_max_skip_fail() {
+    let mut input = "2024-01-05T10:00:00 ......... level=INFO";
+    let res = extract((log_timestamp, log_level))
+        .max_skip(5)
+        .fab(&mut input);
+    let err = res.unwrap_err();
+    assert!(err.to_string().contains("ExtractError { capture: 1 }"));
+    assert_eq!("2024-01-05T10:00:00 ......... level=INFO", input);
+}
+
+fn no_context_digit(input: &mut &str) -> Result<char, NoContextFabError> {
+    ('0'..='9').fab(input)
+}
+
+#[test]
+fn map_err_success() {
+    let mut input = "1a";
+    let res: Result<_, FabError> = no_context_digit
+        .fab_map_err(FabError::convert_error)
+        .fab(&mut input);
+    assert_eq!('1', res.unwrap());
+    assert_eq!("a", input);
+}
+
+#[test]
+fn convert_err_in_sequence_success() {
+    let mut input = "1a2";
+    let res: Result<_, FabError> = (
+        no_context_digit.fab_convert_err::<FabError>(),
+        'a',
+        no_context_digit.fab_convert_err(),
+    )
+        .fab(&mut input);
+    assert_eq!(('1', 'a', '2'), res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn convert_err_in_sequence_fail() {
+    let mut input = "1ab";
+    let res: Result<_, FabError> = (
+        no_context_digit.fab_convert_err::<FabError>(),
+        'a',
+        no_context_digit.fab_convert_err(),
+    )
+        .fab(&mut input);
+    let err = res.unwrap_err();
+    assert!(err.get_loc().is_some());
+    assert_eq!("1ab", input);
+}
+
+fn fab_error_a(input: &mut &str) -> Result<char, FabError> {
+    'a'.fab(input)
+}
+
+#[test]
+fn convert_err_to_no_context() {
+    let mut input = "b";
+    let res: Result<_, NoContextFabError> = fab_error_a
+        .fab_convert_err::<NoContextFabError>()
+        .fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("b", input);
+}
+
+#[test]
+fn question_mark_infers_no_context() {
+    fn letters(input: &mut &str) -> Result<(char, char), NoContextFabError> {
+        let first = 'a'.fab(input)?;
+        let second = fab_error_a
+            .fab_convert_err::<NoContextFabError>()
+            .fab(input)?;
+        Ok((first, second))
+    }
+    let mut input = "aac";
+    assert_eq!(('a', 'a'), letters(&mut input).unwrap());
+    assert_eq!("c", input);
+    let mut input = "b";
+    assert!(letters(&mut input).is_err());
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum JsonValue {
+    Num(u64),
+    Bool(bool),
+    Str(String),
+}
+
+#[test]
+fn replay_json_object_success() {
+    let value = alt((
+        char::is_ascii_digit
+            .fab_repeat()
+            .min(1)
+            .as_input_slice()
+            .fab_try_map(|digits: &str| digits.parse().map(JsonValue::Num)),
+        "true".fab_value(JsonValue::Bool(true)),
+        "false".fab_value(JsonValue::Bool(false)),
+        ('"', (|c: char| c != '"').fab_repeat().as_input_slice(), '"')
+            .fab_map(|(_, s, _): (char, &str, char)| JsonValue::Str(s.to_string())),
+    ));
+    let entry = (
+        '"',
+        char::is_alphabetic.fab_repeat().as_input_slice(),
+        '"',
+        ':',
+        value,
+        alt((','.fab_value(()), "".fab_value(()))),
+    )
+        .fab_map(
+            |(_, key, _, _, value, _): (char, &str, char, char, JsonValue, ())| {
+                (key.to_string(), value)
+            },
+        );
+    let parser = fabparse::recorded(('{', entry.fab_repeat(), '}'));
+    let mut input = r#"{"id":42,"name":"fab","ok":true,"done":false}rest"#;
+    let orig_input = input;
+    let res: Result<_, FabError> = parser.fab_recorded(&mut input);
+    let (out, trace) = res.unwrap();
+    assert_eq!("rest", input);
+    let mut fresh_input = orig_input;
+    let fresh: Result<_, FabError> = parser.fab(&mut fresh_input);
+    assert_eq!(fresh.unwrap(), out);
+    assert_eq!(Ok(out), parser.replay(&trace, orig_input));
+}
+
+#[test]
+fn replay_length_mismatch_fail() {
+    let parser = fabparse::recorded(('[', char::is_ascii_digit.fab_repeat().as_input_slice(), ']'));
+    let mut input = "[123]";
+    let res: Result<_, FabError> = parser.fab_recorded(&mut input);
+    let (_, trace) = res.unwrap();
+    assert_eq!(
+        Err(ReplayError::LengthMismatch {
+            expected: 5,
+            found: 6
+        }),
+        parser.replay(&trace, "[1234]")
+    );
+}
+
+#[test]
+fn fab_opt_success() {
+    let mut input = "abc";
+    let res: Result<_, FabError> = "a".fab_opt().fab(&mut input);
+    assert_eq!(Some("a"), res.unwrap());
+    assert_eq!("bc", input);
+    let mut input = "abc";
+    let res: Result<_, FabError> = "b".fab_opt().fab(&mut input);
+    assert_eq!(None, res.unwrap());
+    assert_eq!("abc", input);
+}
+
+#[test]
+fn fab_opt_in_sequence_success() {
+    let mut input = "-12abc";
+    let res: Result<_, FabError> = ('-'.fab_opt(), num_unsigned_str).fab(&mut input);
+    assert_eq!((Some('-'), "12"), res.unwrap());
+    assert_eq!("abc", input);
+    let mut input = "12abc";
+    let res: Result<_, FabError> =
+        alt((('+'.fab_opt(), "34"), ('-'.fab_opt(), num_unsigned_str))).fab(&mut input);
+    assert_eq!((None, "12"), res.unwrap());
+    assert_eq!("abc", input);
+}
+
+#[test]
+fn fab_or_success() {
+    let sign = || alt(('-'.fab_value(-1), '+'.fab_value(1))).fab_or(1);
+    let mut input = "-12abc";
+    let res: Result<_, FabError> = (sign(), uint::<i32>()).fab(&mut input);
+    assert_eq!((-1, 12), res.unwrap());
+    assert_eq!("abc", input);
+    let mut input = "12abc";
+    let res: Result<_, FabError> = (sign(), uint::<i32>()).fab(&mut input);
+    assert_eq!((1, 12), res.unwrap());
+    assert_eq!("abc", input);
+
+    let port = || (':', uint::<u16>()).fab_map(|(_, port)| port).fab_or(80);
+    let mut input = ":x/";
+    let res: Result<_, FabError> = port().fab(&mut input);
+    assert_eq!(80, res.unwrap());
+    assert_eq!(":x/", input);
+}
+
+#[test]
+fn fab_or_else_success() {
+    let mut input = "abc";
+    let res: Result<_, FabError> = ("ab", "d")
+        .fab_map(|(a, b)| format!("{}{}", a, b))
+        .fab_or_else(|| String::from("none"))
+        .fab(&mut input);
+    assert_eq!("none", res.unwrap());
+    assert_eq!("abc", input);
+    let mut input = "abdc";
+    let res: Result<_, FabError> = ("ab", "d")
+        .fab_map(|(a, b)| format!("{}{}", a, b))
+        .fab_or_else(String::new)
+        .fab(&mut input);
+    assert_eq!("abd", res.unwrap());
+    assert_eq!("c", input);
+}
+
+#[test]
+fn fab_or_streaming_incomplete() {
+    let mut input = "a";
+    let res: Result<_, Streaming<FabError>> = "ab".fab_or("none").fab(&mut input);
+    assert!(res.unwrap_err().is_incomplete());
+    assert_eq!("a", input);
+}
+
+#[test]
+fn fab_parse_to_success() {
+    let mut input = "-9001rest";
+    let res: Result<_, FabError> = num_signed_str.fab_parse_to::<i64>().fab(&mut input);
+    assert_eq!(-9001, res.unwrap());
+    assert_eq!("rest", input);
+}
+
+#[test]
+fn fab_parse_to_fail() {
+    let mut input = "-9001rest";
+    let res: Result<u8, FabError> = num_signed_str.fab_parse_to().fab(&mut input);
+    let err = res.unwrap_err();
+    assert!(err.to_string().contains("ParseIntError"));
+    assert_eq!("-9001rest", input);
+}
+
+#[test]
+fn fab_complete_success() {
+    let mut input = "123";
+    let res: Result<_, FabError> = num_unsigned_str.fab_complete().fab(&mut input);
+    assert_eq!("123", res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn fab_complete_fail() {
+    let mut input = "123abc";
+    let res: Result<_, FabError> = num_unsigned_str.fab_complete().fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(Some(3), err.offset(input));
+    assert_eq!("123abc", input);
+}
+
+#[test]
+fn parse_str_success() {
+    let (out, rest) = fabparse::parse(('a', num_unsigned_str), "a12bc").unwrap();
+    assert_eq!(('a', "12"), out);
+    assert_eq!("bc", rest);
+}
+
+#[test]
+fn parse_slice_success() {
+    let input = [1, 2, 3, 4].as_slice();
+    let (out, rest) = fabparse::parse(alt(([1, 3], [1, 2])), input).unwrap();
+    assert_eq!([1, 2].as_slice(), out);
+    assert_eq!([3, 4].as_slice(), rest);
+}
+
+#[test]
+fn parse_repeat_success() {
+    let (out, rest) =
+        fabparse::parse(('a'.fab_value(1), 'b'.fab_value(2)).fab_repeat(), "ababc").unwrap();
+    assert_eq!(vec![(1, 2), (1, 2)], out);
+    assert_eq!("c", rest);
+}
+
+#[test]
+fn parse_complete_success() {
+    let out = fabparse::parse_complete(num_signed_str.fab_parse_to::<i32>(), "-42").unwrap();
+    assert_eq!(-42, out);
+    let input = [1, 2].as_slice();
+    let out = fabparse::parse_complete((1, 2), input).unwrap();
+    assert_eq!((1, 2), out);
+}
+
+#[test]
+fn parse_complete_fail() {
+    assert!(fabparse::parse_complete(num_unsigned_str, "123abc").is_err());
+}
+
+#[test]
+fn fab_iter_success() {
+    let mut input = "1,22,333,";
+    let parser = (num_unsigned_str.fab_parse_to(), ',').fab_map(|(num, _): (u32, char)| num);
+    let mut iter = parser.fab_iter(&mut input);
+    assert_eq!(Some(1), iter.next());
+    assert_eq!(vec![22, 333], iter.by_ref().collect::<Vec<_>>());
+    let res: Result<_, FabError> = iter.finish();
+    assert!(res.is_ok());
+    assert_eq!("", input);
+}
+
+#[test]
+fn fab_iter_fail() {
+    let mut input = "1,22,x";
+    let parser = (num_unsigned_str.fab_parse_to(), ',').fab_map(|(num, _): (u32, char)| num);
+    let mut iter = parser.fab_iter(&mut input);
+    let res: Vec<_> = iter.by_ref().collect();
+    assert_eq!(vec![1, 22], res);
+    let res: Result<_, FabError> = iter.finish();
+    assert!(res.is_err());
+    assert_eq!("x", input);
+}
+
+#[test]
+fn fab_iter_no_progress_fail() {
+    let mut input = "abc";
+    let mut iter = opt('x').fab_iter(&mut input);
+    assert_eq!(None, iter.next());
+    assert_eq!(None, iter.next());
+    let res: Result<_, FabError> = iter.finish();
+    assert!(res.is_err());
+    assert_eq!("abc", input);
+}
+
+#[test]
+fn repeat_till_success() {
+    let mut input = "a;b;c;end rest";
+    let statement = (char::is_alphabetic, ';').fab_map(|(c, _): (char, char)| c);
+    let res: Result<_, FabError> = repeat_till(statement, "end").fab(&mut input);
+    assert_eq!((vec!['a', 'b', 'c'], "end"), res.unwrap());
+    assert_eq!(" rest", input);
+}
+
+#[test]
+fn repeat_till_empty_success() {
+    let mut input = "end";
+    let res: Result<_, FabError> = repeat_till('a', "end").fab(&mut input);
+    assert_eq!((vec![], "end"), res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn repeat_till_input_slice_success() {
+    let mut input = "abcend rest";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .as_input_slice()
+        .till("end")
+        .fab(&mut input);
+    assert_eq!(("abc", "end"), res.unwrap());
+    assert_eq!(" rest", input);
+}
+
+#[test]
+fn repeat_till_reduce_success() {
+    let mut input = "1234.";
+    let res: Result<_, FabError> = char::is_ascii_digit
+        .fab_repeat()
+        .reduce(0, |acc: &mut u32, c: char| *acc += c.to_digit(10).unwrap())
+        .till('.')
+        .fab(&mut input);
+    assert_eq!((10, '.'), res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn repeat_till_fail() {
+    let mut input = "a;b;1;end";
+    let statement = (char::is_alphabetic, ';').fab_map(|(c, _): (char, char)| c);
+    let res: Result<_, FabError> = repeat_till(statement, "end").fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("a;b;1;end", input);
+}
+
+#[test]
+fn repeat_till_max_fail() {
+    let mut input = "aaaend";
+    let res: Result<_, FabError> = 'a'.fab_repeat().max(2).till("end").fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("aaaend", input);
+}
+
+#[test]
+fn repeat_array_str_success() {
+    let mut input = "1f3Azz";
+    let res: Result<_, FabError> = char::is_ascii_hexdigit
+        .fab_repeat()
+        .array::<4>()
+        .fab(&mut input);
+    assert_eq!(['1', 'f', '3', 'A'], res.unwrap());
+    assert_eq!("zz", input);
+}
+
+#[test]
+fn repeat_array_slice_success() {
+    let mut input = [1u8, 2, 3, 4, 5].as_slice();
+    let res: Result<_, FabError> = (0u8..=255).fab_repeat().array::<3>().fab(&mut input);
+    assert_eq!([1, 2, 3], res.unwrap());
+    assert_eq!([4, 5].as_slice(), input);
+}
+
+#[test]
+fn repeat_array_fail() {
+    let mut input = "1f3";
+    let res: Result<[char; 4], FabError> =
+        char::is_ascii_hexdigit.fab_repeat().array().fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("1f3", input);
+}
+
+#[test]
+fn repeat_array_bounds_changed() {
+    //Raising the maximum lets a fourth item match, which doesn't fit.
+    let mut input = "1f3Azz";
+    let res: Result<[char; 3], FabError> = char::is_ascii_hexdigit
+        .fab_repeat()
+        .array()
+        .max(5)
+        .fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(Some(&ArrayLenError { len: 3 }), err.downcast_cause());
+    assert_eq!("1f3Azz", input);
+    //Lowering the minimum lets the repeat stop before the array is full.
+    let mut input = "1fzz";
+    let res: Result<[char; 3], FabError> = char::is_ascii_hexdigit
+        .fab_repeat()
+        .array()
+        .min(0)
+        .fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(Some(&ArrayLenError { len: 3 }), err.downcast_cause());
+    assert_eq!("1fzz", input);
+    //Bounds set before `array` are replaced.
+    let mut input = "1f3Azz";
+    let res: Result<[char; 3], FabError> = char::is_ascii_hexdigit
+        .fab_repeat()
+        .max(5)
+        .min(0)
+        .array()
+        .fab(&mut input);
+    assert_eq!(['1', 'f', '3'], res.unwrap());
+    assert_eq!("Azz", input);
+}
+
+#[test]
+fn masked_tag_wildcards() {
+    //An ELF header: the magic, then a class byte that can be anything.
+    let elf = masked_tag(
+        &[0x7F, b'E', b'L', b'F', 0x00],
+        &[0xFF, 0xFF, 0xFF, 0xFF, 0x00],
+    );
+    let mut input = [0x7F, b'E', b'L', b'F', 0x02, 0x01].as_slice();
+    let res: Result<_, FabError> = (elf, binary::u8).fab(&mut input);
+    let (header, data) = res.unwrap();
+    assert_eq!([0x7F, b'E', b'L', b'F', 0x02].as_slice(), header);
+    assert_eq!(0x01, data);
+    assert!(input.is_empty());
+
+    let mut input = [0x7F, b'E', b'L', b'G', 0x02].as_slice();
+    let res: Result<_, FabError> = elf.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!([0x7F, b'E', b'L', b'G', 0x02].as_slice(), input);
+}
+
+#[test]
+fn masked_tag_nibbles() {
+    //An IPv4 header starts with a version nibble of 4 and a header length nibble.
+    let version = masked_tag(&[0x40], &[0xF0]);
+    let mut input = [0x45, 0x00, 0x00, 0x54].as_slice();
+    let res: Result<_, FabError> = (version, binary::u8, binary::be_u16).fab(&mut input);
+    let (first, _, total_len) = res.unwrap();
+    assert_eq!(5, first[0] & 0x0F);
+    assert_eq!(0x54, total_len);
+
+    let mut input = [0x65, 0x00].as_slice();
+    let res: Result<_, FabError> = version.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!([0x65, 0x00].as_slice(), input);
+}
+
+#[test]
+fn masked_tag_short_input() {
+    let tag = masked_tag(&[0xCA, 0xFE, 0x00, 0x00], &[0xFF, 0xFF, 0x00, 0x00]);
+    let mut input = [0xCA, 0xFE, 0x01].as_slice();
+    let res: Result<_, FabError> = tag.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!([0xCA, 0xFE, 0x01].as_slice(), input);
+    let res: Result<_, Streaming<FabError>> = tag.fab(&mut input);
+    assert!(res.unwrap_err().is_incomplete());
+    assert_eq!([0xCA, 0xFE, 0x01].as_slice(), input);
+
+    let mut input = [0xCA, 0xFF].as_slice();
+    let res: Result<_, Streaming<FabError>> = tag.fab(&mut input);
+    assert!(!res.unwrap_err().is_incomplete());
+}
+
+#[test]
+#[should_panic(expected = "same length")]
+fn masked_tag_length_mismatch() {
+    masked_tag(&[0x01, 0x02], &[0xFF]);
+}
+
+#[test]
+fn fill_slice_success() {
+    let mut buf = [0u8; 4];
+    let mut input = [1u8, 2, 3, 4, 5].as_slice();
+    let res: Result<_, FabError> = fill(binary::u8, &mut buf).fab(&mut input);
+    assert!(res.is_ok());
+    assert_eq!([1, 2, 3, 4], buf);
+    assert_eq!([5].as_slice(), input);
+
+    let mut buf = [0u16; 2];
+    let mut input = [0x01u8, 0x02, 0x03, 0x04].as_slice();
+    let parser = fill(binary::be_u16, &mut buf);
+    let res: Result<_, FabError> = parser.fab(&mut input);
+    assert!(res.is_ok());
+    assert_eq!([0x0102, 0x0304], parser.into_inner());
+}
+
+#[test]
+fn fill_fail() {
+    let mut buf = [9u8; 4];
+    let mut input = [1u8, 2, 3].as_slice();
+    let res: Result<_, FabError> = fill(binary::u8, &mut buf).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!([1, 2, 3].as_slice(), input);
+    assert_eq!([1, 2, 3, 9], buf);
+
+    let mut buf = ['x'; 2];
+    let mut input = "abc";
+    let res: Result<_, FabError> = fill(opt('a').fab_map(|_| 'a'), &mut buf).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("abc", input);
+}
+
+#[test]
+fn fill_reentrant_fail() {
+    type NestedFill<'a> = dyn Fn(&mut &str) -> Result<(), FabError> + 'a;
+    let nested: OnceCell<&NestedFill> = OnceCell::new();
+    let mut buf = [(); 2];
+    let parser = fill(
+        |input: &mut &str| -> Result<(), FabError> {
+            'a'.fab(input)?;
+            nested.get().unwrap()(input)
+        },
+        &mut buf,
+    );
+    let run = |input: &mut &str| parser.fab(input);
+    nested.get_or_init(|| &run);
+    let mut input = "aa";
+    let err = run(&mut input).unwrap_err();
+    assert!(err.downcast_cause::<FillBorrowedError>().is_some());
+    assert_eq!("aa", input);
+}
+
+#[test]
+fn repeat_collect_string_success() {
+    let mut input = "abc123";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .collect::<String>()
+        .fab(&mut input);
+    assert_eq!("abc", res.unwrap());
+    assert_eq!("123", input);
+}
+
+#[test]
+fn repeat_into_string() {
+    let mut input = "ñandú42";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .min(2)
+        .into_string()
+        .fab(&mut input);
+    assert_eq!("ñandú", res.unwrap());
+    assert_eq!("42", input);
+    let mut input = "aé€";
+    let res: Result<_, FabError> = take_not('x')
+        .fab_repeat()
+        .max(2)
+        .into_string()
+        .fab(&mut input);
+    assert_eq!("aé", res.unwrap());
+    assert_eq!("€", input);
+
+    let mut input = "é1";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .min(2)
+        .into_string()
+        .fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("é1", input);
+}
+
+#[test]
+fn repeat_collect_btree_map_success() {
+    let mut input = "a=1,b=2,c=3";
+    let entry = (char::is_alphabetic, '=', char::is_ascii_digit, opt(','))
+        .fab_map(|(k, _, v, _): (char, char, char, Option<char>)| (k, v));
+    let res: Result<_, FabError> = entry
+        .fab_repeat()
+        .min(1)
+        .collect::<BTreeMap<_, _>>()
+        .fab(&mut input);
+    let expected = BTreeMap::from([('a', '1'), ('b', '2'), ('c', '3')]);
+    assert_eq!(expected, res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn repeat_collect_bounds_fail() {
+    let mut input = "ab1";
+    let res: Result<HashSet<char>, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .min(3)
+        .collect()
+        .fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("ab1", input);
+}
+
+#[test]
+fn repeat_with_spans() {
+    let source = "ab,cde,f;rest";
+    let mut input = source;
+    let word = (
+        char::is_alphabetic.fab_repeat().min(1).as_input_slice(),
+        opt(','),
+    );
+    let res: Result<_, FabError> = word
+        .fab_map(|(w, _)| w)
+        .fab_repeat()
+        .with_spans()
+        .fab(&mut input);
+    let items = res.unwrap();
+    assert_eq!(vec![("ab", "ab,"), ("cde", "cde,"), ("f", "f")], items);
+    assert_eq!(";rest", input);
+    //The spans are contiguous and cover the matched input.
+    let mut end = 0;
+    for (_, span) in &items {
+        assert_eq!(source.as_ptr() as usize + end, span.as_ptr() as usize);
+        end += span.len();
+    }
+    assert_eq!(source.len() - input.len(), end);
+}
+
+#[test]
+fn repeat_with_spans_bounds() {
+    let mut input = [1u8, 2, 3, 4].as_slice();
+    let res: Result<_, FabError> = binary::be_u16
+        .fab_repeat()
+        .max(1)
+        .with_spans()
+        .fab(&mut input);
+    assert_eq!(vec![(0x0102, [1u8, 2].as_slice())], res.unwrap());
+    assert_eq!([3, 4].as_slice(), input);
+
+    let mut input = "aab";
+    let res: Result<_, FabError> = opt('a').fab_repeat().with_spans().fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("aab", input);
+    let res: Result<_, FabError> = opt('a')
+        .fab_repeat()
+        .with_spans()
+        .allow_empty_matches()
+        .min(3)
+        .fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("aab", input);
+    let res: Result<_, FabError> = opt('a')
+        .fab_repeat()
+        .with_spans()
+        .allow_empty_matches()
+        .count()
+        .fab(&mut input);
+    assert_eq!(2, res.unwrap());
+    assert_eq!("b", input);
+}
+
+#[test]
+fn repeat_collect_small_vec() {
+    let mut input = "abc1";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .collect::<smallvec::SmallVec<[char; 2]>>()
+        .fab(&mut input);
+    let res = res.unwrap();
+    assert_eq!(['a', 'b', 'c'].as_slice(), res.as_slice());
+    assert!(res.spilled());
+    assert_eq!("1", input);
+
+    let mut input = "ab1";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .min(1)
+        .collect::<smallvec::SmallVec<[char; 4]>>()
+        .fab(&mut input);
+    let res = res.unwrap();
+    assert_eq!(['a', 'b'].as_slice(), res.as_slice());
+    assert!(!res.spilled());
+}
+
+#[cfg(feature = "arrayvec")]
+#[test]
+fn repeat_array_vec_stops_at_capacity() {
+    let mut input = "abcde1";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .array_vec::<3>()
+        .fab(&mut input);
+    assert_eq!(['a', 'b', 'c'].as_slice(), res.unwrap().as_slice());
+    assert_eq!("de1", input);
+
+    let mut input = "ab1";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .max(5)
+        .array_vec::<3>()
+        .fab(&mut input);
+    assert_eq!(['a', 'b'].as_slice(), res.unwrap().as_slice());
+    assert_eq!("1", input);
+
+    let mut input = "abcde";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .min(4)
+        .array_vec::<3>()
+        .fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("abcde", input);
+}
+
+#[cfg(feature = "arrayvec")]
+#[test]
+fn repeat_array_vec_overflow() {
+    let mut input = "abcde";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .array_vec::<3>()
+        .max(5)
+        .fab(&mut input);
+    let err = res.unwrap_err();
+    assert!(err
+        .cause()
+        .and_then(|cause| cause.downcast_ref::<arrayvec::CapacityError>())
+        .is_some());
+    assert_eq!("abcde", input);
+
+    let mut input = "abc1";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .array_vec::<3>()
+        .max(5)
+        .fab(&mut input);
+    assert_eq!(['a', 'b', 'c'].as_slice(), res.unwrap().as_slice());
+}
+
+#[test]
+fn repeat_reduce_enumerated_success() {
+    let mut input = "abc1";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .reduce(Vec::new(), |acc: &mut Vec<(usize, char)>, index, c| {
+            acc.push((index, c))
+        })
+        .fab(&mut input);
+    assert_eq!(vec![(0, 'a'), (1, 'b'), (2, 'c')], res.unwrap());
+    assert_eq!("1", input);
+}
+
+#[test]
+fn repeat_reduce_enumerated_fail() {
+    let mut input = "abcd";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .reduce(Vec::new(), |acc: &mut Vec<char>, index, c| {
+            acc.push(c);
+            index < 2
+        })
+        .fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("abcd", input);
+}
+
+//Takes header lines until the blank line that ends the header.
+fn header_lines<'a>(input: &mut &'a str) -> Result<Vec<&'a str>, FabError> {
+    (take_not('\n').fab_repeat().as_input_slice(), '\n')
+        .fab_map(|(line, _)| line)
+        .fab_repeat()
+        .reduce(Vec::new(), |acc: &mut Vec<&'a str>, line: &'a str| {
+            if line.is_empty() {
+                ControlFlow::Break(())
+            } else {
+                acc.push(line);
+                ControlFlow::Continue(())
+            }
+        })
+        .fab(input)
+}
+
+#[test]
+fn repeat_reduce_control_flow_break() {
+    let mut input = "a: 1\nb: 2\n\nbody\n";
+    let res = header_lines(&mut input);
+    assert_eq!(vec!["a: 1", "b: 2"], res.unwrap());
+    assert_eq!("body\n", input);
+    let mut input = "a: 1\n";
+    let res = header_lines(&mut input);
+    assert_eq!(vec!["a: 1"], res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn repeat_reduce_control_flow_bounds() {
+    let sum_until_zero = |acc: &mut u32, index: usize, digit: char| {
+        *acc += digit.to_digit(10).unwrap();
+        if digit == '0' || index == 9 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    };
+    let mut input = "1230456";
+    let res: Result<_, FabError> = char::is_ascii_digit
+        .fab_repeat()
+        .min(4)
+        .reduce(0, sum_until_zero)
+        .fab(&mut input);
+    assert_eq!(6, res.unwrap());
+    assert_eq!("456", input);
+    let mut input = "1230456";
+    let res: Result<_, FabError> = char::is_ascii_digit
+        .fab_repeat()
+        .min(5)
+        .reduce(0, sum_until_zero)
+        .fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!("1230456", input);
+    assert_eq!(
+        Some(vec![(4, ParserType::RepeatIter), (0, ParserType::Repeat)]),
+        err.stack_offsets("1230456")
+    );
+    let mut input = "1230456";
+    let res: Result<_, FabError> = char::is_ascii_digit
+        .fab_repeat()
+        .max(2)
+        .reduce(0, sum_until_zero)
+        .fab(&mut input);
+    assert_eq!(3, res.unwrap());
+    assert_eq!("30456", input);
+}
+
+#[test]
+fn repeat_reduce_control_flow_replay() {
+    let parser = fabparse::recorded((
+        '<',
+        char::is_ascii_digit
+            .fab_repeat()
+            .reduce(0, |acc: &mut u32, digit: char| {
+                *acc += 1;
+                if digit == '0' {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            }),
+    ));
+    let mut input = "<1202";
+    let res: Result<_, FabError> = parser.fab_recorded(&mut input);
+    let (out, trace) = res.unwrap();
+    assert_eq!(('<', 3), out);
+    assert_eq!("2", input);
+    assert_eq!(Ok(out), parser.replay(&trace, "<3405"));
+    assert_eq!(
+        Err(ReplayError::TraceMismatch),
+        parser.replay(&trace, "<1002")
+    );
+}
+
+#[derive(Debug, PartialEq)]
+struct Node(char);
+
+#[test]
+fn repeat_non_clone_success() {
+    let mut input = "abc1";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_map(Node)
+        .fab_repeat()
+        .fab(&mut input);
+    assert_eq!(vec![Node('a'), Node('b'), Node('c')], res.unwrap());
+    assert_eq!("1", input);
+}
+
+#[test]
+fn repeat_reduce_with_success() {
+    let mut input = "abc1";
+    let parser = char::is_alphabetic.fab_map(Node).fab_repeat().reduce_with(
+        || vec![Node('x')],
+        |acc: &mut Vec<Node>, node| acc.push(node),
+    );
+    let res: Result<_, FabError> = parser.fab(&mut input);
+    assert_eq!(
+        vec![Node('x'), Node('a'), Node('b'), Node('c')],
+        res.unwrap()
+    );
+    assert_eq!("1", input);
+    let mut input = "d";
+    let res: Result<_, FabError> = parser.fab(&mut input);
+    assert_eq!(vec![Node('x'), Node('d')], res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn repeat_count_success() {
+    let mut input = "   x";
+    let res: Result<_, FabError> = ' '.fab_repeat().count().fab(&mut input);
+    assert_eq!(3, res.unwrap());
+    assert_eq!("x", input);
+}
+
+#[test]
+fn repeat_count_bounds_fail() {
+    let mut input = "12a";
+    let res: Result<_, FabError> = char::is_ascii_digit
+        .fab_repeat()
+        .min(3)
+        .count()
+        .fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("12a", input);
+}
+
+#[test]
+fn repeat_count_no_progress_fail() {
+    let mut input = "abc";
+    let res: Result<_, FabError> = opt('x').fab_repeat().count().fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("abc", input);
+}
+
+#[test]
+fn take_range_success() {
+    let mut input = "1234567890";
+    let res: Result<_, FabError> = take(1..=8).fab(&mut input);
+    assert_eq!("12345678", res.unwrap());
+    assert_eq!("90", input);
+    let mut input = "123";
+    let res: Result<_, FabError> = take(1..=8).fab(&mut input);
+    assert_eq!("123", res.unwrap());
+    assert_eq!("", input);
+    let mut input = [1, 2, 3, 4, 5, 6].as_slice();
+    let res: Result<_, FabError> = take(..5).fab(&mut input);
+    assert_eq!([1, 2, 3, 4].as_slice(), res.unwrap());
+    assert_eq!([5, 6].as_slice(), input);
+    let mut input = "abcd";
+    let res: Result<_, FabError> = take(3..).fab(&mut input);
+    assert_eq!("abcd", res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn take_range_fail() {
+    let mut input = "ab";
+    let res: Result<_, FabError> = take(3..).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("ab", input);
+    let mut input = "ab";
+    let res: Result<_, FabError> = take(0..0).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("ab", input);
+}
+
+#[test]
+fn alt_vec_success() {
+    let keywords = ["let".to_string(), "fn".to_string(), "for".to_string()];
+    let mut input = "for x";
+    let parser = alt(keywords.iter().map(|k| k.as_str()).collect::<Vec<_>>());
+    let res: Result<_, FabError> = parser.fab(&mut input);
+    assert_eq!("for", res.unwrap());
+    assert_eq!(" x", input);
+}
+
+#[test]
+fn alt_slice_fail() {
+    let parsers = [('a', 'b', 'c'), ('a', 'x', 'y')];
+    let mut input = "abd";
+    let res: Result<_, FabError> = alt(parsers.as_slice()).fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(Some(2), err.offset(input));
+    assert_eq!("abd", input);
+}
+
+#[test]
+fn fab_alt_fallback() {
+    let mut input = "abz";
+    let res: Result<_, FabError> = ('a', 'b', 'c')
+        .fab_map(|_| 1)
+        .fab_alt("ab".fab_value(2))
+        .fab(&mut input);
+    assert_eq!(2, res.unwrap());
+    assert_eq!("z", input);
+
+    let mut input = "x";
+    let res: Result<_, FabError> = "ab".fab_alt("cd").fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("x", input);
+
+    let mut input = "cd";
+    let res: Result<_, FabError> = "ab".fab_alt(lazy(|| "cd")).fab(&mut input);
+    assert_eq!("cd", res.unwrap());
+}
+
+#[test]
+fn fab_alt_chain_errors() {
+    let text = "abz";
+    let mut input = text;
+    let res: Result<_, FabError> = 'x'
+        .fab_value(0)
+        .fab_alt(('a', 'b', 'c').fab_value(1))
+        .fab_alt(('a', 'b', 'd').fab_value(2))
+        .fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(text, input);
+    assert_eq!(Some(2), err.offset(text));
+    assert_eq!(
+        Some(&Expected::OneOf(vec![
+            Expected::Char('c'),
+            Expected::Char('d')
+        ])),
+        err.expected()
+    );
+    let flat: FabError = alt((
+        'x'.fab_value(0),
+        ('a', 'b', 'c').fab_value(1),
+        ('a', 'b', 'd').fab_value(2),
+    ))
+    .fab(&mut input)
+    .unwrap_err();
+    assert_eq!(flat.to_string(), err.to_string());
+
+    //The furthest error wins from any position in the chain.
+    let res: Result<_, FabError> = ('a', 'b', 'c')
+        .fab_alt(('x', 'b', 'c'))
+        .fab_alt(('a', 'y', 'c'))
+        .fab(&mut input);
+    assert_eq!(Some(2), res.unwrap_err().offset(text));
+
+    let mut input = "ab";
+    let res: Result<_, Streaming<FabError>> = "abc".fab_alt("ab").fab_alt("a").fab(&mut input);
+    assert!(res.unwrap_err().is_incomplete());
+    assert_eq!("ab", input);
+}
+
+#[test]
+fn alt_empty_vec_fail() {
+    let mut input = "abc";
+    let res: Result<&str, FabError> = alt(Vec::<&str>::new()).fab(&mut input);
+    let err = res.unwrap_err();
+    assert!(err.to_string().contains("EmptyAltError"));
+    assert_eq!("abc", input);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Message {
+    Ping,
+    Move(u8, u8),
+}
+
+fn ping_body(input: &mut &[u8]) -> Result<Message, FabError> {
+    0u8.fab_value(Message::Ping).fab(input)
+}
+
+fn move_body(input: &mut &[u8]) -> Result<Message, FabError> {
+    (take(1), take(1))
+        .fab_map(|(x, y): (&[u8], &[u8])| Message::Move(x[0], y[0]))
+        .fab(input)
+}
+
+type MessageBody = fn(&mut &[u8]) -> Result<Message, FabError>;
+
+fn message_table(key: u8) -> Option<MessageBody> {
+    match key {
+        1 => Some(ping_body),
+        2 => Some(move_body),
+        _ => None,
+    }
+}
+
+#[test]
+fn dispatch_success() {
+    let mut input = [2u8, 5, 7, 1, 0].as_slice();
+    let res: Result<_, FabError> = dispatch(0..=255, message_table).fab(&mut input);
+    assert_eq!(Message::Move(5, 7), res.unwrap());
+    assert_eq!([1, 0].as_slice(), input);
+    let res: Result<_, FabError> = dispatch(0..=255, message_table).fab(&mut input);
+    assert_eq!(Message::Ping, res.unwrap());
+    assert_eq!([0u8; 0].as_slice(), input);
+}
+
+#[test]
+fn dispatch_unknown_key_fail() {
+    let mut input = [9u8, 5, 7].as_slice();
+    let res: Result<_, FabError> = dispatch(0..=255, message_table).fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(Some(0), err.offset(input));
+    assert_eq!([9, 5, 7].as_slice(), input);
+}
+
+#[test]
+fn dispatch_body_fail() {
+    let mut input = [1u8, 5].as_slice();
+    let res: Result<_, FabError> = dispatch(0..=255, message_table).fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(Some(1), err.offset(input));
+    assert!(err.to_string().contains("Dispatch"));
+    assert_eq!([1, 5].as_slice(), input);
+}
+
+#[test]
+fn permutation_opt_success() {
+    let flags = || permutation_opt(("-a", "-b", "-c"));
+    let mut input = "-c-a rest";
+    let res: Result<_, FabError> = flags().fab(&mut input);
+    assert_eq!((Some("-a"), None, Some("-c")), res.unwrap());
+    assert_eq!(" rest", input);
+    let mut input = "-b-c-a";
+    let res: Result<_, FabError> = flags().fab(&mut input);
+    assert_eq!((Some("-a"), Some("-b"), Some("-c")), res.unwrap());
+    assert_eq!("", input);
+    let mut input = "-a-a";
+    let res: Result<_, FabError> = flags().fab(&mut input);
+    assert_eq!((Some("-a"), None, None), res.unwrap());
+    assert_eq!("-a", input);
+}
+
+#[test]
+fn permutation_opt_empty_success() {
+    let mut input = "xyz";
+    let res: Result<_, FabError> = permutation_opt(('a', opt('b'))).fab(&mut input);
+    assert_eq!((None, Some(None)), res.unwrap());
+    assert_eq!("xyz", input);
+}
+
+fn char_num_pair(input: &mut &str) -> Result<(char, u32), FabError> {
+    separated_pair(
+        'a'..='z',
+        '=',
+        ('0'..='9').fab_try_map(|c: char| c.to_digit(10)),
+    )
+    .fab(input)
+}
+
+#[test]
+fn separated_pair_hashmap_success() {
+    let mut input = "a=1b=2c=3";
+    let res: Result<_, FabError> = char_num_pair
+        .fab_repeat()
+        .reduce(HashMap::new(), reducer)
+        .fab(&mut input);
+    let expected_res: HashMap<char, u32> = [('a', 1), ('b', 2), ('c', 3)].into_iter().collect();
+    assert_eq!(expected_res, res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn separated_pair_fail() {
+    let mut input = "localhost:x";
+    let res: Result<_, FabError> = separated_pair(
+        char::is_alphabetic.fab_repeat().as_input_slice(),
+        ':',
+        num_unsigned_str,
+    )
+    .fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("localhost:x", input);
+}
+
+//Parses nested brackets such as "[[],[[]]]", returning the nesting depth.
+fn brackets<'a>() -> BoxedParser<'a, str, usize, FabError> {
+    let inner = |input: &mut &'a str| brackets().fab(input);
+    (
+        '[',
+        (inner, opt(','))
+            .fab_repeat()
+            .reduce(0, |depth: &mut usize, (child, _): (usize, _)| {
+                *depth = (*depth).max(child)
+            }),
+        ']',
+    )
+        .fab_map(|(_, depth, _): (char, usize, char)| depth + 1)
+        .fab_boxed()
+}
+
+#[test]
+fn boxed_recursive_success() {
+    let mut input = "[[],[[]]]rest";
+    let res: Result<_, FabError> = brackets().fab(&mut input);
+    assert_eq!(3, res.unwrap());
+    assert_eq!("rest", input);
+}
+
+#[test]
+fn boxed_in_alt_success() {
+    let parsers: Vec<BoxedParser<str, &str, FabError>> = vec!["ab".fab_boxed(), "cd".fab_boxed()];
+    let mut input = "cdab";
+    let res: Result<_, FabError> = (alt(parsers), 'a').fab(&mut input);
+    assert_eq!(("cd", 'a'), res.unwrap());
+    assert_eq!("b", input);
+}
+
+#[test]
+fn boxed_fail() {
+    let mut input = "[[]";
+    let res: Result<_, FabError> = brackets().fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("[[]", input);
+}
+
+#[test]
+fn ref_parser_reuse_success() {
+    let digits = char::is_ascii_digit.fab_repeat().min(1).as_input_slice();
+    let mut input = "12.345rest";
+    let res: Result<_, FabError> = (&digits, '.', &digits).fab(&mut input);
+    assert_eq!(("12", '.', "345"), res.unwrap());
+    assert_eq!("rest", input);
+    let mut input = "+42";
+    let res: Result<_, FabError> = alt((('-', &digits), ('+', &digits))).fab(&mut input);
+    assert_eq!(('+', "42"), res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn rc_parser_success() {
+    let parser = Rc::new(('a', take(2)));
+    let mut input = "abcd";
+    let res: Result<_, FabError> = (parser.clone(), 'd').fab(&mut input);
+    assert_eq!((('a', "bc"), 'd'), res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn arc_parser_threads_success() {
+    let parser = Arc::new(("ab", take(1..=3)));
+    let handles: Vec<_> = ["abc", "abcdef"]
+        .into_iter()
+        .map(|input| {
+            let parser = Arc::clone(&parser);
+            thread::spawn(move || {
+                let mut input = input;
+                let res: Result<_, FabError> = parser.fab(&mut input);
+                res.ok()
+            })
+        })
+        .collect();
+    let res: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(vec![Some(("ab", "c")), Some(("ab", "cde"))], res);
+}
+
+#[test]
+fn from_fn_mut_interning_success() {
+    let mut interned: Vec<String> = Vec::new();
+    let ident = from_fn_mut(|input: &mut &str| {
+        let name: &str = char::is_alphabetic
+            .fab_repeat()
+            .min(1)
+            .as_input_slice()
+            .fab(input)?;
+        let index = match interned.iter().position(|s| s == name) {
+            Some(index) => index,
+            None => {
+                interned.push(name.to_string());
+                interned.len() - 1
+            }
+        };
+        Ok::<_, FabError>(index)
+    });
+    let mut input = "foo bar foo baz!";
+    let res: Result<_, FabError> = (&ident, opt(' ')).fab_repeat().fab(&mut input);
+    let indices: Vec<usize> = res.unwrap().into_iter().map(|(i, _)| i).collect();
+    assert_eq!(vec![0, 1, 0, 2], indices);
+    assert_eq!("!", input);
+    assert_eq!(vec!["foo", "bar", "baz"], interned);
+}
+
+#[test]
+fn from_fn_mut_fail() {
+    let mut count = 0;
+    let parser = from_fn_mut(|input: &mut &str| {
+        count += 1;
+        ('a', 'b').fab(input)
+    });
+    let mut input = "ac";
+    let res: Result<_, FabError> = parser.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("ac", input);
+    assert_eq!(1, count);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tree {
+    Num(u32),
+    List(Vec<Tree>),
+}
+
+fn tree(input: &mut &str) -> Result<Tree, FabError> {
+    alt((
+        num_unsigned_str.fab_parse_to().fab_map(Tree::Num),
+        lazy(|| {
+            (
+                '[',
+                (tree, opt(','))
+                    .fab_map(|(t, _): (Tree, Option<char>)| t)
+                    .fab_repeat(),
+                ']',
+            )
+                .fab_map(|(_, items, _): (char, Vec<Tree>, char)| Tree::List(items))
+        }),
+    ))
+    .fab(input)
+}
+
+#[test]
+fn lazy_nested_list_success() {
+    let mut input = "[1,[2,[]],3]rest";
+    let res: Result<_, FabError> = tree.fab(&mut input);
+    let expected = Tree::List(vec![
+        Tree::Num(1),
+        Tree::List(vec![Tree::Num(2), Tree::List(vec![])]),
+        Tree::Num(3),
+    ]);
+    assert_eq!(expected, res.unwrap());
+    assert_eq!("rest", input);
+}
+
+#[test]
+fn lazy_nested_list_fail() {
+    let mut input = "[1,[2,x]]";
+    let res: Result<_, FabError> = tree.fab(&mut input);
+    let err = res.unwrap_err();
+    //The outer list stops at the inner list that failed, and expects a ']' there.
+    assert_eq!(Some(3), err.offset(input));
+    assert_eq!("[1,[2,x]]", input);
+}
+
+#[test]
+fn success_parser() {
+    let mut input = "abc";
+    let res: Result<_, FabError> = ("ab", success(5)).fab(&mut input);
+    assert_eq!(("ab", 5), res.unwrap());
+    assert_eq!("c", input);
+    let mut input = "xyz";
+    let res: Result<_, FabError> = alt(("ab".fab_value("ab"), success("none"))).fab(&mut input);
+    assert_eq!("none", res.unwrap());
+    assert_eq!("xyz", input);
+
+    //success never consumes input, so repeating it trips the zero progress check.
+    let mut input = "abc";
+    let res: Result<Vec<i32>, FabError> = success(1).fab_repeat().fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("abc", input);
+}
+
+#[test]
+fn fail_parser() {
+    let input = "abc";
+    let mut rest = input;
+    let res: Result<char, FabError> = fail().fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(Some(0), err.offset(input));
+    assert_eq!(Some(vec![(0, ParserType::Fail)]), err.stack_offsets(input));
+    assert_eq!("abc", rest);
+
+    //The branch that consumed input before failing makes the most progress.
+    let mut rest = input;
+    let res: Result<_, FabError> = alt((fail(), ("ab", 'x').fab_value("abx"))).fab(&mut rest);
+    assert_eq!(Some(2), res.unwrap_err().offset(input));
+    assert_eq!("abc", rest);
+
+    let mut rest = input;
+    let res: Result<_, FabError> = alt((fail(), 'a')).fab(&mut rest);
+    assert_eq!('a', res.unwrap());
+    assert_eq!("bc", rest);
+
+    let mut rest = input;
+    let res: Result<Vec<char>, FabError> = fail().fab_repeat().fab(&mut rest);
+    assert!(res.unwrap().is_empty());
+    assert_eq!("abc", rest);
+}
+
+#[test]
+fn error_offset_success() {
+    let input = "key=value;key2=";
+    let mut rest = input;
+    let res: Result<_, FabError> = (
+        char::is_alphabetic.fab_repeat().as_input_slice(),
+        '=',
+        char::is_alphabetic.fab_repeat().min(1).as_input_slice(),
+        ';',
+    )
+        .fab_repeat()
+        .min(2)
+        .fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(Some(13), err.offset(input));
+    assert_eq!(Some(13), err.offset(String::from(input).as_str()));
+}
+
+#[test]
+fn error_stack_offsets_success() {
+    let input = [1u16, 2, 3, 4].as_slice();
+    let mut rest = input;
+    let res: Result<_, FabError> = (1, 2, alt((5, 6))).fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(Some(2), err.offset(input));
+    assert_eq!(
+        Some(vec![
+            (2, ParserType::Tag),
+            (2, ParserType::Alt),
+            (0, ParserType::Sequence)
+        ]),
+        err.stack_offsets(input)
+    );
+}
+
+#[test]
+fn error_line_col_first_line() {
+    let input = "abc1\ndef";
+    let mut rest = input;
+    let res: Result<_, FabError> = (char::is_alphabetic.fab_repeat(), '\n').fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(Some((1, 4)), err.line_col(input));
+    assert_eq!(Some("abc1"), err.error_line(input));
+    let frames = err.stack_offsets(input).unwrap().len();
+    assert_eq!(Some((1, 1)), err.line_col_at(input, frames - 1));
+    assert_eq!(None, err.line_col_at(input, frames));
+}
+
+#[test]
+fn error_line_col_trailing_newline() {
+    let input = "ab\r\ncd\r\n";
+    let mut rest = input;
+    let res: Result<_, FabError> = (
+        char::is_alphabetic.fab_repeat(),
+        "\r\n",
+        char::is_alphabetic.fab_repeat(),
+        "\r\n",
+        'e',
+    )
+        .fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(Some((3, 1)), err.line_col(input));
+    assert_eq!(Some(""), err.error_line(input));
+}
+
+#[test]
+fn error_line_col_multibyte() {
+    let input = "línea\nñandú!";
+    let mut rest = input;
+    let res: Result<_, FabError> = (
+        char::is_alphabetic.fab_repeat(),
+        '\n',
+        char::is_alphabetic.fab_repeat(),
+        '\n',
+    )
+        .fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(Some((2, 6)), err.line_col(input));
+    assert_eq!(Some("ñandú!"), err.error_line(input));
+}
+
+#[test]
+fn error_annotate() {
+    let input = "x = 1\n\tfoo = tru\n";
+    let mut rest = input;
+    let key_value = (
+        char::is_whitespace.fab_repeat(),
+        char::is_alphanumeric.fab_repeat().min(1),
+        " = ",
+        alt((
+            "true",
+            "false",
+            char::is_numeric.fab_repeat().min(1).as_input_slice(),
+        )),
+        '\n',
+    );
+    let res: Result<_, FabError> = (&key_value, &key_value).fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(
+        "line 2 |     foo = tru\n\
+         \x20                  ^ from parser Tag\n\
+         line 2 |     foo = tru\n\
+         \x20                  ^ from parser Repeat\n\
+         line 2 |     foo = tru\n\
+         \x20                  ^ from parser Alt\n\
+         line 2 |     foo = tru\n\
+         \x20        ^ from parser Sequence\n\
+         line 1 | x = 1\n\
+         \x20        ^ from parser Sequence\n",
+        err.annotate(input)
+    );
+}
+
+#[test]
+fn error_annotate_end_of_input() {
+    let input = "x = 1\ny";
+    let mut rest = input;
+    let res: Result<_, FabError> = ("x = 1\n", 'y', '=').fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(
+        "line 2 | y\n\
+         \x20         ^ from parser Tag: expected '=', found end of input\n\
+         line 1 | x = 1\n\
+         \x20        ^ from parser Sequence\n",
+        err.annotate(input)
+    );
+}
+
+#[test]
+fn fab_msg_survives_alt() {
+    let input = "deps = y";
+    let mut rest = input;
+    let res: Result<_, FabError> = alt((
+        ("deps", " = ", 'x').fab_msg("while parsing the deps table"),
+        ("name", " = ", 'x').fab_msg("while parsing the name"),
+    ))
+    .fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!("deps = y", rest);
+    assert_eq!(
+        "line 1 | deps = y\n\
+         \x20               ^ from parser Tag: expected 'x', found 'y'\n\
+         line 1 | deps = y\n\
+         \x20        ^ from parser Sequence\n\
+         line 1 | deps = y\n\
+         \x20        ^ from parser Message: while parsing the deps table\n\
+         line 1 | deps = y\n\
+         \x20        ^ from parser Alt\n",
+        err.annotate(input)
+    );
+    assert!(err
+        .trace_string(input)
+        .contains("from parser Message: while parsing the deps table\n"));
+}
+
+#[test]
+fn fab_msg_no_context() {
+    let mut input = "b";
+    let res: Result<_, NoContextFabError> = 'a'.fab_msg(String::from("expected a")).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("b", input);
+}
+
+#[test]
+fn alt_verbose_success() {
+    let mut input = "fn main";
+    let res: Result<_, FabError> = alt_verbose(("let", "fn", "struct")).fab(&mut input);
+    assert_eq!("fn", res.unwrap());
+    assert_eq!(" main", input);
+}
+
+#[test]
+fn alt_verbose_trace() {
+    let input = "for x";
+    let mut rest = input;
+    let res: Result<&str, FabError> = alt_verbose((
+        "let".fab_msg("let"),
+        "fn".fab_msg("fn"),
+        ("fo", 'o').fab_value("foo"),
+        "struct".fab_msg("struct"),
+    ))
+    .fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!("for x", rest);
+    assert_eq!(
+        "Location [\"\"]^[\"for x\"] from parser Alt\n\
+         \x20 Branches at [\"fo\"]^[\"r x\"]: expected 'o'\n\
+         \x20 Branches at [\"\"]^[\"for x\"]: let, fn, struct\n\
+         Location [\"\"]^[\"for x\"] from parser Value\n\
+         Location [\"\"]^[\"for x\"] from parser Sequence\n\
+         Location [\"fo\"]^[\"r x\"] from parser Tag: expected 'o', found 'r'\n",
+        err.trace_string(input)
+    );
+}
+
+#[test]
+fn alt_verbose_vec_fail() {
+    let mut input = "c";
+    let res: Result<char, NoContextFabError> = alt_verbose(vec!['a', 'b']).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("c", input);
+    let res: Result<char, FabError> = alt_verbose(Vec::<char>::new()).fab(&mut input);
+    assert!(res.is_err());
+}
+
+fn parser_types(err: &FabError, input: &str) -> Vec<ParserType> {
+    err.stack_offsets(input)
+        .unwrap()
+        .into_iter()
+        .map(|(_, parser_type)| parser_type)
+        .collect()
+}
+
+#[test]
+fn parser_type_value() {
+    let input = "ac";
+    let mut rest = input;
+    let res: Result<_, FabError> = ('a', 'b').fab_value(1).fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(
+        vec![ParserType::Tag, ParserType::Sequence, ParserType::Value],
+        parser_types(&err, input)
+    );
+}
+
+#[test]
+fn parser_type_try_map() {
+    let input = "ba";
+    let mut rest = input;
+    let parser = 'a'.fab_try_map(|c: char| c.to_digit(16));
+    let res: Result<_, FabError> = parser.fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(
+        vec![ParserType::Tag, ParserType::TryMap],
+        parser_types(&err, input)
+    );
+    let res: Result<_, FabError> = ('b', parser).fab(&mut rest);
+    assert_eq!(('b', 10), res.unwrap());
+    let input = "a";
+    let mut rest = input;
+    let res: Result<_, FabError> = 'a'.fab_try_map(|c: char| c.to_digit(10)).fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(vec![ParserType::TryMap], parser_types(&err, input));
+}
+
+#[test]
+fn parser_type_take() {
+    let input = "ab";
+    let mut rest = input;
+    let res: Result<_, FabError> = ('a', take(3)).fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(
+        vec![ParserType::Take, ParserType::Sequence],
+        parser_types(&err, input)
+    );
+}
+
+fn identifier<'a>(input: &mut &'a str) -> Result<&'a str, FabError> {
+    let checkpoint = *input;
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .min(1)
+        .as_input_slice()
+        .fab(input);
+    res.map_err(|_| FabError::from_parser_error(checkpoint, ParserType::Custom("identifier")))
+}
+
+#[test]
+fn parser_type_custom() {
+    let input = "let  = 5";
+    let mut rest = input;
+    let res: Result<_, FabError> = ("let ", identifier, " = ").fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(
+        vec![
+            ParserType::Custom("identifier"),
+            ParserType::Function,
+            ParserType::Sequence
+        ],
+        parser_types(&err, input)
+    );
+}
+
+#[test]
+fn error_downcast_cause() {
+    let mut input = "ab";
+    let res: Result<_, FabError> = take(1)
+        .fab_try_map(|_| Err::<i32, _>(TestError))
+        .fab(&mut input);
+    let err = res.unwrap_err();
+    assert!(err.downcast_cause::<TestError>().is_some());
+    assert!(err.downcast_cause::<TryReducerError>().is_none());
+    assert!(err.source().unwrap().is::<TestError>());
+
+    let mut input = "aab";
+    let res: Result<_, FabError> = 'a'
+        .fab_repeat()
+        .reduce(0, |count: &mut i32, _| count.checked_sub(1).is_none())
+        .fab(&mut input);
+    let err = res.unwrap_err();
+    assert!(err.downcast_cause::<TryReducerError>().is_some());
+    assert!(err.source().unwrap().is::<TryReducerError>());
+
+    let mut input = "b";
+    let res: Result<_, FabError> = 'a'.fab(&mut input);
+    let err = res.unwrap_err();
+    assert!(err.downcast_cause::<TestError>().is_none());
+    assert!(err.source().is_none());
+}
+
+#[test]
+fn error_frames() {
+    let input = "let  = 5";
+    let mut rest = input;
+    let res: Result<_, FabError> = ("let ", identifier, " = ").fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(
+        vec![
+            (Some(4), ParserType::Custom("identifier")),
+            (Some(4), ParserType::Function),
+            (Some(0), ParserType::Sequence)
+        ],
+        err.frames(input).collect::<Vec<_>>()
+    );
+    //Locations are measured from the end, so the start of the input is outside of this suffix.
+    assert_eq!(
+        vec![
+            (Some(1), ParserType::Custom("identifier")),
+            (Some(1), ParserType::Function),
+            (None, ParserType::Sequence)
+        ],
+        err.frames(&input[3..]).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn alt_error_furthest_branch() {
+    let input = "abd";
+    let mut rest = input;
+    let res: Result<_, FabError> = alt((
+        ('a', 'c').fab_value(1),
+        ('a', 'b', 'c').fab_msg("abc").fab_value(2),
+        ('b', 'c').fab_value(3),
+    ))
+    .fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!("abd", rest);
+    assert_eq!(Some(2), err.offset(input));
+    assert_eq!(Some(&Expected::Char('c')), err.expected());
+    assert_eq!(
+        Some(vec![
+            (2, ParserType::Tag),
+            (0, ParserType::Sequence),
+            (0, ParserType::Message),
+            (0, ParserType::Value),
+            (0, ParserType::Alt)
+        ]),
+        err.stack_offsets(input)
+    );
+    assert!(err.trace_string(input).contains("Message: abc"));
+}
+
+#[test]
+fn alt_error_tied_branches() {
+    let input = "x";
+    let mut rest = input;
+    let res: Result<_, FabError> = alt((
+        "a".fab_value(1),
+        'b'.fab_value(2),
+        "c".fab_msg("c").fab_value(3),
+    ))
+    .fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(Some(0), err.offset(input));
+    assert_eq!(
+        Some(vec![
+            (0, ParserType::Tag),
+            (0, ParserType::Message),
+            (0, ParserType::Value),
+            (0, ParserType::Alt)
+        ]),
+        err.stack_offsets(input)
+    );
+    assert_eq!(
+        Some(&Expected::OneOf(vec![
+            Expected::Str("a".into()),
+            Expected::Char('b'),
+            Expected::Str("c".into())
+        ])),
+        err.expected()
+    );
+}
+
+#[test]
+fn alt_error_nested() {
+    let input = "1+x";
+    let mut rest = input;
+    let res: Result<_, FabError> = alt((
+        (uint::<u32>(), '+', alt(("y", uint::<u32>().fab_value("n")))),
+        (uint::<u32>(), '-').fab_value((0, '-', "")),
+    ))
+    .fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!("1+x", rest);
+    assert_eq!(Some(2), err.offset(input));
+    assert_eq!(
+        Some(vec![
+            (2, ParserType::Tag),
+            (2, ParserType::Value),
+            (2, ParserType::Alt),
+            (0, ParserType::Sequence),
+            (0, ParserType::Alt)
+        ]),
+        err.stack_offsets(input)
+    );
+}
+
+#[test]
+fn alt_error_external_cause() {
+    let input = "x300";
+    let mut rest = input;
+    let res: Result<_, FabError> = alt((
+        (
+            'x',
+            take_while(|c: char| c.is_ascii_digit())
+                .min(1)
+                .fab_try_map(u8::from_str),
+        )
+            .fab_value(1),
+        "x30x".fab_value(2),
+        'z'.fab_value(3),
+    ))
+    .fab(&mut rest);
+    let err = res.unwrap_err();
+    assert!(err.downcast_cause::<std::num::ParseIntError>().is_some());
+    assert_eq!(
+        Some(vec![
+            (1, ParserType::TryMap),
+            (0, ParserType::Sequence),
+            (0, ParserType::Value),
+            (0, ParserType::Alt)
+        ]),
+        err.stack_offsets(input)
+    );
+}
+
+#[test]
+fn permutation_error_stack() {
+    let input = "ab!";
+    let mut rest = input;
+    let res: Result<_, FabError> = permutation(('a', 'b', 'c')).fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!("ab!", rest);
+    assert_eq!(Some(2), err.offset(input));
+    assert_eq!(
+        Some(vec![(2, ParserType::Tag), (0, ParserType::Permutation)]),
+        err.stack_offsets(input)
+    );
+}
+
+fn nested_parens(input: &mut &str, depth: usize, calls: &Cell<usize>) -> Result<(), FabError> {
+    let leaf = |input: &mut &str| -> Result<(), FabError> {
+        calls.set(calls.get() + 1);
+        'x'.fab(input).map(|_| ())
+    };
+    if depth == 0 {
+        return leaf(input);
+    }
+    alt((
+        ('(', |input: &mut &str| {
+            nested_parens(input, depth - 1, calls)
+        })
+            .fab_value(()),
+        leaf,
+    ))
+    .fab(input)
+}
+
+#[test]
+fn alt_runs_each_branch_once() {
+    let text = format!("{}!", "(".repeat(80));
+    let mut input = text.as_str();
+    let calls = Cell::new(0);
+    let err = nested_parens(&mut input, 80, &calls).unwrap_err();
+    assert_eq!(Some(80), err.offset(text.as_str()));
+    assert_eq!(81, calls.get());
+}
+
+#[test]
+fn alt_failed_branch_reports_once() {
+    let errors = State::new(Vec::new());
+    let mut input = "c";
+    let res: Result<_, FabError> =
+        alt(((errors.opt('x'), 'a').fab_value(()), 'b'.fab_value(()))).fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(
+        Some(&Expected::OneOf(vec![
+            Expected::Char('a'),
+            Expected::Char('b')
+        ])),
+        err.expected()
+    );
+    assert_eq!(1, errors.into_inner().len());
+}
+
+#[test]
+fn permutation_runs_each_member_once() {
+    let calls = Cell::new(0);
+    let counted = from_fn_mut(|input: &mut &str| -> Result<char, FabError> {
+        calls.set(calls.get() + 1);
+        'c'.fab(input)
+    });
+    let mut input = "ab!";
+    let res: Result<_, FabError> = permutation(('a', 'b', counted)).fab(&mut input);
+    assert!(res.is_err());
+    //'a' and 'b' match before the member is tried, so it only runs at the '!'.
+    assert_eq!(1, calls.get());
+}
+
+#[test]
+fn nested_alt_keeps_message() {
+    let input = "deps = y";
+    let mut rest = input;
+    let res: Result<_, FabError> = alt((
+        alt((
+            ("deps", " = ", 'x')
+                .fab_msg("while parsing the deps table")
+                .fab_value(1),
+            "name".fab_value(2),
+        )),
+        "version".fab_value(3),
+    ))
+    .fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(Some(7), err.offset(input));
+    assert!(err
+        .trace_string(input)
+        .contains("from parser Message: while parsing the deps table\n"));
+}
+
+#[test]
+fn alt_keeps_recovered_errors() {
+    let text = "let = 2;";
+    let mut input = text;
+    let res: Result<_, FabError> = alt((
+        recover(let_statement, ';').fab_map(Some),
+        'x'.fab_value(None),
+    ))
+    .fab(&mut input);
+    let recovered = res.unwrap().unwrap().unwrap_err();
+    assert_eq!(Some(4), recovered.offset(text));
+    assert_eq!(
+        Some(vec![
+            (4, ParserType::Tag),
+            (4, ParserType::Function),
+            (0, ParserType::Sequence),
+            (0, ParserType::Function)
+        ]),
+        recovered.stack_offsets(text)
+    );
+}
+
+#[test]
+fn alt_memoized_error() {
+    let input = "ab";
+    let memo = ('a', 'c').fab_memoize();
+    let mut rest = input;
+    let res: Result<_, FabError> =
+        alt(((&memo).fab_value(1), ('a', 'd').fab_value(2))).fab(&mut rest);
+    assert!(res.is_err());
+    let mut rest = input;
+    let res: Result<_, FabError> = memo.fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(Some(&Expected::Char('c')), err.expected());
+    assert_eq!(
+        Some(vec![(1, ParserType::Tag), (0, ParserType::Sequence)]),
+        err.stack_offsets(input)
+    );
+}
+
+#[test]
+fn alt_streaming_incomplete() {
+    let mut input = "le";
+    let res: Result<_, Streaming<FabError>> = alt(("let", "fn")).fab(&mut input);
+    let err = res.unwrap_err();
+    assert!(err.is_incomplete());
+    assert_eq!("le", input);
+}
+
+#[test]
+fn alt_longest_keywords() {
+    let orders = [
+        ["in", "int", "integer"],
+        ["integer", "int", "in"],
+        ["int", "in", "integer"],
+    ];
+    for [k1, k2, k3] in orders {
+        for (text, keyword) in [("integer x", "integer"), ("int x", "int"), ("in x", "in")] {
+            let mut input = text;
+            let res: Result<_, FabError> = alt_longest((k1, k2, k3)).fab(&mut input);
+            assert_eq!(keyword, res.unwrap());
+            assert_eq!(" x", input);
+            let mut input = text;
+            let res: Result<_, FabError> = alt_longest(vec![k1, k2, k3]).fab(&mut input);
+            assert_eq!(keyword, res.unwrap());
+            assert_eq!(" x", input);
+        }
+    }
+}
+
+#[test]
+fn alt_longest_ties() {
+    let mut input = "ab!";
+    let res: Result<_, FabError> =
+        alt_longest(("a".fab_value(1), "ab".fab_value(2), ('a', 'b').fab_value(3))).fab(&mut input);
+    assert_eq!(2, res.unwrap());
+    assert_eq!("!", input);
+}
+
+#[test]
+fn alt_longest_output_not_clone() {
+    #[derive(Debug, PartialEq)]
+    struct Token(usize);
+    let mut input = "<<=1";
+    let res: Result<_, FabError> = alt_longest((
+        "<".fab_map(|op: &str| Token(op.len())),
+        "<<".fab_map(|op: &str| Token(op.len())),
+        "<<=".fab_map(|op: &str| Token(op.len())),
+    ))
+    .fab(&mut input);
+    assert_eq!(Token(3), res.unwrap());
+    assert_eq!("1", input);
+}
+
+#[test]
+fn alt_longest_error() {
+    let input = "abd";
+    let branches = || {
+        (
+            ('a', 'c').fab_value(1),
+            ('a', 'b', 'c').fab_value(2),
+            "ab".fab_msg("ab").fab_value(3),
+        )
+    };
+    let mut rest = input;
+    let res: Result<_, FabError> = alt_longest(("ab", "abc")).fab(&mut rest);
+    assert_eq!("ab", res.unwrap());
+    let mut rest = "axd";
+    let longest: FabError = alt_longest(branches()).fab(&mut rest).unwrap_err();
+    assert_eq!("axd", rest);
+    let first: FabError = alt(branches()).fab(&mut rest).unwrap_err();
+    assert_eq!(first.stack_offsets("axd"), longest.stack_offsets("axd"));
+    assert_eq!(first.expected(), longest.expected());
+    assert!(longest.expected().is_some());
+    let mut rest = input;
+    let res: Result<i32, FabError> = alt_longest(branches()).fab(&mut rest);
+    assert_eq!(3, res.unwrap());
+    assert_eq!("d", rest);
+}
+
+#[test]
+fn alt_longest_streaming_incomplete() {
+    let mut input = "in";
+    let res: Result<_, Streaming<FabError>> = alt_longest(("in", "int")).fab(&mut input);
+    let err = res.unwrap_err();
+    assert!(err.is_incomplete());
+    assert_eq!("in", input);
+}
+
+#[test]
+fn offset_error_furthest_alt() {
+    let input = "abd";
+    let mut rest = input;
+    let res: Result<_, OffsetFabError> = alt((
+        ('a', 'c').fab_value(1),
+        ('a', 'b', 'c').fab_value(2),
+        ('b', 'c').fab_value(3),
+    ))
+    .fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!("abd", rest);
+    assert_eq!(Some(2), err.offset(input));
+    assert_eq!(Some(ParserType::Tag), err.parser_type());
+}
+
+#[test]
+fn offset_error_convert() {
+    let input = "abd";
+    let mut rest = input;
+    let inner = |input: &mut &str| -> Result<_, FabError> { ('a', 'b', 'c').fab(input) };
+    let res: Result<_, FabError> = inner
+        .fab_convert_err::<OffsetFabError>()
+        .fab_convert_err()
+        .fab(&mut rest);
+    let err = res.unwrap_err();
+    assert_eq!(
+        Some(vec![(2, ParserType::Tag), (0, ParserType::MapErr)]),
+        err.stack_offsets(input)
+    );
+}
+
+#[test]
+fn offset_error_convert_empty() {
+    let empty = FabError::convert_error(NoContextFabError);
+    let err = OffsetFabError::convert_error(empty);
+    assert_eq!(None, err.get_loc());
+    assert_eq!(None, err.parser_type());
+    let back = FabError::convert_error(err);
+    assert_eq!(Some(vec![]), back.stack_offsets(""));
+}
+
+fn offset_here<'a>(input: &mut &'a Located<'a, str>) -> Result<Option<usize>, FabError> {
+    Ok(input.current_offset())
+}
+
+#[test]
+fn located_offset() {
+    let mut input = "let x";
+    let res: Result<_, FabError> = with_location(("let ", offset_here, 'x')).fab(&mut input);
+    let (tag, offset, _) = res.unwrap();
+    assert_eq!("let ", tag);
+    assert_eq!(Some(4), offset);
+    assert_eq!("", input);
+    //The output is kept after the parser that created it has finished.
+    assert_eq!(None, tag.current_offset());
+
+    let items = [1u8, 2, 3, 4];
+    let mut input = items.as_slice();
+    let res: Result<_, FabError> = with_location((
+        1u8,
+        2u8,
+        3u8,
+        |input: &mut &Located<[u8]>| -> Result<_, FabError> { Ok(input.current_offset()) },
+    ))
+    .fab(&mut input);
+    assert_eq!(Some(3), res.unwrap().3);
+    assert_eq!([4].as_slice(), input);
+}
+
+#[test]
+fn located_relative_to_start() {
+    let mut input = "ab cd";
+    let res: Result<_, FabError> = ("ab ", with_location(offset_here)).fab(&mut input);
+    assert_eq!(Some(0), res.unwrap().1);
+}
+
+#[test]
+fn located_spanned_alt() {
+    let mut input = "x = let";
+    let keyword = alt((
+        spanned(("le", 'x')).fab_value("lex"),
+        spanned("let").fab_map(|(span, _)| span).fab_value("let"),
+    ));
+    let res: Result<_, FabError> =
+        with_location(("x = ", spanned(keyword), offset_here)).fab(&mut input);
+    let (_, keyword, offset) = res.unwrap();
+    assert_eq!((4..7, "let"), keyword);
+    assert_eq!(Some(7), offset);
+    assert_eq!("", input);
+}
+
+#[test]
+fn located_spanned_repeat() {
+    let mut input = "ab  cde f";
+    let word = spanned(char::is_alphabetic.fab_repeat().min(1).as_input_slice())
+        .fab_map(|(span, word): (_, &Located<str>)| (span, word.len()));
+    let res: Result<Vec<_>, FabError> = with_location(
+        (word, ' '.fab_repeat())
+            .fab_map(|(word, _)| word)
+            .fab_repeat(),
+    )
+    .fab(&mut input);
+    assert_eq!(vec![(0..2, 2), (4..7, 3), (8..9, 1)], res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn located_inner_parsers() {
+    let mut input = "let x1 = 2";
+    let res: Result<_, FabError> = with_location((
+        "let ",
+        spanned(inner(util::identifier)),
+        " = ",
+        inner(int::<u32>()),
+    ))
+    .fab(&mut input);
+    let (_, (span, name), _, value) = res.unwrap();
+    assert_eq!((4..6, "x1", 2), (span, name, value));
+    assert_eq!("", input);
+}
+
+#[test]
+fn spanned_without_location() {
+    let located = Located::wrap("abc");
+    let mut input = located;
+    let res: Result<_, FabError> = spanned('a').fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("abc", input);
+}
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+enum Tok {
+    LParen,
+    RParen,
+    Comma,
+    Ident,
+    Digit(u8),
+}
+
+//Lexes the source into tokens and the byte span of each token.
+fn lex_toks(source: &str) -> (Vec<Tok>, Vec<std::ops::Range<usize>>) {
+    let mut toks = Vec::new();
+    let mut spans = Vec::new();
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        let start = source.len() - rest.len();
+        let len = match c {
+            '(' | ')' | ',' => {
+                toks.push(match c {
+                    '(' => Tok::LParen,
+                    ')' => Tok::RParen,
+                    _ => Tok::Comma,
+                });
+                1
+            }
+            '0'..='9' => {
+                toks.push(Tok::Digit(c as u8 - b'0'));
+                1
+            }
+            ' ' => {
+                rest = &rest[1..];
+                continue;
+            }
+            _ => {
+                toks.push(Tok::Ident);
+                rest.find(|c: char| !c.is_alphabetic())
+                    .unwrap_or(rest.len())
+            }
+        };
+        spans.push(start..start + len);
+        rest = &rest[len..];
+    }
+    (toks, spans)
+}
+
+type TokStream<'a> = SliceLike<'a, Tok, (&'static str, Vec<std::ops::Range<usize>>)>;
+
+//Returns the source text of the consumed tokens.
+fn tok_text(consumed: &TokStream) -> &'static str {
+    let range = consumed.range().unwrap();
+    consumed
+        .extras(|(source, spans)| {
+            let source: &'static str = source;
+            &source[spans[range.start].start..spans[range.end - 1].end]
+        })
+        .unwrap()
+}
+
+fn tok_list<'a>(input: &mut &'a TokStream<'a>) -> Result<Vec<&'static str>, FabError> {
+    let item = alt((
+        Tok::Ident.fab_value(()),
+        (Tok::Digit(0)..=Tok::Digit(9)).fab_value(()),
+    ))
+    .fab_map_with_span(|_, consumed| tok_text(consumed));
+    let (_, first, rest, _) = (
+        Tok::LParen,
+        &item,
+        (Tok::Comma, &item).fab_map(|(_, text)| text).fab_repeat(),
+        Tok::RParen,
+    )
+        .fab(input)?;
+    let mut items: Vec<&str> = rest;
+    items.insert(0, first);
+    Ok(items)
+}
+
+fn tok_offset<'a>(input: &mut &'a TokStream<'a>) -> Result<Option<usize>, FabError> {
+    Ok(input.offset())
+}
+
+fn take_with_range<'a>(
+    input: &mut &'a TokStream<'a>,
+) -> Result<(&'a TokStream<'a>, Option<std::ops::Range<usize>>), FabError> {
+    let toks = take(2).fab(input)?;
+    Ok((toks, toks.range()))
+}
+
+#[test]
+fn slice_like_token_stream() {
+    let source = "(abc, 7,de ,0)";
+    let (toks, spans) = lex_toks(source);
+    let mut input = toks.as_slice();
+    let res: Result<_, FabError> =
+        with_extras((source, spans), (tok_list, tok_offset)).fab(&mut input);
+    let (list, offset) = res.unwrap();
+    assert_eq!(vec!["abc", "7", "de", "0"], list);
+    assert_eq!(Some(9), offset);
+    assert!(input.is_empty());
+
+    let (toks, spans) = lex_toks(source);
+    let mut input = toks.as_slice();
+    let res: Result<_, FabError> =
+        with_extras((source, spans), (Tok::LParen, take_with_range)).fab(&mut input);
+    let (_, (consumed, range)) = res.unwrap();
+    assert_eq!(Some(1..3), range);
+    assert_eq!([Tok::Ident, Tok::Comma].as_slice(), consumed);
+    //The output is kept after the parser that created it has finished.
+    assert_eq!(None, consumed.range());
+}
+
+#[test]
+fn slice_like_token_stream_fail() {
+    let source = "(a, ,b)";
+    let (toks, spans) = lex_toks(source);
+    let mut input = toks.as_slice();
+    let res: Result<_, FabError> = with_extras((source, spans.clone()), tok_list).fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(toks.as_slice(), input);
+    let index = err.offset(toks.as_slice()).unwrap();
+    //The repeat stops before the second comma, where a closing paren is expected.
+    assert_eq!(2, index);
+    assert_eq!(2..3, spans[index]);
+}
+
+#[derive(Debug, PartialEq)]
+enum Stmt {
+    Simple(String),
+    If(String, Vec<Stmt>),
+}
+
+fn indent_of(input: &str) -> usize {
+    input.len() - input.trim_start_matches(' ').len()
+}
+
+//Consumes the indentation of a line if it matches the current block.
+fn same_indent(input: &mut &Stateful<str, Vec<usize>>) -> Result<(), FabError> {
+    let line = input.inner();
+    let spaces = indent_of(line);
+    if input.state(|stack| stack.last() == Some(&spaces)) == Some(true) {
+        *input = Stateful::wrap(&line[spaces..]);
+        Ok(())
+    } else {
+        Err(FabError::from_parser_error(
+            *input,
+            ParserType::Custom("indent"),
+        ))
+    }
+}
+
+//Opens a new block if the next line is indented further than the current block.
+fn indent(input: &mut &Stateful<str, Vec<usize>>) -> Result<(), FabError> {
+    let spaces = indent_of(input.inner());
+    let opened = input.state(|stack| {
+        let deeper = stack.last().is_some_and(|top| spaces > *top);
+        if deeper {
+            stack.push(spaces);
+        }
+        deeper
+    });
+    if opened == Some(true) {
+        Ok(())
+    } else {
+        Err(FabError::from_parser_error(
+            *input,
+            ParserType::Custom("indent"),
+        ))
+    }
+}
+
+fn statements(input: &mut &Stateful<str, Vec<usize>>) -> Result<Vec<Stmt>, FabError> {
+    (same_indent, statement)
+        .fab_map(|(_, stmt)| stmt)
+        .fab_repeat()
+        .min(1)
+        .fab(input)
+}
+
+fn block(input: &mut &Stateful<str, Vec<usize>>) -> Result<Vec<Stmt>, FabError> {
+    indent(input)?;
+    let res = statements(input);
+    input.state(|stack| stack.pop());
+    res
+}
+
+fn statement(input: &mut &Stateful<str, Vec<usize>>) -> Result<Stmt, FabError> {
+    let name =
+        inner(char::is_alphabetic.fab_repeat().min(1).as_input_slice()).fab_map(String::from);
+    alt((
+        ("if ", &name, ":\n", block).fab_map(|(_, cond, _, body)| Stmt::If(cond, body)),
+        (&name, '\n').fab_map(|(name, _)| Stmt::Simple(name)),
+    ))
+    .fab(input)
+}
+
+#[test]
+fn stateful_indented_blocks() {
+    let mut input = "a\nif x:\n  b\n  if y:\n    c\n  d\ne\n";
+    let state = State::new(vec![0]);
+    let res: Result<_, FabError> = state.with_state(statements).fab(&mut input);
+    assert_eq!(
+        vec![
+            Stmt::Simple("a".to_string()),
+            Stmt::If(
+                "x".to_string(),
+                vec![
+                    Stmt::Simple("b".to_string()),
+                    Stmt::If("y".to_string(), vec![Stmt::Simple("c".to_string())]),
+                    Stmt::Simple("d".to_string()),
+                ]
+            ),
+            Stmt::Simple("e".to_string()),
+        ],
+        res.unwrap()
+    );
+    assert_eq!("", input);
+    assert_eq!(vec![0], state.into_inner());
+}
+
+fn count_letter<'a>(
+    input: &mut &'a Stateful<'a, str, usize>,
+) -> Result<&'a Stateful<'a, str, usize>, FabError> {
+    input.state(|count| *count += 1);
+    //The state is already borrowed inside a call to state.
+    assert_eq!(Some(None), input.state(|_| input.state(|_| ())));
+    take(1).fab(input)
+}
+
+#[test]
+fn stateful_state_access() {
+    let mut input = "ab";
+    let state = State::new(0);
+    let res: Result<_, FabError> = state
+        .with_state((count_letter, count_letter))
+        .fab(&mut input);
+    let (a, b) = res.unwrap();
+    assert_eq!(("a", "b"), (a.inner(), b.inner()));
+    assert_eq!("", input);
+    assert_eq!(2, *state.borrow());
+    //The output is kept after the parser that created it has finished.
+    assert_eq!(None, a.state(|count| *count));
+}
+
+#[test]
+fn str_bytes_success() {
+    let request = "GET /päth HTTP/1.1";
+    let view = StrBytes::new(request);
+    let mut input = view.input();
+    let res: Result<_, FabError> = (
+        view.as_str(b"GET"),
+        b' ',
+        view.as_str((|b: u8| b != b' ').fab_repeat().min(1)),
+        b' ',
+        view.as_str(b"HTTP/1.1"),
+    )
+        .fab(&mut input);
+    assert_eq!(("GET", b' ', "/päth", b' ', "HTTP/1.1"), res.unwrap());
+    assert_eq!(Some(""), view.to_str(input));
+    let mut input = view.input();
+    let res: Result<_, FabError> = (b"GE", b'T').fab(&mut input);
+    assert_eq!((b"GE".as_slice(), b'T'), res.unwrap());
+    assert_eq!(Some(" /päth HTTP/1.1"), view.to_str(input));
+}
+
+#[test]
+fn str_bytes_char_boundary_fail() {
+    let view = StrBytes::new("äb");
+    let mut input = view.input();
+    let res: Result<_, FabError> = view.as_str(take(1)).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!(view.input(), input);
+    assert_eq!(Some("b"), view.to_str(&input[2..]));
+    assert_eq!(None, view.to_str(&input[1..]));
+    assert_eq!(None, view.to_str(b"b"));
+}
+
+#[test]
+fn binary_header_success() {
+    let data = [
+        0x89, b'P', b'N', b'G', 0x01, 0x02, 0x03, 0x04, 0x01, 0x02, 0xff, 0xfe, 0x00, 0x00, 0xc0,
+        0x3f, 0x07,
+    ];
+    let mut input = data.as_slice();
+    let res: Result<_, FabError> = (
+        binary::be_u32,
+        binary::le_u32,
+        binary::be_u16,
+        binary::le_i16,
+        binary::le_f32,
+    )
+        .fab(&mut input);
+    assert_eq!((0x89504e47, 0x04030201, 0x0102, -257, 1.5), res.unwrap());
+    assert_eq!([0x07].as_slice(), input);
+}
+
+#[test]
+fn binary_round_trip() {
+    let values = [1.25f64, -3.5e10, f64::MAX];
+    let bytes: Vec<u8> = values.iter().flat_map(|val| val.to_be_bytes()).collect();
+    let mut input = bytes.as_slice();
+    let res: Result<Vec<f64>, FabError> = binary::be_f64.fab_repeat().fab(&mut input);
+    assert_eq!(values.as_slice(), res.unwrap());
+    let bytes = (-42i64).to_le_bytes();
+    let mut input = bytes.as_slice();
+    let res: Result<_, FabError> = binary::le_i64.fab(&mut input);
+    assert_eq!(-42, res.unwrap());
+    let bytes = u128::MAX.to_be_bytes();
+    let mut input = bytes.as_slice();
+    let res: Result<_, FabError> = (binary::u8, binary::i8, binary::be_u64).fab(&mut input);
+    assert_eq!((255, -1, u64::MAX), res.unwrap());
+    assert_eq!(6, input.len());
+}
+
+#[test]
+fn binary_truncated_fail() {
+    let data = [0x01, 0x02, 0x03];
+    let mut input = data.as_slice();
+    let res: Result<u32, FabError> = binary::le_u32.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!(data.as_slice(), input);
+    let res: Result<_, FabError> = (binary::be_u16, binary::be_u16).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!(data.as_slice(), input);
+    let mut input: &[u8] = &[];
+    let res: Result<u8, NoContextFabError> = binary::u8.fab(&mut input);
+    assert!(res.is_err());
+}
+
+#[test]
+fn length_take_success() {
+    let data = [3, b'a', b'b', b'c', b'd'];
+    let mut input = data.as_slice();
+    let res: Result<_, FabError> = length_take(binary::u8).fab(&mut input);
+    assert_eq!(b"abc".as_slice(), res.unwrap());
+    assert_eq!(b"d".as_slice(), input);
+    let mut input = "3:äbcd";
+    let res: Result<_, FabError> =
+        length_take((num_unsigned_str.fab_parse_to::<usize>(), ':').fab_map(|(len, _)| len))
+            .fab(&mut input);
+    assert_eq!("äbc", res.unwrap());
+    assert_eq!("d", input);
+}
+
+#[test]
+fn length_take_truncated_fail() {
+    let data = [5, 1, 2];
+    let mut input = data.as_slice();
+    let res: Result<_, FabError> = length_take(binary::u8).fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(data.as_slice(), input);
+    assert_eq!(
+        Some(vec![(1, ParserType::Take), (0, ParserType::Length)]),
+        err.stack_offsets(data.as_slice())
+    );
+}
+
+#[test]
+fn length_repeat_success() {
+    let data = [2, 0, 1, 0, 2, 9];
+    let mut input = data.as_slice();
+    let res: Result<_, FabError> = length_repeat(binary::u8, binary::be_u16).fab(&mut input);
+    assert_eq!(vec![1, 2], res.unwrap());
+    assert_eq!([9].as_slice(), input);
+    let mut input = "2ab!";
+    let res: Result<_, FabError> =
+        length_repeat(num_unsigned_str.fab_parse_to::<u8>(), char::is_alphabetic).fab(&mut input);
+    assert_eq!(vec!['a', 'b'], res.unwrap());
+    assert_eq!("!", input);
+}
+
+#[test]
+fn length_repeat_fail() {
+    let data = [3, 0, 1, 0];
+    let mut input = data.as_slice();
+    let res: Result<Vec<u16>, FabError> = length_repeat(binary::u8, binary::le_u16).fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(data.as_slice(), input);
+    assert_eq!(
+        Some(vec![
+            (3, ParserType::Take),
+            (3, ParserType::Function),
+            (0, ParserType::Length)
+        ]),
+        err.stack_offsets(data.as_slice())
+    );
+    let mut input = "-1ab";
+    let res: Result<Vec<char>, FabError> =
+        length_repeat(num_signed_str.fab_parse_to::<i32>(), char::is_alphabetic).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("-1ab", input);
+}
+
+#[test]
+fn util_uint_success() {
+    let mut input = "255,65535x";
+    let res: Result<_, FabError> = (uint::<u8>(), ',', uint::<u16>()).fab(&mut input);
+    assert_eq!((255, ',', 65535), res.unwrap());
+    assert_eq!("x", input);
+    let mut input = "007";
+    let res: Result<_, FabError> = uint::<i64>().fab(&mut input);
+    assert_eq!(7, res.unwrap());
+}
+
+#[test]
+fn util_uint_overflow_fail() {
+    let mut input = "256";
+    let res: Result<_, FabError> = uint::<u8>().fab(&mut input);
+    assert!(res.unwrap_err().to_string().contains("IntOverflowError"));
+    assert_eq!("256", input);
+    let mut input = "99999999999999999999";
+    let res: Result<_, FabError> = uint::<u64>().fab(&mut input);
+    assert!(res.unwrap_err().to_string().contains("IntOverflowError"));
+    assert_eq!("99999999999999999999", input);
+    for text in [" 1", "+1", "", "x"] {
+        let mut input = text;
+        let res: Result<u32, FabError> = uint().fab(&mut input);
+        assert!(res.is_err());
+        assert_eq!(text, input);
+    }
+}
+
+#[test]
+fn util_int_success() {
+    let mut input = "-128 +127 -0 42";
+    let item = (int::<i8>(), opt(' ')).fab_map(|(num, _)| num);
+    let res: Result<Vec<_>, FabError> = item.fab_repeat().fab(&mut input);
+    assert_eq!(vec![-128, 127, 0, 42], res.unwrap());
+    assert_eq!("", input);
+    let mut input = "x";
+    let res: Result<_, FabError> =
+        alt((int::<i32>().fab_map(Some), 'x'.fab_value(None))).fab(&mut input);
+    assert_eq!(None, res.unwrap());
+    let mut input = "+5";
+    let res: Result<_, FabError> = int::<u32>().fab(&mut input);
+    assert_eq!(5, res.unwrap());
+}
+
+#[test]
+fn util_int_fail() {
+    for text in ["-129", "128", "+", "-", " -1", "+-1"] {
+        let mut input = text;
+        let res: Result<i8, FabError> = int().fab(&mut input);
+        assert!(res.is_err());
+        assert_eq!(text, input);
+    }
+    let mut input = "-129";
+    let res: Result<i8, FabError> = int().fab(&mut input);
+    assert!(res.unwrap_err().to_string().contains("IntOverflowError"));
+    let mut input = "-1";
+    let res: Result<u8, FabError> = int().fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("-1", input);
+}
+
+/**
+ * Parses text as str and as bytes, and checks that both give the same result and rest.
+ */
+fn int_matches_bytes<T: PartialEq + fmt::Debug>(
+    text: &str,
+    parser: impl for<'a> Parser<'a, str, T, FabError, IntParser>
+        + for<'a> Parser<'a, [u8], T, FabError, IntParser>,
+) -> Option<T> {
+    let mut str_input = text;
+    let from_str: Result<T, FabError> = parser.fab(&mut str_input);
+    let mut byte_input = text.as_bytes();
+    let from_bytes: Result<T, FabError> = parser.fab(&mut byte_input);
+    assert_eq!(str_input.as_bytes(), byte_input, "{:?}", text);
+    match (from_str, from_bytes) {
+        (Ok(from_str), Ok(from_bytes)) => {
+            assert_eq!(from_str, from_bytes, "{:?}", text);
+            Some(from_str)
+        }
+        (Err(from_str), Err(from_bytes)) => {
+            assert_eq!(from_str.offset(text), from_bytes.offset(text.as_bytes()));
+            assert_eq!(
+                from_str.to_string().contains("IntOverflowError"),
+                from_bytes.to_string().contains("IntOverflowError")
+            );
+            None
+        }
+        (from_str, from_bytes) => panic!("{:?}: {:?} {:?}", text, from_str, from_bytes),
+    }
+}
+
+#[test]
+fn util_int_bytes() {
+    assert_eq!(
+        Some(u64::MAX),
+        int_matches_bytes("18446744073709551615", uint::<u64>())
+    );
+    assert_eq!(
+        None,
+        int_matches_bytes("18446744073709551616", uint::<u64>())
+    );
+    assert_eq!(
+        Some(i64::MIN),
+        int_matches_bytes("-9223372036854775808", int::<i64>())
+    );
+    assert_eq!(
+        None,
+        int_matches_bytes("-9223372036854775809", int::<i64>())
+    );
+    assert_eq!(
+        Some(i64::MAX),
+        int_matches_bytes("+9223372036854775807", int::<i64>())
+    );
+    assert_eq!(None, int_matches_bytes("9223372036854775808", int::<i64>()));
+    assert_eq!(Some(7), int_matches_bytes("0007 ", uint::<u8>()));
+    assert_eq!(Some(255), int_matches_bytes("255", uint::<u8>()));
+    assert_eq!(None, int_matches_bytes("256", uint::<u8>()));
+    assert_eq!(Some(5), int_matches_bytes("+5", int::<u32>()));
+    for text in ["+", "-", "", "x1", " 1", "+-1", "-1"] {
+        assert_eq!(None, int_matches_bytes(text, uint::<u32>()));
+        assert_eq!(None, int_matches_bytes(text, int::<u32>()));
+    }
+    assert_eq!(Some(-1), int_matches_bytes("-1", int::<i8>()));
+
+    let mut input: &[u8] = b"GET 200 1532\n";
+    let res: Result<_, FabError> =
+        (b"GET ".as_slice(), uint::<u16>(), b' ', uint::<u64>()).fab(&mut input);
+    let (_, status, _, size) = res.unwrap();
+    assert_eq!((200, 1532), (status, size));
+    assert_eq!(b"\n", input);
+    let mut input: &[u8] = &[b'1', 0xFF];
+    let res: Result<_, FabError> = uint::<u8>().fab(&mut input);
+    assert_eq!(1, res.unwrap());
+    assert_eq!(&[0xFF], input);
+}
+
+#[test]
+fn util_float_success() {
+    let cases = [
+        ("1.5x", 1.5, "x"),
+        (".5", 0.5, ""),
+        ("5.", 5.0, ""),
+        ("-2", -2.0, ""),
+        ("+1e10", 1e10, ""),
+        ("2.5E-3,", 2.5e-3, ","),
+        ("1e+2e", 100.0, "e"),
+    ];
+    for (text, expected, rest) in cases {
+        let mut input = text;
+        let res: Result<_, FabError> = float().fab(&mut input);
+        assert_eq!(expected, res.unwrap());
+        assert_eq!(rest, input);
+    }
+    let mut input = "1.5x";
+    let res: Result<_, FabError> = float_str.fab(&mut input);
+    assert_eq!("1.5", res.unwrap());
+    assert_eq!("x", input);
+}
+
+#[test]
+fn util_float_fail() {
+    for text in ["1e", "1.5E+", "-", ".", "-.e5", "inf", "NaN", " 1"] {
+        let mut input = text;
+        let res: Result<f64, FabError> = float().fab(&mut input);
+        assert!(res.is_err());
+        assert_eq!(text, input);
+    }
+    let mut input = "inf";
+    let res: Result<_, FabError> = alt((float(), "inf".fab_value(f64::INFINITY))).fab(&mut input);
+    assert_eq!(f64::INFINITY, res.unwrap());
+}
+
+#[test]
+fn util_radix_uint_success() {
+    let cases: [(&str, RadixUInt<u8>, u8, &str); 9] = [
+        ("0xFF", hex_uint(), u8::MAX, ""),
+        ("0XfE", hex_uint(), 254, ""),
+        ("0x0_F_f", hex_uint(), 255, ""),
+        ("0xF__F", hex_uint(), 15, "__F"),
+        ("0xFF_", hex_uint(), 255, "_"),
+        ("0o377", oct_uint(), u8::MAX, ""),
+        ("0O1_7z", oct_uint(), 15, "z"),
+        ("0b1111_1111", bin_uint(), u8::MAX, ""),
+        ("0B102", bin_uint(), 2, "2"),
+    ];
+    for (text, parser, expected, rest) in cases {
+        let mut input = text;
+        let res: Result<_, FabError> = parser.fab(&mut input);
+        assert_eq!(expected, res.unwrap());
+        assert_eq!(rest, input);
+    }
+    let mut input = "0x7F";
+    let res: Result<_, FabError> = hex_uint::<i8>().fab(&mut input);
+    assert_eq!(i8::MAX, res.unwrap());
+}
+
+#[test]
+fn util_radix_uint_fail() {
+    let cases: [(&str, RadixUInt<u8>); 10] = [
+        ("0x100", hex_uint()),
+        ("0o400", oct_uint()),
+        ("0b1_0000_0000", bin_uint()),
+        ("0x", hex_uint()),
+        ("0x_1", hex_uint()),
+        ("0o8", oct_uint()),
+        ("0b", bin_uint()),
+        ("0o7", hex_uint()),
+        ("x1", hex_uint()),
+        ("", hex_uint()),
+    ];
+    for (text, parser) in cases {
+        let mut input = text;
+        let res: Result<_, FabError> = parser.fab(&mut input);
+        assert!(res.is_err());
+        assert_eq!(text, input);
+    }
+    let mut input = "0x100";
+    let res: Result<_, FabError> = hex_uint::<u8>().fab(&mut input);
+    assert!(res.unwrap_err().to_string().contains("IntOverflowError"));
+    let mut input = "0x80";
+    let res: Result<_, FabError> = hex_uint::<i8>().fab(&mut input);
+    assert!(res.unwrap_err().to_string().contains("IntOverflowError"));
+}
+
+#[test]
+fn util_hex_bytes() {
+    let mut input = "DeadBEEF rest";
+    let res: Result<_, FabError> = hex_bytes().fab(&mut input);
+    assert_eq!(vec![0xde, 0xad, 0xbe, 0xef], res.unwrap());
+    assert_eq!(" rest", input);
+    let mut input = "";
+    let res: Result<_, FabError> = hex_bytes().fab(&mut input);
+    assert_eq!(Vec::<u8>::new(), res.unwrap());
+    let mut input = "xyz";
+    let res: Result<_, FabError> = hex_bytes().fab(&mut input);
+    assert_eq!(Vec::<u8>::new(), res.unwrap());
+    assert_eq!("xyz", input);
+    for text in ["", "xyz"] {
+        let mut input = text;
+        let res: Result<_, FabError> = hex_bytes().non_empty().fab(&mut input);
+        assert_eq!(Some(0), res.unwrap_err().offset(text));
+        assert_eq!(text, input);
+    }
+}
+
+#[test]
+fn util_hex_bytes_odd_digits() {
+    let text = "deadbee";
+    let mut input = text;
+    let res: Result<_, FabError> = hex_bytes().fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(text, input);
+    assert_eq!(Some(6), err.offset(text));
+    assert_eq!(
+        Some(&HexBytesError::OddDigits),
+        err.downcast_cause::<HexBytesError>()
+    );
+    let text = "00:1a:2";
+    let mut input = text;
+    let res: Result<_, FabError> = hex_bytes().separated().fab(&mut input);
+    assert_eq!(Some(6), res.unwrap_err().offset(text));
+    assert_eq!(text, input);
+}
+
+#[test]
+fn util_hex_bytes_separated() {
+    let mut input = "00:1a:2B:3c:4d:5e";
+    let res: Result<_, FabError> = hex_bytes().separated().fab(&mut input);
+    assert_eq!(vec![0x00, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e], res.unwrap());
+    assert_eq!("", input);
+    let mut input = "00-1a 2b";
+    let res: Result<_, FabError> = hex_bytes().separated().fab(&mut input);
+    assert_eq!(vec![0x00, 0x1a], res.unwrap());
+    assert_eq!(" 2b", input);
+    let mut input = "01 02 zz";
+    let res: Result<_, FabError> = hex_bytes().separated().fab(&mut input);
+    assert_eq!(vec![1, 2], res.unwrap());
+    assert_eq!(" zz", input);
+    let mut input = "01:02";
+    let res: Result<_, FabError> = hex_bytes().fab(&mut input);
+    assert_eq!(vec![1], res.unwrap());
+    assert_eq!(":02", input);
+}
+
+#[test]
+fn util_hex_bytes_exact() {
+    let mut input = "00:1A:2b:3c:4d:5e!";
+    let res: Result<_, FabError> = hex_bytes_exact::<6>().separated().fab(&mut input);
+    assert_eq!([0x00, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e], res.unwrap());
+    assert_eq!("!", input);
+    let mut input = "cafe";
+    let res: Result<[u8; 0], FabError> = hex_bytes_exact().fab(&mut input);
+    assert!(res.is_err());
+    let mut input = "";
+    let res: Result<[u8; 0], FabError> = hex_bytes_exact().fab(&mut input);
+    assert_eq!([0u8; 0], res.unwrap());
+    for (text, offset) in [("cafe", 4), ("cafebabe", 6), ("ca fe", 2)] {
+        let mut input = text;
+        let res: Result<_, FabError> = hex_bytes_exact::<3>().fab(&mut input);
+        let err = res.unwrap_err();
+        assert_eq!(text, input);
+        assert_eq!(Some(offset), err.offset(text));
+        assert_eq!(
+            Some(&HexBytesError::Length),
+            err.downcast_cause::<HexBytesError>()
+        );
+    }
+    let text = "caf";
+    let mut input = text;
+    let res: Result<_, FabError> = hex_bytes_exact::<2>().fab(&mut input);
+    assert_eq!(
+        Some(&HexBytesError::OddDigits),
+        res.unwrap_err().downcast_cause::<HexBytesError>()
+    );
+}
+
+#[test]
+fn util_ipv4_addr() {
+    let mut input = "192.0.2.1:80";
+    let res: Result<_, FabError> = ipv4_addr().fab(&mut input);
+    assert_eq!(Ipv4Addr::new(192, 0, 2, 1), res.unwrap());
+    assert_eq!(":80", input);
+    let text = "255.255.255.256";
+    let mut input = text;
+    let res: Result<_, FabError> = ipv4_addr().fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(text, input);
+    assert_eq!(Some(12), err.offset(text));
+    assert!(err.downcast_cause::<IntOverflowError>().is_some());
+    let mut input = "1.2.3";
+    let res: Result<_, FabError> = ipv4_addr().fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("1.2.3", input);
+    //Leading zeros are rejected like std does, but a lone zero is fine.
+    let text = "010.0.0.1";
+    let mut input = text;
+    let res: Result<_, FabError> = ipv4_addr().fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(text, input);
+    assert_eq!(Some(0), err.offset(text));
+    assert_eq!(
+        Some(&IpAddrError::LeadingZero),
+        err.downcast_cause::<IpAddrError>()
+    );
+    assert!(text.parse::<Ipv4Addr>().is_err());
+    let mut input = "10.0.0.100";
+    let res: Result<_, FabError> = ipv4_addr().fab(&mut input);
+    assert_eq!(Ipv4Addr::new(10, 0, 0, 100), res.unwrap());
+}
+
+#[test]
+fn util_ipv6_addr() {
+    let cases = [
+        ("::", Ipv6Addr::UNSPECIFIED, ""),
+        ("::1", Ipv6Addr::LOCALHOST, ""),
+        ("2001:DB8::1 x", "2001:db8::1".parse().unwrap(), " x"),
+        ("fe80::%eth0", "fe80::".parse().unwrap(), "%eth0"),
+        (
+            "::ffff:192.0.2.1",
+            Ipv4Addr::new(192, 0, 2, 1).to_ipv6_mapped(),
+            "",
+        ),
+        ("1:2:3:4:5:6:7:8:", "1:2:3:4:5:6:7:8".parse().unwrap(), ":"),
+        ("1:2:3:4:5:6::]", "1:2:3:4:5:6::".parse().unwrap(), "]"),
+        (
+            "1:2:3:4:5:6:1.2.3.4",
+            "1:2:3:4:5:6:1.2.3.4".parse().unwrap(),
+            "",
+        ),
+    ];
+    for (text, addr, rest) in cases {
+        let mut input = text;
+        let res: Result<_, FabError> = ipv6_addr().fab(&mut input);
+        assert_eq!(addr, res.unwrap(), "{text}");
+        assert_eq!(rest, input);
+    }
+}
+
+#[test]
+fn util_ipv6_addr_malformed() {
+    let cases = [
+        ("1::2::3", 4, Some(IpAddrError::DoubleColon)),
+        ("1:2:3:4:5:6:7:8:9", 16, Some(IpAddrError::GroupCount)),
+        ("1:2:3:4:5:6:7::8", 15, Some(IpAddrError::GroupCount)),
+        ("1:2:3", 5, Some(IpAddrError::GroupCount)),
+        ("1:2:3:4:5:6:7:1.2.3.4", 14, Some(IpAddrError::GroupCount)),
+        ("::ffff:1.2.3.400", 13, None),
+        ("1:23456::", 2, Some(IpAddrError::GroupLength)),
+        ("00000001::1", 0, Some(IpAddrError::GroupLength)),
+        ("::ffff:1.2.3.04", 13, Some(IpAddrError::LeadingZero)),
+        (":1", 0, None),
+    ];
+    for (text, offset, cause) in cases {
+        let mut input = text;
+        let res: Result<_, FabError> = ipv6_addr().fab(&mut input);
+        let err = res.unwrap_err();
+        assert_eq!(text, input);
+        assert_eq!(Some(offset), err.offset(text), "{text}");
+        assert_eq!(
+            cause.as_ref(),
+            err.downcast_cause::<IpAddrError>(),
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn util_socket_addr() {
+    let mut input = "[::1]:8080 rest";
+    let res: Result<_, FabError> = socket_addr().fab(&mut input);
+    assert_eq!(
+        SocketAddr::new(Ipv6Addr::LOCALHOST.into(), 8080),
+        res.unwrap()
+    );
+    assert_eq!(" rest", input);
+    let mut input = "127.0.0.1:80";
+    let res: Result<_, FabError> = socket_addr().fab(&mut input);
+    assert_eq!(SocketAddr::from(([127, 0, 0, 1], 80)), res.unwrap());
+    assert_eq!("", input);
+    for text in [
+        "[::1]",
+        "::1:80",
+        "[::1:80",
+        "1.2.3.4:65536",
+        "[1::2::3]:80",
+    ] {
+        let mut input = text;
+        let res: Result<_, FabError> = socket_addr().fab(&mut input);
+        assert!(res.is_err(), "{text}");
+        assert_eq!(text, input);
+    }
+}
+
+#[test]
+fn util_prefixed_int() {
+    let cases = [
+        ("0x1F", Some(31), ""),
+        ("0o17", Some(15), ""),
+        ("0B101", Some(5), ""),
+        ("42,", Some(42), ","),
+        ("0", Some(0), ""),
+        ("0x", None, "0x"),
+        ("0b2", None, "0b2"),
+        ("0x1_0000_0000", None, "0x1_0000_0000"),
+    ];
+    for (text, expected, rest) in cases {
+        let mut input = text;
+        let res: Result<u32, FabError> = prefixed_int().fab(&mut input);
+        assert_eq!(expected, res.ok());
+        assert_eq!(rest, input);
+    }
+    let mut input = "0xff,0o7,0b1";
+    let res: Result<_, FabError> = (prefixed_int::<u64>(), ',')
+        .fab_map(|(num, _)| num)
+        .fab_repeat()
+        .fab(&mut input);
+    assert_eq!(vec![255, 7], res.unwrap());
+    assert_eq!("0b1", input);
+}
+
+#[test]
+fn util_space_success() {
+    let mut input = " \t x";
+    let res: Result<_, FabError> = space0.fab(&mut input);
+    assert_eq!(" \t ", res.unwrap());
+    assert_eq!("x", input);
+    let res: Result<_, FabError> = space0.fab(&mut input);
+    assert_eq!("", res.unwrap());
+    let res: Result<_, FabError> = space1.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("x", input);
+    let mut input = " \n\ty";
+    let res: Result<_, FabError> = space1.fab(&mut input);
+    assert_eq!(" ", res.unwrap());
+    assert_eq!("\n\ty", input);
+    let res: Result<_, FabError> = multispace1.fab(&mut input);
+    assert_eq!("\n\t", res.unwrap());
+    let res: Result<_, FabError> = multispace0.fab(&mut input);
+    assert_eq!("", res.unwrap());
+    assert_eq!("y", input);
+}
+
+#[test]
+fn util_ws_success() {
+    let mut input = "x \n = 2.5 ;";
+    let res: Result<_, FabError> = ('x', ws("="), float(), ws(';')).fab(&mut input);
+    assert_eq!(('x', "=", 2.5, ';'), res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn util_ws_fail() {
+    let source = "x   + 1";
+    let mut input = source;
+    let res: Result<_, FabError> = ('x', ws('=')).fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(source, input);
+    assert_eq!(Some(4), err.offset(source));
+}
+
+#[test]
+fn util_line_ending() {
+    let mut input = "\r\n\nx";
+    let res: Result<_, FabError> = (line_ending, line_ending).fab(&mut input);
+    assert_eq!(("\r\n", "\n"), res.unwrap());
+    assert_eq!("x", input);
+    let mut input = "\rx";
+    let res: Result<_, FabError> = line_ending.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("\rx", input);
+}
+
+#[test]
+fn util_not_line_ending() {
+    let cases = [
+        ("abc\r\ndef", "abc", "\r\ndef"),
+        ("a\rb\nc", "a\rb", "\nc"),
+        ("abc\r", "abc\r", ""),
+        ("\n", "", "\n"),
+        ("", "", ""),
+    ];
+    for (text, line, rest) in cases {
+        let mut input = text;
+        let res: Result<_, FabError> = not_line_ending.fab(&mut input);
+        assert_eq!(line, res.unwrap());
+        assert_eq!(rest, input);
+    }
+}
+
+#[test]
+fn util_rest_of_line_document() {
+    let mut input = "[section]\r\nkey=va\rlue\n\nlast";
+    let res: Result<Vec<_>, FabError> = rest_of_line.fab_repeat().fab(&mut input);
+    assert_eq!(vec!["[section]", "key=va\rlue", "", "last"], res.unwrap());
+    assert_eq!("", input);
+    let mut input = "a\n";
+    let res: Result<Vec<_>, FabError> = rest_of_line.fab_repeat().fab(&mut input);
+    assert_eq!(vec!["a"], res.unwrap());
+    let res: Result<_, FabError> = rest_of_line.fab(&mut input);
+    assert!(res.is_err());
+}
+
+#[test]
+fn util_identifier_success() {
+    let mut input = "foo_bar1 rest";
+    let res: Result<_, FabError> = util::identifier.fab(&mut input);
+    assert_eq!("foo_bar1", res.unwrap());
+    assert_eq!(" rest", input);
+    for text in ["x", "_", "_1"] {
+        let mut input = text;
+        let res: Result<_, FabError> = util::identifier.fab(&mut input);
+        assert_eq!(text, res.unwrap());
+        assert_eq!("", input);
+    }
+    let mut input = "größe=1";
+    let res: Result<_, FabError> = unicode_identifier.fab(&mut input);
+    assert_eq!("größe", res.unwrap());
+    assert_eq!("=1", input);
+}
+
+#[test]
+fn util_identifier_fail() {
+    for text in ["1abc", "", " abc", "-"] {
+        let mut input = text;
+        let res: Result<_, FabError> = util::identifier.fab(&mut input);
+        assert!(res.is_err());
+        assert_eq!(text, input);
+    }
+    let mut input = "größe";
+    let res: Result<_, FabError> = util::identifier.fab(&mut input);
+    assert_eq!("gr", res.unwrap());
+    assert_eq!("öße", input);
+    let mut input = "ößx";
+    let res: Result<_, FabError> = util::identifier.fab(&mut input);
+    assert!(res.is_err());
+}
+
+#[test]
+fn util_quoted_string_success() {
+    let mut input = r#""plain" rest"#;
+    let res: Result<_, FabError> = quoted_string.fab(&mut input);
+    let out = res.unwrap();
+    assert!(matches!(out, Cow::Borrowed("plain")));
+    assert_eq!(" rest", input);
+    let mut input = r#""a\"b\\c\n\t\u{1F600}\u{e9}d""#;
+    let res: Result<_, FabError> = quoted_string.fab(&mut input);
+    let out = res.unwrap();
+    assert!(matches!(out, Cow::Owned(_)));
+    assert_eq!("a\"b\\c\n\t\u{1F600}\u{e9}d", out);
+    assert_eq!("", input);
+    let mut input = r#"'it\'s "fine"'x"#;
+    let res: Result<_, FabError> = quoted('\'').fab(&mut input);
+    assert_eq!("it's \"fine\"", res.unwrap());
+    assert_eq!("x", input);
+    let mut input = r#""""#;
+    let res: Result<_, FabError> = quoted_string.fab(&mut input);
+    assert_eq!("", res.unwrap());
+}
+
+#[test]
+fn util_quoted_string_fail() {
+    let cases = [
+        (r#"x "abc"#, 2, QuotedStringError::Unterminated),
+        (r#"x "abc\""#, 2, QuotedStringError::Unterminated),
+        (r#"x "a\qb""#, 4, QuotedStringError::InvalidEscape),
+        (r#"x "a\u{110000}""#, 4, QuotedStringError::InvalidUnicode),
+        (r#"x "a\u{D800}""#, 4, QuotedStringError::InvalidUnicode),
+        (r#"x "a\u{}""#, 4, QuotedStringError::InvalidUnicode),
+        (r#"x "a\u12""#, 4, QuotedStringError::InvalidUnicode),
+    ];
+    for (text, offset, cause) in cases {
+        let mut input = text;
+        let res: Result<_, FabError> = ("x ", quoted_string).fab(&mut input);
+        let err = res.unwrap_err();
+        assert_eq!(text, input);
+        assert_eq!(Some(offset), err.offset(text));
+        assert!(err.to_string().contains(&format!("{:?}", cause)));
+    }
+    let mut input = "abc";
+    let res: Result<_, FabError> = quoted_string.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("abc", input);
+}
+
+#[test]
+fn escaped_slice() {
+    let normal = take_not(|c: char| c == '\\' || c == '"');
+    let escapable = |c: char| "\\\"nrt".contains(c);
+    let mut input = r#"ab\"c\\d" rest"#;
+    let res: Result<_, FabError> = escaped(&normal, '\\', escapable).fab(&mut input);
+    assert_eq!(r#"ab\"c\\d"#, res.unwrap());
+    assert_eq!(r#"" rest"#, input);
+    let mut input = r#"" rest"#;
+    let res: Result<_, FabError> = escaped(&normal, '\\', escapable).fab(&mut input);
+    assert_eq!("", res.unwrap());
+    assert_eq!(r#"" rest"#, input);
+    let mut input = [1u8, 2, 0, 9, 3, 4].as_slice();
+    let res: Result<_, FabError> = escaped(take_not(0u8), 0u8, |b: u8| b == 9).fab(&mut input);
+    assert_eq!(&[1u8, 2, 0, 9, 3, 4], res.unwrap());
+}
+
+#[test]
+fn escaped_zero_length() {
+    let mut input = "abc";
+    let res: Result<_, FabError> = escaped(opt('x'), opt('\\'), opt('n')).fab(&mut input);
+    assert_eq!("", res.unwrap());
+    assert_eq!("abc", input);
+}
+
+#[test]
+fn escaped_fail() {
+    let text = r#"ab\qc"#;
+    let mut input = text;
+    let normal = take_not(|c: char| c == '\\');
+    let res: Result<_, FabError> =
+        escaped(&normal, '\\', |c: char| "\\\"nrt".contains(c)).fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(text, input);
+    assert_eq!(Some(2), err.offset(text));
+    assert_eq!(
+        Some(vec![(2, ParserType::Escaped)]),
+        err.stack_offsets(text)
+    );
+}
+
+#[test]
+fn escaped_transform_str() {
+    let transform = alt((
+        'n'.fab_value('\n'),
+        '\\'.fab_value('\\'),
+        '"'.fab_value('"'),
+    ));
+    let normal = take_not(|c: char| c == '\\' || c == '"');
+    let mut input = r#"a\nb\"c\\"x"#;
+    let res: Result<_, FabError> = escaped_transform(&normal, '\\', &transform).fab(&mut input);
+    assert_eq!(Cow::<str>::Owned("a\nb\"c\\".to_string()), res.unwrap());
+    assert_eq!(r#""x"#, input);
+    let mut input = r#"abc"x"#;
+    let res: Result<_, FabError> = escaped_transform(&normal, '\\', &transform).fab(&mut input);
+    assert!(matches!(res.unwrap(), Cow::Borrowed("abc")));
+    let mut input = r#"a\tb"#;
+    let tab = 't'.fab_value("    ");
+    let res: Result<_, FabError> = escaped_transform(&normal, '\\', tab).fab(&mut input);
+    assert_eq!("a    b", res.unwrap());
+    let text = r#"a\qb"#;
+    let mut input = text;
+    let res: Result<_, FabError> = escaped_transform(&normal, '\\', &transform).fab(&mut input);
+    assert_eq!(Some(1), res.unwrap_err().offset(text));
+    assert_eq!(text, input);
+}
+
+#[test]
+fn util_rust_number() {
+    let cases = [
+        ("1_000_000 x", "1_000_000", 10, None),
+        ("0xFF_u8 x", "FF", 16, Some("u8")),
+        ("0xFF_f32 x", "FF_f32", 16, None),
+        ("3.14_f32 x", "3.14", 10, Some("f32")),
+        ("2.5e-3 x", "2.5e-3", 10, None),
+        ("1E1_0f64 x", "1E1_0", 10, Some("f64")),
+        ("0b1010i64 x", "1010", 2, Some("i64")),
+        ("0o7_7usize x", "7_7", 8, Some("usize")),
+        ("42u128 x", "42", 10, Some("u128")),
+    ];
+    for (text, digits, radix, suffix) in cases {
+        let mut input = text;
+        let res: Result<_, FabError> = rust_number.fab(&mut input);
+        let expected = NumberLiteral {
+            digits,
+            radix,
+            suffix,
+        };
+        assert_eq!(expected, res.unwrap());
+        assert_eq!(" x", input);
+    }
+    let mut input = "1.max()";
+    let res: Result<_, FabError> = rust_number.fab(&mut input);
+    assert_eq!("1", res.unwrap().digits);
+    assert_eq!(".max()", input);
+    let mut input = "7u8x";
+    let res: Result<_, FabError> = rust_number.fab(&mut input);
+    assert_eq!(None, res.unwrap().suffix);
+    assert_eq!("u8x", input);
+}
+
+#[test]
+fn util_rust_number_fail() {
+    for text in ["_1", "1__2", "1_", "1_x", "0x", "0x_1", "1e", "1e+", "abc"] {
+        let mut input = text;
+        let res: Result<_, FabError> = rust_number.fab(&mut input);
+        assert!(res.is_err(), "{}", text);
+        assert_eq!(text, input);
+    }
+}
+
+#[test]
+fn fab_dbg_transparent() {
+    let parser = |input: &mut &str| -> Result<_, FabError> {
+        ('a'.fab_dbg("a"), ('b', 'c').fab_dbg("bc"))
+            .fab_dbg("abc")
+            .fab(input)
+    };
+    let plain = |input: &mut &str| -> Result<_, FabError> { ('a', ('b', 'c')).fab(input) };
+    let mut input = "abcd";
+    assert_eq!(('a', ('b', 'c')), parser(&mut input).unwrap());
+    assert_eq!("d", input);
+    let text = "abxd";
+    let mut input = text;
+    let err = parser(&mut input).unwrap_err();
+    assert_eq!(text, input);
+    let mut plain_input = text;
+    let plain_err = plain(&mut plain_input).unwrap_err();
+    assert_eq!(plain_err.stack_offsets(text), err.stack_offsets(text));
+    let mut input = [1u8, 2].as_slice();
+    let res: Result<_, FabError> = 1u8.fab_dbg("byte").fab(&mut input);
+    assert_eq!(1, res.unwrap());
+    assert_eq!(&[2], input);
+}
+
+#[test]
+fn take_while_matches_repeat() {
+    let inputs = [
+        "",
+        " ",
+        "  a",
+        "\t\n x",
+        "   ",
+        "a  ",
+        " é ",
+        "é",
+        " \u{3000}x",
+        "  ab  ",
+    ];
+    let bounds = [
+        (0, usize::MAX),
+        (1, usize::MAX),
+        (2, usize::MAX),
+        (0, 2),
+        (1, 3),
+        (3, 3),
+        (0, 0),
+        (2, 1),
+    ];
+    for text in inputs {
+        for (min, max) in bounds {
+            let mut fast_input = text;
+            let fast: Result<_, FabError> = take_while(char::is_whitespace)
+                .min(min)
+                .max(max)
+                .fab(&mut fast_input);
+            let mut slow_input = text;
+            let slow: Result<_, FabError> = char::is_whitespace
+                .fab_repeat()
+                .min(min)
+                .max(max)
+                .as_input_slice()
+                .fab(&mut slow_input);
+            assert_eq!(slow_input, fast_input, "{:?} {}..{}", text, min, max);
+            match (slow, fast) {
+                (Ok(slow), Ok(fast)) => assert_eq!(slow, fast),
+                (Err(slow), Err(fast)) => {
+                    assert_eq!(slow.stack_offsets(text), fast.stack_offsets(text))
+                }
+                (slow, fast) => panic!("{:?} {}..{}: {:?} {:?}", text, min, max, slow, fast),
+            }
+        }
+    }
+}
+
+#[test]
+fn take_while_predicates() {
+    let mut input = "  \tabc";
+    let res: Result<_, FabError> = take_while(char::is_ascii_whitespace).fab(&mut input);
+    assert_eq!("  \t", res.unwrap());
+    assert_eq!("abc", input);
+    let mut input = "ééa";
+    let res: Result<_, FabError> = take_while(|c: char| c == 'é').bound(1..=1).fab(&mut input);
+    assert_eq!("é", res.unwrap());
+    assert_eq!("éa", input);
+}
+
+#[test]
+fn repeat_empty_match_default_fails() {
+    let text = "aab";
+    let mut input = text;
+    let res: Result<_, FabError> = opt('a').fab_repeat().fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(text, input);
+    assert_eq!(
+        Some(vec![(2, ParserType::RepeatIter), (0, ParserType::Repeat)]),
+        err.stack_offsets(text)
+    );
+}
+
+#[test]
+fn repeat_allow_empty_matches() {
+    let mut input = "aab";
+    let res: Result<_, FabError> = opt('a').fab_repeat().allow_empty_matches().fab(&mut input);
+    assert_eq!(vec![Some('a'), Some('a')], res.unwrap());
+    assert_eq!("b", input);
+    let mut input = "b";
+    let res: Result<_, FabError> = opt('a')
+        .fab_repeat()
+        .allow_empty_matches()
+        .count()
+        .fab(&mut input);
+    assert_eq!(0, res.unwrap());
+    assert_eq!("b", input);
+    let mut input = "aab";
+    let res: Result<_, FabError> = opt('a')
+        .fab_repeat()
+        .allow_empty_matches()
+        .min(3)
+        .as_input_slice()
+        .fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("aab", input);
+}
+
+#[test]
+fn repeat_bounds_matrix() {
+    let text = "aaab";
+    let cases = [
+        ('a'.fab_repeat().max(0), Some(0)),
+        ('a'.fab_repeat().max(2), Some(2)),
+        ('a'.fab_repeat().max(3), Some(3)),
+        ('a'.fab_repeat().max(5), Some(3)),
+        ('a'.fab_repeat().min(3).max(3), Some(3)),
+        ('a'.fab_repeat().min(4).max(5), None),
+        ('a'.fab_repeat().min(3).max(2), None),
+        ('a'.fab_repeat().bound(1..=2), Some(2)),
+        ('a'.fab_repeat().bound(1..3), Some(2)),
+        ('a'.fab_repeat().bound(..=0), Some(0)),
+        ('a'.fab_repeat().bound(2..2), None),
+        ('a'.fab_repeat().bound(2..), Some(3)),
+    ];
+    for (index, (parser, expected)) in cases.into_iter().enumerate() {
+        let mut input = text;
+        let res: Result<Vec<char>, FabError> = parser.fab(&mut input);
+        match expected {
+            Some(count) => {
+                assert_eq!(count, res.unwrap().len(), "case {}", index);
+                assert_eq!(&text[count..], input, "case {}", index);
+            }
+            None => {
+                assert!(res.is_err(), "case {}", index);
+                assert_eq!(text, input, "case {}", index);
+            }
+        }
+    }
+}
+
+#[test]
+fn sequence_15_success() {
+    let mut input = "abcdefghijklmnoz";
+    let res: Result<_, FabError> = (
+        'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o',
+    )
+        .fab(&mut input);
+    let (a, _, _, _, _, _, _, _, _, _, _, _, _, n, o) = res.unwrap();
+    assert_eq!(('a', 'n', 'o'), (a, n, o));
+    assert_eq!("z", input);
+}
+
+#[test]
+fn sequence_15_fail() {
+    let text = "abcdefghijklmnz";
+    let mut input = text;
+    let res: Result<_, FabError> = (
+        'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o',
+    )
+        .fab(&mut input);
+    let err = res.err().unwrap();
+    assert_eq!(text, input);
+    assert_eq!(
+        Some(vec![(14, ParserType::Tag), (0, ParserType::Sequence)]),
+        err.stack_offsets(text)
+    );
+}
+
+#[test]
+fn sequence_21_replay() {
+    let parser = fabparse::recorded((
+        'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r',
+        's', 't', 'u',
+    ));
+    let text = "abcdefghijklmnopqrstuz";
+    let mut input = text;
+    let res: Result<_, FabError> = parser.fab_recorded(&mut input);
+    let (out, trace) = res.unwrap();
+    assert_eq!("z", input);
+    assert_eq!(('a', 'u'), (out.0, out.20));
+    let replayed = parser.replay(&trace, text).unwrap();
+    assert_eq!(('a', 'u'), (replayed.0, replayed.20));
+}
+
+#[test]
+fn recorded_alt_error_matches_alt() {
+    let branches = || alt((('a', 'b'), ('a', 'c'), ('x', 'y')));
+    let parser = fabparse::recorded(branches());
+    let mut input = "ad";
+    let res: Result<_, FabError> = parser.fab_recorded(&mut input);
+    let recorded = res.unwrap_err();
+    assert_eq!("ad", input);
+    let res: Result<_, FabError> = branches().fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(
+        Some(&Expected::OneOf(vec![
+            Expected::Char('b'),
+            Expected::Char('c')
+        ])),
+        recorded.expected()
+    );
+    assert_eq!(err.expected(), recorded.expected());
+    assert_eq!(err.stack_offsets("ad"), recorded.stack_offsets("ad"));
+}
+
+#[test]
+fn fn_bool_by_value_and_ref() {
+    let mut input = "ab 12";
+    let res: Result<_, FabError> = char::is_alphabetic
+        .fab_repeat()
+        .as_input_slice()
+        .fab(&mut input);
+    assert_eq!("ab", res.unwrap());
+    let res: Result<_, FabError> = char::is_whitespace.fab(&mut input);
+    assert_eq!(' ', res.unwrap());
+    let res: Result<_, FabError> = char::is_numeric
+        .fab_repeat()
+        .as_input_slice()
+        .fab(&mut input);
+    assert_eq!("12", res.unwrap());
+    assert_eq!("", input);
+    let chars = ['a', '1'];
+    let mut input = chars.as_slice();
+    let res: Result<_, FabError> = (char::is_alphabetic, char::is_ascii_digit).fab(&mut input);
+    assert_eq!(('a', '1'), res.unwrap());
+    let mut input = b"12ab".as_slice();
+    let res: Result<_, FabError> = u8::is_ascii_digit
+        .fab_repeat()
+        .as_input_slice()
+        .fab(&mut input);
+    assert_eq!(b"12", res.unwrap());
+    let res: Result<_, FabError> = (|b: u8| b == b'a').fab(&mut input);
+    assert_eq!(b'a', res.unwrap());
+    assert_eq!(b"b", input);
+}
+
+#[test]
+fn str_tag_on_bytes() {
+    let mut input = b"GET /index HTTP/1.1".as_slice();
+    let res: Result<_, FabError> = (
+        "GET",
+        b" ",
+        take_not(b' ').fab_repeat().as_input_slice(),
+        " HTTP/1.1",
+    )
+        .fab(&mut input);
+    let (method, _, path, version) = res.unwrap();
+    assert_eq!(b"GET", method);
+    assert_eq!(b"/index", path);
+    assert_eq!(b" HTTP/1.1", version);
+    assert_eq!(b"", input);
+    let mut input = "héllo".as_bytes();
+    let res: Result<_, FabError> = "hé".fab(&mut input);
+    assert_eq!("hé".as_bytes(), res.unwrap());
+    assert_eq!(b"llo", input);
+}
+
+#[test]
+fn str_tag_on_bytes_fail() {
+    let text = b"POST /";
+    let mut input = text.as_slice();
+    let res: Result<_, FabError> = "PUT".fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(text, input);
+    assert_eq!(Some(0), err.offset(text.as_slice()));
+    let mut input = b"GE".as_slice();
+    let res: Result<_, FabError> = "GET".fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!(b"GE", input);
+}
+
+#[test]
+fn ascii_no_case_char() {
+    let mut input = "Xx";
+    let res: Result<_, FabError> = (ascii_no_case('x'), ascii_no_case('X')).fab(&mut input);
+    assert_eq!(('X', 'x'), res.unwrap());
+    let mut input = "#aB3fE0z";
+    let res: Result<_, FabError> = (
+        '#',
+        alt((ascii_no_case('a'..='f'), '0'..='9'))
+            .fab_repeat()
+            .as_input_slice(),
+    )
+        .fab(&mut input);
+    assert_eq!(('#', "aB3fE0"), res.unwrap());
+    assert_eq!("z", input);
+}
+
+#[test]
+fn ascii_no_case_bytes() {
+    let mut input = b"GeT".as_slice();
+    let res: Result<_, FabError> = (
+        ascii_no_case(b'g'),
+        ascii_no_case(b'E'),
+        ascii_no_case(b'a'..=b'z'),
+    )
+        .fab(&mut input);
+    assert_eq!((b'G', b'e', b'T'), res.unwrap());
+    assert_eq!(b"", input);
+}
+
+#[test]
+fn ascii_no_case_fail() {
+    for (text, matcher) in [("É", 'é'), ("g", 'f'), ("", 'a')] {
+        let mut input = text;
+        let res: Result<_, FabError> = ascii_no_case(matcher).fab(&mut input);
+        assert!(res.is_err());
+        assert_eq!(text, input);
+    }
+    let mut input = "G";
+    let res: Result<_, FabError> = ascii_no_case('a'..='f').fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("G", input);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kw {
+    In,
+    Int,
+    Integer,
+    Let,
+}
+
+#[test]
+fn literals_longest_match() {
+    let pairs = [
+        ("in", Kw::In),
+        ("int", Kw::Int),
+        ("integer", Kw::Integer),
+        ("let", Kw::Let),
+    ];
+    let orders = [pairs, [pairs[2], pairs[3], pairs[1], pairs[0]]];
+    for order in orders {
+        let keywords = literals(order);
+        for (text, kw, rest) in [
+            ("integer x", Kw::Integer, " x"),
+            ("intege", Kw::Int, "ege"),
+            ("int", Kw::Int, ""),
+            ("inx", Kw::In, "x"),
+            ("let", Kw::Let, ""),
+        ] {
+            let mut input = text;
+            let res: Result<_, FabError> = keywords.fab(&mut input);
+            assert_eq!(kw, res.unwrap());
+            assert_eq!(rest, input);
+        }
+    }
+    let keywords: fabparse::tag::Literals<Kw> = pairs.into_iter().collect();
+    let mut input = "in".as_bytes();
+    let res: Result<_, FabError> = keywords.fab(&mut input);
+    assert_eq!(Kw::In, res.unwrap());
+    assert!(input.is_empty());
+}
+
+#[test]
+fn literals_fail() {
+    let keywords = literals([("in", 1), ("let", 2), ("in", 3), ("→", 4)]);
+    let mut input = "→x";
+    let res: Result<_, FabError> = keywords.fab(&mut input);
+    assert_eq!(4, res.unwrap());
+    assert_eq!("x", input);
+    let mut input = "in";
+    let res: Result<_, FabError> = keywords.fab(&mut input);
+    assert_eq!(3, res.unwrap());
+    for text in ["le", "lex", "", "i"] {
+        let mut input = text;
+        let res: Result<i32, FabError> = keywords.fab(&mut input);
+        let err = res.unwrap_err();
+        assert_eq!(text, input);
+        assert_eq!(Some(0), err.offset(text));
+        assert_eq!(
+            Some(&Expected::OneOf(vec![
+                Expected::Str("in".into()),
+                Expected::Str("let".into()),
+                Expected::Str("→".into())
+            ])),
+            err.expected()
+        );
+    }
+}
+
+#[test]
+fn literals_streaming() {
+    let keywords = literals([("in", Kw::In), ("int", Kw::Int)]);
+    let mut input = "in";
+    let res: Result<_, Streaming<FabError>> = keywords.fab(&mut input);
+    assert!(res.unwrap_err().is_incomplete());
+    assert_eq!("in", input);
+    let mut input = "in ";
+    let res: Result<_, Streaming<FabError>> = keywords.fab(&mut input);
+    assert_eq!(Kw::In, res.unwrap());
+    assert_eq!(" ", input);
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn util_xid_identifier() {
+    use fabparse::util::{xid_continue, xid_start};
+    for (text, expected) in [
+        ("résumé x", "résumé"),
+        ("e\u{301}t\u{301} x", "e\u{301}t\u{301}"),
+        ("привет_мир x", "привет_мир"),
+        ("変数1 x", "変数1"),
+        ("_αβ x", "_αβ"),
+    ] {
+        let mut input = text;
+        let res: Result<_, FabError> = unicode_identifier.fab(&mut input);
+        assert_eq!(expected, res.unwrap());
+        assert_eq!(" x", input);
+    }
+    let mut input = "a\u{301}";
+    let res: Result<_, FabError> = (xid_start, xid_continue).fab(&mut input);
+    assert_eq!(('a', '\u{301}'), res.unwrap());
+    for text in ["\u{200B}abc", "\u{301}a", "1abc", "-a"] {
+        let mut input = text;
+        let res: Result<_, FabError> = unicode_identifier.fab(&mut input);
+        assert!(res.is_err(), "{:?}", text);
+        assert_eq!(text, input);
+        let res: Result<_, FabError> = xid_start.fab(&mut input);
+        assert!(res.is_err());
+    }
+}
+
+fn let_statement<'a>(input: &mut &'a str) -> Result<(&'a str, u32), FabError> {
+    let (_, name, _, value, _, _) =
+        ("let ", util::identifier, " = ", uint(), ';', multispace0).fab(input)?;
+    Ok((name, value))
+}
+
+#[test]
+fn recover_statements() {
+    let text = "let a = 1; let = 2; let c = 3;";
+    let mut input = text;
+    let res: Result<_, FabError> = recover(let_statement, (';', multispace0))
+        .fab_repeat()
+        .fab(&mut input);
+    let res = res.unwrap();
+    assert_eq!(3, res.len());
+    assert_eq!(&("a", 1), res[0].as_ref().unwrap());
+    assert_eq!(&("c", 3), res[2].as_ref().unwrap());
+    let err = res[1].as_ref().unwrap_err();
+    assert_eq!(Some(15), err.offset(text));
+    assert_eq!("", input);
+}
+
+#[test]
+fn recover_without_sync() {
+    let text = "let a = x";
+    let mut input = text;
+    let res: Result<_, FabError> = recover(let_statement, ';').fab(&mut input);
+    assert!(res.unwrap().is_err());
+    assert_eq!("", input);
+    let mut input = "";
+    let res: Result<_, FabError> = recover(let_statement, ';').fab(&mut input);
+    assert!(res.is_err());
+}
+
+#[test]
+fn stateful_error_sink() {
+    let text = "let a = 1; let = 2; let c = x; let d = 4;";
+    let mut input = text;
+    let errors: State<Vec<FabError>> = State::default();
+    let res: Result<_, FabError> = errors
+        .recover(let_statement, (';', multispace0))
+        .fab_repeat()
+        .fab(&mut input);
+    let outputs: Vec<_> = res.unwrap().into_iter().flatten().collect();
+    assert_eq!(vec![("a", 1), ("d", 4)], outputs);
+    assert_eq!("", input);
+    let offsets: Vec<_> = errors
+        .into_inner()
+        .iter()
+        .map(|err| err.offset(text))
+        .collect();
+    assert_eq!(vec![Some(15), Some(28)], offsets);
+}
+
+#[test]
+fn stateful_error_sink_opt() {
+    let text = "ab";
+    let mut input = text;
+    let errors = State::new(Vec::new());
+    let res: Result<_, FabError> = (errors.opt('a'), errors.opt('c'), 'b').fab(&mut input);
+    assert_eq!((Some('a'), None, 'b'), res.unwrap());
+    let errors = errors.into_inner();
+    assert_eq!(1, errors.len());
+    assert_eq!(Some(1), errors[0].offset(text));
+    let mut input = "ab";
+    let errors: State<Vec<FabError>> = State::default();
+    let res: Result<_, FabError> = errors.opt('a').fab(&mut input);
+    assert_eq!(Some('a'), res.unwrap());
+    assert!(errors.borrow().is_empty());
+    assert_eq!(0, errors.borrow().capacity());
+}
+
+#[test]
+fn stateful_error_sink_clone() {
+    let mut input = "x;y;";
+    let errors: State<Vec<FabError>> = State::default();
+    let opt = errors.opt('a');
+    let recover = errors.recover('b', take_not(';'));
+    let res: Result<_, FabError> =
+        (opt.clone(), recover.clone(), ';', opt, recover, ';').fab(&mut input);
+    assert!(res.is_ok());
+    assert_eq!("", input);
+    //The clones report into the same errors as the originals.
+    assert_eq!(4, errors.into_inner().len());
+}
+
+#[test]
+fn stateful_error_sink_in_failed_alt() {
+    //Each branch of the failed alt runs once, so the missing 'b' is reported once.
+    let mut input = "ac";
+    let errors: State<Vec<FabError>> = State::default();
+    let res: Result<_, FabError> = alt((
+        ('a', errors.opt('b'), 'x').fab_value(1),
+        ('q', 'y', 'z').fab_value(2),
+    ))
+    .fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!(1, errors.borrow().len());
+    let mut input = "ac;";
+    let errors: State<Vec<FabError>> = State::default();
+    let res: Result<_, FabError> = alt((
+        ('a', errors.recover('b', take_not(';')), 'x').fab_value(1),
+        ('q', 'y', 'z').fab_value(2),
+    ))
+    .fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!(1, errors.borrow().len());
+}
+
+fn record<'a, E: ParserError>(input: &mut &'a str) -> Result<(&'a str, u32, &'a str), E> {
+    let name = take_while(|c: char| c.is_ascii_alphabetic()).min(1);
+    let value = take_while(|c: char| c.is_ascii_digit())
+        .min(1)
+        .fab_try_map(u32::from_str);
+    let (name, _, value, _, tags, _) = (
+        name,
+        ':',
+        value,
+        ':',
+        opt(take_while(|c: char| c != ';')),
+        ';',
+    )
+        .fab(input)?;
+    Ok((name, value, tags.unwrap_or("")))
+}
+
+#[test]
+fn streaming_partial_buffers() {
+    let full = "abc:123:x,y;";
+    for end in 0..full.len() {
+        let mut input = &full[..end];
+        let res: Result<_, Streaming<FabError>> = record.fab(&mut input);
+        let err = res.unwrap_err();
+        assert!(err.is_incomplete(), "prefix {:?}", &full[..end]);
+        assert_eq!(&full[..end], input);
+    }
+    let mut input = full;
+    let res: Result<_, Streaming<FabError>> = record.fab(&mut input);
+    assert_eq!(("abc", 123, "x,y"), res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn streaming_definite_mismatch() {
+    let mut input = "abc;123";
+    let res: Result<_, Streaming<FabError>> = record.fab(&mut input);
+    assert!(!res.unwrap_err().is_incomplete());
+    let mut input = "ab";
+    let res: Result<_, FabError> = record.fab(&mut input);
+    assert!(!res.unwrap_err().is_incomplete());
+}
+
+#[test]
+fn streaming_alt_and_repeat() {
+    let mut input = "fal";
+    let res: Result<_, Streaming<FabError>> = alt(("false", "fx")).fab(&mut input);
+    assert!(res.unwrap_err().is_incomplete());
+    assert_eq!("fal", input);
+    let mut input = "fal";
+    let res: Result<_, FabError> = alt(("false", "fa")).fab(&mut input);
+    assert_eq!("fa", res.unwrap());
+    let mut input = "aaa";
+    let res: Result<_, Streaming<FabError>> = 'a'.fab_repeat().fab(&mut input);
+    assert!(res.unwrap_err().is_incomplete());
+    assert_eq!("aaa", input);
+    let mut input = "aaab";
+    let res: Result<Vec<_>, Streaming<FabError>> = 'a'.fab_repeat().fab(&mut input);
+    assert_eq!(vec!['a', 'a', 'a'], res.unwrap());
+    let mut input = "aaa";
+    let res: Result<Vec<_>, Streaming<FabError>> = 'a'.fab_repeat().max(2).fab(&mut input);
+    assert_eq!(vec!['a', 'a'], res.unwrap());
+}
+
+struct Keywords {
+    keywords: Vec<String>,
+}
+
+impl Keywords {
+    fn parse<'a>(&self, input: &mut &'a str) -> Result<&'a str, FabError> {
+        alt(self.keywords.as_slice()).fab(input)
+    }
+}
+
+#[test]
+fn owned_string_tag() {
+    let keyword = String::from("let");
+    let mut input = "let x";
+    let res: Result<_, FabError> = keyword.fab(&mut input);
+    assert_eq!("let", res.unwrap());
+    assert_eq!(" x", input);
+    let mut input = "lex";
+    let res: Result<_, FabError> = keyword.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("lex", input);
+    let keywords = Keywords {
+        keywords: vec!["fn".to_string(), "let".to_string()],
+    };
+    let mut input = "let x";
+    assert_eq!("let", keywords.parse(&mut input).unwrap());
+    assert_eq!(" x", input);
+}
+
+#[test]
+fn owned_vec_tag() {
+    let tag = vec![1, 2];
+    let mut slice = [1, 2, 3].as_slice();
+    let res: Result<_, FabError> = tag.fab(&mut slice);
+    assert_eq!([1, 2], res.unwrap());
+    assert_eq!([3], slice);
+    let mut slice = [1, 3, 3].as_slice();
+    let res: Result<_, FabError> = tag.fab(&mut slice);
+    assert!(res.is_err());
+    assert_eq!([1, 3, 3], slice);
+}
+
+type CharPredicate = fn(char) -> bool;
+type PushChar = fn(&mut Vec<char>, char);
+type StoredRecord<'a> = (Option<char>, u32, Vec<char>, &'a str, bool);
+
+#[derive(Clone, Debug)]
+struct StoredParsers {
+    digit: ParserMap<CharPredicate, str, char, FabError, fn(char) -> u32>,
+    sign: Opt<Alt<(char, char)>>,
+    word: Repeat<CharPredicate, str, char, FabError, PushChar, Vec<char>>,
+    pair: Take,
+    flag: Value<&'static str, bool, str, &'static str, FabError>,
+}
+
+impl StoredParsers {
+    fn new() -> Self {
+        let is_digit: CharPredicate = |c| c.is_ascii_digit();
+        let to_digit: fn(char) -> u32 = |c| c.to_digit(10).unwrap();
+        let is_alpha: CharPredicate = |c| c.is_ascii_alphabetic();
+        StoredParsers {
+            digit: is_digit.fab_map(to_digit),
+            sign: opt(alt(('+', '-'))),
+            word: is_alpha.fab_repeat(),
+            pair: take(2),
+            flag: "yes".fab_value(true),
+        }
+    }
+    fn parse<'a>(&self, input: &mut &'a str) -> Result<StoredRecord<'a>, FabError> {
+        (&self.sign, &self.digit, &self.word, &self.pair, &self.flag).fab(input)
+    }
+}
+
+#[test]
+fn stored_parser_clone() {
+    let parsers = StoredParsers::new();
+    let copy = parsers.clone();
+    let mut input = "-7ab::yes";
+    let res = parsers.parse(&mut input);
+    assert_eq!((Some('-'), 7, vec!['a', 'b'], "::", true), res.unwrap());
+    let mut input = "3x..yes!";
+    let res = copy.parse(&mut input);
+    assert_eq!((None, 3, vec!['x'], "..", true), res.unwrap());
+    assert_eq!("!", input);
+    let debug = format!("{:?}", parsers.sign);
+    assert!(debug.starts_with("Opt"));
+    assert!(format!("{:?}", parsers.word).starts_with("Repeat"));
+}
+
+thread_local! {
+    static MEMO_CALLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+fn counted_number(input: &mut &str) -> Result<u32, OffsetFabError> {
+    MEMO_CALLS.with(|calls| calls.set(calls.get() + 1));
+    take_while(|c: char| c.is_ascii_digit())
+        .min(1)
+        .fab_try_map(u32::from_str)
+        .fab(input)
+}
+
+#[test]
+fn memoize_shared_prefix() {
+    let plain = alt((
+        (counted_number, '+', counted_number),
+        (counted_number, '-', counted_number),
+    ));
+    let mut input = "12-34";
+    let res: Result<(u32, char, u32), OffsetFabError> = plain.fab(&mut input);
+    assert_eq!((12, '-', 34), res.unwrap());
+    assert_eq!(3, MEMO_CALLS.with(|calls| calls.get()));
+    MEMO_CALLS.with(|calls| calls.set(0));
+    let memo = counted_number.fab_memoize();
+    let cached = alt(((&memo, '+', &memo), (&memo, '-', &memo)));
+    let mut input = "12-34";
+    let res: Result<(u32, char, u32), OffsetFabError> = cached.fab(&mut input);
+    assert_eq!((12, '-', 34), res.unwrap());
+    assert_eq!("", input);
+    assert_eq!(2, MEMO_CALLS.with(|calls| calls.get()));
+    MEMO_CALLS.with(|calls| calls.set(0));
+    let mut input = "x";
+    let res: Result<u32, OffsetFabError> = memo.fab(&mut input);
+    assert!(res.is_err());
+    let res: Result<u32, OffsetFabError> = memo.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("x", input);
+    assert_eq!(1, MEMO_CALLS.with(|calls| calls.get()));
+    memo.clear();
+    let res: Result<u32, OffsetFabError> = memo.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!(2, MEMO_CALLS.with(|calls| calls.get()));
+}
+
+fn arithmetic(input: &mut &str) -> Result<i64, FabError> {
+    precedence(
+        uint::<i64>(),
+        vec![
+            Infix::left('+', 1, |a, b| a + b),
+            Infix::left('-', 1, |a, b| a - b),
+            Infix::left('*', 2, |a, b| a * b),
+            Infix::right('^', 3, |a: i64, b| a.pow(b as u32)),
+        ],
+    )
+    .prefix(vec![Prefix::new('-', 2, |a: i64| -a)])
+    .postfix(vec![Postfix::new('!', 4, |a: i64| (1..=a).product())])
+    .fab(input)
+}
+
+#[test]
+fn precedence_arithmetic() {
+    let mut input = "1+2*3-4^2^1";
+    assert_eq!(-9, arithmetic(&mut input).unwrap());
+    assert_eq!("", input);
+    let mut input = "2^3^2";
+    assert_eq!(512, arithmetic(&mut input).unwrap());
+    let mut input = "10-4-3";
+    assert_eq!(3, arithmetic(&mut input).unwrap());
+    let mut input = "-2^2+3!*2";
+    assert_eq!(8, arithmetic(&mut input).unwrap());
+    let mut input = "2*3)";
+    assert_eq!(6, arithmetic(&mut input).unwrap());
+    assert_eq!(")", input);
+}
+
+#[test]
+fn precedence_error_location() {
+    let text = "1+2*)";
+    let mut input = text;
+    let err = arithmetic(&mut input).unwrap_err();
+    assert_eq!("1+2*)", input);
+    assert_eq!(Some(4), err.offset(text));
+    let text = "1+2";
+    let mut input = text;
+    let res: Result<_, FabError> =
+        precedence(util::identifier, vec![Infix::left("+", 1, |a: &str, _| a)]).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!(Some(0), res.unwrap_err().offset(text));
+}
+
+#[test]
+fn trace_wrong_input() {
+    let mut input = "abc";
+    let err: FabError = ('a', 'x').fab(&mut input).unwrap_err();
+    //Both frames are further from the end than this input is long.
+    let trace = err.trace_string("x");
+    assert_eq!(2, trace.matches("Location unavailable").count());
+    assert_eq!(Err(TraceError { frame: 0 }), err.try_trace_string("x"));
+    assert!(err.try_trace_string(input).is_ok());
+    //The outer frame is before the start of this suffix of the input.
+    let trace = err.trace_string(&input[1..]);
+    assert_eq!(1, trace.matches("Location unavailable").count());
+}
+
+#[test]
+fn trace_reallocated_input() {
+    let text = String::from("é1");
+    let err = {
+        let mut input = text.as_str();
+        let res: Result<_, FabError> = ('é', 'x').fab(&mut input);
+        res.unwrap_err()
+    };
+    let trace = err.trace_string(text.as_str());
+    drop(text);
+    //The error doesn't point into the dropped String, so a copy of the input traces the same.
+    let copy = String::from("é1");
+    assert_eq!(trace, err.trace_string(copy.as_str()));
+    assert_eq!(Some(2), err.offset(copy.as_str()));
+    assert_eq!(Some(2), err.offset(copy.as_bytes()));
+    //On a different input the location can land inside a char, which isn't traced.
+    let other = "éé";
+    assert_eq!(2, err.trace_string(other).matches("unavailable").count());
+    assert_eq!(Err(TraceError { frame: 0 }), err.try_trace_string(other));
+}
+
+#[test]
+fn fab_error_clone() {
+    let text = "99999999999";
+    let mut input = text;
+    let res: Result<u32, FabError> = take_while(|c: char| c.is_ascii_digit())
+        .fab_try_map(u32::from_str)
+        .fab(&mut input);
+    let err = res.unwrap_err();
+    let copy = err.clone();
+    assert_eq!(err.trace_string(text), copy.trace_string(text));
+    assert!(copy
+        .trace_string(text)
+        .contains("From cause [number too large"));
+    assert_eq!(err.offset(text), copy.offset(text));
+    let number = take_while(|c: char| c.is_ascii_digit())
+        .fab_try_map(u32::from_str)
+        .fab_memoize();
+    let mut input = text;
+    let first: Result<u32, FabError> = number.fab(&mut input);
+    let second: Result<u32, FabError> = number.fab(&mut input);
+    assert_eq!(
+        first.unwrap_err().trace_string(text),
+        second.unwrap_err().trace_string(text)
+    );
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn errors_are_send_sync() {
+    assert_send_sync::<FabError>();
+    assert_send_sync::<NoContextFabError>();
+    assert_send_sync::<OffsetFabError>();
+    assert_send_sync::<Streaming<FabError>>();
+    assert_send_sync::<StoredParsers>();
+    let parsers = StoredParsers::new();
+    let text = "3x..no";
+    let handle = thread::spawn(move || {
+        let mut input = text;
+        let res = parsers.parse(&mut input);
+        res.map(|_| ())
+    });
+    let err = handle.join().unwrap().unwrap_err();
+    assert_eq!(Some(4), err.offset(text));
+}
+
+#[test]
+fn error_expected_found() {
+    let text = "abz";
+    let mut input = text;
+    let err: FabError = ("ab", 'a'..='y').fab(&mut input).unwrap_err();
+    assert_eq!(
+        Some(&Expected::Range(String::from("'a'..='y'"))),
+        err.expected()
+    );
+    assert_eq!(Some(Found::Char('z')), err.found());
+    let mut input = "ac";
+    let err: FabError = "abc".fab(&mut input).unwrap_err();
+    assert_eq!(Some(&Expected::Str(String::from("abc"))), err.expected());
+    assert_eq!(Some(Found::Char('a')), err.found());
+    let mut input = "ab!";
+    let err: FabError = "ab".fab_complete().fab(&mut input).unwrap_err();
+    assert_eq!(Some(&Expected::Eof), err.expected());
+    assert_eq!(Some(Found::Char('!')), err.found());
+    let mut slice = [1, 3].as_slice();
+    let err: FabError = 2.fab(&mut slice).unwrap_err();
+    assert_eq!(None, err.expected());
+}
+
+#[test]
+fn error_expected_alt_merge() {
+    let text = "x = z";
+    let mut input = text;
+    let value = alt((
+        'a'.fab_value(1),
+        "true".fab_value(2),
+        ('b'..='d').fab_value(3),
+    ));
+    let err: FabError = ("x = ", value).fab(&mut input).unwrap_err();
+    assert_eq!(
+        Some(&Expected::OneOf(vec![
+            Expected::Char('a'),
+            Expected::Str(String::from("true")),
+            Expected::Range(String::from("'b'..='d'")),
+        ])),
+        err.expected()
+    );
+    assert!(err.annotate(text).starts_with(
+        "line 1 | x = z\n\
+         \x20            ^ from parser Tag: expected one of 'a', \"true\", 'b'..='d', found 'z'\n"
+    ));
+    //The branch that made more progress wins, so its expectation is kept alone.
+    let mut input = "ab";
+    let err: FabError = alt(('b'.fab_value('b'), ('a', 'c').fab_map(|(_, c)| c)))
+        .fab(&mut input)
+        .unwrap_err();
+    assert_eq!(Some(&Expected::Char('c')), err.expected());
+    let mut input = "c";
+    let res: Result<char, NoContextFabError> = alt(('a', 'b')).fab(&mut input);
+    assert!(res.is_err());
+}
+
+#[cfg(feature = "miette")]
+#[test]
+fn miette_report() {
+    use miette::{Diagnostic, NarratableReportHandler};
+    let input = "name = tru\n";
+    let mut rest = input;
+    let res: Result<_, FabError> = (
+        "name = ",
+        alt(("true".fab_value(true), "false".fab_value(false))).fab_msg("while parsing a bool"),
+    )
+        .fab(&mut rest);
+    let report = res.unwrap_err().into_report(input);
+    let labels: Vec<_> = report.labels().unwrap().collect();
+    assert_eq!(7, labels[0].offset());
+    assert!(labels[0].primary());
+    let mut out = String::new();
+    NarratableReportHandler::new()
+        .render_report(&mut out, &report)
+        .unwrap();
+    assert_eq!(
+        "Parse error in Tag: expected one of \"true\", \"false\", found 't'\n\
+         \x20   Diagnostic severity: error\n\
+         Begin snippet starting at line 1, column 1\n\
+         \n\
+         snippet line 1: name = tru\n\
+         \x20   label at line 1, column 1: Sequence\n\
+         \x20   label at line 1, column 8: Tag: expected one of \"true\", \"false\", found 't'\n\
+         \x20   label at line 1, column 8: Value\n\
+         \x20   label at line 1, column 8: Alt\n\
+         \x20   label at line 1, column 8: Message: while parsing a bool\n",
+        out
+    );
+    let input = "99999999999";
+    let mut rest = input;
+    let res: Result<u32, FabError> = take_while(|c: char| c.is_ascii_digit())
+        .fab_try_map(u32::from_str)
+        .fab(&mut rest);
+    let report = res.unwrap_err().into_report(input);
+    assert_eq!(
+        "number too large to fit in target type",
+        report.source().unwrap().to_string()
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_report_round_trip() {
+    use fabparse::ErrorReport;
+    use serde_json::json;
+    let input = "name = tru\n";
+    let mut rest = input;
+    let res: Result<_, FabError> = (
+        "name = ",
+        alt(("true".fab_value(true), "false".fab_value(false))).fab_msg("while parsing a bool"),
+    )
+        .fab(&mut rest);
+    let err = res.unwrap_err();
+    let report = err.to_report(input);
+    let expected = json!({
+        "frames": [
+            {"offset": 7, "parser": "Tag", "message": null,
+             "expected": "one of \"true\", \"false\"", "found": "'t'"},
+            {"offset": 7, "parser": "Value", "message": null, "expected": null, "found": null},
+            {"offset": 7, "parser": "Alt", "message": null, "expected": null, "found": null},
+            {"offset": 7, "parser": "Message", "message": "while parsing a bool",
+             "expected": null, "found": null},
+            {"offset": 0, "parser": "Sequence", "message": null, "expected": null, "found": null}
+        ],
+        "cause": null,
+        "caused_by": []
+    });
+    let json = serde_json::to_string(&report).unwrap();
+    assert_eq!(
+        expected,
+        serde_json::from_str::<serde_json::Value>(&json).unwrap()
+    );
+    let parsed: ErrorReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(report, parsed);
+    assert_eq!(err.annotate(input), parsed.render(input));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_report_cause() {
+    use anyhow::Context;
+    use fabparse::ErrorReport;
+    use serde_json::json;
+    let input = "port=99999";
+    let mut rest = input;
+    let res: Result<_, FabError> = (
+        "port=",
+        take_while(|c: char| c.is_ascii_digit()).fab_try_map(|digits: &str| {
+            u16::from_str(digits).with_context(|| format!("invalid port {digits}"))
+        }),
+    )
+        .fab(&mut rest);
+    let err = res.unwrap_err();
+    let report = err.to_report(input);
+    let expected = json!({
+        "frames": [
+            {"offset": 5, "parser": "TryMap", "message": null, "expected": null, "found": null},
+            {"offset": 0, "parser": "Sequence", "message": null, "expected": null, "found": null}
+        ],
+        "cause": "invalid port 99999",
+        "caused_by": ["number too large to fit in target type"]
+    });
+    let json = serde_json::to_string(&report).unwrap();
+    assert_eq!(
+        expected,
+        serde_json::from_str::<serde_json::Value>(&json).unwrap()
+    );
+    let parsed: ErrorReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(report, parsed);
+    assert_eq!(err.annotate(input), parsed.render(input));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_report_render() {
+    use fabparse::ErrorReport;
+    let input = "a\tb";
+    let mut rest = input;
+    let res: Result<_, FabError> = ('a', '\t', 'c').fab(&mut rest);
+    let err = res.unwrap_err();
+    let report = err.to_report("");
+    assert!(report.frames.iter().all(|frame| frame.offset.is_none()));
+    assert_eq!("", report.render(""));
+    let report = err.to_report(input);
+    let json = serde_json::to_string(&report).unwrap();
+    let parsed: ErrorReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        "line 1 | a    b\n\
+         \x20             ^ from parser Tag: expected 'c', found 'b'\n\
+         line 1 | a    b\n\
+         \x20        ^ from parser Sequence\n",
+        parsed.render(input)
+    );
+    assert_eq!(err.annotate(input), parsed.render(input));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_parser_type() {
+    assert_eq!("\"Tag\"", serde_json::to_string(&ParserType::Tag).unwrap());
+    assert_eq!(
+        "{\"Custom\":\"identifier\"}",
+        serde_json::to_string(&ParserType::Custom("identifier")).unwrap()
+    );
+}
+
+fn nesting(input: &mut &str) -> Result<usize, FabError> {
+    let (_, depth, _) = ('(', depth_guard(100, nesting_body), ')').fab(input)?;
+    Ok(depth + 1)
+}
+
+fn nesting_body(input: &mut &str) -> Result<usize, FabError> {
+    if input.starts_with('(') {
+        nesting(input)
+    } else {
+        Ok(0)
+    }
+}
+
+#[test]
+fn depth_guard_limit() {
+    let text = format!("{}{}", "(".repeat(100), ")".repeat(100));
+    let mut input = text.as_str();
+    assert_eq!(100, nesting(&mut input).unwrap());
+    assert_eq!("", input);
+
+    let text = format!("{}{}", "(".repeat(100_000), ")".repeat(100_000));
+    let mut input = text.as_str();
+    let err = nesting(&mut input).unwrap_err();
+    assert_eq!(text, input);
+    assert_eq!(
+        Some(&DepthLimitError { limit: 100 }),
+        err.cause().and_then(|cause| cause.downcast_ref())
+    );
+    assert_eq!(Some(101), err.offset(text.as_str()));
+
+    let mut input = "(())";
+    assert_eq!(2, nesting(&mut input).unwrap());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn regex_anchored() {
+    use fabparse::{re, re_captures};
+    let version = re(r"\d+\.\d+\.\d+");
+    let mut input = "1.22.3-beta";
+    let res: Result<_, FabError> = version.fab(&mut input);
+    assert_eq!("1.22.3", res.unwrap());
+    assert_eq!("-beta", input);
+
+    let mut input = "v1.22.3";
+    let res: Result<_, FabError> = version.fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!("v1.22.3", input);
+    assert_eq!(
+        Some(&Expected::Pattern(r"\d+\.\d+\.\d+".to_string())),
+        err.expected()
+    );
+    assert_eq!(Some(Found::Char('v')), err.found());
+
+    let date = re_captures(r"(?P<year>\d{4})-(?P<month>\d{2})");
+    let mut input = "2024-05 rest";
+    let res: Result<_, FabError> = date.fab(&mut input);
+    let captures = res.unwrap();
+    assert_eq!("2024", &captures["year"]);
+    assert_eq!("05", &captures["month"]);
+    assert_eq!(" rest", input);
+
+    let mut input = "on 2024-05";
+    let res: Result<_, FabError> = date.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("on 2024-05", input);
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn regex_empty_match() {
+    use fabparse::{re, re::Re};
+    let mut input = "abc";
+    let res: Result<_, FabError> = re("[0-9]*").fab(&mut input);
+    assert_eq!("", res.unwrap());
+    assert_eq!("abc", input);
+
+    let mut input = "abc";
+    let res: Result<_, FabError> = (re("a|b|c"), re("x*"), re("b")).fab(&mut input);
+    assert_eq!(("a", "", "b"), res.unwrap());
+    assert_eq!("c", input);
+
+    assert!(Re::new("(").is_err());
+}
+
+#[cfg(feature = "nom")]
+fn nom_assignment(input: &str) -> nom::IResult<&str, (&str, u32)> {
+    use nom::{
+        bytes::complete::tag,
+        character::complete::{alpha1, digit1, space0},
+        combinator::map_res,
+        sequence::{delimited, separated_pair},
+        Parser as _,
+    };
+    separated_pair(
+        alpha1,
+        delimited(space0, tag("="), space0),
+        map_res(digit1, str::parse),
+    )
+    .parse(input)
+}
+
+#[cfg(feature = "nom")]
+#[test]
+fn nom_from_nom() {
+    use fabparse::{from_nom, nom_compat::NomErrorCause};
+    let mut input = "x = 12;y=3;";
+    let res: Result<_, FabError> = (from_nom(nom_assignment), ';')
+        .fab_map(|(assignment, _)| assignment)
+        .fab_repeat()
+        .fab(&mut input);
+    assert_eq!(vec![("x", 12), ("y", 3)], res.unwrap());
+    assert_eq!("", input);
+
+    //The first branch fails partway through, so alt backtracks to the second.
+    let mut input = "x = y";
+    let res: Result<_, FabError> = alt((
+        from_nom(nom_assignment).fab_map(|(name, _)| name),
+        (util::identifier, " = ", util::identifier).fab_map(|(_, _, value)| value),
+    ))
+    .fab(&mut input);
+    assert_eq!("y", res.unwrap());
+    assert_eq!("", input);
+
+    let text = "x = y";
+    let mut input = text;
+    let res: Result<_, FabError> = from_nom(nom_assignment).fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(Some(4), err.offset(text));
+    let cause = err
+        .cause()
+        .unwrap()
+        .downcast_ref::<NomErrorCause>()
+        .unwrap();
+    assert_eq!(nom::error::ErrorKind::Digit, cause.kind);
+    assert_eq!(text, input);
+}
+
+#[cfg(feature = "nom")]
+#[test]
+fn nom_to_nom() {
+    use fabparse::to_nom;
+    use nom::{multi::separated_list1, Parser as _};
+    let mut list = separated_list1(nom::character::complete::char(','), to_nom(uint::<u32>()));
+    assert_eq!(Ok(("]", vec![1, 22, 3])), list.parse("1,22,3]"));
+
+    let err = to_nom(("a", uint::<u32>())).parse("ax").unwrap_err();
+    assert_eq!(
+        nom::Err::Error(nom::error::Error::new("x", nom::error::ErrorKind::Fail)),
+        err
+    );
+}
+
+#[test]
+fn alt_either_branches() {
+    let parser = alt_either((float(), util::identifier));
+    let mut input = "2.5 x";
+    let res: Result<_, FabError> = parser.fab(&mut input);
+    assert_eq!(Either::Left(2.5), res.unwrap());
+    assert_eq!(" x", input);
+
+    let mut input = "x 2.5";
+    let res: Result<_, FabError> = parser.fab(&mut input);
+    assert_eq!(Either::Right("x"), res.unwrap());
+    assert_eq!(" 2.5", input);
+
+    let parser = alt_either(('a', "bc", uint::<u32>()));
+    let mut input = "12";
+    let res: Result<_, FabError> = parser.fab(&mut input);
+    assert_eq!(Either::Right(Either::Right(12)), res.unwrap());
+    let mut input = "bcd";
+    let res: Result<_, FabError> = parser.fab(&mut input);
+    assert_eq!(Either::Right(Either::Left("bc")), res.unwrap());
+    assert_eq!("d", input);
+}
+
+#[test]
+fn alt_either_backtracking() {
+    let parser = alt_either((("ab", 'c'), ("a", uint::<u32>())));
+    let mut input = "a7";
+    let res: Result<_, FabError> = parser.fab(&mut input);
+    assert_eq!(Either::Right(("a", 7)), res.unwrap());
+    assert_eq!("", input);
+
+    let text = "abd";
+    let mut input = text;
+    let res: Result<_, FabError> = parser.fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(text, input);
+    assert_eq!(Some(2), err.offset(text));
+    assert_eq!(Some(&Expected::Char('c')), err.expected());
+    assert_eq!(Some(Found::Char('d')), err.found());
+}
+
+#[test]
+fn keyword_boundary() {
+    let mut input = "if x";
+    let res: Result<_, FabError> = keyword("if").fab(&mut input);
+    assert_eq!("if", res.unwrap());
+    assert_eq!(" x", input);
+
+    let mut input = "if(x)";
+    let res: Result<_, FabError> = keyword("if").fab(&mut input);
+    assert_eq!("if", res.unwrap());
+    assert_eq!("(x)", input);
+
+    let mut input = "if";
+    let res: Result<_, FabError> = keyword("if").fab(&mut input);
+    assert_eq!("if", res.unwrap());
+    assert_eq!("", input);
+
+    for text in ["ifoo", "if_", "if2", "ifé"] {
+        let mut input = text;
+        let res: Result<_, FabError> = keyword("if").fab(&mut input);
+        let err = res.unwrap_err();
+        assert_eq!(text, input);
+        assert_eq!(Some(2), err.offset(text));
+    }
+
+    let mut input = "iff";
+    let res: Result<_, FabError> = keyword("if").fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("iff", input);
+
+    let mut input = "if→";
+    let res: Result<_, FabError> = keyword("if").fab(&mut input);
+    assert_eq!("if", res.unwrap());
+    assert_eq!("→", input);
+}
+
+#[test]
+fn bool_literal() {
+    let mut input = "true,";
+    let res: Result<_, FabError> = bool_lit.fab(&mut input);
+    assert!(res.unwrap());
+    assert_eq!(",", input);
+
+    let mut input = "false";
+    let res: Result<_, FabError> = bool_lit.fab(&mut input);
+    assert!(!res.unwrap());
+    assert_eq!("", input);
+
+    let mut input = "trueish";
+    let res: Result<_, FabError> = bool_lit.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("trueish", input);
+}
+
+#[test]
+fn line_comment_at_eof() {
+    let mut input = "// note\nx";
+    let res: Result<_, FabError> = line_comment("//").fab(&mut input);
+    assert_eq!("// note", res.unwrap());
+    assert_eq!("\nx", input);
+
+    let mut input = "# last";
+    let res: Result<_, FabError> = line_comment("#").fab(&mut input);
+    assert_eq!("# last", res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn block_comment_nesting() {
+    let mut input = "/* a /* b */ c */ d";
+    let res: Result<_, FabError> = block_comment("/*", "*/", true).fab(&mut input);
+    assert_eq!("/* a /* b */ c */", res.unwrap());
+    assert_eq!(" d", input);
+
+    let mut input = "/* a /* b */ c */ d";
+    let res: Result<_, FabError> = block_comment("/*", "*/", false).fab(&mut input);
+    assert_eq!("/* a /* b */", res.unwrap());
+    assert_eq!(" c */ d", input);
+
+    let text = "x /* a /* b */ c";
+    let mut input = &text[2..];
+    let res: Result<_, FabError> = block_comment("/*", "*/", true).fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(&text[2..], input);
+    assert_eq!(Some(2), err.offset(text));
+    assert_eq!(Some(&Expected::Str("*/".to_string())), err.expected());
+}
+
+#[test]
+fn balanced_nested() {
+    let mut input = "{ a { b } c } d";
+    let res: Result<_, FabError> = balanced('{', '}').fab(&mut input);
+    assert_eq!(" a { b } c ", res.unwrap());
+    assert_eq!(" d", input);
+
+    let mut input = "((((x))))(y)";
+    let res: Result<_, FabError> = balanced('(', ')').fab(&mut input);
+    assert_eq!("(((x)))", res.unwrap());
+    assert_eq!("(y)", input);
+
+    let deep = format!("{}{}", "[".repeat(100), "]".repeat(100));
+    let mut input = deep.as_str();
+    let res: Result<_, FabError> = balanced('[', ']').fab(&mut input);
+    assert_eq!(&deep[1..199], res.unwrap());
+    assert_eq!("", input);
+
+    let mut input = "begin x begin y end end!";
+    let res: Result<_, FabError> = balanced("begin", "end").outer().fab(&mut input);
+    assert_eq!("begin x begin y end end", res.unwrap());
+    assert_eq!("!", input);
+}
+
+#[test]
+fn balanced_adjacent_groups() {
+    let mut input = "(a)(b (c))()";
+    let res: Result<_, FabError> = balanced('(', ')').fab_repeat().fab(&mut input);
+    assert_eq!(vec!["a", "b (c)", ""], res.unwrap());
+    assert_eq!("", input);
+}
+
+#[test]
+fn balanced_quoted() {
+    let mut input = r#"{ s = "}" ; t = '\'{' } rest"#;
+    let res: Result<_, FabError> = balanced('{', '}').skip_quoted("\"'").fab(&mut input);
+    assert_eq!(r#" s = "}" ; t = '\'{' "#, res.unwrap());
+    assert_eq!(" rest", input);
+
+    let mut input = r#"{ s = "}" }"#;
+    let res: Result<_, FabError> = balanced('{', '}').fab(&mut input);
+    assert_eq!(r#" s = ""#, res.unwrap());
+    assert_eq!(r#"" }"#, input);
+
+    let mut input = r#"{ s = "} }"#;
+    let res: Result<_, FabError> = balanced('{', '}').skip_quoted("\"").fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!(r#"{ s = "} }"#, input);
+}
+
+#[test]
+fn balanced_unbalanced() {
+    let text = "x = { a { b }";
+    let mut input = &text[4..];
+    let res: Result<_, FabError> = balanced('{', '}').fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(&text[4..], input);
+    assert_eq!(Some(4), err.offset(text));
+    assert_eq!(Some(&Expected::Str("}".to_string())), err.expected());
+
+    let mut input = "a }";
+    let res: Result<_, FabError> = balanced('{', '}').fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("a }", input);
+}
+
+#[test]
+fn skip_comments() {
+    let skip = skip_ws_and_comments(line_comment("//"), block_comment("/*", "*/", true));
+    let mut input = "  // one\n /* two /* three */ */\n\tx = 1";
+    let res: Result<_, FabError> = (skip, 'x').fab(&mut input);
+    assert_eq!('x', res.unwrap().1);
+    assert_eq!(" = 1", input);
+
+    let text = " /* only */ // comments";
+    let mut input = text;
+    let res: Result<_, FabError> = skip.fab(&mut input);
+    assert_eq!(text, res.unwrap());
+    assert_eq!("", input);
+
+    let mut input = "x";
+    let res: Result<_, FabError> = skip.fab(&mut input);
+    assert_eq!("", res.unwrap());
+    assert_eq!("x", input);
+
+    let text = "// fine\n/* open";
+    let mut input = text;
+    let res: Result<_, FabError> = skip.fab(&mut input);
+    assert_eq!(Some(8), res.unwrap_err().offset(text));
+    assert_eq!(text, input);
+
+    let hash = skip_ws_and_comments(line_comment("#"), None);
+    let mut input = "# a\n# b\nkey";
+    let res: Result<_, FabError> = hash.fab(&mut input);
+    assert!(res.is_ok());
+    assert_eq!("key", input);
+}
+
+#[test]
+fn str_split_inside_char() {
+    let text = "🇷🇺abc";
+    assert_eq!(None, text.try_split_at(2));
+    assert_eq!(None, text.try_split_at(5));
+    assert_eq!(Some(("🇷", "🇺abc")), text.try_split_at(4));
+    assert_eq!(None, text.try_split_at(text.len() + 1));
+
+    let mut input = text;
+    let res: Result<_, FabError> = "ab".fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!(text, input);
+
+    let tag = String::from("🇷x");
+    let mut input = text;
+    let res: Result<_, FabError> = tag.fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!(text, input);
+
+    let mut input = text;
+    let res: Result<_, FabError> = (take(1), "🇺", take(2)).fab(&mut input);
+    assert_eq!(("🇷", "🇺", "ab"), res.unwrap());
+    assert_eq!("c", input);
+}
+
+#[test]
+fn separated_trailing() {
+    let list = |allow_trailing: bool| {
+        let items = separated(uint::<u32>(), ',');
+        let items = if allow_trailing {
+            items.allow_trailing()
+        } else {
+            items
+        };
+        ('[', items, ']').fab_map(|(_, items, _)| items)
+    };
+
+    let mut input = "[1,2]";
+    let res: Result<_, FabError> = list(false).fab(&mut input);
+    assert_eq!(vec![1, 2], res.unwrap());
+
+    let text = "[1,2,]";
+    let mut input = text;
+    let res: Result<_, FabError> = list(true).fab(&mut input);
+    assert_eq!(vec![1, 2], res.unwrap());
+    assert_eq!("", input);
+    let mut input = text;
+    let res: Result<_, FabError> = list(false).fab(&mut input);
+    assert_eq!(Some(4), res.unwrap_err().offset(text));
+    assert_eq!(text, input);
+    let mut input = &text[1..];
+    let res: Result<_, FabError> = separated(uint::<u32>(), ',').fab(&mut input);
+    assert_eq!(vec![1, 2], res.unwrap());
+    assert_eq!(",]", input);
+
+    let text = "[1,,2]";
+    let mut input = text;
+    let res: Result<_, FabError> = list(true).fab(&mut input);
+    assert_eq!(Some(3), res.unwrap_err().offset(text));
+    let mut input = text;
+    let res: Result<_, FabError> = list(false).fab(&mut input);
+    assert_eq!(Some(2), res.unwrap_err().offset(text));
+    assert_eq!(text, input);
+
+    let mut input = "[]";
+    let res: Result<_, FabError> = list(true).fab(&mut input);
+    assert_eq!(Vec::<u32>::new(), res.unwrap());
+    let mut input = "[,]";
+    let res: Result<_, FabError> = list(true).fab(&mut input);
+    assert!(res.is_err());
+}
+
+#[test]
+fn separated_bounds() {
+    let text = "1, 2, x";
+    let mut input = text;
+    let res: Result<_, FabError> = separated(uint::<u32>(), ", ").min(3).fab(&mut input);
+    let err = res.unwrap_err();
+    assert_eq!(text, input);
+    assert_eq!(
+        Some(vec![(6, ParserType::Tag), (0, ParserType::Repeat)]),
+        err.stack_offsets(text)
+    );
+
+    let mut input = "1,2,3,4";
+    let res: Result<_, FabError> = separated(uint::<u32>(), ',').max(2).fab(&mut input);
+    assert_eq!(vec![1, 2], res.unwrap());
+    assert_eq!(",3,4", input);
+    let mut input = "1,2,3,4";
+    let res: Result<_, FabError> = separated(uint::<u32>(), ',')
+        .max(2)
+        .allow_trailing()
+        .fab(&mut input);
+    assert_eq!(vec![1, 2], res.unwrap());
+    assert_eq!("3,4", input);
+
+    let mut input = "x";
+    let res: Result<_, FabError> = separated(uint::<u32>(), ',').fab(&mut input);
+    assert_eq!(Vec::<u32>::new(), res.unwrap());
+    assert_eq!("x", input);
+    let mut input = "x";
+    let res: Result<Vec<u32>, FabError> = separated(uint(), ',').bound(1..=2).fab(&mut input);
+    assert!(res.is_err());
+    assert_eq!("x", input);
+}
+
+#[test]
+fn permutation_separated_by() {
+    let style = alt(("solid", "dashed"));
+    let width = (uint::<u32>(), "px").fab_map(|(width, _)| width);
+    let color = alt(("red", "blue"));
+    let border = permutation((style, width, color)).separated_by(' ');
+    for text in ["solid 2px red", "red solid 2px", "2px blue dashed"] {
+        let mut input = text;
+        let res: Result<_, FabError> = border.fab(&mut input);
+        assert!(res.is_ok(), "{}", text);
+        assert_eq!("", input);
+    }
+    let mut input = "red solid 2px;";
+    let res: Result<_, FabError> = border.fab(&mut input);
+    assert_eq!(("solid", 2, "red"), res.unwrap());
+    assert_eq!(";", input);
+
+    let mut input = "solid 2px red ";
+    let res: Result<_, FabError> = border.fab(&mut input);
+    assert!(res.is_ok());
+    assert_eq!(" ", input);
+
+    for text in ["solid2px red", "solid 2px  red", "solid 2px"] {
+        let mut input = text;
+        let res: Result<_, FabError> = border.fab(&mut input);
+        assert!(res.is_err(), "{}", text);
+        assert_eq!(text, input);
+    }
+    let text = "solid 2px green";
+    let mut input = text;
+    let res: Result<_, FabError> = border.fab(&mut input);
+    assert_eq!(Some(10), res.unwrap_err().offset(text));
+}